@@ -2,7 +2,9 @@
 
 cd ecdsa
 ./benchmark.sh
-cd ../merkle_tree
+cd keccak_ecdsa
+./benchmark.sh
+cd ../../merkle_tree
 ./benchmark.sh
 cd ../zkml
 ./benchmark_mnist.sh
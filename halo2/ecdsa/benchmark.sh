@@ -1,12 +1,14 @@
 #!/bin/bash
 
+# Usage: ./benchmark.sh [secp256k1|p256|ed25519]
+SCHEME=${1:-secp256k1}
 BUILD_DIR=.
-OUTPUT="ecdsa_measurement.json"
-EXECUTABLE="./target/release/run_ecdsa"
-if [ ! -f "$OUTPUT" ]; then
-    touch "$BUILD_DIR"/"$OUTPUT"
-    echo "{}" > "$OUTPUT"
+if [ "$SCHEME" = "secp256k1" ]; then
+    OUTPUT="ecdsa_measurement.json"
+else
+    OUTPUT="ecdsa_${SCHEME}_measurement.json"
 fi
+EXECUTABLE="./target/release/run_ecdsa"
 
 if [ ! -d "./params_kzg" ]; then
     echo "No params directory found. Creating the directory..."
@@ -15,31 +17,21 @@ fi
 
 cargo build --release
 
-echo "$(jq '. += {"Framework": "Halo2" }' "$BUILD_DIR"/"$OUTPUT")" > "$BUILD_DIR"/"$OUTPUT"
-echo "$(jq '. += {"Circuit": "ECDSA" }' "$BUILD_DIR"/"$OUTPUT")" > "$BUILD_DIR"/"$OUTPUT"
-echo "$(jq '. += {"Backend": "Plonk" }' "$BUILD_DIR"/"$OUTPUT")" > "$BUILD_DIR"/"$OUTPUT"
-echo "$(jq '. += {"Curve": "BN254" }' "$BUILD_DIR"/"$OUTPUT")" > "$BUILD_DIR"/"$OUTPUT"
-echo "$(jq --arg tmp $(lscpu | grep "Model name:" | sed -e "s/^Model name:                      //" | sed -e "s/\s\+/./g") \
-'. += {"Hardware": $tmp }' "$BUILD_DIR"/"$OUTPUT")" > "$BUILD_DIR"/"$OUTPUT"
+rm -f "$BUILD_DIR"/"$OUTPUT"
+
+if [ "$SCHEME" != "secp256k1" ]; then
+    echo "****GENERATING $SCHEME SAMPLE INPUT****"
+    $EXECUTABLE generate "$BUILD_DIR"/"$OUTPUT" "$SCHEME"
+fi
 
 echo "****Setup****"
-$EXECUTABLE setup
+$EXECUTABLE setup "$BUILD_DIR"/"$OUTPUT" "$SCHEME"
 
 echo "****GENERATING PROOF FOR SAMPLE INPUT****"
-start=`date +%s%N`
-touch /tmp/test
-{ /usr/bin/time -v $EXECUTABLE prove > ecdsa_witness.log; } 2> /tmp/test
-echo "$(jq --arg tmp $(echo "scale=6; $(cat /tmp/test | grep "Maximum resident set size" | tr -d -c 0-9)/1024" | bc) '.+={"MemoryConsumption": $tmp }' "$BUILD_DIR"/"$OUTPUT")" > "$BUILD_DIR"/"$OUTPUT"
-rm /tmp/test
-end=`date +%s%N`
-echo "$(jq --arg tmp $(stat -c %s "$BUILD_DIR"/proof) '.+={"ProofSize": $tmp}' "$BUILD_DIR"/"$OUTPUT")" > "$BUILD_DIR"/"$OUTPUT"
-echo "$(jq --arg tmp $(echo "scale=6; $((end-start))/1000000000" | bc) '.+={"ProverTime": $tmp}' "$BUILD_DIR"/"$OUTPUT")" > "$BUILD_DIR"/"$OUTPUT"
+$EXECUTABLE prove "$BUILD_DIR"/"$OUTPUT" "$SCHEME" > ecdsa_witness.log
 echo "Proof Size: $(stat -c %s "$BUILD_DIR"/proof)bytes"
-echo "DONE ($((end-start))ns)"
+echo "DONE ($(jq .ProverTime "$BUILD_DIR"/"$OUTPUT")s)"
 
 echo "****VERIFYING PROOF FOR SAMPLE INPUT****"
-start=`date +%s%N`
-$EXECUTABLE verify
-end=`date +%s%N`
-echo "$(jq --arg tmp $(echo "scale=6; $((end-start))/1000000" | bc) '.+={"VerifierTime": $tmp}' "$BUILD_DIR"/"$OUTPUT")" > "$BUILD_DIR"/"$OUTPUT"
-echo "DONE ($((end-start))ns)"
+$EXECUTABLE verify "$BUILD_DIR"/"$OUTPUT" "$SCHEME"
+echo "DONE ($(jq .VerifierTime "$BUILD_DIR"/"$OUTPUT")ms)"
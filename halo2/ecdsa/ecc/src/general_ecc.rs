@@ -5,7 +5,7 @@ use crate::integer::{IntegerChip, IntegerInstructions, Range, UnassignedInteger}
 use crate::maingate;
 use halo2::arithmetic::CurveAffine;
 use halo2::circuit::{Layouter, Value};
-use halo2::halo2curves::ff::PrimeField;
+use halo2::halo2curves::ff::{Field, PrimeField};
 use halo2::plonk::Error;
 use integer::maingate::RegionCtx;
 use maingate::{AssignedCondition, MainGate};
@@ -131,6 +131,17 @@ impl<
         Point { x, y }
     }
 
+    /// Returns emulated EC constant $a$
+    fn parameter_a(&self) -> Integer<Emulated::Base, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB> {
+        Integer::from_fe(Emulated::a(), self.rns_base())
+    }
+
+    /// Returns true if the emulated EC has $a = 0$, e.g. secp256k1, in
+    /// which case the $a$ terms are not constrained at all
+    fn is_a_zero(&self) -> bool {
+        bool::from(Emulated::a().is_zero())
+    }
+
     /// Returns emulated EC constant $b$
     fn parameter_b(&self) -> Integer<Emulated::Base, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB> {
         Integer::from_fe(Emulated::b(), self.rns_base())
@@ -267,6 +278,13 @@ impl<
         let y_square = &integer_chip.square(ctx, point.y())?;
         let x_square = &integer_chip.square(ctx, point.x())?;
         let x_cube = &integer_chip.mul(ctx, point.x(), x_square)?;
+        // y^2 = x^3 + a * x + b
+        let x_cube = &if self.is_a_zero() {
+            x_cube.clone()
+        } else {
+            let a_x = &integer_chip.mul_constant(ctx, point.x(), &self.parameter_a())?;
+            integer_chip.add(ctx, x_cube, a_x)?
+        };
         let x_cube_b = &integer_chip.add_constant(ctx, x_cube, &self.parameter_b())?;
         integer_chip.assert_equal(ctx, x_cube_b, y_square)?;
         Ok(())
@@ -422,6 +440,7 @@ mod tests {
         EpAffine as Pallas, EqAffine as Vesta, Fp as PastaFp, Fq as PastaFq,
     };
     use crate::curves::secp256k1::Secp256k1Affine as Secp256k1;
+    use crate::curves::secp256r1::Secp256r1Affine as Secp256r1;
 
     const NUMBER_OF_LIMBS: usize = 4;
     const BIT_LEN_LIMB: usize = 68;
@@ -622,6 +641,9 @@ mod tests {
         run::<Secp256k1, BnScalar, NUMBER_OF_LIMBS, BIT_LEN_LIMB>();
         run::<Secp256k1, PastaFp, NUMBER_OF_LIMBS, BIT_LEN_LIMB>();
         run::<Secp256k1, PastaFq, NUMBER_OF_LIMBS, BIT_LEN_LIMB>();
+
+        // a != 0
+        run::<Secp256r1, BnScalar, NUMBER_OF_LIMBS, BIT_LEN_LIMB>();
     }
 
     #[derive(Default, Clone, Debug)]
@@ -1009,4 +1031,6 @@ mod tests {
     test_general_ecc_mul_batch_circuit!(Secp256k1, BnScalar, NUMBER_OF_LIMBS, BIT_LEN_LIMB);
     test_general_ecc_mul_batch_circuit!(Secp256k1, PastaFp, NUMBER_OF_LIMBS, BIT_LEN_LIMB);
     test_general_ecc_mul_batch_circuit!(Secp256k1, PastaFq, NUMBER_OF_LIMBS, BIT_LEN_LIMB);
+
+    test_general_ecc_mul_batch_circuit!(Secp256r1, BnScalar, NUMBER_OF_LIMBS, BIT_LEN_LIMB);
 }
@@ -56,9 +56,14 @@ impl<
     ) -> Result<AssignedPoint<Emulated::Base, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>, Error> {
         let ch = self.base_field_chip();
 
-        // lambda = (3 * a_x^2) / 2 * a_y
+        // lambda = (3 * a_x^2 + a) / 2 * a_y, where `a` is the curve constant
         let x_0_square = &ch.square(ctx, &point.x)?;
         let numerator = &ch.mul3(ctx, x_0_square)?;
+        let numerator = &if self.is_a_zero() {
+            numerator.clone()
+        } else {
+            ch.add_constant(ctx, numerator, &self.parameter_a())?
+        };
         let denominator = &ch.mul2(ctx, &point.y)?;
         let lambda = &ch.div_incomplete(ctx, numerator, denominator)?;
 
@@ -1,8 +1,19 @@
-use ecdsa::test::test_ecdsa_verifier;
+use ecdsa::test::{test_ecdsa_verifier, Scheme};
+use jemallocator::Jemalloc;
+
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
 
 fn main() {
 
     let step = std::env::args().nth(1).expect("Step to Process");
-    
-    test_ecdsa_verifier(step);
-}
\ No newline at end of file
+    let outp_json = std::env::args()
+        .nth(2)
+        .unwrap_or_else(|| "ecdsa_measurement.json".to_string());
+    let scheme = std::env::args()
+        .nth(3)
+        .map(|name| Scheme::from_name(&name).expect("Scheme is secp256k1, p256 or ed25519"))
+        .unwrap_or(Scheme::Secp256k1);
+
+    test_ecdsa_verifier(step, outp_json, scheme);
+}
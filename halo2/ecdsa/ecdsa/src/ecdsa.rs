@@ -352,8 +352,10 @@ mod tests {
         use crate::curves::bn256::Fr as BnScalar;
         use crate::curves::pasta::{Fp as PastaFp, Fq as PastaFq};
         use crate::curves::secp256k1::Secp256k1Affine as Secp256k1;
+        use crate::curves::secp256r1::Secp256r1Affine as Secp256r1;
         run::<Secp256k1, BnScalar>();
         run::<Secp256k1, PastaFp>();
         run::<Secp256k1, PastaFq>();
+        run::<Secp256r1, BnScalar>();
     }
 }
@@ -0,0 +1,201 @@
+//! Ed25519 over its native fields, for building and checking EdDSA witnesses.
+//!
+//! halo2curves does not ship curve25519, and the twisted Edwards form does not
+//! fit `CurveAffine`, so only the fields and the affine group law needed by
+//! `EddsaChip` are defined here.
+
+use ff::{Field, PrimeField};
+use rand::RngCore;
+use std::ops::{Add, Mul, Neg};
+
+/// Base field of Ed25519, `2^255 - 19`
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "57896044618658097711785492504343953926634992332820282019728792003956564819949"]
+#[PrimeFieldGenerator = "2"]
+#[PrimeFieldReprEndianness = "little"]
+pub struct Fp([u64; 4]);
+
+/// Scalar field of Ed25519, the order `l` of the base point
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "7237005577332262213973186563042994240857116359379907606001950938285454250989"]
+#[PrimeFieldGenerator = "2"]
+#[PrimeFieldReprEndianness = "little"]
+pub struct Fq([u64; 4]);
+
+/// Curve constant `d` of `-x^2 + y^2 = 1 + d * x^2 * y^2`
+pub fn d() -> Fp {
+    Fp::from_str_vartime(
+        "37095705934669439343138083508754565189542113879843219016388785533085940283555",
+    )
+    .unwrap()
+}
+
+/// Point of Ed25519 in affine coordinates. The identity is `(0, 1)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ed25519Affine {
+    pub x: Fp,
+    pub y: Fp,
+}
+
+impl Default for Ed25519Affine {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Ed25519Affine {
+    pub fn identity() -> Self {
+        Self {
+            x: Fp::ZERO,
+            y: Fp::ONE,
+        }
+    }
+
+    /// Base point `B` of RFC 8032
+    pub fn generator() -> Self {
+        Self {
+            x: Fp::from_str_vartime(
+                "15112221349535400772501151409588531511454012693041857206046113283949847762202",
+            )
+            .unwrap(),
+            y: Fp::from_str_vartime(
+                "46316835694926478169428394003475163141307993866256225615783033603165251855960",
+            )
+            .unwrap(),
+        }
+    }
+
+    pub fn from_xy(x: Fp, y: Fp) -> Option<Self> {
+        let point = Self { x, y };
+        point.is_on_curve().then_some(point)
+    }
+
+    pub fn is_on_curve(&self) -> bool {
+        let x2 = self.x.square();
+        let y2 = self.y.square();
+        y2 - x2 == Fp::ONE + d() * x2 * y2
+    }
+
+    pub fn random(mut rng: impl RngCore) -> Self {
+        Self::generator() * Fq::random(&mut rng)
+    }
+}
+
+impl Add for Ed25519Affine {
+    type Output = Self;
+
+    /// Unified addition, complete since `a = -1` is a square and `d` is not
+    fn add(self, other: Self) -> Self {
+        let x1y2 = self.x * other.y;
+        let y1x2 = self.y * other.x;
+        let x1x2 = self.x * other.x;
+        let y1y2 = self.y * other.y;
+        let t = d() * x1x2 * y1y2;
+        Self {
+            x: (x1y2 + y1x2) * (Fp::ONE + t).invert().unwrap(),
+            y: (y1y2 + x1x2) * (Fp::ONE - t).invert().unwrap(),
+        }
+    }
+}
+
+impl Neg for Ed25519Affine {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            x: -self.x,
+            y: self.y,
+        }
+    }
+}
+
+impl Mul<Fq> for Ed25519Affine {
+    type Output = Self;
+
+    fn mul(self, scalar: Fq) -> Self {
+        let mut acc = Self::identity();
+        for byte in scalar.to_repr().as_ref().iter().rev() {
+            for i in (0..8).rev() {
+                acc = acc + acc;
+                if (byte >> i) & 1 == 1 {
+                    acc = acc + self;
+                }
+            }
+        }
+        acc
+    }
+}
+
+/// EdDSA signature `(R, S)`
+#[derive(Clone, Copy, Debug)]
+pub struct EddsaSig {
+    pub r: Ed25519Affine,
+    pub s: Fq,
+}
+
+/// Signs with the secret scalar `sk` and nonce `nonce`. `challenge` stands in
+/// for `SHA-512(R || A || M) mod l`, which is computed outside the circuit in
+/// the same way as the ECDSA message hash.
+pub fn sign(sk: Fq, nonce: Fq, challenge: Fq) -> EddsaSig {
+    EddsaSig {
+        r: Ed25519Affine::generator() * nonce,
+        s: nonce + challenge * sk,
+    }
+}
+
+/// Checks `[S]B = R + [k]A`
+pub fn verify(sig: &EddsaSig, public_key: &Ed25519Affine, challenge: Fq) -> bool {
+    Ed25519Affine::generator() * sig.s == sig.r + *public_key * challenge
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sign, verify, Ed25519Affine, Fq};
+    use ff::{Field, PrimeField};
+    use rand_core::OsRng;
+
+    #[test]
+    fn test_generator_has_order_l() {
+        let g = Ed25519Affine::generator();
+        assert!(g.is_on_curve());
+        assert_eq!(g * -Fq::ONE + g, Ed25519Affine::identity());
+        assert_eq!(g + -g, Ed25519Affine::identity());
+        assert_eq!(g * Fq::from(2), g + g);
+    }
+
+    #[test]
+    fn test_rfc8032_public_key() {
+        // TEST 1 of RFC 8032 section 7.1, with the clamped secret scalar
+        // reduced mod l
+        let mut sk = <Fq as PrimeField>::Repr::default();
+        sk.as_mut().copy_from_slice(&hex_bytes(
+            "7c2cac12e69be96ae9065065462385e8fcff2768d980c0a3a520f006904de90f",
+        ));
+        let sk = Fq::from_repr(sk).unwrap();
+        let pk = Ed25519Affine::generator() * sk;
+        // The encoding is y with the sign of x in the top bit
+        let mut encoded = pk.y.to_repr();
+        encoded.as_mut()[31] |= pk.x.is_odd().unwrap_u8() << 7;
+        assert_eq!(
+            encoded.as_ref(),
+            &hex_bytes("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a")[..]
+        );
+    }
+
+    #[test]
+    fn test_eddsa_native() {
+        let sk = Fq::random(OsRng);
+        let pk = Ed25519Affine::generator() * sk;
+        let challenge = Fq::random(OsRng);
+        let sig = sign(sk, Fq::random(OsRng), challenge);
+        assert!(verify(&sig, &pk, challenge));
+        assert!(!verify(&sig, &pk, challenge + Fq::ONE));
+    }
+
+    fn hex_bytes(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+}
@@ -0,0 +1,298 @@
+use super::integer::{IntegerChip, IntegerConfig};
+use crate::ecdsa::AssignedPublicKey;
+use crate::ed25519::{d, Ed25519Affine, Fp, Fq};
+use crate::halo2;
+use crate::integer;
+use crate::maingate;
+use ecc::maingate::RegionCtx;
+use ecc::AssignedPoint;
+use halo2::halo2curves::ff::{Field, PrimeField};
+use halo2::{circuit::Value, plonk::Error};
+use integer::rns::{Integer, Rns};
+use integer::{AssignedInteger, IntegerInstructions, Range};
+use maingate::{AssignedCondition, MainGate};
+use std::rc::Rc;
+
+pub struct AssignedEddsaSig<N: PrimeField, const NUMBER_OF_LIMBS: usize, const BIT_LEN_LIMB: usize>
+{
+    pub r: AssignedPoint<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    pub s: AssignedInteger<Fq, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+}
+
+/// Constrains Ed25519 signature verification.
+///
+/// Ed25519 is a twisted Edwards curve, which `GeneralEccChip` (short
+/// Weierstrass only) cannot emulate, so the group law is built here directly
+/// on the `IntegerChip` RNS arithmetic. Points reuse `AssignedPoint`.
+#[derive(Clone, Debug)]
+pub struct EddsaChip<N: PrimeField, const NUMBER_OF_LIMBS: usize, const BIT_LEN_LIMB: usize> {
+    base_field_chip: IntegerChip<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    scalar_field_chip: IntegerChip<Fq, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+}
+
+impl<N: PrimeField, const NUMBER_OF_LIMBS: usize, const BIT_LEN_LIMB: usize>
+    EddsaChip<N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>
+{
+    /// Residue numeral systems of the base and scalar field of Ed25519
+    pub fn rns() -> (
+        Rns<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        Rns<Fq, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    ) {
+        (Rns::construct(), Rns::construct())
+    }
+
+    pub fn new(config: IntegerConfig) -> Self {
+        let (rns_base_field, rns_scalar_field) = Self::rns();
+        Self {
+            base_field_chip: IntegerChip::new(config.clone(), Rc::new(rns_base_field)),
+            scalar_field_chip: IntegerChip::new(config, Rc::new(rns_scalar_field)),
+        }
+    }
+
+    pub fn base_field_chip(&self) -> &IntegerChip<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB> {
+        &self.base_field_chip
+    }
+
+    pub fn scalar_field_chip(&self) -> &IntegerChip<Fq, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB> {
+        &self.scalar_field_chip
+    }
+
+    pub fn main_gate(&self) -> &MainGate<N> {
+        self.base_field_chip.main_gate()
+    }
+
+    fn constant(&self, e: Fp) -> Integer<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB> {
+        Integer::from_fe(e, self.base_field_chip.rns())
+    }
+
+    pub fn assign_constant(
+        &self,
+        ctx: &mut RegionCtx<'_, N>,
+        point: Ed25519Affine,
+    ) -> Result<AssignedPoint<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>, Error> {
+        let ch = self.base_field_chip();
+        let x = ch.assign_constant(ctx, point.x)?;
+        let y = ch.assign_constant(ctx, point.y)?;
+        Ok(AssignedPoint::new(x, y))
+    }
+
+    /// Assigns a point and constrains it to be on the curve
+    pub fn assign_point(
+        &self,
+        ctx: &mut RegionCtx<'_, N>,
+        point: Value<Ed25519Affine>,
+    ) -> Result<AssignedPoint<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>, Error> {
+        let ch = self.base_field_chip();
+        let rns = ch.rns();
+        let (x, y) = point
+            .map(|point| {
+                (
+                    Integer::from_fe(point.x, rns.clone()),
+                    Integer::from_fe(point.y, rns.clone()),
+                )
+            })
+            .unzip();
+        let x = ch.assign_integer(ctx, x.into(), Range::Remainder)?;
+        let y = ch.assign_integer(ctx, y.into(), Range::Remainder)?;
+
+        let point = AssignedPoint::new(x, y);
+        self.assert_is_on_curve(ctx, &point)?;
+        Ok(point)
+    }
+
+    /// Constrains `-x^2 + y^2 = 1 + d * x^2 * y^2`
+    pub fn assert_is_on_curve(
+        &self,
+        ctx: &mut RegionCtx<'_, N>,
+        point: &AssignedPoint<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    ) -> Result<(), Error> {
+        let ch = self.base_field_chip();
+        let x_square = &ch.square(ctx, point.x())?;
+        let y_square = &ch.square(ctx, point.y())?;
+        let lhs = &ch.sub(ctx, y_square, x_square)?;
+        let t = &ch.mul(ctx, x_square, y_square)?;
+        let t = &ch.mul_constant(ctx, t, &self.constant(d()))?;
+        let rhs = &ch.add_constant(ctx, t, &self.constant(Fp::ONE))?;
+        ch.assert_equal(ctx, lhs, rhs)
+    }
+
+    pub fn assert_equal(
+        &self,
+        ctx: &mut RegionCtx<'_, N>,
+        p0: &AssignedPoint<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        p1: &AssignedPoint<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    ) -> Result<(), Error> {
+        let ch = self.base_field_chip();
+        ch.assert_equal(ctx, p0.x(), p1.x())?;
+        ch.assert_equal(ctx, p0.y(), p1.y())
+    }
+
+    /// Returns `p1` if `c` is set and `p2` otherwise
+    pub fn select(
+        &self,
+        ctx: &mut RegionCtx<'_, N>,
+        c: &AssignedCondition<N>,
+        p1: &AssignedPoint<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        p2: &AssignedPoint<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    ) -> Result<AssignedPoint<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>, Error> {
+        let ch = self.base_field_chip();
+        let x = ch.select(ctx, p1.x(), p2.x(), c)?;
+        let y = ch.select(ctx, p1.y(), p2.y(), c)?;
+        Ok(AssignedPoint::new(x, y))
+    }
+
+    pub fn neg(
+        &self,
+        ctx: &mut RegionCtx<'_, N>,
+        p: &AssignedPoint<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    ) -> Result<AssignedPoint<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>, Error> {
+        let x_neg = self.base_field_chip().neg(ctx, p.x())?;
+        Ok(AssignedPoint::new(x_neg, p.y().clone()))
+    }
+
+    /// Unified addition. Unlike the short Weierstrass formulas it is complete,
+    /// so the operands may be equal or the identity.
+    pub fn add(
+        &self,
+        ctx: &mut RegionCtx<'_, N>,
+        p0: &AssignedPoint<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        p1: &AssignedPoint<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    ) -> Result<AssignedPoint<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>, Error> {
+        let ch = self.base_field_chip();
+        let one = &self.constant(Fp::ONE);
+
+        // t = d * x_0 * x_1 * y_0 * y_1
+        let x0_y1 = &ch.mul(ctx, p0.x(), p1.y())?;
+        let y0_x1 = &ch.mul(ctx, p0.y(), p1.x())?;
+        let x0_x1 = &ch.mul(ctx, p0.x(), p1.x())?;
+        let y0_y1 = &ch.mul(ctx, p0.y(), p1.y())?;
+        let t = &ch.mul(ctx, x0_x1, y0_y1)?;
+        let t = &ch.mul_constant(ctx, t, &self.constant(d()))?;
+
+        // x = (x_0 * y_1 + y_0 * x_1) / (1 + t)
+        let numerator = &ch.add(ctx, x0_y1, y0_x1)?;
+        let denominator = &ch.add_constant(ctx, t, one)?;
+        let x = ch.div_incomplete(ctx, numerator, denominator)?;
+
+        // y = (y_0 * y_1 + x_0 * x_1) / (1 - t)
+        let numerator = &ch.add(ctx, y0_y1, x0_x1)?;
+        let t_neg = &ch.neg(ctx, t)?;
+        let denominator = &ch.add_constant(ctx, t_neg, one)?;
+        let y = ch.div_incomplete(ctx, numerator, denominator)?;
+
+        Ok(AssignedPoint::new(x, y))
+    }
+
+    /// Doubling, which saves the `d` term of `add` by using the curve equation
+    pub fn double(
+        &self,
+        ctx: &mut RegionCtx<'_, N>,
+        p: &AssignedPoint<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    ) -> Result<AssignedPoint<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>, Error> {
+        let ch = self.base_field_chip();
+        let x_square = &ch.square(ctx, p.x())?;
+        let y_square = &ch.square(ctx, p.y())?;
+
+        // x = 2 * x * y / (y^2 - x^2)
+        let xy = &ch.mul(ctx, p.x(), p.y())?;
+        let numerator = &ch.mul2(ctx, xy)?;
+        let denominator = &ch.sub(ctx, y_square, x_square)?;
+        let x = ch.div_incomplete(ctx, numerator, denominator)?;
+
+        // y = (y^2 + x^2) / (2 - y^2 + x^2)
+        let numerator = &ch.add(ctx, y_square, x_square)?;
+        let denominator = &ch.sub(ctx, x_square, y_square)?;
+        let denominator = &ch.add_constant(ctx, denominator, &self.constant(Fp::from(2)))?;
+        let y = ch.div_incomplete(ctx, numerator, denominator)?;
+
+        Ok(AssignedPoint::new(x, y))
+    }
+
+    /// Verifies `[S]B = R + [k]A` (cofactorless) for the public key `A`,
+    /// signature `(R, S)` and challenge `k = SHA-512(R || A || M) mod l`. The
+    /// challenge is an input, like the message hash of `EcdsaChip::verify`.
+    pub fn verify(
+        &self,
+        ctx: &mut RegionCtx<'_, N>,
+        sig: &AssignedEddsaSig<N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        pk: &AssignedPublicKey<Fp, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        challenge: &AssignedInteger<Fq, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    ) -> Result<(), Error> {
+        let scalar_chip = self.scalar_field_chip();
+
+        // 1. decompose S and k, which also checks S < l
+        let s_bits = scalar_chip.decompose(ctx, &sig.s)?;
+        let k_bits = scalar_chip.decompose(ctx, challenge)?;
+
+        // 2. table of [S]B + [k](-A) for one bit of each scalar
+        let identity = &self.assign_constant(ctx, Ed25519Affine::identity())?;
+        let generator = &self.assign_constant(ctx, Ed25519Affine::generator())?;
+        let pk_neg = &self.neg(ctx, &pk.point)?;
+        let generator_pk_neg = &self.add(ctx, generator, pk_neg)?;
+
+        // 3. shared double-and-add from the most significant bit. The formulas
+        // are complete, so starting from the identity needs no auxiliary point.
+        let mut acc = identity.clone();
+        for (s_bit, k_bit) in s_bits.iter().zip(k_bits.iter()).rev() {
+            acc = self.double(ctx, &acc)?;
+            let with_pk = self.select(ctx, s_bit, generator_pk_neg, pk_neg)?;
+            let without_pk = self.select(ctx, s_bit, generator, identity)?;
+            let to_add = self.select(ctx, k_bit, &with_pk, &without_pk)?;
+            acc = self.add(ctx, &acc, &to_add)?;
+        }
+
+        // 4. check [S]B - [k]A == R
+        self.assert_equal(ctx, &acc, &sig.r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ed25519::{sign, Ed25519Affine, Fq};
+    use crate::halo2;
+    use crate::maingate;
+    use crate::test::{eddsa_public_inputs, TestCircuitEddsaVerify};
+    use halo2::circuit::Value;
+    use halo2::halo2curves::ff::{Field, FromUniformBytes, PrimeField};
+    use maingate::mock_prover_verify;
+    use rand_core::OsRng;
+
+    fn test_circuit<N: PrimeField>(valid: bool) -> (TestCircuitEddsaVerify<N>, Vec<N>) {
+        let sk = Fq::random(OsRng);
+        let public_key = Ed25519Affine::generator() * sk;
+        let challenge = Fq::random(OsRng);
+        let sig = sign(sk, Fq::random(OsRng), challenge);
+        let challenge = if valid {
+            challenge
+        } else {
+            challenge + Fq::ONE
+        };
+
+        let circuit = TestCircuitEddsaVerify {
+            public_key: Value::known(public_key),
+            signature: Value::known((sig.r, sig.s)),
+            challenge: Value::known(challenge),
+            ..Default::default()
+        };
+        (circuit, eddsa_public_inputs(public_key, challenge))
+    }
+
+    #[test]
+    fn test_eddsa_verifier() {
+        fn run<N: FromUniformBytes<64> + Ord>() {
+            let (circuit, instance) = test_circuit::<N>(true);
+            mock_prover_verify(&circuit, vec![instance]);
+        }
+
+        use crate::curves::bn256::Fr as BnScalar;
+        run::<BnScalar>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_eddsa_verifier_wrong_challenge() {
+        use crate::curves::bn256::Fr as BnScalar;
+        let (circuit, instance) = test_circuit::<BnScalar>(false);
+        mock_prover_verify(&circuit, vec![instance]);
+    }
+}
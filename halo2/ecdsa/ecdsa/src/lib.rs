@@ -1,4 +1,6 @@
 pub mod ecdsa;
+pub mod ed25519;
+pub mod eddsa;
 pub mod test;
 
 pub(crate) use ecc::halo2;
@@ -2,17 +2,20 @@ use crate::halo2;
 use crate::integer;
 use crate::maingate;
 use crate::ecdsa::{AssignedEcdsaSig, AssignedPublicKey, EcdsaChip};
+use crate::eddsa::{AssignedEddsaSig, EddsaChip};
+use crate::ed25519::{self, Ed25519Affine, Fq as Ed25519Scalar};
 use crate::curves::bn256::{Fr as BnScalar, Bn256, G1Affine};
 use crate::curves::secp256k1::Secp256k1Affine as Secp256k1;
+use crate::curves::secp256r1::Secp256r1Affine as Secp256r1;
 use ark_std::{end_timer, start_timer};
-use ecc::halo2::halo2curves::secp256k1::Fp;
-use ecc::halo2::halo2curves::secp256k1::Fq;
 use ecc::halo2::plonk::ProvingKey;
 use ecc::integer::Range;
 use ecc::maingate::big_to_fe;
 use ecc::maingate::fe_to_big;
 use ecc::maingate::RegionCtx;
-use ecc::{EccConfig, GeneralEccChip};
+use ecc::integer::rns::Integer;
+use ecc::maingate::MainGateInstructions;
+use ecc::{EccConfig, GeneralEccChip, Point};
 use halo2::{
     dev::MockProver,
     transcript::{
@@ -35,23 +38,26 @@ use halo2::halo2curves::{
     group::{Curve, Group},
 };
 use halo2::plonk::{Circuit, ConstraintSystem, Error, create_proof, keygen_pk, keygen_vk, verify_proof, VerifyingKey};
-use integer::IntegerInstructions;
+use integer::{IntegerConfig, IntegerInstructions};
 use maingate::{MainGate, MainGateConfig, RangeChip, RangeConfig, RangeInstructions};
 use std::marker::PhantomData;
+use std::rc::Rc;
 use std::{
     fs::File,
     io::{BufReader, Write},
     path::Path,
+    time::Instant,
 };
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json;
+use zkperf_report::{BenchmarkResult, PhaseTracker};
 
 const BIT_LEN_LIMB: usize = 68;
 const NUMBER_OF_LIMBS: usize = 4;
 
 
 #[derive(Clone, Debug)]
-struct TestCircuitEcdsaVerifyConfig {
+pub(crate) struct TestCircuitEcdsaVerifyConfig {
     main_gate_config: MainGateConfig,
     range_config: RangeConfig,
 }
@@ -60,10 +66,25 @@ impl TestCircuitEcdsaVerifyConfig {
     pub fn new<C: CurveAffine, N: PrimeField>(meta: &mut ConstraintSystem<N>) -> Self {
         let (rns_base, rns_scalar) =
             GeneralEccChip::<C, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::rns();
-        let main_gate_config = MainGate::<N>::configure(meta);
         let mut overflow_bit_lens: Vec<usize> = vec![];
         overflow_bit_lens.extend(rns_base.overflow_lengths());
         overflow_bit_lens.extend(rns_scalar.overflow_lengths());
+        Self::with_overflow_lengths(meta, overflow_bit_lens)
+    }
+
+    pub fn new_eddsa<N: PrimeField>(meta: &mut ConstraintSystem<N>) -> Self {
+        let (rns_base, rns_scalar) = EddsaChip::<N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::rns();
+        let mut overflow_bit_lens: Vec<usize> = vec![];
+        overflow_bit_lens.extend(rns_base.overflow_lengths());
+        overflow_bit_lens.extend(rns_scalar.overflow_lengths());
+        Self::with_overflow_lengths(meta, overflow_bit_lens)
+    }
+
+    fn with_overflow_lengths<N: PrimeField>(
+        meta: &mut ConstraintSystem<N>,
+        overflow_bit_lens: Vec<usize>,
+    ) -> Self {
+        let main_gate_config = MainGate::<N>::configure(meta);
         let composition_bit_lens = vec![BIT_LEN_LIMB / NUMBER_OF_LIMBS];
 
         let range_config = RangeChip::<N>::configure(
@@ -82,6 +103,10 @@ impl TestCircuitEcdsaVerifyConfig {
         EccConfig::new(self.range_config.clone(), self.main_gate_config.clone())
     }
 
+    pub fn integer_chip_config(&self) -> IntegerConfig {
+        IntegerConfig::new(self.range_config.clone(), self.main_gate_config.clone())
+    }
+
     pub fn config_range<N: PrimeField>(
         &self,
         layouter: &mut impl Layouter<N>,
@@ -142,7 +167,7 @@ impl<E: CurveAffine, N: PrimeField> Circuit<N> for TestCircuitEcdsaVerify<E, N>
         let ecdsa_chip = EcdsaChip::new(ecc_chip.clone());
         let scalar_chip = ecc_chip.scalar_field_chip();
 
-        layouter.assign_region(
+        let public = layouter.assign_region(
             || "region 0",
             |region| {
                 let offset = 0;
@@ -168,16 +193,137 @@ impl<E: CurveAffine, N: PrimeField> Circuit<N> for TestCircuitEcdsaVerify<E, N>
                     point: pk_in_circuit,
                 };
                 let msg_hash = scalar_chip.assign_integer(ctx, msg_hash, Range::Remainder)?;
-                ecdsa_chip.verify(ctx, &sig, &pk_assigned, &msg_hash)
+                ecdsa_chip.verify(ctx, &sig, &pk_assigned, &msg_hash)?;
+                Ok((pk_assigned.point, msg_hash))
+            },
+        )?;
+
+        // The statement is "the signature verifies for this key and message hash", so the
+        // key and the hash are public. See `public_inputs` for the layout.
+        let (public_key, msg_hash) = public;
+        ecc_chip.expose_public(layouter.namespace(|| "public key"), public_key, 0)?;
+        let main_gate = ecc_chip.main_gate();
+        for (i, limb) in msg_hash.limbs().iter().enumerate() {
+            main_gate.expose_public(
+                layouter.namespace(|| "msg hash"),
+                limb.into(),
+                2 * NUMBER_OF_LIMBS + i,
+            )?;
+        }
+
+        config.config_range(&mut layouter)?;
+
+        Ok(())
+    }
+}
+
+/// Instance column of `TestCircuitEcdsaVerify`: the limbs of the public key x and y
+/// coordinates, followed by the limbs of the message hash.
+pub fn public_inputs<C: CurveAffine, N: PrimeField>(public_key: C, msg_hash: C::Scalar) -> Vec<N> {
+    let (rns_base, rns_scalar) = GeneralEccChip::<C, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::rns();
+    let mut public_data = Point::new(Rc::new(rns_base), public_key).public();
+    public_data.extend(Integer::from_fe(msg_hash, Rc::new(rns_scalar)).limbs());
+    public_data
+}
+
+#[derive(Default, Clone)]
+pub(crate) struct TestCircuitEddsaVerify<N: PrimeField> {
+    pub(crate) public_key: Value<Ed25519Affine>,
+    pub(crate) signature: Value<(Ed25519Affine, Ed25519Scalar)>,
+    pub(crate) challenge: Value<Ed25519Scalar>,
+    pub(crate) _marker: PhantomData<N>,
+}
+
+impl<N: PrimeField> Circuit<N> for TestCircuitEddsaVerify<N> {
+    type Config = TestCircuitEcdsaVerifyConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<N>) -> Self::Config {
+        TestCircuitEcdsaVerifyConfig::new_eddsa::<N>(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<N>,
+    ) -> Result<(), Error> {
+        let eddsa_chip =
+            EddsaChip::<N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::new(config.integer_chip_config());
+        let scalar_chip = eddsa_chip.scalar_field_chip();
+
+        let public = layouter.assign_region(
+            || "region 0",
+            |region| {
+                let offset = 0;
+                let ctx = &mut RegionCtx::new(region, offset);
+
+                let rns_scalar = scalar_chip.rns();
+                let integer_s = self
+                    .signature
+                    .map(|signature| Integer::from_fe(signature.1, rns_scalar.clone()));
+                let challenge = self
+                    .challenge
+                    .map(|challenge| Integer::from_fe(challenge, rns_scalar.clone()));
+
+                let r_assigned =
+                    eddsa_chip.assign_point(ctx, self.signature.map(|signature| signature.0))?;
+                let s_assigned =
+                    scalar_chip.assign_integer(ctx, integer_s.into(), Range::Remainder)?;
+                let sig = AssignedEddsaSig {
+                    r: r_assigned,
+                    s: s_assigned,
+                };
+
+                let pk_in_circuit = eddsa_chip.assign_point(ctx, self.public_key)?;
+                let pk_assigned = AssignedPublicKey {
+                    point: pk_in_circuit,
+                };
+                let challenge =
+                    scalar_chip.assign_integer(ctx, challenge.into(), Range::Remainder)?;
+                eddsa_chip.verify(ctx, &sig, &pk_assigned, &challenge)?;
+                Ok((pk_assigned.point, challenge))
             },
         )?;
 
+        // Same layout as the ECDSA circuit, with the challenge in place of the message hash
+        let (public_key, challenge) = public;
+        let main_gate = eddsa_chip.main_gate();
+        let limbs = public_key
+            .x()
+            .limbs()
+            .iter()
+            .chain(public_key.y().limbs().iter())
+            .chain(challenge.limbs().iter());
+        for (i, limb) in limbs.enumerate() {
+            main_gate.expose_public(layouter.namespace(|| "eddsa statement"), limb.into(), i)?;
+        }
+
         config.config_range(&mut layouter)?;
 
         Ok(())
     }
 }
 
+/// Instance column of `TestCircuitEddsaVerify`: the limbs of the public key x and y
+/// coordinates, followed by the limbs of the challenge.
+pub fn eddsa_public_inputs<N: PrimeField>(
+    public_key: Ed25519Affine,
+    challenge: Ed25519Scalar,
+) -> Vec<N> {
+    let (rns_base, rns_scalar) = EddsaChip::<N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::rns();
+    let rns_base = Rc::new(rns_base);
+    let mut public_data = Integer::from_fe(public_key.x, rns_base.clone()).limbs();
+    public_data.extend(Integer::from_fe(public_key.y, rns_base).limbs());
+    public_data.extend(Integer::from_fe(challenge, Rc::new(rns_scalar)).limbs());
+    public_data
+}
+
 pub fn get_kzg_params(params_dir: &str, degree: u32) -> ParamsKZG<Bn256> {
     let rng = rand::thread_rng();
     let path = format!("{}/{}.params", params_dir, degree);
@@ -204,6 +350,61 @@ pub fn serialize(data: &Vec<u8>, path: &str) -> u64 {
     file.metadata().unwrap().len()
 }
 
+/// Signature scheme and curve of a benchmark run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scheme {
+    Secp256k1,
+    P256,
+    Ed25519,
+}
+
+impl Scheme {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "secp256k1" => Some(Scheme::Secp256k1),
+            "p256" => Some(Scheme::P256),
+            "ed25519" => Some(Scheme::Ed25519),
+            _ => None,
+        }
+    }
+
+    /// Circuit name in the measurement json. secp256k1 keeps the original "ECDSA".
+    pub fn circuit_name(&self) -> &'static str {
+        match self {
+            Scheme::Secp256k1 => "ECDSA",
+            Scheme::P256 => "ECDSA-P256",
+            Scheme::Ed25519 => "EdDSA-Ed25519",
+        }
+    }
+
+    pub fn sample_path(&self) -> &'static str {
+        match self {
+            Scheme::Secp256k1 => "sample.json",
+            Scheme::P256 => "sample_p256.json",
+            Scheme::Ed25519 => "sample_ed25519.json",
+        }
+    }
+
+    /// log2 of the number of rows. The Ed25519 double-and-add does not fit in 2^18.
+    pub fn degree(&self) -> u32 {
+        match self {
+            Scheme::Secp256k1 | Scheme::P256 => 18,
+            Scheme::Ed25519 => 19,
+        }
+    }
+}
+
+fn from_bytes<F: PrimeField>(bytes: &[u8; 32]) -> F {
+    let mut repr = F::Repr::default();
+    repr.as_mut().copy_from_slice(bytes);
+    F::from_repr(repr).unwrap()
+}
+
+fn to_bytes<F: PrimeField>(e: F) -> [u8; 32] {
+    e.to_repr().as_ref().try_into().unwrap()
+}
+
+/// ECDSA sample, for any curve whose fields have a 32-byte little-endian encoding
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CircuitInput {
     pub pub_key: ([u8; 32], [u8; 32]),
@@ -213,169 +414,264 @@ pub struct CircuitInput {
     pub aux: ([u8; 32], [u8; 32]),
 }
 
+impl CircuitInput {
+    fn generate<C: CurveAffine>() -> Self {
+        fn mod_n<C: CurveAffine>(x: C::Base) -> C::Scalar {
+            let x_big = fe_to_big(x);
+            big_to_fe(x_big)
+        }
 
+        let rng = rand::thread_rng();
+        let g = C::generator();
 
-pub fn test_ecdsa_verifier(step: String) {
-    fn mod_n<C: CurveAffine>(x: C::Base) -> C::Scalar {
-        let x_big = fe_to_big(x);
-        big_to_fe(x_big)
+        // Generate a key pair
+        let sk = <C as CurveAffine>::ScalarExt::random(rng.clone());
+        let public_key = (g * sk).to_affine();
+
+        // Generate a valid signature
+        // Suppose `m_hash` is the message hash
+        let msg_hash = <C as CurveAffine>::ScalarExt::random(rng.clone());
+
+        // Draw arandomness
+        let k = <C as CurveAffine>::ScalarExt::random(rng.clone());
+        let k_inv = k.invert().unwrap();
+
+        // Calculate `r`
+        let r_point = (g * k).to_affine().coordinates().unwrap();
+        let x = r_point.x();
+        let r = mod_n::<C>(*x);
+
+        // Calculate `s`
+        let s = k_inv * (msg_hash + (r * sk));
+
+        let aux_generator = <C as CurveAffine>::CurveExt::random(rng.clone()).to_affine();
+
+        let public_key = public_key.coordinates().unwrap();
+        let aux_generator = aux_generator.coordinates().unwrap();
+        CircuitInput {
+            pub_key: (to_bytes(*public_key.x()), to_bytes(*public_key.y())),
+            msg_hash: to_bytes(msg_hash),
+            sig_r: to_bytes(r),
+            sig_s: to_bytes(s),
+            aux: (to_bytes(*aux_generator.x()), to_bytes(*aux_generator.y())),
+        }
     }
 
-    fn run(step: String) {
+    fn public_key<C: CurveAffine>(&self) -> C {
+        C::from_xy(from_bytes(&self.pub_key.0), from_bytes(&self.pub_key.1)).unwrap()
+    }
 
-        type C = Secp256k1;
-        type N = BnScalar;
+    fn msg_hash<C: CurveAffine>(&self) -> C::Scalar {
+        from_bytes(&self.msg_hash)
+    }
 
-        if step == "generate" {
-            let rng = rand::thread_rng();
-            let g = C::generator();
-    
-            // Generate a key pair
-            let sk = <C as CurveAffine>::ScalarExt::random(rng.clone());
-            let public_key = (g * sk).to_affine();
+    fn public_inputs<C: CurveAffine>(&self) -> Vec<BnScalar> {
+        public_inputs::<C, BnScalar>(self.public_key(), self.msg_hash::<C>())
+    }
 
-            // Generate a valid signature
-            // Suppose `m_hash` is the message hash
-            let msg_hash = <C as CurveAffine>::ScalarExt::random(rng.clone());
-    
-            // Draw arandomness
-            let k = <C as CurveAffine>::ScalarExt::random(rng.clone());
-            let k_inv = k.invert().unwrap();
-    
-            // Calculate `r`
-            let r_point = (g * k).to_affine().coordinates().unwrap();
-            let x = r_point.x();
-            let r = mod_n::<C>(*x);
-    
-            // Calculate `s`
-            let s = k_inv * (msg_hash + (r * sk));
-    
-            let aux_generator = <Secp256k1 as CurveAffine>::CurveExt::random(rng.clone()).to_affine();
-            
-            let rand_input = CircuitInput {
-                pub_key: (public_key.x.to_bytes(), public_key.y.to_bytes()),
-                msg_hash: msg_hash.to_bytes(),
-                sig_r: r.to_bytes(),
-                sig_s: s.to_bytes(),
-                aux: (aux_generator.x.to_bytes(), aux_generator.y.to_bytes())
-            };
-
-            serde_json::to_writer(
-                File::create("sample.json").unwrap(), 
-                &rand_input).unwrap();
-            
-        } else if step == "setup" {
-            let circuit_input: CircuitInput = serde_json::from_reader(
-                File::open("sample.json").unwrap()
-            ).unwrap();
-
-            let circuit = TestCircuitEcdsaVerify::<C, N> {
-                public_key: Value::known(Secp256k1::from_xy(
-                    Fp::from_bytes(&circuit_input.pub_key.0).unwrap(), 
-                    Fp::from_bytes(&circuit_input.pub_key.1).unwrap()
-                ).unwrap()),
-                signature: Value::known((
-                    Fq::from_bytes(&circuit_input.sig_r).unwrap(), 
-                    Fq::from_bytes(&circuit_input.sig_s).unwrap()
-                )),
-                msg_hash: Value::known(
-                    Fq::from_bytes(&circuit_input.msg_hash).unwrap()
-                ),
-                aux_generator: Secp256k1::from_xy(
-                    Fp::from_bytes(&circuit_input.aux.0).unwrap(), 
-                    Fp::from_bytes(&circuit_input.aux.1).unwrap()
-                ).unwrap(),
-                window_size: 4,
-                ..Default::default()
-            };
-            
-            let degree = 18 as u32;
-            let params = get_kzg_params("./params_kzg", degree);
-            let vk_circuit = circuit.clone();
-            let vk = keygen_vk(&params, &vk_circuit).unwrap();
-            drop(vk_circuit);
-            let _ = serialize(&vk.to_bytes(SerdeFormat::RawBytes), "vkey");
-            let pk_circuit = circuit.clone();
-            let pk = keygen_pk(&params, vk.clone(), &pk_circuit).unwrap();
-            drop(pk_circuit);
-            let _ = serialize(&pk.to_bytes(SerdeFormat::RawBytes), "pkey");
-            let proof_circuit = circuit.clone();
-            let _prover = MockProver::run(degree, &proof_circuit, vec![vec![]]).unwrap();
-        } else if step == "prove" {
-            let rng = rand::thread_rng();
-            let circuit_input: CircuitInput = serde_json::from_reader(
-                File::open("sample.json").unwrap()
-            ).unwrap();
-            let circuit = TestCircuitEcdsaVerify::<C, N> {
-                public_key: Value::known(Secp256k1::from_xy(
-                    Fp::from_bytes(&circuit_input.pub_key.0).unwrap(), 
-                    Fp::from_bytes(&circuit_input.pub_key.1).unwrap()
-                ).unwrap()),
-                signature: Value::known((
-                    Fq::from_bytes(&circuit_input.sig_r).unwrap(), 
-                    Fq::from_bytes(&circuit_input.sig_s).unwrap()
-                )),
-                msg_hash: Value::known(
-                    Fq::from_bytes(&circuit_input.msg_hash).unwrap()
-                ),
-                aux_generator: Secp256k1::from_xy(
-                    Fp::from_bytes(&circuit_input.aux.0).unwrap(), 
-                    Fp::from_bytes(&circuit_input.aux.1).unwrap()
-                ).unwrap(),
-                window_size: 4,
-                ..Default::default()
-            };
-            let degree = 18 as u32;
-            let params = get_kzg_params("./params_kzg", degree);
-            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
-            let pk = ProvingKey::read::<BufReader<File>, TestCircuitEcdsaVerify<C, N>>(
-                &mut BufReader::new(File::open("pkey").unwrap()),
-                SerdeFormat::RawBytes,
-              )
-              .unwrap();
-            create_proof::<
-              KZGCommitmentScheme<Bn256>,
-              ProverSHPLONK<'_, Bn256>,
-              Challenge255<G1Affine>,
-              _,
-              Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
-              TestCircuitEcdsaVerify<C, N>,
-            >(
-              &params,
-              &pk,
-              &[circuit],
-              &[&[&[]]],
-              rng.clone(),
-              &mut transcript,
-            )
-            .unwrap();
-            let proof = transcript.finalize();
-            let _ = serialize(&proof, "proof");
-        } else if step == "verify" {
-            let degree = 18 as u32;
-            let params = get_kzg_params("./params_kzg", degree);
-            let proof = std::fs::read("proof").unwrap();  
-            let strategy = SingleStrategy::new(&params);
-            let mut transcript_read = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
-            let vk = VerifyingKey::read::<BufReader<File>, TestCircuitEcdsaVerify::<C, N>>(
-                &mut BufReader::new(File::open("vkey").unwrap()),
-                SerdeFormat::RawBytes,
-              )
-              .unwrap();
-            assert!(
-            verify_proof::<
-                KZGCommitmentScheme<Bn256>,
-                VerifierSHPLONK<'_, Bn256>,
-                Challenge255<G1Affine>,
-                Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
-                SingleStrategy<'_, Bn256>,
-            >(&params, &vk, strategy, &[&[&[]]], &mut transcript_read)
-            .is_ok());
+    fn circuit<C: CurveAffine>(&self) -> TestCircuitEcdsaVerify<C, BnScalar> {
+        TestCircuitEcdsaVerify {
+            public_key: Value::known(self.public_key()),
+            signature: Value::known((from_bytes(&self.sig_r), from_bytes(&self.sig_s))),
+            msg_hash: Value::known(self.msg_hash::<C>()),
+            aux_generator: C::from_xy(from_bytes(&self.aux.0), from_bytes(&self.aux.1))
+                .unwrap(),
+            window_size: 4,
+            ..Default::default()
         }
     }
+}
 
-    run(step);
-    
+/// EdDSA sample. `challenge` stands in for `SHA-512(R || A || M) mod l`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EddsaCircuitInput {
+    pub pub_key: ([u8; 32], [u8; 32]),
+    pub challenge: [u8; 32],
+    pub sig_r: ([u8; 32], [u8; 32]),
+    pub sig_s: [u8; 32],
+}
+
+impl EddsaCircuitInput {
+    fn generate() -> Self {
+        let rng = rand::thread_rng();
+        let sk = Ed25519Scalar::random(rng.clone());
+        let public_key = Ed25519Affine::generator() * sk;
+        let challenge = Ed25519Scalar::random(rng.clone());
+        let sig = ed25519::sign(sk, Ed25519Scalar::random(rng.clone()), challenge);
+        assert!(ed25519::verify(&sig, &public_key, challenge));
+
+        EddsaCircuitInput {
+            pub_key: (to_bytes(public_key.x), to_bytes(public_key.y)),
+            challenge: to_bytes(challenge),
+            sig_r: (to_bytes(sig.r.x), to_bytes(sig.r.y)),
+            sig_s: to_bytes(sig.s),
+        }
+    }
+
+    fn point(coordinates: &([u8; 32], [u8; 32])) -> Ed25519Affine {
+        Ed25519Affine::from_xy(from_bytes(&coordinates.0), from_bytes(&coordinates.1)).unwrap()
+    }
+
+    fn public_inputs(&self) -> Vec<BnScalar> {
+        eddsa_public_inputs(Self::point(&self.pub_key), from_bytes(&self.challenge))
+    }
+
+    fn circuit(&self) -> TestCircuitEddsaVerify<BnScalar> {
+        TestCircuitEddsaVerify {
+            public_key: Value::known(Self::point(&self.pub_key)),
+            signature: Value::known((Self::point(&self.sig_r), from_bytes(&self.sig_s))),
+            challenge: Value::known(from_bytes(&self.challenge)),
+            ..Default::default()
+        }
+    }
+}
+
+fn read_sample<T: DeserializeOwned>(path: &str) -> T {
+    serde_json::from_reader(File::open(path).unwrap()).unwrap()
+}
+
+fn write_sample<T: Serialize>(path: &str, input: &T) {
+    serde_json::to_writer(File::create(path).unwrap(), input).unwrap();
+}
+
+pub fn test_ecdsa_verifier(step: String, outp_json: String, scheme: Scheme) {
+    let results =
+        BenchmarkResult::load_or_new(&outp_json, "Halo2", scheme.circuit_name(), "Plonk", "BN254");
+    let path = scheme.sample_path();
+    match scheme {
+        Scheme::Secp256k1 => run::<TestCircuitEcdsaVerify<Secp256k1, BnScalar>>(
+            &step,
+            &outp_json,
+            results,
+            scheme.degree(),
+            || write_sample(path, &CircuitInput::generate::<Secp256k1>()),
+            || {
+                let input: CircuitInput = read_sample(path);
+                (input.circuit::<Secp256k1>(), input.public_inputs::<Secp256k1>())
+            },
+        ),
+        Scheme::P256 => run::<TestCircuitEcdsaVerify<Secp256r1, BnScalar>>(
+            &step,
+            &outp_json,
+            results,
+            scheme.degree(),
+            || write_sample(path, &CircuitInput::generate::<Secp256r1>()),
+            || {
+                let input: CircuitInput = read_sample(path);
+                (input.circuit::<Secp256r1>(), input.public_inputs::<Secp256r1>())
+            },
+        ),
+        Scheme::Ed25519 => run::<TestCircuitEddsaVerify<BnScalar>>(
+            &step,
+            &outp_json,
+            results,
+            scheme.degree(),
+            || write_sample(path, &EddsaCircuitInput::generate()),
+            || {
+                let input: EddsaCircuitInput = read_sample(path);
+                (input.circuit(), input.public_inputs())
+            },
+        ),
+    }
+}
+
+/// Runs one step of the benchmark. `generate` writes a sample and `load` reads it back as
+/// the circuit and its instance column.
+fn run<C: Circuit<BnScalar> + Clone>(
+    step: &str,
+    outp_json: &str,
+    mut results: BenchmarkResult,
+    degree: u32,
+    generate: impl FnOnce(),
+    load: impl FnOnce() -> (C, Vec<BnScalar>),
+) {
+    if step == "generate" {
+        generate();
+    } else if step == "setup" {
+        let (circuit, instances) = load();
+
+        let params = get_kzg_params("./params_kzg", degree);
+        let tracker = PhaseTracker::start("keygen");
+        let vk_circuit = circuit.clone();
+        let vk = keygen_vk(&params, &vk_circuit).unwrap();
+        drop(vk_circuit);
+        let _ = serialize(&vk.to_bytes(SerdeFormat::RawBytes), "vkey");
+        let pk_circuit = circuit.clone();
+        let pk = keygen_pk(&params, vk.clone(), &pk_circuit).unwrap();
+        drop(pk_circuit);
+        let _ = serialize(&pk.to_bytes(SerdeFormat::RawBytes), "pkey");
+        results.record_phase(tracker.finish());
+        // MockProver checks the sample against the constraints; this is not the prover's witness
+        // generation, which happens inside create_proof
+        let tracker = PhaseTracker::start("check");
+        let proof_circuit = circuit.clone();
+        let _prover = MockProver::run(degree, &proof_circuit, vec![instances]).unwrap();
+        results.record_phase(tracker.finish());
+
+        results.set_k(degree, vk.cs().num_advice_columns());
+        results.write(outp_json).unwrap();
+    } else if step == "prove" {
+        let rng = rand::thread_rng();
+        let (circuit, instances) = load();
+        let params = get_kzg_params("./params_kzg", degree);
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        let pk = ProvingKey::read::<BufReader<File>, C>(
+            &mut BufReader::new(File::open("pkey").unwrap()),
+            SerdeFormat::RawBytes,
+        )
+        .unwrap();
+        let tracker = PhaseTracker::start("prove");
+        let start = Instant::now();
+        create_proof::<
+            KZGCommitmentScheme<Bn256>,
+            ProverSHPLONK<'_, Bn256>,
+            Challenge255<G1Affine>,
+            _,
+            Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
+            C,
+        >(
+            &params,
+            &pk,
+            &[circuit],
+            &[&[&instances]],
+            rng.clone(),
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.finalize();
+        results.set_prover_time(start.elapsed());
+        results.record_phase(tracker.finish());
+        let proof_size = serialize(&proof, "proof");
+
+        results.set_proof_size(proof_size as usize);
+        results.record_peak_rss();
+        results.write(outp_json).unwrap();
+    } else if step == "verify" {
+        // The verifier only needs the statement, not the signature
+        let (_, instances) = load();
+        let params = get_kzg_params("./params_kzg", degree);
+        let proof = std::fs::read("proof").unwrap();
+        let strategy = SingleStrategy::new(&params);
+        let mut transcript_read = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        let vk = VerifyingKey::read::<BufReader<File>, C>(
+            &mut BufReader::new(File::open("vkey").unwrap()),
+            SerdeFormat::RawBytes,
+        )
+        .unwrap();
+        let tracker = PhaseTracker::start("verify");
+        let start = Instant::now();
+        assert!(verify_proof::<
+            KZGCommitmentScheme<Bn256>,
+            VerifierSHPLONK<'_, Bn256>,
+            Challenge255<G1Affine>,
+            Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
+            SingleStrategy<'_, Bn256>,
+        >(&params, &vk, strategy, &[&[&instances]], &mut transcript_read)
+        .is_ok());
+        results.set_verifier_time(start.elapsed());
+        results.record_phase(tracker.finish());
+        results.write(outp_json).unwrap();
+    }
 }
 
 pub fn breakdown_ecdsa() {
@@ -423,6 +719,7 @@ pub fn breakdown_ecdsa() {
         ..Default::default()
     };
     
+    let instances = public_inputs::<C, N>(public_key, msg_hash);
     let degree = 18 as u32;
     let params = get_kzg_params("./params_kzg", degree);
     // end_timer!(timer);
@@ -437,7 +734,7 @@ pub fn breakdown_ecdsa() {
     drop(pk_circuit);
 
     let proof_circuit = circuit.clone();
-    let _prover = MockProver::run(degree, &proof_circuit, vec![vec![]]).unwrap();
+    let _prover = MockProver::run(degree, &proof_circuit, vec![instances.clone()]).unwrap();
     // end_timer!(timer);
 
     // let timer = start_timer!(|| "Prove");
@@ -454,7 +751,7 @@ pub fn breakdown_ecdsa() {
         &params,
         &pk,
         &[circuit],
-        &[&[&[]]],
+        &[&[&instances]],
         rng.clone(),
         &mut transcript,
     )
@@ -473,8 +770,63 @@ pub fn breakdown_ecdsa() {
         Challenge255<G1Affine>,
         Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
         SingleStrategy<'_, Bn256>,
-    >(&params, &vk, strategy, &[&[&[]]], &mut transcript_read)
+    >(&params, &vk, strategy, &[&[&instances]], &mut transcript_read)
     .is_ok());
     // end_timer!(timer);
     
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_input() -> CircuitInput {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../sample.json");
+        serde_json::from_reader(File::open(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_public_key_and_msg_hash_are_public() {
+        let circuit_input = sample_input();
+        let circuit = circuit_input.circuit::<Secp256k1>();
+        let instances = circuit_input.public_inputs::<Secp256k1>();
+        assert_eq!(instances.len(), 3 * NUMBER_OF_LIMBS);
+
+        let prover = MockProver::run(18, &circuit, vec![instances.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // A proof for this signature does not verify another message hash
+        let mut wrong_instances = instances;
+        wrong_instances[2 * NUMBER_OF_LIMBS] += BnScalar::ONE;
+        let prover = MockProver::run(18, &circuit, vec![wrong_instances]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_p256_sample_verifies() {
+        let circuit_input = CircuitInput::generate::<Secp256r1>();
+        let circuit = circuit_input.circuit::<Secp256r1>();
+        let instances = circuit_input.public_inputs::<Secp256r1>();
+
+        let prover = MockProver::run(Scheme::P256.degree(), &circuit, vec![instances]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_ed25519_sample_verifies() {
+        let circuit_input = EddsaCircuitInput::generate();
+        let circuit = circuit_input.circuit();
+        let instances = circuit_input.public_inputs();
+        assert_eq!(instances.len(), 3 * NUMBER_OF_LIMBS);
+
+        let degree = Scheme::Ed25519.degree();
+        let prover = MockProver::run(degree, &circuit, vec![instances.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The signature does not verify under another challenge
+        let mut wrong_instances = instances;
+        wrong_instances[2 * NUMBER_OF_LIMBS] += BnScalar::ONE;
+        let prover = MockProver::run(degree, &circuit, vec![wrong_instances]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
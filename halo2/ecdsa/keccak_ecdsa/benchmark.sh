@@ -0,0 +1,31 @@
+#!/bin/bash
+
+# Usage: ./benchmark.sh [msg_len]
+MSG_LEN=${1:-64}
+BUILD_DIR=.
+OUTPUT="ecdsa_keccak_measurement.json"
+EXECUTABLE="./target/release/keccak_ecdsa"
+CIRCUIT="ecdsa_keccak"
+K=18
+export LOOKUP_BITS=17
+
+mkdir -p data params
+cargo build --release
+
+rm -f "$BUILD_DIR"/"$OUTPUT"
+
+echo "****GENERATING SAMPLE INPUT ($MSG_LEN BYTES)****"
+./target/release/generate_sample "$MSG_LEN" data/"$CIRCUIT".json
+
+echo "****Setup****"
+$EXECUTABLE --name $CIRCUIT -k $K --output "$BUILD_DIR"/"$OUTPUT" mock
+$EXECUTABLE --name $CIRCUIT -k $K --output "$BUILD_DIR"/"$OUTPUT" keygen
+
+echo "****GENERATING PROOF FOR SAMPLE INPUT****"
+$EXECUTABLE --name $CIRCUIT -k $K --output "$BUILD_DIR"/"$OUTPUT" prove > ecdsa_keccak.log
+echo "Proof Size: $(stat -c %s "$BUILD_DIR"/data/"$CIRCUIT".proof)bytes"
+echo "DONE ($(jq .ProverTime "$BUILD_DIR"/"$OUTPUT")s)"
+
+echo "****VERIFYING PROOF FOR SAMPLE INPUT****"
+$EXECUTABLE --name $CIRCUIT -k $K --output "$BUILD_DIR"/"$OUTPUT" verify
+echo "DONE ($(jq .VerifierTime "$BUILD_DIR"/"$OUTPUT")ms)"
@@ -0,0 +1,9 @@
+#!/bin/bash
+
+rustup toolchain install nightly-2022-10-28
+rustup override set nightly-2022-10-28
+cargo update -p clap --precise 4.0.13
+cargo update -p rust_decimal --precise 1.25.0
+cargo update -p toml_datetime --precise 0.6.3
+
+cargo build --release
\ No newline at end of file
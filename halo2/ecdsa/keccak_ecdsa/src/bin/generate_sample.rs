@@ -0,0 +1,16 @@
+use std::fs::File;
+
+use keccak_ecdsa::ecdsa_keccak::CircuitInput;
+
+// Writes a signature over a random message of the given length, as read by `keccak_ecdsa`
+fn main() {
+    let msg_len = std::env::args().nth(1).map_or(64, |len| {
+        len.parse::<usize>().expect("Message length in bytes")
+    });
+    let outp_json = std::env::args()
+        .nth(2)
+        .unwrap_or_else(|| "data/ecdsa_keccak.json".to_string());
+
+    let input = CircuitInput::generate(msg_len);
+    serde_json::to_writer(File::create(outp_json).unwrap(), &input).unwrap();
+}
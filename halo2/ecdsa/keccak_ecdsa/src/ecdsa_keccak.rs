@@ -0,0 +1,186 @@
+use axiom_eth::{keccak::KeccakChip, EthChip};
+use halo2_base::{
+    gates::{GateInstructions, RangeInstructions},
+    halo2_proofs::halo2curves::{
+        bn256::Fr,
+        group::{
+            ff::{Field, PrimeField},
+            Curve, Group,
+        },
+        secp256k1::{Fp, Fq, Secp256k1, Secp256k1Affine},
+    },
+    utils::{biguint_to_fe, fe_to_biguint, modulus, ScalarField},
+    AssignedValue, Context,
+    QuantumCell::Constant,
+};
+use halo2_ecc::{
+    bigint::ProperCrtUint,
+    ecc::{ecdsa::ecdsa_verify_no_pubkey_check, EccChip},
+    fields::FieldChip,
+    secp256k1::{FpChip, FqChip},
+};
+use num_bigint::BigUint;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+// The secp256k1 configuration of halo2-ecc's ECDSA circuit
+const LIMB_BITS: usize = 88;
+const NUM_LIMBS: usize = 3;
+const WINDOW_BITS: usize = 4;
+
+/// A secp256k1 signature over raw message bytes. Field elements are little-endian, as in the
+/// halo2wrong benchmark's `sample.json`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CircuitInput {
+    pub msg: Vec<u8>,
+    pub pub_key: ([u8; 32], [u8; 32]),
+    pub sig_r: [u8; 32],
+    pub sig_s: [u8; 32],
+}
+
+impl CircuitInput {
+    /// Signs a random message of `msg_len` bytes with a random key
+    pub fn generate(msg_len: usize) -> Self {
+        let mut rng = rand::thread_rng();
+        let msg = (0..msg_len).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+        let msg_hash = digest_to_scalar_native(&Keccak256::digest(&msg));
+
+        let sk = Fq::random(&mut rng);
+        let pub_key = (Secp256k1::generator() * sk).to_affine();
+        let k = Fq::random(&mut rng);
+        let r_point = (Secp256k1::generator() * k).to_affine();
+        let r = biguint_to_fe::<Fq>(&(fe_to_biguint(&r_point.x) % modulus::<Fq>()));
+        let s = k.invert().unwrap() * (msg_hash + r * sk);
+
+        Self {
+            msg,
+            pub_key: (pub_key.x.to_repr(), pub_key.y.to_repr()),
+            sig_r: r.to_repr(),
+            sig_s: s.to_repr(),
+        }
+    }
+}
+
+// The big-endian Keccak-256 digest reduced mod the group order, as Ethereum signs transactions
+fn digest_to_scalar_native(digest: &[u8]) -> Fq {
+    biguint_to_fe(&(BigUint::from_bytes_be(digest) % modulus::<Fq>()))
+}
+
+// Loads the big-endian digest bytes as a secp256k1 scalar: limb i holds bits
+// [i * LIMB_BITS, (i + 1) * LIMB_BITS) of the digest, packed from the bytes in the circuit. The
+// Keccak chip already constrains each byte to 8 bits. The loaded scalar is reduced mod the group
+// order, so a digest at or above the order (probability about 2^-128) can't be proved.
+fn digest_to_scalar(
+    ctx: &mut Context<Fr>,
+    fq_chip: &FqChip<Fr>,
+    digest: &[AssignedValue<Fr>],
+) -> ProperCrtUint<Fr> {
+    let gate = fq_chip.gate();
+    let bytes_le = digest.iter().rev().copied().collect::<Vec<_>>();
+    let limbs = bytes_le
+        .chunks(LIMB_BITS / 8)
+        .map(|chunk| {
+            let powers = (0..chunk.len()).map(|i| Constant(gate.pow_of_two()[8 * i]));
+            gate.inner_product(ctx, chunk.iter().copied(), powers)
+        })
+        .collect::<Vec<_>>();
+
+    let digest_bytes = digest
+        .iter()
+        .map(|byte| byte.value().get_lower_32() as u8)
+        .collect::<Vec<_>>();
+    let msg_hash = fq_chip.load_private(ctx, digest_to_scalar_native(&digest_bytes));
+    assert_eq!(msg_hash.limbs().len(), limbs.len());
+    for (limb, packed) in msg_hash.limbs().iter().zip(limbs.iter()) {
+        ctx.constrain_equal(limb, packed);
+    }
+    msg_hash
+}
+
+/// Verifies an ECDSA signature over the message bytes, which are hashed with Keccak-256 in the
+/// circuit. The public inputs are the message bytes followed by the limbs of the public key x and
+/// y coordinates.
+///
+/// The message length is fixed by the input, so the circuit depends on it.
+pub fn ecdsa_keccak(
+    ctx: &mut Context<Fr>,
+    eth_chip: &EthChip<Fr>,
+    keccak: &mut KeccakChip<Fr>,
+    input: CircuitInput,
+    make_public: &mut Vec<AssignedValue<Fr>>,
+) -> impl FnOnce(&mut Context<Fr>, &mut Context<Fr>, &EthChip<Fr>) + Clone {
+    let range = eth_chip.range();
+
+    let msg = ctx.assign_witnesses(input.msg.iter().map(|b| Fr::from(*b as u64)));
+    for byte in &msg {
+        make_public.push(*byte);
+        range.range_check(ctx, *byte, 8);
+    }
+    // Like the Merkle circuit, this only assigns the digest; the permutation is constrained in the
+    // second phase by the scaffold
+    let hash_idx = keccak.keccak_fixed_len(ctx, range.gate(), msg, Some(input.msg));
+    let digest = keccak.fixed_len_queries[hash_idx].output_assigned.clone();
+    assert_eq!(digest.len(), 32);
+
+    let fp_chip = FpChip::<Fr>::new(range, LIMB_BITS, NUM_LIMBS);
+    let fq_chip = FqChip::<Fr>::new(range, LIMB_BITS, NUM_LIMBS);
+    let msg_hash = digest_to_scalar(ctx, &fq_chip, &digest);
+    let [r, s] = [input.sig_r, input.sig_s]
+        .map(|x| fq_chip.load_private(ctx, Fq::from_repr(x).expect("signature out of range")));
+
+    let ecc_chip = EccChip::<Fr, FpChip<Fr>>::new(&fp_chip);
+    let (x, y) = input.pub_key;
+    let pub_key = ecc_chip.load_private_unchecked(
+        ctx,
+        (
+            Fp::from_repr(x).expect("public key out of range"),
+            Fp::from_repr(y).expect("public key out of range"),
+        ),
+    );
+    ecc_chip.assert_is_on_curve::<Secp256k1Affine>(ctx, &pub_key);
+    for limb in pub_key.x().limbs().iter().chain(pub_key.y().limbs()) {
+        make_public.push(*limb);
+    }
+
+    let valid = ecdsa_verify_no_pubkey_check::<Fr, Fp, Fq, Secp256k1Affine>(
+        &ecc_chip,
+        ctx,
+        pub_key,
+        r,
+        s,
+        msg_hash,
+        WINDOW_BITS,
+        WINDOW_BITS,
+    );
+    range.gate().assert_is_const(ctx, &valid, &Fr::from(1));
+
+    // Keccak is constrained behind the scenes in the second phase, nothing else uses the challenge
+    #[allow(clippy::let_and_return)]
+    let callback =
+        |_ctx_gate: &mut Context<Fr>, _ctx_rlc: &mut Context<Fr>, _eth_chip: &EthChip<Fr>| {};
+
+    callback
+}
+
+#[cfg(test)]
+mod tests {
+    use merkle_tree::scaffold::mock_eth;
+
+    use super::{ecdsa_keccak, CircuitInput};
+
+    const K: u32 = 18;
+
+    #[test]
+    fn verifies_signature_over_message() {
+        let input = CircuitInput::generate(64);
+        mock_eth(ecdsa_keccak, K, input).unwrap();
+    }
+
+    #[test]
+    fn rejects_other_message() {
+        let mut input = CircuitInput::generate(64);
+        input.msg[0] ^= 1;
+        assert!(mock_eth(ecdsa_keccak, K, input).is_err());
+    }
+}
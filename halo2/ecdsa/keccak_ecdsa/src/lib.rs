@@ -0,0 +1 @@
+pub mod ecdsa_keccak;
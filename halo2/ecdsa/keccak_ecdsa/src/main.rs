@@ -0,0 +1,13 @@
+use clap::Parser;
+use jemallocator::Jemalloc;
+use keccak_ecdsa::ecdsa_keccak::ecdsa_keccak;
+use merkle_tree::{cmd::Cli, scaffold::run_eth};
+
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+fn main() {
+    env_logger::init();
+    let args = Cli::parse();
+    run_eth(ecdsa_keccak, args);
+}
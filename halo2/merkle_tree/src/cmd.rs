@@ -29,4 +29,7 @@ pub struct Cli {
     pub config_path: Option<PathBuf>,
     #[arg(short, long = "data-path")]
     pub data_path: Option<PathBuf>,
+    /// Benchmark result JSON, updated by the keygen, prove and verify steps
+    #[arg(short, long = "output")]
+    pub output_path: Option<PathBuf>,
 }
\ No newline at end of file
@@ -0,0 +1,8 @@
+#![allow(unused_imports)]
+#![feature(return_position_impl_trait_in_trait)]
+#![allow(incomplete_features)]
+pub mod keccak_merkle;
+pub mod keccak_one;
+pub mod cmd;
+pub mod scaffold;
+pub mod test_circuit;
@@ -1,20 +1,10 @@
-#![allow(unused_imports)]
-#![feature(return_position_impl_trait_in_trait)]
-#![allow(incomplete_features)]
-pub mod keccak_merkle;
-pub mod keccak_one;
-pub mod cmd;
-pub mod scaffold;
-pub mod test_circuit;
-
-
-use cmd::Cli;
 use clap::Parser;
-use keccak_merkle::merkle_tree_keccak;
-use keccak_one::compute_fixed_len_keccak;
-use scaffold::run_eth;
-// use test_circuit::run_merkle;
+use jemallocator::Jemalloc;
+use merkle_tree::{cmd::Cli, keccak_merkle::merkle_tree_keccak, scaffold::run_eth};
+// use merkle_tree::test_circuit::run_merkle;
 
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
 
 fn main() {
     env_logger::init();
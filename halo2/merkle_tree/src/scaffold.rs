@@ -24,7 +24,7 @@ use bincode;
 use halo2_base::{
     gates::builder::{CircuitBuilderStage, GateThreadBuilder},
     halo2_proofs::{
-        dev::MockProver,
+        dev::{MockProver, VerifyFailure},
         halo2curves::bn256::{Bn256, Fr, G1Affine},
         plonk::{create_proof, verify_proof, Circuit, VerifyingKey, ProvingKey, keygen_pk, keygen_vk},
         poly::{
@@ -50,6 +50,7 @@ use snark_verifier_sdk::{
     halo2::{gen_snark_shplonk, read_snark, PoseidonTranscript},
     CircuitExt, NativeLoader, read_pk
 };
+use zkperf_report::{BenchmarkResult, PhaseTracker};
 use crate::cmd::{Cli, SnarkCmd};
 
 #[derive(Clone)]
@@ -176,6 +177,48 @@ where
     run_cli(precircuit, cli);
 }
 
+/// Runs the mock prover at degree `k` on the circuit built by `f`, returning the failed
+/// constraints instead of panicking. No proving key or files are involved, so tests can use it.
+pub fn mock_eth<T, FN, F1>(f: FN, k: u32, private_inputs: T) -> Result<(), Vec<VerifyFailure>>
+where
+    FN: FnOnce(
+        &mut Context<Fr>,
+        &EthChip<Fr>,
+        &mut KeccakChip<Fr>,
+        T,
+        &mut Vec<AssignedValue<Fr>>,
+    ) -> F1 + Clone,
+    F1: FnOnce(&mut Context<Fr>, &mut Context<Fr>, &EthChip<Fr>) + Clone,
+{
+    mock_eth_builder(
+        |builder, chip, keccak, inp, public| f(builder.main(0), chip, keccak, inp, public),
+        k,
+        private_inputs,
+    )
+}
+
+pub fn mock_eth_builder<T, FN, F1>(
+    f: FN,
+    k: u32,
+    private_inputs: T,
+) -> Result<(), Vec<VerifyFailure>>
+where
+    FN: FnOnce(
+        &mut GateThreadBuilder<Fr>,
+        &EthChip<Fr>,
+        &mut KeccakChip<Fr>,
+        T,
+        &mut Vec<AssignedValue<Fr>>,
+    ) -> F1 + Clone,
+    F1: FnOnce(&mut Context<Fr>, &mut Context<Fr>, &EthChip<Fr>) + Clone,
+{
+    let precircuit = EthScaffold { f, private_inputs, _f1: PhantomData };
+    // Only the degree of the parameters is read when building the circuit
+    let params = ParamsKZG::<Bn256>::setup(k, rand::thread_rng());
+    let circuit = precircuit.create_circuit(CircuitBuilderStage::Mock, None, &params);
+    MockProver::run(k, &circuit, circuit.instances()).unwrap().verify()
+}
+
 pub fn run_cli<P: PreCircuit + Clone>(precircuit: P, cli: Cli) {
 
     let name = cli.name;
@@ -187,6 +230,9 @@ pub fn run_cli<P: PreCircuit + Clone>(precircuit: P, cli: Cli) {
     fs::create_dir_all(&data_path).unwrap();
 
     let params = get_kzg_params(k);
+    let output_path = cli.output_path.unwrap_or_else(|| PathBuf::from("measurement.json"));
+    let mut results =
+        BenchmarkResult::load_or_new(&output_path, "Halo2", &name, "Plonk", "BN254");
     match cli.command {
         SnarkCmd::Mock => {
             let circuit = precircuit.create_circuit(CircuitBuilderStage::Mock, None, &params);
@@ -197,14 +243,19 @@ pub fn run_cli<P: PreCircuit + Clone>(precircuit: P, cli: Cli) {
             let pk_path = data_path.join(PathBuf::from(format!("{name}.pk")));
             let vk_path = data_path.join(PathBuf::from(format!("{name}.vk")));
             let pinning_path = config_path.join(PathBuf::from(format!("{name}.json")));
+            let tracker = PhaseTracker::start("keygen");
             let key_gen_circuit = precircuit.clone().create_circuit(CircuitBuilderStage::Keygen, None, &params);
             let vk = keygen_vk(&params, &key_gen_circuit).unwrap();
             let pk = keygen_pk(&params, vk.clone(), &key_gen_circuit).unwrap();
+            results.record_phase(tracker.finish());
 
             key_gen_circuit.write_pinning(pinning_path.clone());
             serialize(&vk.to_bytes(SerdeFormat::RawBytes), &vk_path);
             serialize(&pk.to_bytes(SerdeFormat::RawBytes), &pk_path);
 
+            results.set_k(k, vk.cs().num_advice_columns());
+            results.write(&output_path).unwrap();
+
         }
         SnarkCmd::Prove => {
             let rng = rand::thread_rng();
@@ -215,8 +266,10 @@ pub fn run_cli<P: PreCircuit + Clone>(precircuit: P, cli: Cli) {
             let pinning = P::Pinning::from_path(pinning_path);
             pinning.set_var();
             
+            let tracker = PhaseTracker::start("witness");
             let circuit =
                 precircuit.create_circuit(CircuitBuilderStage::Prover, Some(pinning), &params);
+            results.record_phase(tracker.finish());
             let pk = custom_read_pk(&pk_path, &circuit);
 
             let instances = circuit.instances();
@@ -224,6 +277,8 @@ pub fn run_cli<P: PreCircuit + Clone>(precircuit: P, cli: Cli) {
             
             let instances = instances.iter().map(Vec::as_slice).collect_vec();
 
+            let tracker = PhaseTracker::start("prove");
+            let start = Instant::now();
             let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
             create_proof::<
                 KZGCommitmentScheme<Bn256>,
@@ -242,7 +297,13 @@ pub fn run_cli<P: PreCircuit + Clone>(precircuit: P, cli: Cli) {
             )
             .unwrap();
             let proof = transcript.finalize();
-            serialize(&proof, &proof_path);
+            results.set_prover_time(start.elapsed());
+            results.record_phase(tracker.finish());
+            let proof_size = serialize(&proof, &proof_path);
+
+            results.set_proof_size(proof_size as usize);
+            results.record_peak_rss();
+            results.write(&output_path).unwrap();
         }
         SnarkCmd::Verify => {
             let vk_path = data_path.join(PathBuf::from(format!("{name}.vk")));
@@ -257,6 +318,8 @@ pub fn run_cli<P: PreCircuit + Clone>(precircuit: P, cli: Cli) {
 
             let instances: Vec<Vec<Fr>> = bincode::deserialize_from(File::open(instances_path).unwrap()).unwrap();
             let instances = instances.iter().map(Vec::as_slice).collect_vec();
+            let tracker = PhaseTracker::start("verify");
+            let start = Instant::now();
             verify_proof::<
                 KZGCommitmentScheme<Bn256>,
                 VerifierSHPLONK<'_, Bn256>,
@@ -265,21 +328,31 @@ pub fn run_cli<P: PreCircuit + Clone>(precircuit: P, cli: Cli) {
                 SingleStrategy<'_, Bn256>,
             >(&params, &vk, strategy, &[&instances], &mut transcript)
             .unwrap();
+            results.set_verifier_time(start.elapsed());
+            results.record_phase(tracker.finish());
+            results.write(&output_path).unwrap();
         }
         SnarkCmd::Full => {
             let rng = rand::thread_rng();
             let pinning_path = config_path.join(PathBuf::from(format!("{name}.json")));
+            let tracker = PhaseTracker::start("keygen");
             let key_gen_circuit = precircuit.clone().create_circuit(CircuitBuilderStage::Keygen, None, &params);
             let vk = keygen_vk(&params, &key_gen_circuit).unwrap();
             let pk = keygen_pk(&params, vk.clone(), &key_gen_circuit).unwrap();
+            results.record_phase(tracker.finish());
             key_gen_circuit.write_pinning(pinning_path.clone());
             let pinning = P::Pinning::from_path(pinning_path);
             pinning.set_var();
+            let tracker = PhaseTracker::start("witness");
             let circuit =
             precircuit.create_circuit(CircuitBuilderStage::Prover, Some(pinning), &params);
+            results.record_phase(tracker.finish());
             let instances = circuit.instances();
             let instances = instances.iter().map(Vec::as_slice).collect_vec();
+            results.set_k(k, vk.cs().num_advice_columns());
 
+            let tracker = PhaseTracker::start("prove");
+            let start = Instant::now();
             let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
             create_proof::<
               KZGCommitmentScheme<Bn256>,
@@ -298,10 +371,16 @@ pub fn run_cli<P: PreCircuit + Clone>(precircuit: P, cli: Cli) {
             )
             .unwrap();
             let proof = transcript.finalize();
+            results.set_prover_time(start.elapsed());
+            results.record_phase(tracker.finish());
+            results.set_proof_size(proof.len());
+            results.record_peak_rss();
 
             let strategy = SingleStrategy::new(&params);
             let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
 
+            let tracker = PhaseTracker::start("verify");
+            let start = Instant::now();
             verify_proof::<
                 KZGCommitmentScheme<Bn256>,
                 VerifierSHPLONK<'_, Bn256>,
@@ -310,6 +389,9 @@ pub fn run_cli<P: PreCircuit + Clone>(precircuit: P, cli: Cli) {
                 SingleStrategy<'_, Bn256>,
             >(&params, &vk, strategy, &[&instances], &mut transcript)
             .unwrap();
+            results.set_verifier_time(start.elapsed());
+            results.record_phase(tracker.finish());
+            results.write(&output_path).unwrap();
         }
     }
 }
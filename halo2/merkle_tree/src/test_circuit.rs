@@ -39,7 +39,7 @@ use std::{
     path::PathBuf
 };
 use crate::keccak_merkle::CircuitInput;
-use crate::Cli;
+use crate::cmd::Cli;
 
 fn test_keccak_merkle_circuit<F: Field>(
     k: u32,
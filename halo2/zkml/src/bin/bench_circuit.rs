@@ -1,13 +1,19 @@
 use halo2_proofs::halo2curves::bn256::Fr;
+use jemallocator::Jemalloc;
 use zkml::{
   model::ModelCircuit,
   utils::bench_kzg::bench_kzg,
 };
 
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
 fn main() {
   let config_fname = std::env::args().nth(1).expect("config file path");
   let inp_fname = std::env::args().nth(2).expect("input file path");
   let step = std::env::args().nth(3).expect("step");
+  let outp_json = std::env::args().nth(4).expect("output json file path");
+  let circuit_name = std::env::args().nth(5).expect("circuit name");
   let circuit = ModelCircuit::<Fr>::generate_from_file(&config_fname, &inp_fname);
-  bench_kzg(step, circuit);
+  bench_kzg(step, circuit, &outp_json, &circuit_name);
 }
@@ -1,6 +1,5 @@
 use ark_std::{end_timer, start_timer};
 use halo2_proofs::{
-    dev::MockProver,
     halo2curves::bn256::{Bn256, Fr, G1Affine},
     plonk::{create_proof, keygen_pk, keygen_vk},
     poly::kzg::{
@@ -17,7 +16,7 @@ use zkml::{
     model::ModelCircuit,
     utils::{
         proving_kzg::{get_kzg_params, verify_kzg},
-        helpers::get_public_values,
+        witness::compute_public_values,
     }
     
 };
@@ -46,8 +45,7 @@ fn main() {
     // end_timer!(timer);
 
     let proof_circuit = circuit.clone();
-    let _prover = MockProver::run(degree, &proof_circuit, vec![vec![]]).unwrap();
-    let public_vals = get_public_values();
+    let public_vals = compute_public_values(&proof_circuit);
   
     // let timer = start_timer!(|| "Prove");
     let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
@@ -0,0 +1,40 @@
+use std::{fs::File, io::BufWriter};
+
+use zkml::utils::{
+  evaluator::{tensors_to_msgpack, ModelEvaluator},
+  loader::load_model_msgpack,
+};
+
+fn main() {
+  let config_fname = std::env::args().nth(1).expect("config file path");
+  let inp_fname = std::env::args().nth(2).expect("input file path");
+  // Optionally dump every tensor in the DAG, in the same format as the inputs
+  let tensors_fname = std::env::args().nth(3);
+
+  let config = load_model_msgpack(&config_fname, &inp_fname);
+  let (tensor_map, final_out) = ModelEvaluator::from_msgpack(&config)
+    .and_then(|evaluator| evaluator.forward())
+    .unwrap_or_else(|e| {
+      eprintln!("{}", e);
+      std::process::exit(1);
+    });
+
+  let scale_factor = config.global_sf as f64;
+  for (out_idx, tensor) in final_out.iter().enumerate() {
+    for (idx, x) in tensor.iter().enumerate() {
+      println!(
+        "out {}[{}] x: {} ({})",
+        out_idx,
+        idx,
+        x,
+        *x as f64 / scale_factor
+      );
+    }
+  }
+
+  if let Some(tensors_fname) = tensors_fname {
+    let f = File::create(tensors_fname).unwrap();
+    let mut buf = BufWriter::new(f);
+    rmp_serde::encode::write_named(&mut buf, &tensors_to_msgpack(&tensor_map)).unwrap();
+  }
+}
@@ -0,0 +1,47 @@
+use std::fs::File;
+
+use halo2_proofs::halo2curves::bn256::Fr;
+use zkml::{model::ModelCircuit, utils::profiler::profile_model};
+
+fn main() {
+  let config_fname = std::env::args().nth(1).expect("config file path");
+  let inp_fname = std::env::args().nth(2).expect("input file path");
+  let outp_json = std::env::args().nth(3);
+  let outp_csv = std::env::args().nth(4);
+
+  let circuit = ModelCircuit::<Fr>::generate_from_file(&config_fname, &inp_fname);
+  let report = profile_model(&circuit);
+
+  println!(
+    "k: {}, rows: {}, lookups: {}, time: {:.3} ms",
+    report.k, report.rows, report.lookups, report.time_ms
+  );
+  for layer in report.layers.iter() {
+    println!(
+      "  layer {}: {} rows, {} lookups, {:.3} ms ({:.1}%)",
+      layer.name,
+      layer.rows,
+      layer.lookups,
+      layer.time_ms,
+      layer.time_share * 100.
+    );
+  }
+  println!("  outside the DAG: {} rows", report.outside_rows);
+  for gadget in report.gadgets.iter() {
+    println!(
+      "  gadget {}: {} rows, {} lookups, {:.3} ms ({:.1}%)",
+      gadget.name,
+      gadget.rows,
+      gadget.lookups,
+      gadget.time_ms,
+      gadget.time_share * 100.
+    );
+  }
+
+  if let Some(outp_json) = outp_json {
+    serde_json::to_writer_pretty(File::create(outp_json).unwrap(), &report).unwrap();
+  }
+  if let Some(outp_csv) = outp_csv {
+    std::fs::write(outp_csv, report.to_csv()).unwrap();
+  }
+}
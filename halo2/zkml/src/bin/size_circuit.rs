@@ -0,0 +1,55 @@
+use std::fs::File;
+
+use halo2_proofs::halo2curves::bn256::Fr;
+use zkml::utils::{loader::load_model_msgpack, sizing::sweep_cols};
+
+fn main() {
+  let config_fname = std::env::args().nth(1).expect("config file path");
+  let inp_fname = std::env::args().nth(2).expect("input file path");
+  let config = load_model_msgpack(&config_fname, &inp_fname);
+
+  // Comma separated column budgets, defaults to the model's num_cols
+  let cols = std::env::args()
+    .nth(3)
+    .map(|cols| {
+      cols
+        .split(',')
+        .map(|c| c.parse::<i64>().expect("invalid column count"))
+        .collect::<Vec<_>>()
+    })
+    .unwrap_or(vec![config.num_cols]);
+  // k also sets the fixed-point range, so don't go below the model's k unless asked to
+  let min_k = std::env::args()
+    .nth(4)
+    .map(|k| k.parse::<i64>().expect("invalid min k"))
+    .unwrap_or(config.k);
+  let outp_json = std::env::args().nth(5);
+
+  let reports = sweep_cols::<Fr>(&config, &cols, min_k);
+  for (num_cols, report) in cols.iter().zip(reports.iter()) {
+    match report {
+      Some(report) => {
+        println!(
+          "num_cols: {}, k: {}, rows: {} / {} (advice: {}, tables: {})",
+          num_cols,
+          report.k,
+          report.rows,
+          report.usable_rows,
+          report.advice_rows,
+          report.table_rows
+        );
+        for layer in report.layers.iter() {
+          println!("  layer {}: {} rows", layer.name, layer.rows);
+        }
+        for (gadget, rows) in report.gadgets.iter() {
+          println!("  gadget {}: {} rows", gadget, rows);
+        }
+      }
+      None => println!("num_cols: {}, does not fit", num_cols),
+    }
+  }
+
+  if let Some(outp_json) = outp_json {
+    serde_json::to_writer_pretty(File::create(outp_json).unwrap(), &reports).unwrap();
+  }
+}
@@ -2,8 +2,10 @@ use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
 use zkml::{
   model::ModelCircuit,
   utils::{
+    evaluator::{outputs_to_field, ModelEvaluator},
     helpers::get_public_values,
     loader::{load_model_msgpack, ModelMsgpack},
+    witness::compute_public_values,
   },
 };
 
@@ -17,6 +19,18 @@ fn main() {
 
   let _prover = MockProver::run(config.k.try_into().unwrap(), &circuit, vec![vec![]]).unwrap();
   let public_vals = get_public_values();
+  // The direct witness path must agree with MockProver
+  assert_eq!(compute_public_values(&circuit), public_vals);
+
+  // The outputs come after the commitments and must match the native evaluator
+  let (_, final_out) = ModelEvaluator::from_msgpack(&config)
+    .and_then(|evaluator| evaluator.forward())
+    .unwrap();
+  let outputs = outputs_to_field::<Fr>(&final_out);
+  assert_eq!(
+    public_vals[public_vals.len() - outputs.len()..],
+    outputs[..]
+  );
 
   let prover = MockProver::run(config.k.try_into().unwrap(), &circuit, vec![public_vals]).unwrap();
   assert_eq!(prover.verify(), Ok(()));
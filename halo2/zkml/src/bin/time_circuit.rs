@@ -1,23 +1,38 @@
 use halo2_proofs::halo2curves::{bn256::Fr, pasta::Fp};
+use jemallocator::Jemalloc;
 use zkml::{
   model::ModelCircuit,
   utils::{proving_ipa::time_circuit_ipa, proving_kzg::time_circuit_kzg},
 };
+use zkperf_report::BenchmarkResult;
+
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
 
 fn main() {
   let config_fname = std::env::args().nth(1).expect("config file path");
   let inp_fname = std::env::args().nth(2).expect("input file path");
   let kzg_or_ipa = std::env::args().nth(3).expect("kzg or ipa");
+  let outp_json = std::env::args().nth(4);
+  let circuit_name = std::env::args().nth(5).unwrap_or("zkml".to_string());
 
   if kzg_or_ipa != "kzg" && kzg_or_ipa != "ipa" {
     panic!("Must specify kzg or ipa");
   }
 
-  if kzg_or_ipa == "kzg" {
+  let results = if kzg_or_ipa == "kzg" {
     let circuit = ModelCircuit::<Fr>::generate_from_file(&config_fname, &inp_fname);
-    time_circuit_kzg(circuit);
+    let mut results = BenchmarkResult::new("Halo2", &circuit_name, "Plonk", "BN254");
+    time_circuit_kzg(circuit, &mut results);
+    results
   } else {
     let circuit = ModelCircuit::<Fp>::generate_from_file(&config_fname, &inp_fname);
-    time_circuit_ipa(circuit);
+    let mut results = BenchmarkResult::new("Halo2", &circuit_name, "Plonk+IPA", "Pasta");
+    time_circuit_ipa(circuit, &mut results);
+    results
+  };
+
+  if let Some(outp_json) = outp_json {
+    results.write(outp_json).unwrap();
   }
 }
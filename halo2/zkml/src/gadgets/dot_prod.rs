@@ -179,7 +179,7 @@ impl<F: PrimeField> Gadget<F> for DotProductChip<F> {
 
     let outputs = layouter
       .assign_region(
-        || "dot prod rows",
+        || format!("gadget {}", self.name()),
         |mut region| {
           let mut outputs = vec![];
           for i in 0..inputs.len() / self.num_inputs_per_row() {
@@ -77,6 +77,47 @@ lazy_static! {
   pub static ref PUBLIC_VALS: Mutex<Vec<BigUint>> = Mutex::new(vec![]);
 }
 
+// Maps the layer names in the msgpack config to layer types
+pub fn match_layer(x: &str) -> LayerType {
+  match x {
+    "AveragePool2D" => LayerType::AvgPool2D,
+    "Add" => LayerType::Add,
+    "BatchMatMul" => LayerType::BatchMatMul,
+    "Broadcast" => LayerType::Broadcast,
+    "Concatenation" => LayerType::Concatenation,
+    "Conv2D" => LayerType::Conv2D,
+    "Div" => LayerType::DivFixed, // TODO: rename to DivFixed
+    "DivVar" => LayerType::DivVar,
+    "FullyConnected" => LayerType::FullyConnected,
+    "Gather" => LayerType::Gather,
+    "Logistic" => LayerType::Logistic,
+    "MaskNegInf" => LayerType::MaskNegInf,
+    "MaxPool2D" => LayerType::MaxPool2D,
+    "Mean" => LayerType::Mean,
+    "Mul" => LayerType::Mul,
+    "Noop" => LayerType::Noop,
+    "Pack" => LayerType::Pack,
+    "Pad" => LayerType::Pad,
+    "Pow" => LayerType::Pow,
+    "Permute" => LayerType::Permute,
+    "Reshape" => LayerType::Reshape,
+    "ResizeNearestNeighbor" => LayerType::ResizeNN,
+    "Rotate" => LayerType::Rotate,
+    "Rsqrt" => LayerType::Rsqrt,
+    "Slice" => LayerType::Slice,
+    "Softmax" => LayerType::Softmax,
+    "Split" => LayerType::Split,
+    "Sqrt" => LayerType::Sqrt,
+    "Square" => LayerType::Square,
+    "SquaredDifference" => LayerType::SquaredDifference,
+    "Sub" => LayerType::Sub,
+    "Tanh" => LayerType::Tanh,
+    "Transpose" => LayerType::Transpose,
+    "Update" => LayerType::Update,
+    _ => panic!("unknown op: {}", x),
+  }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct ModelCircuit<F: PrimeField> {
   pub used_gadgets: Arc<BTreeSet<GadgetType>>,
@@ -337,44 +378,6 @@ impl<F: PrimeField + Ord + FromUniformBytes<64>> ModelCircuit<F> {
       F::from(x_pos as u64) - F::from(bias as u64)
     };
 
-    let match_layer = |x: &str| match x {
-      "AveragePool2D" => LayerType::AvgPool2D,
-      "Add" => LayerType::Add,
-      "BatchMatMul" => LayerType::BatchMatMul,
-      "Broadcast" => LayerType::Broadcast,
-      "Concatenation" => LayerType::Concatenation,
-      "Conv2D" => LayerType::Conv2D,
-      "Div" => LayerType::DivFixed, // TODO: rename to DivFixed
-      "DivVar" => LayerType::DivVar,
-      "FullyConnected" => LayerType::FullyConnected,
-      "Gather" => LayerType::Gather,
-      "Logistic" => LayerType::Logistic,
-      "MaskNegInf" => LayerType::MaskNegInf,
-      "MaxPool2D" => LayerType::MaxPool2D,
-      "Mean" => LayerType::Mean,
-      "Mul" => LayerType::Mul,
-      "Noop" => LayerType::Noop,
-      "Pack" => LayerType::Pack,
-      "Pad" => LayerType::Pad,
-      "Pow" => LayerType::Pow,
-      "Permute" => LayerType::Permute,
-      "Reshape" => LayerType::Reshape,
-      "ResizeNearestNeighbor" => LayerType::ResizeNN,
-      "Rotate" => LayerType::Rotate,
-      "Rsqrt" => LayerType::Rsqrt,
-      "Slice" => LayerType::Slice,
-      "Softmax" => LayerType::Softmax,
-      "Split" => LayerType::Split,
-      "Sqrt" => LayerType::Sqrt,
-      "Square" => LayerType::Square,
-      "SquaredDifference" => LayerType::SquaredDifference,
-      "Sub" => LayerType::Sub,
-      "Tanh" => LayerType::Tanh,
-      "Transpose" => LayerType::Transpose,
-      "Update" => LayerType::Update,
-      _ => panic!("unknown op: {}", x),
-    };
-
     let mut tensors = BTreeMap::new();
     for flat in config.tensors {
       let value_flat = flat.data.iter().map(|x| to_field(*x)).collect::<Vec<_>>();
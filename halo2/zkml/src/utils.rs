@@ -1,5 +1,11 @@
+pub mod evaluator;
 pub mod helpers;
 pub mod loader;
+pub mod profiler;
 pub mod proving_ipa;
 pub mod proving_kzg;
+pub mod sizing;
+#[cfg(test)]
+pub mod testing;
+pub mod witness;
 pub mod bench_kzg;
\ No newline at end of file
@@ -1,10 +1,10 @@
 use std::{
     fs::File,
     io::BufReader,
+    time::Instant,
   };
   
 use halo2_proofs::{
-    dev::MockProver,
     halo2curves::bn256::{Bn256, Fr, G1Affine},
     plonk::{create_proof, keygen_pk, keygen_vk, VerifyingKey, ProvingKey},
     poly::kzg::{
@@ -18,10 +18,11 @@ use halo2_proofs::{
     SerdeFormat,
 };
 
-use crate::{model::ModelCircuit, utils::helpers::get_public_values};
+use crate::{model::ModelCircuit, utils::witness::compute_public_values};
 use crate::utils::proving_kzg::{serialize, get_kzg_params, verify_kzg};
 use serde_derive::{Serialize, Deserialize};
 use serde_json;
+use zkperf_report::{BenchmarkResult, PhaseTracker};
 
 #[derive(Serialize, Deserialize)]
 pub struct PublicVal {
@@ -29,11 +30,13 @@ pub struct PublicVal {
 }
 
 
-pub fn bench_kzg(step: String, circuit: ModelCircuit<Fr>) {
+pub fn bench_kzg(step: String, circuit: ModelCircuit<Fr>, outp_json: &str, circuit_name: &str) {
+    let mut results = BenchmarkResult::load_or_new(outp_json, "Halo2", circuit_name, "Plonk", "BN254");
 
     if step == "setup" {
         let degree = circuit.k as u32;
         let params = get_kzg_params("./params_kzg", degree);
+        let tracker = PhaseTracker::start("keygen");
         let vk_circuit = circuit.clone();
         let vk = keygen_vk(&params, &vk_circuit).unwrap();
         drop(vk_circuit);
@@ -43,10 +46,13 @@ pub fn bench_kzg(step: String, circuit: ModelCircuit<Fr>) {
         let pk = keygen_pk(&params, vk, &pk_circuit).unwrap();
         drop(pk_circuit);        
         let _ = serialize(&pk.to_bytes(SerdeFormat::RawBytes), "pkey");
+        results.set_k(degree, pk.get_vk().cs().num_advice_columns());
+        results.record_phase(tracker.finish());
 
+        let tracker = PhaseTracker::start("witness");
         let proof_circuit = circuit.clone();
-        let _prover = MockProver::run(degree, &proof_circuit, vec![vec![]]).unwrap();
-        let public_vals = get_public_values();
+        let public_vals = compute_public_values(&proof_circuit);
+        results.record_phase(tracker.finish());
         let public_vals_u8_32: Vec<[u8; 32]> = public_vals
         .iter()
         .map(|v: &Fr| v.to_bytes())
@@ -55,6 +61,7 @@ pub fn bench_kzg(step: String, circuit: ModelCircuit<Fr>) {
             File::create("public_vals").unwrap(), &PublicVal{
                 vals: public_vals_u8_32
             }).unwrap();
+        results.write(outp_json).unwrap();
     } else if step == "prove" {
         let rng = rand::thread_rng();
         let degree = circuit.k as u32;
@@ -70,6 +77,8 @@ pub fn bench_kzg(step: String, circuit: ModelCircuit<Fr>) {
             ).unwrap();
         let public_vals = public_val_raw.vals.iter().map(|x| Fr::from_bytes(x).unwrap()).collect::<Vec<Fr>>();
 
+        let tracker = PhaseTracker::start("prove");
+        let start = Instant::now();
         let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
         create_proof::<
           KZGCommitmentScheme<Bn256>,
@@ -88,7 +97,12 @@ pub fn bench_kzg(step: String, circuit: ModelCircuit<Fr>) {
         )
         .unwrap();
         let proof = transcript.finalize();
-        let _ = serialize(&proof, "proof");
+        results.set_prover_time(start.elapsed());
+        results.record_phase(tracker.finish());
+        let proof_size = serialize(&proof, "proof");
+        results.set_proof_size(proof_size as usize);
+        results.record_peak_rss();
+        results.write(outp_json).unwrap();
     } else if step == "verify" {
         let degree = circuit.k as u32;
         let params = get_kzg_params("./params_kzg", degree);
@@ -105,6 +119,8 @@ pub fn bench_kzg(step: String, circuit: ModelCircuit<Fr>) {
             ).unwrap();
         let public_vals = public_val_raw.vals.iter().map(|x| Fr::from_bytes(x).unwrap()).collect::<Vec<Fr>>();
         let transcript_read = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        let tracker = PhaseTracker::start("verify");
+        let start = Instant::now();
         verify_kzg(
             &params,
             &vk,
@@ -112,6 +128,9 @@ pub fn bench_kzg(step: String, circuit: ModelCircuit<Fr>) {
             &public_vals,
             transcript_read,
         );
+        results.set_verifier_time(start.elapsed());
+        results.record_phase(tracker.finish());
+        results.write(outp_json).unwrap();
 
     }
 }
\ No newline at end of file
@@ -0,0 +1,942 @@
+use std::collections::{BTreeMap, HashMap};
+
+use halo2_proofs::halo2curves::{bn256::Fr, ff::PrimeField};
+use ndarray::{concatenate, Array, ArrayView, Axis, IxDyn, Slice};
+
+use crate::{
+  gadgets::gadget::GadgetType,
+  layers::{
+    conv2d::{Conv2DChip, ConvLayerEnum, PaddingEnum},
+    dag::DAGLayerConfig,
+    layer::{ActivationType, LayerConfig, LayerType},
+    mean::MeanChip,
+    shape::pad::PadChip,
+  },
+  model::match_layer,
+  utils::{
+    helpers::broadcast,
+    loader::{ModelMsgpack, TensorMsgpack},
+  },
+};
+
+pub type Tensor = Array<i64, IxDyn>;
+
+// Errors describe the model input the evaluator can't run; `forward` prefixes the layer
+pub type EvalResult<T> = Result<T, String>;
+
+// The conv helpers only live on the chip, the field is never used
+type Conv2D = Conv2DChip<Fr>;
+
+// Fixed-point parameters, derived from the msgpack exactly like the GadgetConfig in
+// ModelCircuit::generate_from_msgpack
+#[derive(Clone, Debug)]
+pub struct FixedPointConfig {
+  pub scale_factor: i64,
+  pub min_val: i64,
+  pub max_val: i64,
+  pub num_rows: i64,
+}
+
+impl FixedPointConfig {
+  pub fn from_msgpack(config: &ModelMsgpack) -> Self {
+    Self {
+      scale_factor: config.global_sf,
+      min_val: -(1 << (config.k - 1)),
+      max_val: (1 << (config.k - 1)) - 10,
+      num_rows: (1 << config.k) - 10 + 1,
+    }
+  }
+
+  // VarDivRound and the big variants: c = (2 * a + b) / (2 * b), rounded towards -inf
+  pub fn div_round(&self, a: i64, b: i64) -> i64 {
+    (2 * a + b).div_euclid(2 * b)
+  }
+
+  // Lookups are over [min_val, min_val + num_rows). The first row of every nonlinear table is
+  // loaded as zero, so the smallest input always maps to zero.
+  fn lookup(&self, name: &str, x: i64, f: impl Fn(i64) -> i64) -> EvalResult<i64> {
+    let idx = x - self.min_val;
+    if idx < 0 || idx >= self.num_rows {
+      return Err(format!(
+        "{} input {} is outside the lookup table [{}, {})",
+        name,
+        x,
+        self.min_val,
+        self.min_val + self.num_rows
+      ));
+    }
+    if idx == 0 {
+      Ok(0)
+    } else {
+      Ok(f(x))
+    }
+  }
+
+  // Same formulas as the generate_map of each nonlinear gadget
+  pub fn nonlinearity(&self, gadget_type: GadgetType, x: i64) -> EvalResult<i64> {
+    let scale_factor = self.scale_factor as u64;
+    self.lookup(&format!("{:?}", gadget_type), x, |shifted| {
+      let x = (shifted as f64) / (scale_factor as f64);
+      match gadget_type {
+        GadgetType::Exp => (x.exp() * ((scale_factor * scale_factor) as f64)).round() as i64,
+        GadgetType::Logistic => {
+          let logistic = 1. / (1. + (-x).exp());
+          (logistic * (scale_factor as f64)).round() as i64
+        }
+        GadgetType::Pow => (x.powf(3.) * (scale_factor as f64)).round() as i64,
+        GadgetType::Relu => shifted.max(0),
+        GadgetType::Rsqrt => ((1.0 / x.sqrt()) * (scale_factor as f64)).round() as i64,
+        GadgetType::Sqrt => (x.sqrt() * (scale_factor as f64)).round() as i64,
+        GadgetType::Tanh => (x.tanh() * (scale_factor as f64)).round() as i64,
+        _ => panic!("{:?} is not a nonlinearity", gadget_type),
+      }
+    })
+  }
+
+  // BiasDivRoundRelu6: returns (relu6, div_res), the bias is added after the division
+  pub fn bias_div_round_relu6(&self, x: i64, bias: i64) -> EvalResult<(i64, i64)> {
+    let div_res = self.div_round(x, self.scale_factor) + bias;
+    let six = 6 * self.scale_factor;
+    let relu6 = self.lookup("BiasDivRoundRelu6", div_res, |x| x.clamp(0, six))?;
+    Ok((relu6, div_res))
+  }
+}
+
+// Evaluates the model natively over i64, reproducing the fixed-point semantics of the circuit.
+// Every tensor the DAG produces can be compared against the circuit witness.
+pub struct ModelEvaluator {
+  pub fixed_point: FixedPointConfig,
+  pub dag_config: DAGLayerConfig,
+  pub tensors: BTreeMap<i64, Tensor>,
+}
+
+impl ModelEvaluator {
+  pub fn from_msgpack(config: &ModelMsgpack) -> EvalResult<Self> {
+    let mut tensors = BTreeMap::new();
+    for flat in config.tensors.iter() {
+      let shape = flat.shape.iter().map(|x| *x as usize).collect::<Vec<_>>();
+      let tensor = Array::from_shape_vec(IxDyn(&shape), flat.data.clone()).map_err(|_| {
+        format!(
+          "tensor {}: shape {:?} doesn't match its {} values",
+          flat.idx,
+          flat.shape,
+          flat.data.len()
+        )
+      })?;
+      tensors.insert(flat.idx, tensor);
+    }
+
+    let i64_to_usize = |x: &Vec<i64>| x.iter().map(|x| *x as usize).collect::<Vec<_>>();
+    let dag_config = DAGLayerConfig {
+      ops: config
+        .layers
+        .iter()
+        .map(|layer| LayerConfig {
+          layer_type: match_layer(&layer.layer_type),
+          layer_params: layer.params.clone(),
+          inp_shapes: layer.inp_shapes.iter().map(|x| i64_to_usize(x)).collect(),
+          out_shapes: layer.out_shapes.iter().map(|x| i64_to_usize(x)).collect(),
+          mask: layer.mask.clone(),
+        })
+        .collect(),
+      inp_idxes: config
+        .layers
+        .iter()
+        .map(|layer| i64_to_usize(&layer.inp_idxes))
+        .collect(),
+      out_idxes: config
+        .layers
+        .iter()
+        .map(|layer| i64_to_usize(&layer.out_idxes))
+        .collect(),
+      final_out_idxes: i64_to_usize(&config.out_idxes),
+    };
+
+    Ok(Self {
+      fixed_point: FixedPointConfig::from_msgpack(config),
+      dag_config,
+      tensors,
+    })
+  }
+
+  // Mirrors DAGLayerChip::forward: returns the tensor map and the final outputs
+  pub fn forward(&self) -> EvalResult<(HashMap<usize, Tensor>, Vec<Tensor>)> {
+    let mut tensor_map = HashMap::new();
+    for (idx, tensor) in self.tensors.iter() {
+      tensor_map.insert(*idx as usize, tensor.clone());
+    }
+
+    for (layer_idx, layer_config) in self.dag_config.ops.iter().enumerate() {
+      let inp_idxes = &self.dag_config.inp_idxes[layer_idx];
+      let out_idxes = &self.dag_config.out_idxes[layer_idx];
+      let vec_inps = inp_idxes
+        .iter()
+        .map(|idx| get_tensor(&tensor_map, *idx))
+        .collect::<EvalResult<Vec<_>>>();
+
+      let out = vec_inps
+        .and_then(|inps| self.forward_layer(&inps, layer_config))
+        .map_err(|e| format!("layer {} ({:?}): {}", layer_idx, layer_config.layer_type, e))?;
+      for (idx, tensor_idx) in out_idxes.iter().enumerate() {
+        tensor_map.insert(*tensor_idx, out[idx].clone());
+      }
+    }
+
+    let final_out = self
+      .dag_config
+      .final_out_idxes
+      .iter()
+      .map(|idx| get_tensor(&tensor_map, *idx))
+      .collect::<EvalResult<Vec<_>>>()?;
+
+    Ok((tensor_map, final_out))
+  }
+
+  pub fn forward_layer(
+    &self,
+    tensors: &Vec<Tensor>,
+    layer_config: &LayerConfig,
+  ) -> EvalResult<Vec<Tensor>> {
+    let fp = &self.fixed_point;
+    let sf = fp.scale_factor;
+    let params = &layer_config.layer_params;
+
+    let out = match layer_config.layer_type {
+      LayerType::Add => {
+        let out = self.zip_broadcast(tensors, |a, b| a + b)?;
+        match params[0] {
+          0 => vec![out],
+          1 => vec![self.map(&out, GadgetType::Relu)?],
+          _ => return Err(format!("unsupported activation {}", params[0])),
+        }
+      }
+      LayerType::Sub => vec![self.zip_broadcast(tensors, |a, b| a - b)?],
+      LayerType::Mul => vec![self.zip_broadcast(tensors, |a, b| fp.div_round(a * b, sf))?],
+      LayerType::SquaredDifference => {
+        vec![self.zip_broadcast(tensors, |a, b| fp.div_round((a - b) * (a - b), sf))?]
+      }
+      LayerType::DivVar => {
+        if tensors[1].shape() != [1] {
+          return Err(format!(
+            "the divisor must have shape [1], not {:?}",
+            tensors[1].shape()
+          ));
+        }
+        let div = tensors[1][[0]];
+        if div == 0 {
+          return Err("division by zero".to_string());
+        }
+        vec![tensors[0].mapv(|x| fp.div_round(x * sf, div))]
+      }
+      LayerType::DivFixed => vec![tensors[0].mapv(|x| fp.div_round(x, params[0]))],
+      LayerType::Square => vec![tensors[0].mapv(|x| fp.div_round(x * x, sf))],
+      LayerType::Update => {
+        // Plain floor division, with eta = sf / 1000
+        let eta = sf / 1000;
+        let out = tensors[0]
+          .iter()
+          .zip(tensors[1].iter())
+          .map(|(w, dw)| (w * sf - dw * eta).div_euclid(sf))
+          .collect::<Vec<_>>();
+        vec![Array::from_shape_vec(tensors[0].raw_dim(), out).unwrap()]
+      }
+      LayerType::Logistic => vec![self.map(&tensors[0], GadgetType::Logistic)?],
+      LayerType::Pow => vec![self.map(&tensors[0], GadgetType::Pow)?],
+      LayerType::Tanh => vec![self.map(&tensors[0], GadgetType::Tanh)?],
+      LayerType::Sqrt => vec![self.map(
+        &self.apply_mask(&tensors[0], layer_config)?,
+        GadgetType::Sqrt,
+      )?],
+      LayerType::Rsqrt => {
+        vec![self.map(
+          &self.apply_mask(&tensors[0], layer_config)?,
+          GadgetType::Rsqrt,
+        )?]
+      }
+      LayerType::AvgPool2D => {
+        let (out, out_shape) = self.pool(&tensors[0], layer_config, |window| {
+          fp.div_round(window.iter().sum(), params[0] * params[1])
+        })?;
+        vec![Array::from_shape_vec(IxDyn(&out_shape), out).unwrap()]
+      }
+      LayerType::MaxPool2D => {
+        let (out, out_shape) = self.pool(&tensors[0], layer_config, |window| {
+          *window.iter().max().unwrap()
+        })?;
+        vec![Array::from_shape_vec(IxDyn(&out_shape), out).unwrap()]
+      }
+      LayerType::Mean => {
+        let inp = &tensors[0];
+        let keep_axis = MeanChip {}.get_keep_axis(layer_config);
+        let div = inp.len() / inp.shape()[keep_axis];
+        let out = inp
+          .axis_iter(Axis(keep_axis))
+          .map(|x| fp.div_round(x.sum(), div as i64))
+          .collect::<Vec<_>>();
+        reshape(out, &layer_config.out_shapes[0])?
+      }
+      LayerType::FullyConnected => {
+        let activation = match params[0] {
+          0 => ActivationType::None,
+          1 => ActivationType::Relu,
+          _ => return Err(format!("unsupported activation {}", params[0])),
+        };
+        let input = if tensors[0].ndim() == 2 {
+          tensors[0].view()
+        } else {
+          tensors[0].index_axis(Axis(0), 0)
+        };
+        if input.shape()[1] != tensors[1].shape()[1] {
+          return Err(format!(
+            "input of shape {:?} doesn't match weights of shape {:?}",
+            tensors[0].shape(),
+            tensors[1].shape()
+          ));
+        }
+        let mm = self.mm_round(&input, &tensors[1].view());
+        let mm = if tensors.len() == 3 {
+          let bias = tensors[2].broadcast(mm.raw_dim()).ok_or(format!(
+            "bias of shape {:?} doesn't broadcast to {:?}",
+            tensors[2].shape(),
+            mm.shape()
+          ))?;
+          &mm + &bias
+        } else {
+          mm
+        };
+        if activation == ActivationType::Relu {
+          vec![self.map(&mm, GadgetType::Relu)?]
+        } else {
+          vec![mm]
+        }
+      }
+      LayerType::BatchMatMul => {
+        let (inp1, inp2) = (&tensors[0], &tensors[1]);
+        check_ndim(inp1, &[3])?;
+        check_ndim(inp2, &[3])?;
+        let adj_y = params[1] == 1;
+        let mut outp = vec![];
+        for i in 0..inp1.shape()[0] {
+          let inp2_slice = inp2.index_axis(Axis(0), i);
+          let weight = if adj_y { inp2_slice } else { inp2_slice.t() };
+          let out = self.mm_round(&inp1.index_axis(Axis(0), i), &weight);
+          outp.extend(out.iter().cloned());
+        }
+        let out_shape = if adj_y {
+          vec![inp1.shape()[0], inp1.shape()[1], inp2.shape()[1]]
+        } else {
+          vec![inp1.shape()[0], inp1.shape()[1], inp2.shape()[2]]
+        };
+        vec![Array::from_shape_vec(IxDyn(&out_shape), outp).unwrap()]
+      }
+      LayerType::Conv2D => vec![self.conv2d(tensors, layer_config)?],
+      LayerType::Softmax => vec![self.softmax(&tensors[0], layer_config)?],
+      _ => shape_forward(tensors, layer_config, fp)?,
+    };
+    Ok(out)
+  }
+
+  fn map(&self, inp: &Tensor, gadget_type: GadgetType) -> EvalResult<Tensor> {
+    let out = inp
+      .iter()
+      .map(|x| self.fixed_point.nonlinearity(gadget_type, *x))
+      .collect::<EvalResult<Vec<_>>>()?;
+    Ok(Array::from_shape_vec(inp.raw_dim(), out).unwrap())
+  }
+
+  fn zip_broadcast(
+    &self,
+    tensors: &Vec<Tensor>,
+    f: impl Fn(i64, i64) -> i64,
+  ) -> EvalResult<Tensor> {
+    if tensors.len() != 2 {
+      return Err(format!("expected 2 inputs, got {}", tensors.len()));
+    }
+    let (inp1, inp2) = broadcast(&tensors[0], &tensors[1]);
+    let out = inp1
+      .iter()
+      .zip(inp2.iter())
+      .map(|(a, b)| f(*a, *b))
+      .collect::<Vec<_>>();
+    Ok(Array::from_shape_vec(inp1.raw_dim(), out).unwrap())
+  }
+
+  // Masked entries of sqrt / rsqrt are replaced by max_val (1) or min_val (-1)
+  fn apply_mask(&self, inp: &Tensor, layer_config: &LayerConfig) -> EvalResult<Tensor> {
+    let mask = &layer_config.mask;
+    let mut mask_map = HashMap::new();
+    for i in 0..mask.len() / 2 {
+      mask_map.insert(mask[2 * i], mask[2 * i + 1]);
+    }
+    let out = inp
+      .iter()
+      .enumerate()
+      .map(|(i, x)| match mask_map.get(&(i as i64)) {
+        None => Ok(*x),
+        Some(1) => Ok(self.fixed_point.max_val),
+        Some(-1) => Ok(self.fixed_point.min_val),
+        Some(m) => Err(format!("invalid mask value {} at {}", m, i)),
+      })
+      .collect::<EvalResult<Vec<_>>>()?;
+    Ok(Array::from_shape_vec(inp.raw_dim(), out).unwrap())
+  }
+
+  // input x weight^T, divided by the scale factor
+  fn mm_round(&self, input: &ArrayView<i64, IxDyn>, weight_t: &ArrayView<i64, IxDyn>) -> Tensor {
+    let fp = &self.fixed_point;
+    let (n, m) = (input.shape()[0], weight_t.shape()[0]);
+    let mut out = vec![];
+    for i in 0..n {
+      for j in 0..m {
+        let row = input.index_axis(Axis(0), i);
+        let col = weight_t.index_axis(Axis(0), j);
+        let dot: i64 = row.iter().zip(col.iter()).map(|(a, b)| a * b).sum();
+        out.push(fp.div_round(dot, fp.scale_factor));
+      }
+    }
+    Array::from_shape_vec(IxDyn(&[n, m]), out).unwrap()
+  }
+
+  // Windows in the same order as MaxPool2DChip::splat
+  fn pool(
+    &self,
+    inp: &Tensor,
+    layer_config: &LayerConfig,
+    f: impl Fn(&Vec<i64>) -> i64,
+  ) -> EvalResult<(Vec<i64>, Vec<usize>)> {
+    let params = &layer_config.layer_params;
+    let (fx, fy) = (params[0] as usize, params[1] as usize);
+    let (sx, sy) = (params[2] as usize, params[3] as usize);
+    check_ndim(inp, &[4])?;
+    check_batch(inp)?;
+    let (oh, ow) = Conv2D::out_hw(
+      inp.shape()[1],
+      inp.shape()[2],
+      sx,
+      sy,
+      fx,
+      fy,
+      PaddingEnum::Valid,
+    );
+
+    let mut out = vec![];
+    for i in 0..oh {
+      for j in 0..ow {
+        for k in 0..inp.shape()[3] {
+          let mut window = vec![];
+          for x in 0..fx {
+            for y in 0..fy {
+              let (x, y) = (i * sx + x, j * sy + y);
+              if x < inp.shape()[1] && y < inp.shape()[2] {
+                window.push(inp[[0, x, y, k]]);
+              }
+            }
+          }
+          out.push(f(&window));
+        }
+      }
+    }
+    Ok((out, vec![1, oh, ow, inp.shape()[3]]))
+  }
+
+  fn conv2d(&self, tensors: &Vec<Tensor>, layer_config: &LayerConfig) -> EvalResult<Tensor> {
+    let fp = &self.fixed_point;
+    let conv_config = Conv2D::param_vec_to_config(layer_config.layer_params.clone());
+    let inp = &tensors[0];
+    let weights = &tensors[1];
+    check_ndim(inp, &[4])?;
+    check_ndim(weights, &[4])?;
+    let (h, w) = (inp.shape()[1], inp.shape()[2]);
+    let (ch, cw) = (weights.shape()[1], weights.shape()[2]);
+    let (si, sj) = conv_config.stride;
+
+    let (ph, pw) = if conv_config.padding == PaddingEnum::Same {
+      Conv2D::get_padding(h, w, si, sj, ch, cw)
+    } else {
+      ((0, 0), (0, 0))
+    };
+    let inp_pad = pad(inp, &vec![[0, 0], [ph.0, ph.1], [pw.0, pw.1], [0, 0]]);
+    let (oh, ow) = Conv2D::out_hw(h, w, si, sj, ch, cw, conv_config.padding);
+    let batch_size = inp.shape()[0];
+
+    // Accumulate without dividing, the division happens in BiasDivRoundRelu6
+    let mut acc = vec![];
+    let mut biases = vec![];
+    let oc = match conv_config.conv_type {
+      ConvLayerEnum::Conv2D => {
+        let oc = weights.shape()[0];
+        for batch in 0..batch_size {
+          for i in 0..oh {
+            for j in 0..ow {
+              for chan_out in 0..oc {
+                let mut dot = 0;
+                for ci in 0..ch {
+                  for cj in 0..cw {
+                    for ck in 0..weights.shape()[3] {
+                      dot += inp_pad[[batch, i * si + ci, j * sj + cj, ck]]
+                        * weights[[chan_out, ci, cj, ck]];
+                    }
+                  }
+                }
+                acc.push(dot);
+                biases.push(if tensors.len() == 3 {
+                  tensors[2][chan_out]
+                } else {
+                  0
+                });
+              }
+            }
+          }
+        }
+        oc
+      }
+      ConvLayerEnum::DepthwiseConv2D => {
+        if tensors.len() != 3 {
+          return Err("depthwise convolutions need a bias".to_string());
+        }
+        check_batch(inp)?;
+        let oc = weights.shape()[3];
+        for i in 0..oh {
+          for j in 0..ow {
+            for chan_out in 0..oc {
+              let mut dot = 0;
+              for ci in 0..ch {
+                for cj in 0..cw {
+                  dot += inp_pad[[0, i * si + ci, j * sj + cj, chan_out]]
+                    * weights[[0, ci, cj, chan_out]];
+                }
+              }
+              acc.push(dot);
+              biases.push(tensors[2][[chan_out]]);
+            }
+          }
+        }
+        oc
+      }
+    };
+
+    let outp = acc
+      .iter()
+      .zip(biases.iter())
+      .map(|(x, bias)| {
+        let (relu6, div_res) = fp.bias_div_round_relu6(*x, *bias)?;
+        match conv_config.activation {
+          ActivationType::Relu6 => Ok(relu6),
+          ActivationType::None => Ok(div_res),
+          ActivationType::Relu => fp.nonlinearity(GadgetType::Relu, div_res),
+          ref activation => Err(format!("unsupported activation {:?}", activation)),
+        }
+      })
+      .collect::<EvalResult<Vec<_>>>()?;
+    Ok(Array::from_shape_vec(IxDyn(&[batch_size, oh, ow, oc]), outp).unwrap())
+  }
+
+  fn softmax_flat(&self, inp: &Vec<i64>, mask: &Vec<i64>) -> EvalResult<Vec<i64>> {
+    let fp = &self.fixed_point;
+    // 1 = take negative infinity
+    let inp_take = inp
+      .iter()
+      .zip(mask.iter())
+      .filter(|(_, m)| **m == 0)
+      .map(|(x, _)| *x)
+      .collect::<Vec<_>>();
+    let max = *inp_take
+      .iter()
+      .max()
+      .ok_or("softmax over a fully masked row")?;
+    let exp = inp_take
+      .iter()
+      .map(|x| fp.nonlinearity(GadgetType::Exp, x - max))
+      .collect::<EvalResult<Vec<_>>>()?;
+    let sum_div_sf = fp.div_round(exp.iter().sum(), fp.scale_factor);
+    let mut dived = exp.iter().map(|x| fp.div_round(*x, sum_div_sf));
+    Ok(
+      mask
+        .iter()
+        .map(|m| if *m == 1 { 0 } else { dived.next().unwrap() })
+        .collect(),
+    )
+  }
+
+  fn softmax(&self, inp: &Tensor, layer_config: &LayerConfig) -> EvalResult<Tensor> {
+    check_ndim(inp, &[2, 3, 4])?;
+    if inp.ndim() == 4 {
+      check_batch(inp)?;
+    }
+    let params = &layer_config.layer_params;
+    let mask = if params.len() == 0 {
+      Array::zeros(inp.raw_dim())
+    } else {
+      let mask_shape_len = params[0] as usize;
+      let mask_shape = params[1..(1 + mask_shape_len)]
+        .iter()
+        .map(|x| *x as usize)
+        .collect::<Vec<_>>();
+      let mask = reshape(params[(1 + mask_shape_len)..].to_vec(), &mask_shape)?;
+      broadcast_to(&mask, inp.shape())?
+    };
+
+    // The batch dimension of 4D inputs is dropped, as in SoftmaxChip
+    let shape = if inp.ndim() == 4 {
+      vec![inp.shape()[1], inp.shape()[2], inp.shape()[3]]
+    } else {
+      inp.shape().to_vec()
+    };
+    let inp = inp.to_owned().into_shape(shape.clone()).unwrap();
+    let mask = mask.into_shape(shape.clone()).unwrap();
+
+    let mut outp = vec![];
+    for (inp_row, mask_row) in inp
+      .lanes(Axis(shape.len() - 1))
+      .into_iter()
+      .zip(mask.lanes(Axis(shape.len() - 1)).into_iter())
+    {
+      outp.extend(self.softmax_flat(&inp_row.to_vec(), &mask_row.to_vec())?);
+    }
+    Ok(Array::from_shape_vec(IxDyn(&shape), outp).unwrap())
+  }
+}
+
+fn get_tensor(tensor_map: &HashMap<usize, Tensor>, idx: usize) -> EvalResult<Tensor> {
+  tensor_map
+    .get(&idx)
+    .cloned()
+    .ok_or(format!("tensor {} is read before it is computed", idx))
+}
+
+fn check_ndim(inp: &Tensor, ndims: &[usize]) -> EvalResult<()> {
+  if ndims.contains(&inp.ndim()) {
+    Ok(())
+  } else {
+    Err(format!(
+      "expected an input with {:?} dimensions, got shape {:?}",
+      ndims,
+      inp.shape()
+    ))
+  }
+}
+
+fn check_batch(inp: &Tensor) -> EvalResult<()> {
+  if inp.shape()[0] == 1 {
+    Ok(())
+  } else {
+    Err(format!(
+      "only a batch size of 1 is supported, got shape {:?}",
+      inp.shape()
+    ))
+  }
+}
+
+fn reshape(flat: Vec<i64>, shape: &[usize]) -> EvalResult<Tensor> {
+  let len = flat.len();
+  Array::from_shape_vec(IxDyn(shape), flat)
+    .map_err(|_| format!("{} values don't fit the shape {:?}", len, shape))
+}
+
+fn broadcast_to(inp: &Tensor, shape: &[usize]) -> EvalResult<Tensor> {
+  inp
+    .broadcast(IxDyn(shape))
+    .map(|x| x.to_owned())
+    .ok_or(format!(
+      "shape {:?} doesn't broadcast to {:?}",
+      inp.shape(),
+      shape
+    ))
+}
+
+fn pad(inp: &Tensor, padding: &Vec<[usize; 2]>) -> Tensor {
+  let mut padded_shape = inp.raw_dim();
+  for (ax, [pad_lo, pad_hi]) in padding.iter().enumerate() {
+    padded_shape[ax] += pad_lo + pad_hi;
+  }
+  let mut padded = Array::zeros(padded_shape);
+  padded
+    .slice_each_axis_mut(|ax| {
+      let [pad_lo, _] = padding[ax.axis.0];
+      Slice::from(pad_lo..pad_lo + inp.shape()[ax.axis.0])
+    })
+    .assign(inp);
+  padded
+}
+
+// Layers that only move values around, with the same semantics as the chips in layers::shape
+fn shape_forward(
+  tensors: &Vec<Tensor>,
+  layer_config: &LayerConfig,
+  fp: &FixedPointConfig,
+) -> EvalResult<Vec<Tensor>> {
+  let params = &layer_config.layer_params;
+  let inp = &tensors[0];
+
+  let out = match layer_config.layer_type {
+    LayerType::Noop => tensors[params[0] as usize].clone(),
+    LayerType::Broadcast => {
+      check_ndim(inp, &[4])?;
+      broadcast_to(inp, &layer_config.out_shapes[0])?
+    }
+    LayerType::Concatenation => {
+      let views = tensors.iter().map(|x| x.view()).collect::<Vec<_>>();
+      concatenate(Axis(params[0] as usize), views.as_slice()).unwrap_or(inp.clone())
+    }
+    LayerType::Pack => {
+      let axis = params[0] as usize;
+      if axis > 1 {
+        return Err(format!("only axis 0 or 1 is supported, not {}", axis));
+      }
+      let expanded = tensors
+        .iter()
+        .map(|x| x.clone().insert_axis(Axis(axis)))
+        .collect::<Vec<_>>();
+      let views = expanded.iter().map(|x| x.view()).collect::<Vec<_>>();
+      concatenate(Axis(axis), views.as_slice()).unwrap_or(inp.clone())
+    }
+    LayerType::Gather => {
+      let mut tmp = vec![];
+      for col in inp.axis_iter(Axis(1)) {
+        let flatten = col.iter().cloned().collect::<Vec<_>>();
+        params.iter().for_each(|x| tmp.push(flatten[*x as usize]));
+      }
+      Array::from_shape_vec(IxDyn(&[inp.shape()[1], params.len()]), tmp)
+        .unwrap()
+        .reversed_axes()
+    }
+    LayerType::MaskNegInf => {
+      let mask_ndim = params[0] as usize;
+      let mask_shape = params[1..mask_ndim + 1]
+        .iter()
+        .map(|x| *x as usize)
+        .collect::<Vec<_>>();
+      let mask = reshape(params[mask_ndim + 1..].to_vec(), &mask_shape)?;
+      let mask = broadcast_to(&mask, inp.shape())?;
+      let out = inp
+        .iter()
+        .zip(mask.iter())
+        .map(|(x, m)| if *m == 0 { *x } else { fp.min_val })
+        .collect::<Vec<_>>();
+      Array::from_shape_vec(inp.raw_dim(), out).unwrap()
+    }
+    LayerType::Pad => pad(inp, &PadChip::param_vec_to_config(params.clone()).padding),
+    LayerType::Permute => {
+      let params = params.iter().map(|x| *x as usize).collect::<Vec<_>>();
+      check_ndim(inp, &[params.len()])?;
+      inp.clone().permuted_axes(IxDyn(&params))
+    }
+    LayerType::Reshape => reshape(inp.iter().cloned().collect(), &layer_config.out_shapes[0])?,
+    LayerType::ResizeNN => {
+      let output_shape = &layer_config.out_shapes[0];
+      check_ndim(inp, &[4])?;
+      check_batch(inp)?;
+      let mut flat = vec![];
+      for h in 0..output_shape[1] {
+        let h_in = (h as f64 * (inp.shape()[1] as f64 / output_shape[1] as f64)) as usize;
+        for w in 0..output_shape[2] {
+          let w_in = (w as f64 * (inp.shape()[2] as f64 / output_shape[2] as f64)) as usize;
+          for c in 0..inp.shape()[3] {
+            flat.push(inp[[0, h_in, w_in, c]]);
+          }
+        }
+      }
+      Array::from_shape_vec(IxDyn(output_shape), flat).unwrap()
+    }
+    LayerType::Rotate => {
+      check_ndim(inp, &[4])?;
+      let mut flip = vec![false; 4];
+      for p in params.iter() {
+        flip[*p as usize] = true;
+      }
+      let mut out = inp.clone();
+      for (axis, _) in flip.iter().enumerate().filter(|(_, flip)| **flip) {
+        out.invert_axis(Axis(axis));
+      }
+      out
+    }
+    LayerType::Slice => {
+      if params.len() % 2 != 0 {
+        return Err(format!(
+          "expected an even number of params, got {}",
+          params.len()
+        ));
+      }
+      let num_axes = params.len() / 2;
+      let (starts, sizes) = (&params[0..num_axes], &params[num_axes..]);
+      inp
+        .slice_each_axis(|ax| {
+          let start = starts[ax.axis.0] as usize;
+          let size = sizes[ax.axis.0];
+          if size == -1 {
+            Slice::from(start..)
+          } else {
+            Slice::from(start..(start + size as usize))
+          }
+        })
+        .to_owned()
+    }
+    LayerType::Split => {
+      let axis = params[0] as usize;
+      let num_splits = params[1] as usize;
+      let inp = &tensors[1];
+      let split_len = inp.shape()[axis] / num_splits;
+      return Ok(
+        (0..num_splits)
+          .map(|i| {
+            inp
+              .slice_axis(
+                Axis(axis),
+                Slice::from((i * split_len)..((i + 1) * split_len)),
+              )
+              .to_owned()
+          })
+          .collect(),
+      );
+    }
+    LayerType::Transpose => {
+      if params.len() % 2 != 0 {
+        return Err(format!(
+          "expected an even number of params, got {}",
+          params.len()
+        ));
+      }
+      let ndim = params.len() / 2;
+      let inp_shape = params[0..ndim]
+        .iter()
+        .map(|x| *x as usize)
+        .collect::<Vec<_>>();
+      let permutation = params[ndim..]
+        .iter()
+        .map(|x| *x as usize)
+        .collect::<Vec<_>>();
+      reshape(inp.iter().cloned().collect(), &inp_shape)?.permuted_axes(IxDyn(&permutation))
+    }
+    _ => panic!("{:?} is not a shape layer", layer_config.layer_type),
+  };
+  Ok(vec![out])
+}
+
+// Converts the outputs to field elements, in the order they are exposed as public values
+pub fn outputs_to_field<F: PrimeField>(outputs: &Vec<Tensor>) -> Vec<F> {
+  outputs
+    .iter()
+    .flat_map(|tensor| tensor.iter())
+    .map(|x| {
+      if *x >= 0 {
+        F::from(*x as u64)
+      } else {
+        -F::from(x.unsigned_abs())
+      }
+    })
+    .collect()
+}
+
+// Flattens the tensors into the same format as the msgpack inputs
+pub fn tensors_to_msgpack(tensor_map: &HashMap<usize, Tensor>) -> Vec<TensorMsgpack> {
+  let tensor_map = tensor_map.iter().collect::<BTreeMap<_, _>>();
+  tensor_map
+    .into_iter()
+    .map(|(idx, tensor)| TensorMsgpack {
+      idx: *idx as i64,
+      shape: tensor.shape().iter().map(|x| *x as i64).collect(),
+      data: tensor.iter().cloned().collect(),
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use halo2_proofs::halo2curves::bn256::Fr;
+
+  use super::{outputs_to_field, FixedPointConfig, ModelEvaluator};
+  use crate::{
+    model::ModelCircuit,
+    utils::{
+      loader::ModelMsgpack,
+      testing::{layer, model, tensor, MODEL_LOCK, TEST_SF},
+      witness::compute_public_values,
+    },
+  };
+
+  fn fc_model(activation: i64) -> ModelMsgpack {
+    let inp = tensor(0, &[2, 3], &[16, 32, -16, 8, 0, 48]);
+    let weight = tensor(1, &[2, 3], &[16, 0, 16, 32, -16, 8]);
+    let bias = tensor(2, &[2], &[1, -1]);
+    let fc = layer(
+      "FullyConnected",
+      &[activation],
+      &[&inp, &weight, &bias],
+      3,
+      &[2, 2],
+    );
+    model(vec![inp, weight, bias], vec![fc])
+  }
+
+  fn eval(config: &ModelMsgpack) -> Result<Vec<i64>, String> {
+    let (_, final_out) = ModelEvaluator::from_msgpack(config)?.forward()?;
+    Ok(final_out[0].iter().cloned().collect())
+  }
+
+  fn fixed_point() -> FixedPointConfig {
+    FixedPointConfig::from_msgpack(&fc_model(0))
+  }
+
+  #[test]
+  fn div_round_rounds_half_up() {
+    let fp = fixed_point();
+    assert_eq!(fp.div_round(7, 4), 2);
+    assert_eq!(fp.div_round(6, 4), 2);
+    assert_eq!(fp.div_round(-6, 4), -1);
+    assert_eq!(fp.div_round(-7, 4), -2);
+    assert_eq!(fp.div_round(-3, 2), -1);
+    assert_eq!(fp.div_round(-1, TEST_SF), 0);
+  }
+
+  #[test]
+  fn bias_div_round_relu6_clamps_after_the_bias() {
+    let fp = fixed_point();
+    // (relu6, div_res)
+    assert_eq!(
+      fp.bias_div_round_relu6(10 * TEST_SF * TEST_SF, 0),
+      Ok((6 * TEST_SF, 160))
+    );
+    assert_eq!(fp.bias_div_round_relu6(-2 * TEST_SF, 0), Ok((0, -2)));
+    assert_eq!(fp.bias_div_round_relu6(-2 * TEST_SF, 5), Ok((3, 3)));
+    assert_eq!(fp.bias_div_round_relu6(5 * TEST_SF, 1), Ok((6, 6)));
+    // The division result must fit the lookup table
+    assert!(fp.bias_div_round_relu6(3000 * TEST_SF, 0).is_err());
+  }
+
+  #[test]
+  fn softmax_masks_entries() {
+    // Mask of shape [3], broadcast over the rows
+    let inp = tensor(0, &[2, 3], &[16, 0, 16, 0, 32, 0]);
+    let softmax = layer("Softmax", &[1, 3, 0, 1, 0], &[&inp], 1, &[2, 3]);
+    let out = eval(&model(vec![inp], vec![softmax])).unwrap();
+    // The unmasked entries of each row are equal, so they split the probability
+    assert_eq!(out, vec![8, 0, 8, 8, 0, 8]);
+  }
+
+  #[test]
+  fn fully_connected_matches_circuit() {
+    let config = fc_model(1);
+    let (_, final_out) = ModelEvaluator::from_msgpack(&config)
+      .and_then(|evaluator| evaluator.forward())
+      .unwrap();
+    assert_eq!(
+      final_out[0].iter().cloned().collect::<Vec<_>>(),
+      vec![1, 0, 57, 39]
+    );
+
+    let _guard = MODEL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let circuit = ModelCircuit::<Fr>::generate_from_msgpack(config, true);
+    let public_vals = compute_public_values(&circuit);
+    let outputs = outputs_to_field::<Fr>(&final_out);
+    assert_eq!(
+      public_vals[public_vals.len() - outputs.len()..],
+      outputs[..]
+    );
+  }
+
+  #[test]
+  fn unsupported_activation_names_the_layer() {
+    let err = eval(&fc_model(2)).unwrap_err();
+    assert_eq!(err, "layer 0 (FullyConnected): unsupported activation 2");
+  }
+}
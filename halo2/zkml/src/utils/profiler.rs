@@ -0,0 +1,356 @@
+use std::{
+  collections::BTreeMap,
+  fmt::Write,
+  time::{Duration, Instant},
+};
+
+use halo2_proofs::{
+  circuit::Value,
+  halo2curves::ff::{FromUniformBytes, PrimeField},
+  plonk::{
+    Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error,
+    Expression, Fixed, FloorPlanner, Instance, Selector,
+  },
+};
+use serde_derive::Serialize;
+
+use crate::model::ModelCircuit;
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CostProfile {
+  pub name: String,
+  pub rows: usize,
+  pub lookups: usize,
+  pub time_ms: f64,
+  pub time_share: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ProfileReport {
+  pub k: usize,
+  pub rows: usize,
+  pub lookups: usize,
+  pub time_ms: f64,
+  // Rows added outside the DAG layers: the inputs, constants, random vector and commitments
+  pub outside_rows: usize,
+  pub layers: Vec<CostProfile>,
+  pub gadgets: Vec<CostProfile>,
+}
+
+impl ProfileReport {
+  pub fn to_csv(&self) -> String {
+    let mut csv = "kind,name,rows,lookups,time_ms,time_share\n".to_string();
+    let rows = self
+      .layers
+      .iter()
+      .map(|x| ("layer", x))
+      .chain(self.gadgets.iter().map(|x| ("gadget", x)));
+    for (kind, x) in rows {
+      writeln!(
+        csv,
+        "{},\"{}\",{},{},{:.3},{:.4}",
+        kind, x.name, x.rows, x.lookups, x.time_ms, x.time_share
+      )
+      .unwrap();
+    }
+    csv
+  }
+}
+
+// Collects the selectors an expression is gated by
+fn collect_selectors<F: PrimeField>(expr: &Expression<F>, selectors: &mut Vec<Selector>) {
+  match expr {
+    Expression::Selector(s) => selectors.push(*s),
+    Expression::Negated(a) | Expression::Scaled(a, _) => collect_selectors(a, selectors),
+    Expression::Sum(a, b) | Expression::Product(a, b) => {
+      collect_selectors(a, selectors);
+      collect_selectors(b, selectors);
+    }
+    _ => {}
+  }
+}
+
+// Synthesizes the circuit with the witness values, recording the rows, lookups and time spent in
+// every DAG layer and gadget region
+pub struct LayerProfiler<F: PrimeField> {
+  challenge: F,
+  // Number of lookup arguments gated by each selector
+  lookups_per_selector: Vec<(Selector, usize)>,
+  max_row: usize,
+  outside_rows: usize,
+  lookups: usize,
+  namespaces: Vec<(String, usize, usize, Instant)>,
+  layers: Vec<CostProfile>,
+  region: Option<(String, usize, usize, usize, Instant)>,
+  gadgets: BTreeMap<String, (usize, usize, Duration)>,
+}
+
+impl<F: PrimeField> LayerProfiler<F> {
+  fn new(cs: &ConstraintSystem<F>) -> Self {
+    let mut lookups_per_selector: Vec<(Selector, usize)> = vec![];
+    for lookup in cs.lookups().iter() {
+      let mut selectors = vec![];
+      for expr in lookup.input_expressions().iter() {
+        collect_selectors(expr, &mut selectors);
+      }
+      selectors.dedup();
+      for selector in selectors {
+        match lookups_per_selector
+          .iter_mut()
+          .find(|(s, _)| *s == selector)
+        {
+          Some((_, count)) => *count += 1,
+          None => lookups_per_selector.push((selector, 1)),
+        }
+      }
+    }
+
+    Self {
+      challenge: F::random(rand::thread_rng()),
+      lookups_per_selector,
+      max_row: 0,
+      outside_rows: 0,
+      lookups: 0,
+      namespaces: vec![],
+      layers: vec![],
+      region: None,
+      gadgets: BTreeMap::new(),
+    }
+  }
+
+  fn update(&mut self, row: usize) {
+    if row + 1 > self.max_row {
+      // The DAG names every layer "dag <layer>"
+      if !self
+        .namespaces
+        .iter()
+        .any(|(name, ..)| name.starts_with("dag "))
+      {
+        self.outside_rows += row + 1 - self.max_row;
+      }
+      self.max_row = row + 1;
+    }
+    if let Some((_, start, end, _, _)) = self.region.as_mut() {
+      *start = (*start).min(row);
+      *end = (*end).max(row + 1);
+    }
+  }
+
+  pub fn profile<C: Circuit<F>>(circuit: &C, k: usize) -> Result<ProfileReport, Error> {
+    let mut cs = ConstraintSystem::default();
+    let config = C::configure(&mut cs);
+    let mut profiler = Self::new(&cs);
+
+    let start = Instant::now();
+    C::FloorPlanner::synthesize(&mut profiler, circuit, config, cs.constants().to_vec())?;
+    let total = start.elapsed().as_secs_f64() * 1000.;
+
+    let share = |time_ms: f64| if total > 0. { time_ms / total } else { 0. };
+    let layers = profiler
+      .layers
+      .into_iter()
+      .map(|layer| CostProfile {
+        time_share: share(layer.time_ms),
+        ..layer
+      })
+      .collect();
+    let gadgets = profiler
+      .gadgets
+      .into_iter()
+      .map(|(name, (rows, lookups, time))| {
+        let time_ms = time.as_secs_f64() * 1000.;
+        CostProfile {
+          name,
+          rows,
+          lookups,
+          time_ms,
+          time_share: share(time_ms),
+        }
+      })
+      .collect();
+
+    Ok(ProfileReport {
+      k,
+      rows: profiler.max_row,
+      lookups: profiler.lookups,
+      time_ms: total,
+      outside_rows: profiler.outside_rows,
+      layers,
+      gadgets,
+    })
+  }
+}
+
+impl<F: PrimeField> Assignment<F> for LayerProfiler<F> {
+  fn enter_region<NR, N>(&mut self, name_fn: N)
+  where
+    NR: Into<String>,
+    N: FnOnce() -> NR,
+  {
+    self.region = Some((
+      name_fn().into(),
+      usize::MAX,
+      0,
+      self.lookups,
+      Instant::now(),
+    ));
+  }
+
+  fn exit_region(&mut self) {
+    if let Some((name, start, end, lookups, time)) = self.region.take() {
+      // Gadget::op_aligned_rows names its region "gadget <name>"
+      if name.starts_with("gadget ") {
+        let entry = self
+          .gadgets
+          .entry(name[7..].to_string())
+          .or_insert((0, 0, Duration::ZERO));
+        entry.0 += end.saturating_sub(start);
+        entry.1 += self.lookups - lookups;
+        entry.2 += time.elapsed();
+      }
+    }
+  }
+
+  fn get_challenge(&self, _challenge: Challenge) -> Value<F> {
+    Value::known(self.challenge)
+  }
+
+  fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
+  where
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    if let Some((_, count)) = self
+      .lookups_per_selector
+      .iter()
+      .find(|(s, _)| s == selector)
+    {
+      self.lookups += count;
+    }
+    self.update(row);
+    Ok(())
+  }
+
+  fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+    Ok(Value::unknown())
+  }
+
+  fn annotate_column<A, AR>(&mut self, _annotation: A, _column: Column<Any>)
+  where
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+  }
+
+  fn assign_advice<V, VR, A, AR>(
+    &mut self,
+    _: A,
+    _: Column<Advice>,
+    row: usize,
+    to: V,
+  ) -> Result<(), Error>
+  where
+    V: FnOnce() -> Value<VR>,
+    VR: Into<Assigned<F>>,
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    // Compute the value so the witness generation cost is part of the timings
+    let _ = to().map(|v| v.into().evaluate());
+    self.update(row);
+    Ok(())
+  }
+
+  fn assign_fixed<V, VR, A, AR>(
+    &mut self,
+    _: A,
+    _: Column<Fixed>,
+    _: usize,
+    _: V,
+  ) -> Result<(), Error>
+  where
+    V: FnOnce() -> Value<VR>,
+    VR: Into<Assigned<F>>,
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    Ok(())
+  }
+
+  fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+    Ok(())
+  }
+
+  fn fill_from_row(
+    &mut self,
+    _: Column<Fixed>,
+    _: usize,
+    _: Value<Assigned<F>>,
+  ) -> Result<(), Error> {
+    Ok(())
+  }
+
+  fn push_namespace<NR, N>(&mut self, name_fn: N)
+  where
+    NR: Into<String>,
+    N: FnOnce() -> NR,
+  {
+    self
+      .namespaces
+      .push((name_fn().into(), self.max_row, self.lookups, Instant::now()));
+  }
+
+  fn pop_namespace(&mut self, _: Option<String>) {
+    if let Some((name, start, lookups, time)) = self.namespaces.pop() {
+      if name.starts_with("dag ") {
+        self.layers.push(CostProfile {
+          name: format!("{} {}", self.layers.len(), &name[4..]),
+          rows: self.max_row - start,
+          lookups: self.lookups - lookups,
+          time_ms: time.elapsed().as_secs_f64() * 1000.,
+          time_share: 0.,
+        });
+      }
+    }
+  }
+}
+
+pub fn profile_model<F: PrimeField + Ord + FromUniformBytes<64>>(
+  circuit: &ModelCircuit<F>,
+) -> ProfileReport {
+  LayerProfiler::profile(circuit, circuit.k).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+  use halo2_proofs::halo2curves::bn256::Fr;
+
+  use super::profile_model;
+  use crate::{
+    model::ModelCircuit,
+    utils::testing::{layer, model, tensor, MODEL_LOCK},
+  };
+
+  #[test]
+  fn layer_rows_add_up_to_total() {
+    let _guard = MODEL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let inp = tensor(0, &[2, 3], &[16, 32, -16, 8, 0, 48]);
+    let weight = tensor(1, &[2, 3], &[16, 0, 16, 32, -16, 8]);
+    let bias = tensor(2, &[2], &[1, -1]);
+    let fc = layer("FullyConnected", &[1], &[&inp, &weight, &bias], 3, &[2, 2]);
+    let circuit =
+      ModelCircuit::<Fr>::generate_from_msgpack(model(vec![inp, weight, bias], vec![fc]), true);
+
+    let report = profile_model(&circuit);
+    let layer_rows: usize = report.layers.iter().map(|layer| layer.rows).sum();
+    assert_eq!(report.layers.len(), 1);
+    assert_eq!(layer_rows + report.outside_rows, report.rows);
+
+    // The fully connected layer checks its product with dot products
+    let dot_product = report
+      .gadgets
+      .iter()
+      .find(|gadget| gadget.name == "dot product");
+    assert!(dot_product.unwrap().rows > 0);
+  }
+}
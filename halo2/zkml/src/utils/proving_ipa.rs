@@ -6,7 +6,6 @@ use std::{
 };
 
 use halo2_proofs::{
-  dev::MockProver,
   halo2curves::pasta::{EqAffine, Fp},
   plonk::{create_proof, keygen_pk, keygen_vk, verify_proof},
   poly::{
@@ -23,7 +22,9 @@ use halo2_proofs::{
   },
 };
 
-use crate::{model::ModelCircuit, utils::helpers::get_public_values};
+use zkperf_report::{BenchmarkResult, PhaseTracker};
+
+use crate::{model::ModelCircuit, utils::witness::compute_public_values};
 
 pub fn get_ipa_params(params_dir: &str, degree: u32) -> ParamsIPA<EqAffine> {
   let path = format!("{}/{}.params", params_dir, degree);
@@ -45,7 +46,7 @@ pub fn get_ipa_params(params_dir: &str, degree: u32) -> ParamsIPA<EqAffine> {
   params
 }
 
-pub fn time_circuit_ipa(circuit: ModelCircuit<Fp>) {
+pub fn time_circuit_ipa(circuit: ModelCircuit<Fp>, results: &mut BenchmarkResult) {
   let rng = rand::thread_rng();
   let start = Instant::now();
 
@@ -61,6 +62,7 @@ pub fn time_circuit_ipa(circuit: ModelCircuit<Fp>) {
     circuit_duration
   );
 
+  let tracker = PhaseTracker::start("keygen");
   let vk = keygen_vk(&params, &empty_circuit).unwrap();
   let vk_duration = start.elapsed();
   println!(
@@ -74,16 +76,20 @@ pub fn time_circuit_ipa(circuit: ModelCircuit<Fp>) {
     "Time elapsed in generating pkey: {:?}",
     pk_duration - vk_duration
   );
+  results.set_k(degree, pk.get_vk().cs().num_advice_columns());
+  results.record_phase(tracker.finish());
   drop(empty_circuit);
 
   let fill_duration = start.elapsed();
-  let _prover = MockProver::run(degree, &proof_circuit, vec![vec![]]).unwrap();
-  let public_vals = get_public_values();
+  let tracker = PhaseTracker::start("witness");
+  let public_vals = compute_public_values(&proof_circuit);
+  results.record_phase(tracker.finish());
   println!(
     "Time elapsed in filling circuit: {:?}",
     fill_duration - pk_duration
   );
 
+  let tracker = PhaseTracker::start("prove");
   let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
   create_proof::<IPACommitmentScheme<EqAffine>, ProverIPA<EqAffine>, _, _, _, _>(
     &params,
@@ -97,6 +103,9 @@ pub fn time_circuit_ipa(circuit: ModelCircuit<Fp>) {
   let proof = transcript.finalize();
   let proof_duration = start.elapsed();
   println!("Proving time: {:?}", proof_duration - fill_duration);
+  results.set_prover_time(proof_duration - fill_duration);
+  results.record_phase(tracker.finish());
+  results.record_peak_rss();
 
   let proof_size = {
     let mut folder = std::path::PathBuf::new();
@@ -107,9 +116,11 @@ pub fn time_circuit_ipa(circuit: ModelCircuit<Fp>) {
     fd.metadata().unwrap().len()
   };
   println!("Proof size: {} bytes", proof_size);
+  results.set_proof_size(proof_size as usize);
 
   let strategy = SingleStrategy::new(&params);
   let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+  let tracker = PhaseTracker::start("verify");
   assert!(
     verify_proof(
       &params,
@@ -123,4 +134,6 @@ pub fn time_circuit_ipa(circuit: ModelCircuit<Fp>) {
   );
   let verify_duration = start.elapsed();
   println!("Verifying time: {:?}", verify_duration - proof_duration);
+  results.set_verifier_time(verify_duration - proof_duration);
+  results.record_phase(tracker.finish());
 }
@@ -6,7 +6,6 @@ use std::{
 };
 
 use halo2_proofs::{
-  dev::MockProver,
   halo2curves::bn256::{Bn256, Fr, G1Affine},
   plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, VerifyingKey},
   poly::{
@@ -23,7 +22,9 @@ use halo2_proofs::{
   SerdeFormat,
 };
 
-use crate::{model::ModelCircuit, utils::helpers::get_public_values};
+use zkperf_report::{BenchmarkResult, PhaseTracker};
+
+use crate::{model::ModelCircuit, utils::witness::compute_public_values};
 
 pub fn get_kzg_params(params_dir: &str, degree: u32) -> ParamsKZG<Bn256> {
   let rng = rand::thread_rng();
@@ -71,7 +72,7 @@ pub fn verify_kzg(
   );
 }
 
-pub fn time_circuit_kzg(circuit: ModelCircuit<Fr>) {
+pub fn time_circuit_kzg(circuit: ModelCircuit<Fr>, results: &mut BenchmarkResult) {
   let rng = rand::thread_rng();
   let start = Instant::now();
 
@@ -84,6 +85,7 @@ pub fn time_circuit_kzg(circuit: ModelCircuit<Fr>) {
     circuit_duration
   );
 
+  let tracker = PhaseTracker::start("keygen");
   let vk_circuit = circuit.clone();
   let vk = keygen_vk(&params, &vk_circuit).unwrap();
   drop(vk_circuit);
@@ -107,11 +109,14 @@ pub fn time_circuit_kzg(circuit: ModelCircuit<Fr>) {
 
   let pkey_size = serialize(&pk.to_bytes(SerdeFormat::RawBytes), "pkey");
   println!("pkey size: {} bytes", pkey_size);
+  results.record_phase(tracker.finish());
+  results.set_k(degree, pk.get_vk().cs().num_advice_columns());
 
   let fill_duration = start.elapsed();
+  let tracker = PhaseTracker::start("witness");
   let proof_circuit = circuit.clone();
-  let _prover = MockProver::run(degree, &proof_circuit, vec![vec![]]).unwrap();
-  let public_vals = get_public_values();
+  let public_vals = compute_public_values(&proof_circuit);
+  results.record_phase(tracker.finish());
   println!(
     "Time elapsed in filling circuit: {:?}",
     fill_duration - pk_duration
@@ -126,6 +131,7 @@ pub fn time_circuit_kzg(circuit: ModelCircuit<Fr>) {
   let public_vals_u8_size = serialize(&public_vals_u8, "public_vals");
   println!("Public vals size: {} bytes", public_vals_u8_size);
 
+  let tracker = PhaseTracker::start("prove");
   let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
   create_proof::<
     KZGCommitmentScheme<Bn256>,
@@ -146,16 +152,21 @@ pub fn time_circuit_kzg(circuit: ModelCircuit<Fr>) {
   let proof = transcript.finalize();
   let proof_duration = start.elapsed();
   println!("Proving time: {:?}", proof_duration - fill_duration);
+  results.set_prover_time(proof_duration - fill_duration);
+  results.record_phase(tracker.finish());
+  results.record_peak_rss();
 
   let proof_size = serialize(&proof, "proof");
   let proof = std::fs::read("proof").unwrap();
 
   println!("Proof size: {} bytes", proof_size);
+  results.set_proof_size(proof_size as usize);
 
   let strategy = SingleStrategy::new(&params);
   let transcript_read = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
 
   println!("public vals: {:?}", public_vals);
+  let tracker = PhaseTracker::start("verify");
   verify_kzg(
     &params,
     &pk.get_vk(),
@@ -165,6 +176,8 @@ pub fn time_circuit_kzg(circuit: ModelCircuit<Fr>) {
   );
   let verify_duration = start.elapsed();
   println!("Verifying time: {:?}", verify_duration - proof_duration);
+  results.set_verifier_time(verify_duration - proof_duration);
+  results.record_phase(tracker.finish());
 }
 
 // Standalone verification
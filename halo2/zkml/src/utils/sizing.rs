@@ -0,0 +1,315 @@
+use std::collections::BTreeMap;
+
+use halo2_proofs::{
+  circuit::Value,
+  halo2curves::ff::{FromUniformBytes, PrimeField},
+  plonk::{
+    Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error, Fixed,
+    FloorPlanner, Instance, Selector,
+  },
+};
+use serde_derive::Serialize;
+
+use crate::{model::ModelCircuit, utils::loader::ModelMsgpack};
+
+// Largest k we are willing to try
+pub const MAX_K: i64 = 28;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LayerRows {
+  pub name: String,
+  pub rows: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SizingReport {
+  pub k: i64,
+  pub num_cols: i64,
+  pub rows: usize,
+  pub advice_rows: usize,
+  pub table_rows: usize,
+  pub usable_rows: usize,
+  pub fits: bool,
+  pub layers: Vec<LayerRows>,
+  pub gadgets: BTreeMap<String, usize>,
+}
+
+// Counts the rows used by the layout. The lookup tables are assigned to fixed columns outside of
+// any region and are counted apart from the advice rows, since they grow with k.
+#[derive(Default)]
+pub struct RowCounter {
+  max_row: usize,
+  table_rows: usize,
+  namespaces: Vec<(String, usize)>,
+  layers: Vec<LayerRows>,
+  region: Option<(String, usize, usize)>,
+  gadgets: BTreeMap<String, usize>,
+}
+
+impl RowCounter {
+  fn update(&mut self, row: usize) {
+    self.max_row = self.max_row.max(row + 1);
+    if let Some((_, start, end)) = self.region.as_mut() {
+      *start = (*start).min(row);
+      *end = (*end).max(row + 1);
+    }
+  }
+
+  pub fn measure<F: PrimeField, C: Circuit<F>>(
+    circuit: &C,
+  ) -> Result<(Self, ConstraintSystem<F>), Error> {
+    let mut cs = ConstraintSystem::default();
+    let config = C::configure(&mut cs);
+    let mut counter = Self::default();
+    C::FloorPlanner::synthesize(&mut counter, circuit, config, cs.constants().to_vec())?;
+    Ok((counter, cs))
+  }
+}
+
+impl<F: PrimeField> Assignment<F> for RowCounter {
+  fn enter_region<NR, N>(&mut self, name_fn: N)
+  where
+    NR: Into<String>,
+    N: FnOnce() -> NR,
+  {
+    self.region = Some((name_fn().into(), usize::MAX, 0));
+  }
+
+  fn exit_region(&mut self) {
+    if let Some((name, start, end)) = self.region.take() {
+      // Gadget::op_aligned_rows names its region "gadget <name>"
+      if name.starts_with("gadget ") && end > start {
+        *self.gadgets.entry(name[7..].to_string()).or_insert(0) += end - start;
+      }
+    }
+  }
+
+  fn get_challenge(&self, _challenge: Challenge) -> Value<F> {
+    Value::unknown()
+  }
+
+  fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+  where
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    self.update(row);
+    Ok(())
+  }
+
+  fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+    Ok(Value::unknown())
+  }
+
+  fn annotate_column<A, AR>(&mut self, _annotation: A, _column: Column<Any>)
+  where
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+  }
+
+  fn assign_advice<V, VR, A, AR>(
+    &mut self,
+    _: A,
+    _: Column<Advice>,
+    row: usize,
+    _: V,
+  ) -> Result<(), Error>
+  where
+    V: FnOnce() -> Value<VR>,
+    VR: Into<Assigned<F>>,
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    self.update(row);
+    Ok(())
+  }
+
+  fn assign_fixed<V, VR, A, AR>(
+    &mut self,
+    _: A,
+    _: Column<Fixed>,
+    row: usize,
+    _: V,
+  ) -> Result<(), Error>
+  where
+    V: FnOnce() -> Value<VR>,
+    VR: Into<Assigned<F>>,
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    if self.region.is_none() {
+      self.table_rows = self.table_rows.max(row + 1);
+    } else {
+      self.update(row);
+    }
+    Ok(())
+  }
+
+  fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+    Ok(())
+  }
+
+  fn fill_from_row(
+    &mut self,
+    _: Column<Fixed>,
+    _: usize,
+    _: Value<Assigned<F>>,
+  ) -> Result<(), Error> {
+    Ok(())
+  }
+
+  fn push_namespace<NR, N>(&mut self, name_fn: N)
+  where
+    NR: Into<String>,
+    N: FnOnce() -> NR,
+  {
+    self.namespaces.push((name_fn().into(), self.max_row));
+  }
+
+  fn pop_namespace(&mut self, _: Option<String>) {
+    if let Some((name, start)) = self.namespaces.pop() {
+      // The DAG names every layer "dag <layer>"
+      if name.starts_with("dag ") {
+        self.layers.push(LayerRows {
+          name: name[4..].to_string(),
+          rows: self.max_row - start,
+        });
+      }
+    }
+  }
+}
+
+// Lays out the model with the given k and number of columns and counts the rows it uses
+pub fn measure_model<F: PrimeField + Ord + FromUniformBytes<64>>(
+  config: &ModelMsgpack,
+  k: i64,
+  num_cols: i64,
+) -> SizingReport {
+  let config = ModelMsgpack {
+    k,
+    num_cols,
+    ..config.clone()
+  };
+  let circuit = ModelCircuit::<F>::generate_from_msgpack(config, true);
+  let (counter, cs) = RowCounter::measure(&circuit).unwrap();
+
+  let usable_rows = (1 << k) - (cs.blinding_factors() + 1);
+  let rows = counter.max_row.max(counter.table_rows);
+  SizingReport {
+    k,
+    num_cols,
+    rows,
+    advice_rows: counter.max_row,
+    table_rows: counter.table_rows,
+    usable_rows,
+    fits: rows <= usable_rows,
+    layers: counter.layers,
+    gadgets: counter.gadgets,
+  }
+}
+
+// Finds the smallest k >= min_k that fits the model in num_cols columns. k also sets the
+// fixed-point range and lookup table sizes, so the caller decides how low it may go.
+pub fn find_min_k<F: PrimeField + Ord + FromUniformBytes<64>>(
+  config: &ModelMsgpack,
+  num_cols: i64,
+  min_k: i64,
+) -> Option<SizingReport> {
+  let mut k = min_k;
+  while k <= MAX_K {
+    let report = measure_model::<F>(config, k, num_cols);
+    if report.fits {
+      return Some(report);
+    }
+    // The advice rows barely depend on k, so jump straight to the first k they could fit in. The
+    // tables are resized with k and are checked again there.
+    let needed = (report.advice_rows + (1 << k) - report.usable_rows).next_power_of_two();
+    k = (k + 1).max(needed.trailing_zeros() as i64);
+  }
+  None
+}
+
+// Smallest k for every column budget
+pub fn sweep_cols<F: PrimeField + Ord + FromUniformBytes<64>>(
+  config: &ModelMsgpack,
+  cols: &Vec<i64>,
+  min_k: i64,
+) -> Vec<Option<SizingReport>> {
+  cols
+    .iter()
+    .map(|num_cols| find_min_k::<F>(config, *num_cols, min_k))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+
+  use super::{find_min_k, measure_model, RowCounter};
+  use crate::{
+    model::ModelCircuit,
+    utils::{
+      loader::ModelMsgpack,
+      testing::{layer, model, tensor, MODEL_LOCK, TEST_COLS, TEST_K},
+      witness::compute_public_values,
+    },
+  };
+
+  fn fc_model() -> ModelMsgpack {
+    let inp = tensor(0, &[2, 3], &[16, 32, -16, 8, 0, 48]);
+    let weight = tensor(1, &[2, 3], &[16, 0, 16, 32, -16, 8]);
+    let bias = tensor(2, &[2], &[1, -1]);
+    let fc = layer("FullyConnected", &[1], &[&inp, &weight, &bias], 3, &[2, 2]);
+    model(vec![inp, weight, bias], vec![fc])
+  }
+
+  // The tables have num_rows = 2^k - 9 entries
+  fn table_rows(k: i64) -> usize {
+    (1 << k) - 9
+  }
+
+  #[test]
+  fn row_counter_counts_layers_and_tables() {
+    let _guard = MODEL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let circuit = ModelCircuit::<Fr>::generate_from_msgpack(fc_model(), true);
+    let (counter, _) = RowCounter::measure(&circuit).unwrap();
+
+    assert_eq!(counter.table_rows, table_rows(TEST_K));
+    assert!(counter.max_row > 0);
+    assert_eq!(counter.layers.len(), 1);
+    assert_eq!(counter.layers[0].name, "fully connected");
+    assert!(counter.layers[0].rows > 0 && counter.layers[0].rows <= counter.max_row);
+    assert!(!counter.gadgets.is_empty());
+  }
+
+  #[test]
+  fn small_model_is_sized_by_its_tables() {
+    let _guard = MODEL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let report = measure_model::<Fr>(&fc_model(), TEST_K, TEST_COLS);
+    // A few advice rows, but the tables take up almost all of 2^k
+    assert!(report.advice_rows < report.table_rows);
+    assert_eq!(report.rows, table_rows(TEST_K));
+    assert!(report.fits);
+  }
+
+  #[test]
+  fn find_min_k_fits_the_circuit() {
+    let _guard = MODEL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let config = fc_model();
+    let report = find_min_k::<Fr>(&config, TEST_COLS, TEST_K).unwrap();
+    assert_eq!(report.k, TEST_K);
+    assert_eq!(report.rows, table_rows(report.k));
+
+    // The reported k must be enough for the prover, tables included
+    let config = ModelMsgpack {
+      k: report.k,
+      num_cols: TEST_COLS,
+      ..config
+    };
+    let circuit = ModelCircuit::<Fr>::generate_from_msgpack(config, true);
+    let public_vals = compute_public_values(&circuit);
+    let prover = MockProver::run(report.k as u32, &circuit, vec![public_vals]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+  }
+}
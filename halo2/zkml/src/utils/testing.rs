@@ -0,0 +1,62 @@
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+use crate::utils::loader::{LayerMsgpack, ModelMsgpack, TensorMsgpack};
+
+// Small enough to keep the tables cheap: min_val = -2048, max_val = 2038
+pub const TEST_SF: i64 = 16;
+pub const TEST_K: i64 = 12;
+pub const TEST_COLS: i64 = 10;
+
+lazy_static! {
+  // The model circuits go through the global GADGET_CONFIG, so they must not be built in parallel
+  pub static ref MODEL_LOCK: Mutex<()> = Mutex::new(());
+}
+
+pub fn tensor(idx: i64, shape: &[i64], data: &[i64]) -> TensorMsgpack {
+  TensorMsgpack {
+    idx,
+    shape: shape.to_vec(),
+    data: data.to_vec(),
+  }
+}
+
+// A layer reading `inputs` and writing tensor `out_idx`
+pub fn layer(
+  layer_type: &str,
+  params: &[i64],
+  inputs: &[&TensorMsgpack],
+  out_idx: i64,
+  out_shape: &[i64],
+) -> LayerMsgpack {
+  LayerMsgpack {
+    layer_type: layer_type.to_string(),
+    params: params.to_vec(),
+    inp_idxes: inputs.iter().map(|t| t.idx).collect(),
+    inp_shapes: inputs.iter().map(|t| t.shape.clone()).collect(),
+    out_idxes: vec![out_idx],
+    out_shapes: vec![out_shape.to_vec()],
+    mask: vec![],
+  }
+}
+
+// The model config `load_model_msgpack` would return, with the first tensor as the input
+pub fn model(tensors: Vec<TensorMsgpack>, layers: Vec<LayerMsgpack>) -> ModelMsgpack {
+  let out_idxes = layers.last().unwrap().out_idxes.clone();
+  ModelMsgpack {
+    global_sf: TEST_SF,
+    k: TEST_K,
+    num_cols: TEST_COLS,
+    inp_idxes: vec![tensors[0].idx],
+    out_idxes,
+    tensors,
+    layers,
+    use_selectors: Some(true),
+    commit_before: Some(vec![]),
+    commit_after: Some(vec![]),
+    bits_per_elem: Some(TEST_K),
+    num_random: Some(16),
+    num_witness_cols: Some(3),
+  }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use halo2_proofs::{
+  circuit::Value,
+  halo2curves::ff::{FromUniformBytes, PrimeField},
+  plonk::{
+    Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error, Fixed,
+    FloorPlanner, Instance, Selector,
+  },
+};
+
+use crate::model::ModelCircuit;
+
+// Runs the synthesis without checking any constraints, keeping only the advice values. The public
+// values are the advice cells that get copied into the instance column.
+pub struct PublicValuesCollector<F: PrimeField> {
+  challenge: F,
+  advice: HashMap<(usize, usize), F>,
+  public_vals: HashMap<usize, F>,
+}
+
+impl<F: PrimeField> PublicValuesCollector<F> {
+  pub fn collect<C: Circuit<F>>(circuit: &C) -> Result<Vec<F>, Error> {
+    let mut cs = ConstraintSystem::default();
+    let config = C::configure(&mut cs);
+    let mut collector = Self {
+      // The randomness only feeds the second phase checks, never the public values
+      challenge: F::random(rand::thread_rng()),
+      advice: HashMap::new(),
+      public_vals: HashMap::new(),
+    };
+    C::FloorPlanner::synthesize(&mut collector, circuit, config, cs.constants().to_vec())?;
+
+    let num_public = collector.public_vals.keys().max().map_or(0, |row| row + 1);
+    (0..num_public)
+      .map(|row| {
+        collector
+          .public_vals
+          .get(&row)
+          .cloned()
+          .ok_or(Error::Synthesis)
+      })
+      .collect()
+  }
+}
+
+impl<F: PrimeField> Assignment<F> for PublicValuesCollector<F> {
+  fn enter_region<NR, N>(&mut self, _: N)
+  where
+    NR: Into<String>,
+    N: FnOnce() -> NR,
+  {
+  }
+
+  fn exit_region(&mut self) {}
+
+  fn get_challenge(&self, _challenge: Challenge) -> Value<F> {
+    Value::known(self.challenge)
+  }
+
+  fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, _: usize) -> Result<(), Error>
+  where
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    Ok(())
+  }
+
+  fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+    Ok(Value::unknown())
+  }
+
+  fn annotate_column<A, AR>(&mut self, _annotation: A, _column: Column<Any>)
+  where
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+  }
+
+  fn assign_advice<V, VR, A, AR>(
+    &mut self,
+    _: A,
+    column: Column<Advice>,
+    row: usize,
+    to: V,
+  ) -> Result<(), Error>
+  where
+    V: FnOnce() -> Value<VR>,
+    VR: Into<Assigned<F>>,
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    let advice = &mut self.advice;
+    to().map(|v| advice.insert((column.index(), row), v.into().evaluate()));
+    Ok(())
+  }
+
+  fn assign_fixed<V, VR, A, AR>(
+    &mut self,
+    _: A,
+    _: Column<Fixed>,
+    _: usize,
+    _: V,
+  ) -> Result<(), Error>
+  where
+    V: FnOnce() -> Value<VR>,
+    VR: Into<Assigned<F>>,
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    Ok(())
+  }
+
+  fn copy(
+    &mut self,
+    lhs: Column<Any>,
+    offset_lhs: usize,
+    rhs: Column<Any>,
+    offset_rhs: usize,
+  ) -> Result<(), Error> {
+    // constrain_instance copies an advice cell into the instance column
+    if let (Any::Advice(_), Any::Instance) = (lhs.column_type(), rhs.column_type()) {
+      let val = self
+        .advice
+        .get(&(lhs.index(), offset_lhs))
+        .cloned()
+        .ok_or(Error::Synthesis)?;
+      self.public_vals.insert(offset_rhs, val);
+    }
+    Ok(())
+  }
+
+  fn fill_from_row(
+    &mut self,
+    _: Column<Fixed>,
+    _: usize,
+    _: Value<Assigned<F>>,
+  ) -> Result<(), Error> {
+    Ok(())
+  }
+
+  fn push_namespace<NR, N>(&mut self, _: N)
+  where
+    NR: Into<String>,
+    N: FnOnce() -> NR,
+  {
+  }
+
+  fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+// Computes the public values (commitments, then outputs) without running MockProver
+pub fn compute_public_values<F: PrimeField + Ord + FromUniformBytes<64>>(
+  circuit: &ModelCircuit<F>,
+) -> Vec<F> {
+  PublicValuesCollector::collect(circuit).unwrap()
+}
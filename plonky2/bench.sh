@@ -30,9 +30,8 @@ cd $ECDSA_DIR
 cargo build --release
 cd ..
 touch $ECDSA_LOG
-#"$ECDSA_DIR"/target/release/standard build
-{ RUST_LOG=debug /usr/bin/time -v "$ECDSA_DIR"/target/release/standard "$ECDSA_OUTPUT" 40; } 2> "$ECDSA_LOG"
-echo "$(jq --arg tmp $(echo "scale=6; $(cat "$ECDSA_LOG" | grep "Maximum resident set size" | tr -d -c 0-9)/1024" | bc) '.+={"MemoryConsumption": $tmp }' "$ECDSA_OUTPUT")" > "$ECDSA_OUTPUT"
+"$ECDSA_DIR"/target/release/standard build "$ECDSA_OUTPUT" 40
+{ RUST_LOG=debug /usr/bin/time -v "$ECDSA_DIR"/target/release/standard prove "$ECDSA_OUTPUT" 40; } 2> "$ECDSA_LOG"
 
 cd $MERKLE_DIR
 cargo build --release
@@ -41,23 +40,20 @@ cd ..
 touch $MERKLE_LOG
 RUST_LOG=debug /usr/bin/time -v "$MERKLE_DIR"/target/release/standard build "$MERKLE_OUTPUT"
 { RUST_LOG=debug /usr/bin/time -v "$MERKLE_DIR"/target/release/standard prove "$MERKLE_OUTPUT"; } 2> "$MERKLE_LOG"
-echo "$(jq --arg tmp $(echo "scale=6; $(cat "$MERKLE_LOG" | grep "Maximum resident set size" | tr -d -c 0-9)/1024" | bc) '.+={"MemoryConsumption": $tmp }' "$MERKLE_OUTPUT")" > "$MERKLE_OUTPUT"
 
 cd $ML_DIR
 cargo build --release
 cd ..
 
 touch $MNIST_LOG
-"$ML_DIR"/target/release/time_circuit mnist "$ML_DIR"/examples/mnist/model.msgpack "$ML_DIR"/examples/mnist/inp.msgpack build "$MNIST_OUTPUT"
-{ RUST_LOG=debug /usr/bin/time -v "$ML_DIR"/target/release/time_circuit mnist "$ML_DIR"/examples/mnist/model.msgpack "$ML_DIR"/examples/mnist/inp.msgpack prove "$MNIST_OUTPUT"; } 2> $MNIST_LOG
-echo "$(jq --arg tmp $(echo "scale=6; $(cat "$MNIST_LOG" | grep "Maximum resident set size" | tr -d -c 0-9)/1024" | bc) '.+={"MemoryConsumption": $tmp }' "$MNIST_OUTPUT")" > "$MNIST_OUTPUT"
-echo "$(jq '. += {"Circuit": "MNIST" }' "$MNIST_OUTPUT")" > "$MNIST_OUTPUT"
+"$ML_DIR"/target/release/time_circuit mnist "$ML_DIR"/examples/mnist/model.msgpack "$ML_DIR"/examples/mnist/inp.msgpack build "$MNIST_OUTPUT" --cols 60
+{ RUST_LOG=debug /usr/bin/time -v "$ML_DIR"/target/release/time_circuit mnist "$ML_DIR"/examples/mnist/model.msgpack "$ML_DIR"/examples/mnist/inp.msgpack prove "$MNIST_OUTPUT" --cols 60; } 2> $MNIST_LOG
+{ RUST_LOG=debug "$ML_DIR"/target/release/time_circuit mnist "$ML_DIR"/examples/mnist/model.msgpack "$ML_DIR"/examples/mnist/inp.msgpack verify "$MNIST_OUTPUT"; } 2>> $MNIST_LOG
 
 touch $DLRM_LOG
-"$ML_DIR"/target/release/time_circuit dlrm "$ML_DIR"/examples/dlrm/model.msgpack "$ML_DIR"/examples/dlrm/inp.msgpack build "$DLRM_OUTPUT"
-{ RUST_LOG=debug /usr/bin/time -v "$ML_DIR"/target/release/time_circuit dlrm "$ML_DIR"/examples/dlrm/model.msgpack "$ML_DIR"/examples/dlrm/inp.msgpack prove "$DLRM_OUTPUT"; } 2> $DLRM_LOG
-echo "$(jq --arg tmp $(echo "scale=6; $(cat "$DLRM_LOG" | grep "Maximum resident set size" | tr -d -c 0-9)/1024" | bc) '.+={"MemoryConsumption": $tmp }' "$DLRM_OUTPUT")" > "$DLRM_OUTPUT"
-echo "$(jq '. += {"Circuit": "DLRM" }' "$DLRM_OUTPUT")" > "$DLRM_OUTPUT"
+"$ML_DIR"/target/release/time_circuit dlrm "$ML_DIR"/examples/dlrm/model.msgpack "$ML_DIR"/examples/dlrm/inp.msgpack build "$DLRM_OUTPUT" --cols 110
+{ RUST_LOG=debug /usr/bin/time -v "$ML_DIR"/target/release/time_circuit dlrm "$ML_DIR"/examples/dlrm/model.msgpack "$ML_DIR"/examples/dlrm/inp.msgpack prove "$DLRM_OUTPUT" --cols 110; } 2> $DLRM_LOG
+{ RUST_LOG=debug "$ML_DIR"/target/release/time_circuit dlrm "$ML_DIR"/examples/dlrm/model.msgpack "$ML_DIR"/examples/dlrm/inp.msgpack verify "$DLRM_OUTPUT"; } 2>> $DLRM_LOG
 
 # get breakdowns
 python3 "$SCRIPT_DIR"/scripts/breakdown.py ecdsa $ECDSA_LOG $ECDSA_BREAK_JSON $ECDSA_BREAK_CSV
@@ -15,9 +15,8 @@ declare -a cols=(25 40 50 60 70 80 90 100 110 120 135)
 mkdir -p logs/column_sweep
 for col in "${cols[@]}"; do
     touch "$SWEEP_DIR"/"$col"_ecdsa_log
-    #"$ECDSA_DIR"/target/release/standard build $col
-    { RUST_LOG=debug /usr/bin/time -v "$ECDSA_DIR"/target/release/standard "$SWEEP_DIR"/"$col"_ecdsa.json $col; } 2> "$SWEEP_DIR"/"$col"_ecdsa_log
-    echo "$(jq --arg tmp $(echo "scale=6; $(cat "$SWEEP_DIR"/"$col"_ecdsa_log | grep "Maximum resident set size" | tr -d -c 0-9)/1024" | bc) '.+={"MemoryConsumption": $tmp }' "$SWEEP_DIR"/"$col"_ecdsa.json)" > "$SWEEP_DIR"/"$col"_ecdsa.json
+    "$ECDSA_DIR"/target/release/standard build "$SWEEP_DIR"/"$col"_ecdsa.json $col
+    { RUST_LOG=debug /usr/bin/time -v "$ECDSA_DIR"/target/release/standard prove "$SWEEP_DIR"/"$col"_ecdsa.json $col; } 2> "$SWEEP_DIR"/"$col"_ecdsa_log
 done
 
 cd $MERKLE_DIR
@@ -28,7 +27,6 @@ for col in "${cols[@]}"; do
     touch "$SWEEP_DIR"/"$col"_merkle_log
     #"$MERKLE_DIR"/target/release/standard build $col
     { RUST_LOG=debug /usr/bin/time -v "$MERKLE_DIR"/target/release/standard "$SWEEP_DIR"/"$col"_merkle.json $col; } 2> "$SWEEP_DIR"/"$col"_merkle_log
-    echo "$(jq --arg tmp $(echo "scale=6; $(cat "$SWEEP_DIR"/"$col"_merkle_log | grep "Maximum resident set size" | tr -d -c 0-9)/1024" | bc) '.+={"MemoryConsumption": $tmp }' "$SWEEP_DIR"/"$col"_merkle.json)" > "$SWEEP_DIR"/"$col"_merkle.json
 done
 
 cd $ML_DIR
@@ -39,20 +37,17 @@ for col in "${cols[@]}"; do
     touch "$SWEEP_DIR"/"$col"_mnist_log
     "$ML_DIR"/target/release/time_circuit mnist "$ML_DIR"/examples/mnist/model.msgpack "$ML_DIR"/examples/mnist/inp.msgpack build "$SWEEP_DIR"/"$col"_mnist.json --cols $col
     { RUST_LOG=debug /usr/bin/time -v "$ML_DIR"/target/release/time_circuit mnist "$ML_DIR"/examples/mnist/model.msgpack "$ML_DIR"/examples/mnist/inp.msgpack prove "$SWEEP_DIR"/"$col"_mnist.json --cols $col; } 2> "$SWEEP_DIR"/"$col"_mnist_log
-    echo "$(jq --arg tmp $(echo "scale=6; $(cat "$SWEEP_DIR"/"$col"_mnist_log | grep "Maximum resident set size" | tr -d -c 0-9)/1024" | bc) '.+={"MemoryConsumption": $tmp }' "$SWEEP_DIR"/"$col"_mnist.json)" > "$SWEEP_DIR"/"$col"_mnist.json
-    echo "$(jq '. += {"Circuit": "MNIST" }' "$SWEEP_DIR"/"$col"_mnist.json)" > "$SWEEP_DIR"/"$col"_mnist.json
+    { RUST_LOG=debug "$ML_DIR"/target/release/time_circuit mnist "$ML_DIR"/examples/mnist/model.msgpack "$ML_DIR"/examples/mnist/inp.msgpack verify "$SWEEP_DIR"/"$col"_mnist.json --cols $col; } 2>> "$SWEEP_DIR"/"$col"_mnist_log
 
     touch "$SWEEP_DIR"/"$col"_dlrm_log
     "$ML_DIR"/target/release/time_circuit dlrm "$ML_DIR"/examples/dlrm/model.msgpack "$ML_DIR"/examples/dlrm/inp.msgpack build "$SWEEP_DIR"/"$col"_dlrm.json --cols $col
     { RUST_LOG=debug /usr/bin/time -v "$ML_DIR"/target/release/time_circuit dlrm "$ML_DIR"/examples/dlrm/model.msgpack "$ML_DIR"/examples/dlrm/inp.msgpack prove "$SWEEP_DIR"/"$col"_dlrm.json --cols $col; } 2> "$SWEEP_DIR"/"$col"_dlrm_log
-    echo "$(jq --arg tmp $(echo "scale=6; $(cat "$SWEEP_DIR"/"$col"_dlrm_log | grep "Maximum resident set size" | tr -d -c 0-9)/1024" | bc) '.+={"MemoryConsumption": $tmp }' "$SWEEP_DIR"/"$col"_dlrm.json)" > "$SWEEP_DIR"/"$col"_dlrm.json
-    echo "$(jq '. += {"Circuit": "DLRM" }' "$SWEEP_DIR"/"$col"_dlrm.json)" > "$SWEEP_DIR"/"$col"_dlrm.json
+    { RUST_LOG=debug "$ML_DIR"/target/release/time_circuit dlrm "$ML_DIR"/examples/dlrm/model.msgpack "$ML_DIR"/examples/dlrm/inp.msgpack verify "$SWEEP_DIR"/"$col"_dlrm.json --cols $col; } 2>> "$SWEEP_DIR"/"$col"_dlrm_log
 
     touch "$SWEEP_DIR"/"$col"_mnist_no_lookup_log
     "$ML_DIR"/target/release/time_circuit mnist "$ML_DIR"/examples/mnist/model.msgpack "$ML_DIR"/examples/mnist/inp.msgpack build "$SWEEP_DIR"/"$col"_mnist_no_lookup.json --cols $col --no-lookups
-    { RUST_LOG=debug /usr/bin/time -v "$ML_DIR"/target/release/time_circuit mnist "$ML_DIR"/examples/mnist/model.msgpack "$ML_DIR"/examples/mnist/inp.msgpack prove "$SWEEP_DIR"/"$col"_mnist_no_lookup.json --cols $col --no-lookups; } 2> "$SWEEP_DIR"/"$col"_mnist_log
-    echo "$(jq --arg tmp $(echo "scale=6; $(cat "$SWEEP_DIR"/"$col"_mnist_log | grep "Maximum resident set size" | tr -d -c 0-9)/1024" | bc) '.+={"MemoryConsumption": $tmp }' "$SWEEP_DIR"/"$col"_mnist_no_lookup.json)" > "$SWEEP_DIR"/"$col"_mnist_no_lookup.json
-    echo "$(jq '. += {"Circuit": "MNIST" }' "$SWEEP_DIR"/"$col"_mnist_no_lookup.json)" > "$SWEEP_DIR"/"$col"_mnist_no_lookup.json
+    { RUST_LOG=debug /usr/bin/time -v "$ML_DIR"/target/release/time_circuit mnist "$ML_DIR"/examples/mnist/model.msgpack "$ML_DIR"/examples/mnist/inp.msgpack prove "$SWEEP_DIR"/"$col"_mnist_no_lookup.json --cols $col --no-lookups; } 2> "$SWEEP_DIR"/"$col"_mnist_no_lookup_log
+    { RUST_LOG=debug "$ML_DIR"/target/release/time_circuit mnist "$ML_DIR"/examples/mnist/model.msgpack "$ML_DIR"/examples/mnist/inp.msgpack verify "$SWEEP_DIR"/"$col"_mnist_no_lookup.json --cols $col --no-lookups; } 2>> "$SWEEP_DIR"/"$col"_mnist_no_lookup_log
 done
 
 curl -d "plonky2 sweep" ntfy.sh/zk_benchmark
\ No newline at end of file
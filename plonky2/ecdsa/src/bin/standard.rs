@@ -1,281 +1,202 @@
-use plonky2::field::secp256k1_base::Secp256K1Base;
-use plonky2::get_gate_tag_impl;
-use plonky2::plonk::config::{GenericConfig, KeccakGoldilocksConfig};
-use plonky2::read_gate_impl;
-use plonky2_ecdsa::gadgets::glv::GLVDecompositionGenerator;
-use plonky2_ecdsa::gadgets::nonnative::NonNativeInverseGenerator;
-use plonky2_ecdsa::gadgets::nonnative::NonNativeSubtractionGenerator;
-use plonky2_u32::gates::add_many_u32::U32AddManyGate;
-use plonky2_u32::gates::add_many_u32::U32AddManyGenerator;
-use plonky2_u32::gates::arithmetic_u32::U32ArithmeticGate;
-use plonky2_u32::gates::arithmetic_u32::U32ArithmeticGenerator;
-use plonky2_u32::gates::comparison::ComparisonGate;
-use plonky2_u32::gates::comparison::ComparisonGenerator;
-use plonky2_u32::gates::range_check_u32::U32RangeCheckGate;
-use plonky2_u32::gates::range_check_u32::U32RangeCheckGenerator;
-use plonky2_u32::gates::subtraction_u32::U32SubtractionGate;
-use plonky2_u32::gates::subtraction_u32::U32SubtractionGenerator;
 use std::fs::File;
-use std::io::Write;
+use std::io::{Read, Write};
 use std::time::Instant;
 
 use log::Level;
-use plonky2::field::extension::Extendable;
 use plonky2::field::secp256k1_scalar::Secp256K1Scalar;
-use plonky2::field::types::Sample;
-use plonky2::hash::hash_types::RichField;
+use plonky2::field::types::{PrimeField64, Sample};
+use plonky2::iop::generator::generate_partial_witness;
 use plonky2::iop::witness::PartialWitness;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
-use plonky2::plonk::circuit_data::CircuitConfig;
-use plonky2::plonk::prover::prove;
-use plonky2::util::serialization::{GateSerializer, WitnessGeneratorSerializer};
+use plonky2::plonk::circuit_data::{CircuitConfig, CircuitData};
+use plonky2::plonk::config::{GenericConfig, KeccakGoldilocksConfig};
+use plonky2::plonk::prover::prove_with_partition_witness;
+use plonky2::util::serialization::Buffer;
 use plonky2::util::timing::TimingTree;
-use plonky2::{impl_gate_serializer, impl_generator_serializer};
-use plonky2_ecdsa::gadgets::ecdsa::*;
-
-use plonky2::gadgets::arithmetic::EqualityGenerator;
-use plonky2::gadgets::arithmetic_extension::QuotientGeneratorExtension;
-use plonky2::gadgets::range_check::LowHighGenerator;
-use plonky2::gadgets::split_base::BaseSumGenerator;
-use plonky2::gadgets::split_join::{SplitGenerator, WireSplitGenerator};
-use plonky2::gates::arithmetic_base::ArithmeticBaseGenerator;
-use plonky2::gates::arithmetic_base::ArithmeticGate;
-use plonky2::gates::arithmetic_extension::ArithmeticExtensionGate;
-use plonky2::gates::arithmetic_extension::ArithmeticExtensionGenerator;
-use plonky2::gates::base_sum::BaseSplitGenerator;
-use plonky2::gates::base_sum::BaseSumGate;
-use plonky2::gates::constant::ConstantGate;
-use plonky2::gates::coset_interpolation::CosetInterpolationGate;
-use plonky2::gates::coset_interpolation::InterpolationGenerator;
-use plonky2::gates::exponentiation::ExponentiationGate;
-use plonky2::gates::exponentiation::ExponentiationGenerator;
-use plonky2::gates::lookup::LookupGate;
-use plonky2::gates::lookup::LookupGenerator;
-use plonky2::gates::lookup_table::LookupTableGate;
-use plonky2::gates::lookup_table::LookupTableGenerator;
-use plonky2::gates::multiplication_extension::MulExtensionGate;
-use plonky2::gates::multiplication_extension::MulExtensionGenerator;
-use plonky2::gates::noop::NoopGate;
-use plonky2::gates::poseidon::PoseidonGate;
-use plonky2::gates::poseidon::PoseidonGenerator;
-use plonky2::gates::poseidon_mds::PoseidonMdsGate;
-use plonky2::gates::poseidon_mds::PoseidonMdsGenerator;
-use plonky2::gates::public_input::PublicInputGate;
-use plonky2::gates::random_access::RandomAccessGate;
-use plonky2::gates::random_access::RandomAccessGenerator;
-use plonky2::gates::reducing::ReducingGate;
-use plonky2::gates::reducing::ReducingGenerator;
-use plonky2::gates::reducing_extension::ReducingExtensionGate;
-use plonky2::gates::reducing_extension::ReducingGenerator as ReducingExtensionGenerator;
-use plonky2::get_generator_tag_impl;
-use plonky2::iop::generator::{
-    ConstantGenerator, CopyGenerator, NonzeroTestGenerator, RandomValueGenerator,
-};
-use plonky2::read_generator_impl;
-use plonky2_ecdsa::curve::curve_types::{Curve, CurveScalar};
-use plonky2_ecdsa::curve::ecdsa::{sign_message, ECDSAPublicKey, ECDSASecretKey, ECDSASignature};
+use plonky2_ecdsa::curve::ecdsa::{keccak256_msg_hash, sign_message, ECDSASecretKey};
 use plonky2_ecdsa::curve::secp256k1::Secp256K1;
-use plonky2_ecdsa::gadgets::curve::CircuitBuilderCurve;
-use plonky2_ecdsa::gadgets::nonnative::CircuitBuilderNonNative;
-use plonky2_ecdsa::gadgets::nonnative::{
-    NonNativeAdditionGenerator, NonNativeMultiplicationGenerator,
-};
+use plonky2_ecdsa::gadgets::ecdsa::*;
+use plonky2_ecdsa::serialization::{ECDSAGateSerializer, ECDSAGeneratorSerializer};
 
 use jemallocator::Jemalloc;
-use serde_json::json;
+use zkperf_report::{BenchmarkResult, PhaseTracker};
 
 #[global_allocator]
 static GLOBAL: Jemalloc = Jemalloc;
 
-pub struct ECDSAGateSerializer;
-impl<F: RichField + Extendable<D>, const D: usize> GateSerializer<F, D> for ECDSAGateSerializer {
-    impl_gate_serializer! {
-        DefaultGateSerializer,
-        ArithmeticGate,
-        ArithmeticExtensionGate<D>,
-        BaseSumGate<2>,
-        BaseSumGate<4>,
-        ComparisonGate<F, D>,
-        ConstantGate,
-        CosetInterpolationGate<F, D>,
-        ExponentiationGate<F, D>,
-        LookupGate,
-        LookupTableGate,
-        MulExtensionGate<D>,
-        NoopGate,
-        PoseidonMdsGate<F, D>,
-        PoseidonGate<F, D>,
-        PublicInputGate,
-        RandomAccessGate<F, D>,
-        ReducingExtensionGate<D>,
-        ReducingGate<D>,
-        U32AddManyGate<F, D>,
-        U32ArithmeticGate<F, D>,
-        U32RangeCheckGate<F, D>,
-        U32SubtractionGate<F, D>
-    }
+const D: usize = 2;
+type C = KeccakGoldilocksConfig;
+type F = <C as GenericConfig<D>>::F;
+
+type Curve = Secp256K1;
+
+// Writes the circuit and its serialized targets for the prove step
+fn write_circuit(data: &CircuitData<F, C, D>, target_bytes: &[u8]) {
+    let mut file = File::create("ecdsa_targets").unwrap();
+    file.write_all(target_bytes).unwrap();
+
+    let gate_serializer = ECDSAGateSerializer {};
+    let generator_serializer = ECDSAGeneratorSerializer {};
+    let mut file = File::create("ecdsa.data").unwrap();
+    file.write_all(
+        &data
+            .to_bytes(&gate_serializer, &generator_serializer)
+            .unwrap(),
+    )
+    .unwrap();
 }
-pub struct ECDSAGeneratorSerializer {}
 
-impl<F, const D: usize> WitnessGeneratorSerializer<F, D> for ECDSAGeneratorSerializer
-where
-    F: RichField + Extendable<D>,
-{
-    impl_generator_serializer! {
-        DefaultGeneratorSerializer,
-        ArithmeticBaseGenerator<F, D>,
-        ArithmeticExtensionGenerator<F, D>,
-        BaseSplitGenerator<2>,
-        BaseSumGenerator<2>,
-        ComparisonGenerator<F, D>,
-        ConstantGenerator<F>,
-        CopyGenerator,
-        EqualityGenerator,
-        ExponentiationGenerator<F, D>,
-        GLVDecompositionGenerator<F, D>,
-        InterpolationGenerator<F, D>,
-        LookupGenerator,
-        LookupTableGenerator,
-        LowHighGenerator,
-        MulExtensionGenerator<F, D>,
-        NonNativeMultiplicationGenerator<F, D, Secp256K1Base>,
-        NonNativeAdditionGenerator<F, D, Secp256K1Base>,
-        NonNativeInverseGenerator<F, D, Secp256K1Base>,
-        NonNativeSubtractionGenerator<F, D, Secp256K1Base>,
-        NonzeroTestGenerator,
-        PoseidonGenerator<F, D>,
-        PoseidonMdsGenerator<D>,
-        QuotientGeneratorExtension<D>,
-        RandomAccessGenerator<F, D>,
-        RandomValueGenerator,
-        ReducingGenerator<D>,
-        ReducingExtensionGenerator<D>,
-        SplitGenerator,
-        U32AddManyGenerator<F, D>,
-        U32ArithmeticGenerator<F, D>,
-        U32RangeCheckGenerator<F, D>,
-        U32SubtractionGenerator<F, D>,
-        WireSplitGenerator
-    }
+fn read_circuit() -> (CircuitData<F, C, D>, Vec<u8>) {
+    let mut target_bytes = Vec::new();
+    File::open("ecdsa_targets")
+        .expect("File not found")
+        .read_to_end(&mut target_bytes)
+        .expect("Failed to read the file");
+
+    let mut buffer = Vec::new();
+    File::open("ecdsa.data")
+        .expect("File not found")
+        .read_to_end(&mut buffer)
+        .expect("Failed to read the file");
+    let gate_serializer = ECDSAGateSerializer {};
+    let generator_serializer = ECDSAGeneratorSerializer {};
+    let data = CircuitData::from_bytes(&buffer, &gate_serializer, &generator_serializer).unwrap();
+    (data, target_bytes)
 }
 
 fn main() {
     env_logger::init();
-    let outp_json = std::env::args().nth(1).expect("output json file path");
-    let x = std::env::args().nth(2).expect("cols");
+    let build_prove = std::env::args().nth(1).expect("build or prove");
+    let outp_json = std::env::args().nth(2).expect("output json file path");
+    let x = std::env::args().nth(3).expect("cols");
     let cols = x.parse::<usize>().unwrap();
+    // With a message length, the circuit hashes the message bytes with Keccak-256 in-circuit
+    let msg_len = std::env::args()
+        .nth(4)
+        .map(|x| x.parse::<usize>().expect("message length in bytes"));
+    let circuit_name = if msg_len.is_some() {
+        "ECDSA-Keccak"
+    } else {
+        "ECDSA"
+    };
 
+    if build_prove != "build" && build_prove != "prove" {
+        panic!("Must specify build or prove");
+    }
     if cols < 25 {
         panic!("Invalid cols")
     }
 
-    const D: usize = 2;
-    type C = KeccakGoldilocksConfig;
-    type F = <C as GenericConfig<D>>::F;
-
-    type Curve = Secp256K1;
-
-    let ecdsa_config = {
-        CircuitConfig {
-            zero_knowledge: true,
-            num_routed_wires: cols,
-            ..CircuitConfig::standard_ecc_config()
+    if build_prove == "build" {
+        let ecdsa_config = {
+            CircuitConfig {
+                zero_knowledge: true,
+                num_routed_wires: cols,
+                ..CircuitConfig::standard_ecc_config()
+            }
+        };
+
+        let mut results = BenchmarkResult::new("plonky2", circuit_name, "Plonk+FRI", "NaN");
+        let tracker = PhaseTracker::start("build");
+        let mut builder = CircuitBuilder::<F, D>::new(ecdsa_config);
+        let mut target_bytes = Vec::new();
+        match msg_len {
+            Some(msg_len) => verify_message_bytes_circuit(&mut builder, msg_len)
+                .write_to_serializer(&mut target_bytes),
+            None => {
+                verify_message_witness_circuit(&mut builder).write_to_serializer(&mut target_bytes)
+            }
         }
-    };
-    // let build_prove = std::env::args().nth(1).expect("build or prove");
-    // if build_prove != "build" && build_prove != "prove" {
-    //     panic!("Must specify build or prove");
-    // }
-
-    // if build_prove == "build" {
-
-    // TODO figure out issue with generator serialization to enable prover mem measurement
-    let mut builder = CircuitBuilder::<F, D>::new(ecdsa_config);
+        .unwrap();
+        builder.print_gate_counts(0);
+
+        println!("building circuit");
+        let start = Instant::now();
+        let data = builder.build::<C>();
+        let build_duration = start.elapsed();
+        println!("circuit build duration: {:?}", build_duration);
+        write_circuit(&data, &target_bytes);
+        results.record_phase(tracker.finish());
+        results.write(outp_json).unwrap();
+        return;
+    }
 
-    let msg = Secp256K1Scalar::rand();
-    let msg_target = builder.constant_nonnative(msg);
+    let mut results =
+        BenchmarkResult::load_or_new(&outp_json, "plonky2", circuit_name, "Plonk+FRI", "NaN");
+    let (data, target_bytes) = read_circuit();
+    let mut target_buffer = Buffer::new(&target_bytes);
 
     let sk = ECDSASecretKey::<Curve>(Secp256K1Scalar::rand());
-    let pk = ECDSAPublicKey((CurveScalar(sk.0) * Curve::GENERATOR_PROJECTIVE).to_affine());
-
-    let pk_target = ECDSAPublicKeyTarget(builder.constant_affine_point(pk.0));
-
-    let sig = sign_message(msg, sk);
-
-    let ECDSASignature { r, s } = sig;
-    let r_target = builder.constant_nonnative(r);
-    let s_target = builder.constant_nonnative(s);
-    let sig_target = ECDSASignatureTarget {
-        r: r_target,
-        s: s_target,
+    let pk = sk.to_public();
+
+    let mut pw = PartialWitness::new();
+    let public_inputs = match msg_len {
+        Some(msg_len) => {
+            let targets = ECDSAMessageBytesTargets::deserialize(&mut target_buffer).unwrap();
+            let msg: Vec<u8> = (0..msg_len)
+                .map(|_| F::rand().to_canonical_u64() as u8)
+                .collect();
+            let sig = sign_message(keccak256_msg_hash::<Curve>(&msg), sk);
+            set_ecdsa_message_bytes_targets(&mut pw, &targets, &msg, sig, pk);
+            ecdsa_message_bytes_public_inputs::<F>(&msg, pk)
+        }
+        None => {
+            let targets = ECDSATargets::<Curve>::deserialize(&mut target_buffer).unwrap();
+            let msg = Secp256K1Scalar::rand();
+            let sig = sign_message(msg, sk);
+            set_ecdsa_targets(&mut pw, &targets, msg, sig, pk);
+            ecdsa_public_inputs::<F>(msg, pk)
+        }
     };
+    println!("generating witness");
+    let tracker = PhaseTracker::start("witness");
+    let mut timing = TimingTree::new("witness", Level::Info);
+    let partition_witness = generate_partial_witness(pw, &data.prover_only, &data.common);
+    timing.pop();
+    results.record_phase(tracker.finish());
+    results.record_timing("witness", &timing).unwrap();
+    let witness_duration = timing.duration();
 
-    verify_message_circuit(&mut builder, msg_target, sig_target, pk_target);
-    builder.print_gate_counts(0);
-
-    // dbg!(builder.num_gates());
-    println!("building circuit");
-    let start = Instant::now();
-    let data = builder.build::<C>();
-    let build_duration = start.elapsed();
-    println!("circuit build duration: {:?}", build_duration);
-
-    //     let gate_serializer = ECDSAGateSerializer {};
-    //     let generator_serializer = ECDSAGeneratorSerializer {};
-
-    //     let mut file = File::create("ecdsa.data").unwrap();
-    //     let _ = file.write_all(
-    //         &data
-    //             .to_bytes(&gate_serializer, &generator_serializer)
-    //             .unwrap(),
-    //     );
-    // } else {
-    //     let mut file = File::open("ecdsa.data").expect("File not found");
-
-    //     let mut buffer = Vec::new();
-    //     file.read_to_end(&mut buffer)
-    //         .expect("Failed to read the file");
-
-    //     let gate_serializer = ECDSAGateSerializer {};
-    //     let generator_serializer = ECDSAGeneratorSerializer {};
-    //     let data: CircuitData<F, C, D> =
-    //         CircuitData::from_bytes(&buffer, &gate_serializer, &generator_serializer).unwrap();
-    let pw = PartialWitness::new();
     println!("proving circuit");
+    let tracker = PhaseTracker::start("prove");
     let mut timing = TimingTree::new("prove", Level::Info);
-    let proof = prove::<F, C, D>(&data.prover_only, &data.common, pw, &mut timing).unwrap();
+    let proof = prove_with_partition_witness::<F, C, D>(
+        &data.prover_only,
+        &data.common,
+        partition_witness,
+        &mut timing,
+    )
+    .unwrap();
     timing.pop();
+    results.record_phase(tracker.finish());
     timing.print();
+    results.record_timing("prove", &timing).unwrap();
 
     let proof_duration = timing.duration();
     println!("Proving time: {:?}", proof_duration);
+    assert_eq!(proof.public_inputs, public_inputs);
 
     let proof_bytes = proof.to_bytes();
     let proof_len = proof_bytes.len();
     println!("Proof size: {} bytes", proof_len);
 
     println!("verifying circuit");
+    let tracker = PhaseTracker::start("verify");
     let mut timing = TimingTree::new("verify", Level::Info);
     data.verify(proof).expect("verify error");
     timing.pop();
+    results.record_phase(tracker.finish());
     timing.print();
+    results.record_timing("verify", &timing).unwrap();
 
     let verify_duration = timing.duration();
     println!("Verifying time: {:?}", verify_duration);
 
     println!("writing results");
-    let results = json!({
-      "Framework": "plonky2",
-      "Circuit": "ECDSA",
-      "Backend": "Plonk+FRI",
-      "Curve": "NaN",
-      "ProverTime": proof_duration.as_secs_f32(),
-      "VerifierTime": verify_duration.as_nanos() as f32 / 1000000.,
-      "ProofSize": proof_len
-    });
-
-    let json_string = serde_json::to_string(&results).unwrap();
-
-    let mut file = File::create(outp_json).unwrap();
-    let _ = file.write_all(json_string.as_bytes());
+    results.set_shape(data.common.degree(), data.common.config.num_wires);
+    results.set_prover_time(witness_duration + proof_duration);
+    results.set_verifier_time(verify_duration);
+    results.set_proof_size(proof_len);
+    results.record_peak_rss();
+    results.write(outp_json).unwrap();
 }
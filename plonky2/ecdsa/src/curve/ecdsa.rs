@@ -1,8 +1,12 @@
+use alloc::vec::Vec;
+
+use num::BigUint;
 use plonky2::field::types::{Field, Sample};
 use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
 
 use crate::curve::curve_msm::msm_parallel;
-use crate::curve::curve_types::{base_to_scalar, AffinePoint, Curve, CurveScalar};
+use crate::curve::curve_types::{base_to_scalar, AffinePoint, Curve, CurveScalar, ProjectivePoint};
 
 #[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct ECDSASignature<C: Curve> {
@@ -22,7 +26,22 @@ impl<C: Curve> ECDSASecretKey<C> {
 #[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct ECDSAPublicKey<C: Curve>(pub AffinePoint<C>);
 
+/// The message hash that Ethereum signs for `msg`: its Keccak-256 digest, read as a big-endian
+/// integer and reduced mod the group order.
+pub fn keccak256_msg_hash<C: Curve>(msg: &[u8]) -> C::ScalarField {
+    C::ScalarField::from_noncanonical_biguint(BigUint::from_bytes_be(&Keccak256::digest(msg)))
+}
+
 pub fn sign_message<C: Curve>(msg: C::ScalarField, sk: ECDSASecretKey<C>) -> ECDSASignature<C> {
+    sign_message_with_nonce_point(msg, sk).0
+}
+
+/// Signs `msg` and also returns the nonce point `R = k * G`, whose x coordinate gives `r`. Batch
+/// verification needs `R`, which `r` only determines up to its sign.
+pub fn sign_message_with_nonce_point<C: Curve>(
+    msg: C::ScalarField,
+    sk: ECDSASecretKey<C>,
+) -> (ECDSASignature<C>, AffinePoint<C>) {
     let (k, rr) = {
         let mut k = C::ScalarField::rand();
         let mut rr = (CurveScalar(k) * C::GENERATOR_PROJECTIVE).to_affine();
@@ -36,7 +55,7 @@ pub fn sign_message<C: Curve>(msg: C::ScalarField, sk: ECDSASecretKey<C>) -> ECD
 
     let s = k.inverse() * (msg + r * sk.0);
 
-    ECDSASignature { r, s }
+    (ECDSASignature { r, s }, rr)
 }
 
 pub fn verify_message<C: Curve>(
@@ -61,13 +80,61 @@ pub fn verify_message<C: Curve>(
     r == x
 }
 
+/// Verifies a batch of signatures, given their nonce points, by checking a random linear
+/// combination of the verification equations `u1_i * G + u2_i * pk_i - R_i = 0` with a single MSM.
+pub fn batch_verify_messages<C: Curve>(
+    msgs: &[C::ScalarField],
+    sigs: &[ECDSASignature<C>],
+    pks: &[ECDSAPublicKey<C>],
+    nonce_points: &[AffinePoint<C>],
+) -> bool {
+    let n = msgs.len();
+    assert!(sigs.len() == n && pks.len() == n && nonce_points.len() == n);
+
+    let mut scalars = Vec::with_capacity(2 * n + 1);
+    let mut generators = Vec::with_capacity(2 * n + 1);
+    let mut u1_sum = C::ScalarField::ZERO;
+    for i in 0..n {
+        let ECDSASignature { r, s } = sigs[i];
+        let rr = nonce_points[i];
+
+        let pk = pks[i].0;
+        if pk.zero || !pk.is_valid() {
+            return false;
+        }
+        if rr.zero || !rr.is_valid() || base_to_scalar::<C>(rr.x) != r {
+            return false;
+        }
+
+        let z = C::ScalarField::rand();
+        let c = s.inverse();
+        u1_sum += z * msgs[i] * c;
+        scalars.push(z * r * c);
+        generators.push(pk.to_projective());
+        scalars.push(-z);
+        generators.push(rr.to_projective());
+    }
+    scalars.push(u1_sum);
+    generators.push(C::GENERATOR_PROJECTIVE);
+
+    let w = 5;
+    msm_parallel(&scalars, &generators, w) == ProjectivePoint::ZERO
+}
+
 #[cfg(test)]
 mod tests {
+    use plonky2::field::secp256k1_base::Secp256K1Base;
     use plonky2::field::secp256k1_scalar::Secp256K1Scalar;
-    use plonky2::field::types::Sample;
+    use plonky2::field::types::{Field, Sample};
 
-    use crate::curve::ecdsa::{sign_message, verify_message, ECDSASecretKey};
+    use crate::curve::curve_types::AffinePoint;
+    use crate::curve::ecdsa::{
+        batch_verify_messages, sign_message, sign_message_with_nonce_point, verify_message,
+        ECDSAPublicKey, ECDSASecretKey,
+    };
+    use crate::curve::p256::P256;
     use crate::curve::secp256k1::Secp256K1;
+    use crate::field::p256_scalar::P256Scalar;
 
     #[test]
     fn test_ecdsa_native() {
@@ -81,4 +148,61 @@ mod tests {
         let result = verify_message(msg, sig, pk);
         assert!(result);
     }
+
+    #[test]
+    fn test_ecdsa_native_p256() {
+        type C = P256;
+
+        let msg = P256Scalar::rand();
+        let sk = ECDSASecretKey::<C>(P256Scalar::rand());
+        let pk = sk.to_public();
+
+        let sig = sign_message(msg, sk);
+        assert!(verify_message(msg, sig, pk));
+        assert!(!verify_message(P256Scalar::rand(), sig, pk));
+    }
+
+    #[test]
+    fn test_batch_ecdsa_native() {
+        type C = Secp256K1;
+
+        let n = 8;
+        let mut msgs = Vec::new();
+        let mut sigs = Vec::new();
+        let mut pks = Vec::new();
+        let mut nonce_points = Vec::new();
+        for _ in 0..n {
+            let msg = Secp256K1Scalar::rand();
+            let sk = ECDSASecretKey::<C>(Secp256K1Scalar::rand());
+            let (sig, rr) = sign_message_with_nonce_point(msg, sk);
+            msgs.push(msg);
+            sigs.push(sig);
+            pks.push(sk.to_public());
+            nonce_points.push(rr);
+        }
+        assert!(batch_verify_messages(&msgs, &sigs, &pks, &nonce_points));
+
+        // A single bad signature fails the whole batch
+        let msg = msgs[3];
+        msgs[3] = Secp256K1Scalar::rand();
+        assert!(!batch_verify_messages(&msgs, &sigs, &pks, &nonce_points));
+        msgs[3] = msg;
+        nonce_points[5] = -nonce_points[5];
+        assert!(!batch_verify_messages(&msgs, &sigs, &pks, &nonce_points));
+        nonce_points[5] = -nonce_points[5];
+
+        // An invalid public key is rejected like an invalid nonce point, without panicking
+        let pk = pks[6];
+        // Not through `AffinePoint::nonzero`, which debug asserts that the point is valid
+        pks[6] = ECDSAPublicKey(AffinePoint {
+            x: pk.0.x,
+            y: pk.0.y + Secp256K1Base::ONE,
+            zero: false,
+        });
+        assert!(!batch_verify_messages(&msgs, &sigs, &pks, &nonce_points));
+        pks[6] = ECDSAPublicKey(AffinePoint::ZERO);
+        assert!(!batch_verify_messages(&msgs, &sigs, &pks, &nonce_points));
+        pks[6] = pk;
+        assert!(batch_verify_messages(&msgs, &sigs, &pks, &nonce_points));
+    }
 }
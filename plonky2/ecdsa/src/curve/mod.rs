@@ -5,4 +5,5 @@ pub mod curve_summation;
 pub mod curve_types;
 pub mod ecdsa;
 pub mod glv;
+pub mod p256;
 pub mod secp256k1;
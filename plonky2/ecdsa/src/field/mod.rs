@@ -0,0 +1,2 @@
+pub mod p256_base;
+pub mod p256_scalar;
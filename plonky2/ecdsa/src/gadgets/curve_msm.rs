@@ -1,10 +1,12 @@
 use alloc::vec;
+use alloc::vec::Vec;
 
 use num::BigUint;
 use plonky2::field::extension::Extendable;
 use plonky2::field::types::Field;
 use plonky2::hash::hash_types::RichField;
 use plonky2::hash::keccak::KeccakHash;
+use plonky2::iop::target::Target;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::config::{GenericHashOut, Hasher};
 
@@ -25,10 +27,27 @@ pub fn curve_msm_circuit<C: Curve, F: RichField + Extendable<D>, const D: usize>
     n: &NonNativeTarget<C::ScalarField>,
     m: &NonNativeTarget<C::ScalarField>,
 ) -> AffinePointTarget<C> {
-    let limbs_n = builder.split_nonnative_to_2_bit_limbs(n);
-    let limbs_m = builder.split_nonnative_to_2_bit_limbs(m);
-    assert_eq!(limbs_n.len(), limbs_m.len());
-    let num_limbs = limbs_n.len();
+    curve_multi_msm_circuit(builder, &[p.clone(), q.clone()], &[n.clone(), m.clone()])
+}
+
+/// Computes `sum_i scalars[i] * points[i]` using windowed MSM, with a 2-bit window.
+/// The points are taken in pairs as in `curve_msm_circuit`, but all pairs share one chain of
+/// doublings, so every further pair only costs a table lookup and an addition per window.
+/// Note: Doesn't work if the two points of a pair are equal. All scalars must have the same
+/// number of limbs.
+pub fn curve_multi_msm_circuit<C: Curve, F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    points: &[AffinePointTarget<C>],
+    scalars: &[NonNativeTarget<C::ScalarField>],
+) -> AffinePointTarget<C> {
+    assert!(!points.is_empty());
+    assert_eq!(points.len(), scalars.len());
+    let limbs: Vec<Vec<Target>> = scalars
+        .iter()
+        .map(|scalar| builder.split_nonnative_to_2_bit_limbs(scalar))
+        .collect();
+    let num_limbs = limbs[0].len();
+    assert!(limbs.iter().all(|l| l.len() == num_limbs));
 
     let hash_0 = KeccakHash::<32>::hash_no_pad(&[F::ZERO]);
     let hash_0_scalar = C::ScalarField::from_noncanonical_biguint(BigUint::from_bytes_le(
@@ -38,38 +57,50 @@ pub fn curve_msm_circuit<C: Curve, F: RichField + Extendable<D>, const D: usize>
     let rando_t = builder.constant_affine_point(rando);
     let neg_rando = builder.constant_affine_point(-rando);
 
-    // Precomputes `precomputation[i + 4*j] = i*p + j*q` for `i,j=0..4`.
-    let mut precomputation = vec![p.clone(); 16];
-    let mut cur_p = rando_t.clone();
-    let mut cur_q = rando_t.clone();
-    for i in 0..4 {
-        precomputation[i] = cur_p.clone();
-        precomputation[4 * i] = cur_q.clone();
-        cur_p = builder.curve_add(&cur_p, p);
-        cur_q = builder.curve_add(&cur_q, q);
-    }
-    for i in 1..4 {
-        precomputation[i] = builder.curve_add(&precomputation[i], &neg_rando);
-        precomputation[4 * i] = builder.curve_add(&precomputation[4 * i], &neg_rando);
-    }
-    for i in 1..4 {
-        for j in 1..4 {
-            precomputation[i + 4 * j] =
-                builder.curve_add(&precomputation[i], &precomputation[4 * j]);
+    // Precomputes `precomputation[i + 4*j] = i*p + j*q` for `i,j=0..4` for each pair `(p, q)`,
+    // and `precomputation[i] = i*p` for a last unpaired point `p`.
+    let mut precomputations = Vec::new();
+    for pair in points.chunks(2) {
+        let mut precomputation = vec![pair[0].clone(); 1 << (2 * pair.len())];
+        for (k, p) in pair.iter().enumerate() {
+            let stride = 1 << (2 * k);
+            let mut cur_p = rando_t.clone();
+            for i in 0..4 {
+                precomputation[i * stride] = cur_p.clone();
+                cur_p = builder.curve_add(&cur_p, p);
+            }
+            for i in 1..4 {
+                precomputation[i * stride] =
+                    builder.curve_add(&precomputation[i * stride], &neg_rando);
+            }
+        }
+        if pair.len() == 2 {
+            for i in 1..4 {
+                for j in 1..4 {
+                    precomputation[i + 4 * j] =
+                        builder.curve_add(&precomputation[i], &precomputation[4 * j]);
+                }
+            }
         }
+        precomputations.push(precomputation);
     }
 
     let four = builder.constant(F::from_canonical_usize(4));
 
     let zero = builder.zero();
     let mut result = rando_t;
-    for (limb_n, limb_m) in limbs_n.into_iter().zip(limbs_m).rev() {
+    for w in (0..num_limbs).rev() {
         result = builder.curve_repeated_double(&result, 2);
-        let index = builder.mul_add(four, limb_m, limb_n);
-        let r = builder.random_access_curve_points(index, precomputation.clone());
-        let is_zero = builder.is_equal(index, zero);
-        let should_add = builder.not(is_zero);
-        result = builder.curve_conditional_add(&result, &r, should_add);
+        for (pair_limbs, precomputation) in limbs.chunks(2).zip(&precomputations) {
+            let index = match pair_limbs {
+                [limbs_n, limbs_m] => builder.mul_add(four, limbs_m[w], limbs_n[w]),
+                _ => pair_limbs[0][w],
+            };
+            let r = builder.random_access_curve_points(index, precomputation.clone());
+            let is_zero = builder.is_equal(index, zero);
+            let should_add = builder.not(is_zero);
+            result = builder.curve_conditional_add(&result, &r, should_add);
+        }
     }
     let starting_point_multiplied = (0..2 * num_limbs).fold(rando, |acc, _| acc.double());
     let to_add = builder.constant_affine_point(-starting_point_multiplied);
@@ -91,7 +122,7 @@ mod tests {
     use crate::curve::curve_types::{Curve, CurveScalar};
     use crate::curve::secp256k1::Secp256K1;
     use crate::gadgets::curve::CircuitBuilderCurve;
-    use crate::gadgets::curve_msm::curve_msm_circuit;
+    use crate::gadgets::curve_msm::{curve_msm_circuit, curve_multi_msm_circuit};
     use crate::gadgets::nonnative::CircuitBuilderNonNative;
 
     #[test]
@@ -135,4 +166,54 @@ mod tests {
 
         data.verify(proof)
     }
+
+    #[test]
+    #[ignore]
+    fn test_curve_multi_msm() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let config = CircuitConfig::standard_ecc_config();
+
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        // An odd number of points leaves one point unpaired
+        let points: Vec<_> = (0..3)
+            .map(|_| {
+                (CurveScalar(Secp256K1Scalar::rand()) * Secp256K1::GENERATOR_PROJECTIVE).to_affine()
+            })
+            .collect();
+        let scalars: Vec<_> = (0..3).map(|_| Secp256K1Scalar::rand()).collect();
+
+        let res = points
+            .iter()
+            .zip(&scalars)
+            .map(|(p, n)| CurveScalar(*n) * p.to_projective())
+            .reduce(|acc, p| acc + p)
+            .unwrap()
+            .to_affine();
+        let res_expected = builder.constant_affine_point(res);
+
+        let point_targets: Vec<_> = points
+            .iter()
+            .map(|p| builder.constant_affine_point(*p))
+            .collect();
+        let scalar_targets: Vec<_> = scalars
+            .iter()
+            .map(|n| builder.constant_nonnative(*n))
+            .collect();
+
+        let res_target = curve_multi_msm_circuit(&mut builder, &point_targets, &scalar_targets);
+        builder.curve_assert_valid(&res_target);
+
+        builder.connect_affine_point(&res_target, &res_expected);
+
+        dbg!(builder.num_gates());
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+
+        data.verify(proof)
+    }
 }
@@ -1,14 +1,31 @@
+use alloc::vec::Vec;
 use core::marker::PhantomData;
 
 use plonky2::field::extension::Extendable;
 use plonky2::field::secp256k1_scalar::Secp256K1Scalar;
-use plonky2::hash::hash_types::RichField;
+use plonky2::field::types::{Field, PrimeField};
+use plonky2::hash::hash_types::{HashOutTarget, RichField};
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::iop::witness::{PartialWitness, WitnessWrite};
 use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::util::ceil_div_usize;
+use plonky2::util::serialization::{Buffer, IoResult, Read, Write};
+use plonky2_crypto::hash::keccak256::{CircuitBuilderHashKeccak, KECCAK256_R};
+use plonky2_crypto::hash::CircuitBuilderHash;
+use plonky2_u32::gadgets::arithmetic_u32::U32Target;
+use plonky2_u32::gadgets::range_check::range_check_u32_circuit;
 
 use crate::curve::curve_types::Curve;
+use crate::curve::ecdsa::{ECDSAPublicKey, ECDSASignature};
+use crate::curve::p256::P256;
 use crate::curve::secp256k1::Secp256K1;
+use crate::field::p256_scalar::P256Scalar;
+use crate::gadgets::biguint::{BigUintTarget, WitnessBigUint};
 use crate::gadgets::curve::{AffinePointTarget, CircuitBuilderCurve};
 use crate::gadgets::curve_fixed_base::fixed_base_curve_mul_circuit;
+use crate::gadgets::curve_msm::curve_multi_msm_circuit;
+use crate::gadgets::curve_windowed_mul::CircuitBuilderWindowedMul;
 use crate::gadgets::glv::CircuitBuilderGlv;
 use crate::gadgets::nonnative::{CircuitBuilderNonNative, NonNativeTarget};
 
@@ -24,11 +41,42 @@ pub struct ECDSASignatureTarget<C: Curve> {
     pub s: NonNativeTarget<C::ScalarField>,
 }
 
-pub fn verify_message_circuit<F: RichField + Extendable<D>, const D: usize>(
+/// A curve that `verify_message_circuit` supports, with the variable-base scalar multiplication
+/// it uses for the public key.
+pub trait ECDSACurve: Curve {
+    fn scalar_mul_circuit<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        p: &AffinePointTarget<Self>,
+        k: &NonNativeTarget<Self::ScalarField>,
+    ) -> AffinePointTarget<Self>;
+}
+
+impl ECDSACurve for Secp256K1 {
+    fn scalar_mul_circuit<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        p: &AffinePointTarget<Self>,
+        k: &NonNativeTarget<Secp256K1Scalar>,
+    ) -> AffinePointTarget<Self> {
+        builder.glv_mul(p, k)
+    }
+}
+
+/// P-256 has no efficient endomorphism, so it uses plain windowed multiplication.
+impl ECDSACurve for P256 {
+    fn scalar_mul_circuit<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        p: &AffinePointTarget<Self>,
+        k: &NonNativeTarget<P256Scalar>,
+    ) -> AffinePointTarget<Self> {
+        builder.curve_scalar_mul_windowed(p, k)
+    }
+}
+
+pub fn verify_message_circuit<C: ECDSACurve, F: RichField + Extendable<D>, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
-    msg: NonNativeTarget<Secp256K1Scalar>,
-    sig: ECDSASignatureTarget<Secp256K1>,
-    pk: ECDSAPublicKeyTarget<Secp256K1>,
+    msg: NonNativeTarget<C::ScalarField>,
+    sig: ECDSASignatureTarget<C>,
+    pk: ECDSAPublicKeyTarget<C>,
 ) {
     let ECDSASignatureTarget { r, s } = sig;
 
@@ -38,17 +86,393 @@ pub fn verify_message_circuit<F: RichField + Extendable<D>, const D: usize>(
     let u1 = builder.mul_nonnative(&msg, &c);
     let u2 = builder.mul_nonnative(&r, &c);
 
-    let point1 = fixed_base_curve_mul_circuit(builder, Secp256K1::GENERATOR_AFFINE, &u1);
-    let point2 = builder.glv_mul(&pk.0, &u2);
+    let point1 = fixed_base_curve_mul_circuit(builder, C::GENERATOR_AFFINE, &u1);
+    let point2 = C::scalar_mul_circuit(builder, &pk.0, &u2);
     let point = builder.curve_add(&point1, &point2);
 
-    let x = NonNativeTarget::<Secp256K1Scalar> {
+    // `r` is `x mod n`; like for secp256k1, `x >= n` has negligible probability and is not handled.
+    let x = NonNativeTarget::<C::ScalarField> {
         value: point.x.value,
         _phantom: PhantomData,
     };
     builder.connect_nonnative(&r, &x);
 }
 
+/// Returns the 128-bit challenge of signature `i` of a batch, derived from the hash of the batch.
+fn batch_challenge<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    batch_hash: HashOutTarget,
+    i: usize,
+) -> NonNativeTarget<Secp256K1Scalar> {
+    let index = builder.constant(F::from_canonical_usize(i));
+    let mut inputs = batch_hash.elements.to_vec();
+    inputs.push(index);
+    let hash = builder.hash_n_to_hash_no_pad::<PoseidonHash>(inputs);
+
+    let mut limbs = Vec::new();
+    for element in &hash.elements[..2] {
+        let (low, high) = builder.split_low_high(*element, 32, 64);
+        limbs.push(U32Target(low));
+        limbs.push(U32Target(high));
+    }
+    builder.biguint_to_nonnative(&BigUintTarget { limbs })
+}
+
+/// Verifies a batch of signatures, given their nonce points `R_i` (see
+/// `sign_message_with_nonce_point`), by checking the random linear combination
+/// `sum_i z_i * (u1_i * G + u2_i * pk_i - R_i) = 0` of their verification equations.
+/// The 128-bit `z_i` are hashed from all inputs. `G` is multiplied once by `sum_i z_i * u1_i`, and
+/// the GLV halves of the `z_i * u2_i * pk_i` and the `z_i * R_i` are summed in one MSM that
+/// shares its doublings across the batch.
+pub fn verify_messages_batch_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    msgs: &[NonNativeTarget<Secp256K1Scalar>],
+    sigs: &[ECDSASignatureTarget<Secp256K1>],
+    pks: &[ECDSAPublicKeyTarget<Secp256K1>],
+    nonce_points: &[AffinePointTarget<Secp256K1>],
+) {
+    let n = msgs.len();
+    assert!(n > 0);
+    assert!(sigs.len() == n && pks.len() == n && nonce_points.len() == n);
+
+    let mut inputs = Vec::new();
+    for i in 0..n {
+        for value in [
+            &msgs[i].value,
+            &sigs[i].r.value,
+            &sigs[i].s.value,
+            &pks[i].0.x.value,
+            &pks[i].0.y.value,
+            &nonce_points[i].x.value,
+            &nonce_points[i].y.value,
+        ] {
+            inputs.extend(value.limbs.iter().map(|limb| limb.0));
+        }
+    }
+    let batch_hash = builder.hash_n_to_hash_no_pad::<PoseidonHash>(inputs);
+
+    let beta = builder.secp256k1_glv_beta();
+    let mut u1_sum = builder.zero_nonnative();
+    let mut points = Vec::new();
+    let mut scalars = Vec::new();
+    for i in 0..n {
+        let ECDSASignatureTarget { r, s } = &sigs[i];
+        let pk = &pks[i].0;
+        let rr = &nonce_points[i];
+
+        builder.curve_assert_valid(pk);
+        builder.curve_assert_valid(rr);
+        let x = NonNativeTarget::<Secp256K1Scalar> {
+            value: rr.x.value.clone(),
+            _phantom: PhantomData,
+        };
+        builder.connect_nonnative(r, &x);
+
+        let z = batch_challenge(builder, batch_hash, i);
+        let c = builder.inv_nonnative(s);
+        let u1 = builder.mul_nonnative(&msgs[i], &c);
+        let u2 = builder.mul_nonnative(r, &c);
+        let z_u1 = builder.mul_nonnative(&z, &u1);
+        u1_sum = builder.add_nonnative(&u1_sum, &z_u1);
+        let z_u2 = builder.mul_nonnative(&z, &u2);
+
+        // Same as `glv_mul`, with the two halves added to the batch MSM.
+        let (k1, k2, k1_neg, k2_neg) = builder.decompose_secp256k1_scalar(&z_u2);
+        let beta_px = builder.mul_nonnative(&beta, &pk.x);
+        let sp = AffinePointTarget::<Secp256K1> {
+            x: beta_px,
+            y: pk.y.clone(),
+        };
+        points.push(builder.curve_conditional_neg(pk, k1_neg));
+        points.push(builder.curve_conditional_neg(&sp, k2_neg));
+        scalars.push(k1);
+        scalars.push(k2);
+
+        points.push(builder.curve_neg(rr));
+        scalars.push(z);
+    }
+
+    let point1 = fixed_base_curve_mul_circuit(builder, Secp256K1::GENERATOR_AFFINE, &u1_sum);
+    let point2 = curve_multi_msm_circuit(builder, &points, &scalars);
+    // `point1 + point2` must be the point at infinity, which has no affine representation.
+    let neg_point1 = builder.curve_neg(&point1);
+    builder.connect_affine_point(&point2, &neg_point1);
+}
+
+/// Inputs of [`verify_message_circuit`] as virtual targets, so that one circuit verifies any
+/// signature. See [`verify_message_witness_circuit`] and [`set_ecdsa_targets`].
+#[derive(Clone, Debug)]
+pub struct ECDSATargets<C: Curve> {
+    pub msg: NonNativeTarget<C::ScalarField>,
+    pub sig: ECDSASignatureTarget<C>,
+    pub pk: ECDSAPublicKeyTarget<C>,
+}
+
+fn nonnative_from_biguint<FF: Field>(value: BigUintTarget) -> NonNativeTarget<FF> {
+    NonNativeTarget {
+        value,
+        _phantom: PhantomData,
+    }
+}
+
+impl<C: Curve> ECDSATargets<C> {
+    fn limbs(&self) -> [&BigUintTarget; 5] {
+        [
+            &self.msg.value,
+            &self.sig.r.value,
+            &self.sig.s.value,
+            &self.pk.0.x.value,
+            &self.pk.0.y.value,
+        ]
+    }
+
+    pub fn write_to_serializer(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        self.limbs()
+            .iter()
+            .try_for_each(|value| value.write_to_serializer(dst))
+    }
+
+    pub fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let msg = nonnative_from_biguint(BigUintTarget::deserialize(src)?);
+        let r = nonnative_from_biguint(BigUintTarget::deserialize(src)?);
+        let s = nonnative_from_biguint(BigUintTarget::deserialize(src)?);
+        let x = nonnative_from_biguint(BigUintTarget::deserialize(src)?);
+        let y = nonnative_from_biguint(BigUintTarget::deserialize(src)?);
+        Ok(Self {
+            msg,
+            sig: ECDSASignatureTarget { r, s },
+            pk: ECDSAPublicKeyTarget(AffinePointTarget { x, y }),
+        })
+    }
+}
+
+/// Verifies a signature given through the witness. The limbs of the inputs are range checked to
+/// 32 bits, and the public key (x, then y) and the message hash are registered as public inputs;
+/// see [`ecdsa_public_inputs`].
+pub fn verify_message_witness_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+) -> ECDSATargets<Secp256K1> {
+    let targets = ECDSATargets {
+        msg: builder.add_virtual_nonnative_target(),
+        sig: ECDSASignatureTarget {
+            r: builder.add_virtual_nonnative_target(),
+            s: builder.add_virtual_nonnative_target(),
+        },
+        pk: ECDSAPublicKeyTarget(builder.add_virtual_affine_point_target()),
+    };
+    for value in targets.limbs() {
+        range_check_u32_circuit(builder, value.limbs.clone());
+    }
+    for value in [
+        &targets.pk.0.x.value,
+        &targets.pk.0.y.value,
+        &targets.msg.value,
+    ] {
+        let limbs: Vec<_> = value.limbs.iter().map(|limb| limb.0).collect();
+        builder.register_public_inputs(&limbs);
+    }
+
+    verify_message_circuit(
+        builder,
+        targets.msg.clone(),
+        targets.sig.clone(),
+        targets.pk.clone(),
+    );
+    targets
+}
+
+pub fn set_ecdsa_targets<F: RichField>(
+    pw: &mut PartialWitness<F>,
+    targets: &ECDSATargets<Secp256K1>,
+    msg: Secp256K1Scalar,
+    sig: ECDSASignature<Secp256K1>,
+    pk: ECDSAPublicKey<Secp256K1>,
+) {
+    pw.set_biguint_target(&targets.msg.value, &msg.to_canonical_biguint());
+    pw.set_biguint_target(&targets.sig.r.value, &sig.r.to_canonical_biguint());
+    pw.set_biguint_target(&targets.sig.s.value, &sig.s.to_canonical_biguint());
+    pw.set_biguint_target(&targets.pk.0.x.value, &pk.0.x.to_canonical_biguint());
+    pw.set_biguint_target(&targets.pk.0.y.value, &pk.0.y.to_canonical_biguint());
+}
+
+/// The public inputs of [`verify_message_witness_circuit`]: the 32-bit limbs of the public key x
+/// and y coordinates and of the message hash, least significant first.
+pub fn ecdsa_public_inputs<F: RichField>(
+    msg: Secp256K1Scalar,
+    pk: ECDSAPublicKey<Secp256K1>,
+) -> Vec<F> {
+    limbs(pk.0.x)
+        .chain(limbs(pk.0.y))
+        .chain(limbs(msg))
+        .collect()
+}
+
+fn limbs<F: RichField, FF: PrimeField>(value: FF) -> impl Iterator<Item = F> {
+    let mut digits = value.to_canonical_biguint().to_u32_digits();
+    digits.resize(ceil_div_usize(FF::BITS, 32), 0);
+    digits.into_iter().map(F::from_canonical_u32)
+}
+
+/// Number of Keccak-256 blocks that a message of `msg_len` bytes takes once padded.
+pub fn keccak256_num_blocks(msg_len: usize) -> usize {
+    msg_len / (KECCAK256_R / 8) + 1
+}
+
+/// Pads `msg` for Keccak-256 (a `0x01` byte after the message, `0x80` on the last byte of the
+/// last block) and returns it as 32-bit little-endian limbs, as hashed by
+/// [`verify_message_bytes_circuit`].
+pub fn keccak256_padded_limbs(msg: &[u8]) -> Vec<u32> {
+    let mut padded = msg.to_vec();
+    padded.resize(keccak256_num_blocks(msg.len()) * KECCAK256_R / 8, 0);
+    padded[msg.len()] |= 0x01;
+    *padded.last_mut().unwrap() |= 0x80;
+    padded
+        .chunks(4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .collect()
+}
+
+/// Reads a Keccak-256 digest, given as the little-endian 32-bit words of the sponge state, as a
+/// big-endian integer and reduces it into the scalar field, like [`keccak256_msg_hash`].
+///
+/// [`keccak256_msg_hash`]: crate::curve::ecdsa::keccak256_msg_hash
+fn keccak256_digest_to_scalar<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    digest: &[Target],
+) -> NonNativeTarget<Secp256K1Scalar> {
+    // Digest byte `i` is byte `i % 4` of word `i / 4`, so the limbs of the big-endian integer are
+    // the words in reverse order with their bytes swapped.
+    let limbs = digest
+        .iter()
+        .rev()
+        .map(|&word| {
+            let bits = builder.split_le(word, 32);
+            let swapped: Vec<BoolTarget> = bits.chunks(8).rev().flatten().copied().collect();
+            U32Target(builder.le_sum(swapped.into_iter()))
+        })
+        .collect();
+    builder.reduce(&BigUintTarget { limbs })
+}
+
+/// Inputs of [`verify_message_bytes_circuit`]. The message is given as its padded 32-bit limbs,
+/// see [`keccak256_padded_limbs`].
+#[derive(Clone, Debug)]
+pub struct ECDSAMessageBytesTargets {
+    pub msg: Vec<Target>,
+    pub sig: ECDSASignatureTarget<Secp256K1>,
+    pub pk: ECDSAPublicKeyTarget<Secp256K1>,
+}
+
+impl ECDSAMessageBytesTargets {
+    fn limbs(&self) -> [&BigUintTarget; 4] {
+        [
+            &self.sig.r.value,
+            &self.sig.s.value,
+            &self.pk.0.x.value,
+            &self.pk.0.y.value,
+        ]
+    }
+
+    pub fn write_to_serializer(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_target_vec(&self.msg)?;
+        self.limbs()
+            .iter()
+            .try_for_each(|value| value.write_to_serializer(dst))
+    }
+
+    pub fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let msg = src.read_target_vec()?;
+        let r = nonnative_from_biguint(BigUintTarget::deserialize(src)?);
+        let s = nonnative_from_biguint(BigUintTarget::deserialize(src)?);
+        let x = nonnative_from_biguint(BigUintTarget::deserialize(src)?);
+        let y = nonnative_from_biguint(BigUintTarget::deserialize(src)?);
+        Ok(Self {
+            msg,
+            sig: ECDSASignatureTarget { r, s },
+            pk: ECDSAPublicKeyTarget(AffinePointTarget { x, y }),
+        })
+    }
+}
+
+/// Verifies a signature over raw message bytes, as for an Ethereum transaction: the message is
+/// hashed in-circuit with Keccak-256 and the digest is reduced into the scalar field before
+/// [`verify_message_circuit`]. The circuit hashes a fixed number of blocks, so it accepts the
+/// messages that pad to as many blocks as one of `msg_len` bytes. The public key (x, then y) and
+/// the padded message are registered as public inputs; see [`ecdsa_message_bytes_public_inputs`].
+pub fn verify_message_bytes_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    msg_len: usize,
+) -> ECDSAMessageBytesTargets {
+    let hash_input =
+        builder.add_virtual_hash_input_target(keccak256_num_blocks(msg_len), KECCAK256_R);
+    // The padding is a public input, so every block is absorbed.
+    let absorb = builder._true();
+    for block in &hash_input.blocks {
+        builder.connect(block.target, absorb.target);
+    }
+
+    let targets = ECDSAMessageBytesTargets {
+        msg: hash_input.input.limbs.iter().map(|limb| limb.0).collect(),
+        sig: ECDSASignatureTarget {
+            r: builder.add_virtual_nonnative_target(),
+            s: builder.add_virtual_nonnative_target(),
+        },
+        pk: ECDSAPublicKeyTarget(builder.add_virtual_affine_point_target()),
+    };
+    for value in targets.limbs() {
+        range_check_u32_circuit(builder, value.limbs.clone());
+    }
+    for value in [&targets.pk.0.x.value, &targets.pk.0.y.value] {
+        let limbs: Vec<_> = value.limbs.iter().map(|limb| limb.0).collect();
+        builder.register_public_inputs(&limbs);
+    }
+    builder.register_public_inputs(&targets.msg);
+
+    let digest = builder.hash_keccak256(&hash_input);
+    let digest: Vec<_> = digest.limbs.iter().map(|limb| limb.0).collect();
+    let msg = keccak256_digest_to_scalar(builder, &digest);
+    verify_message_circuit(builder, msg, targets.sig.clone(), targets.pk.clone());
+    targets
+}
+
+pub fn set_ecdsa_message_bytes_targets<F: RichField>(
+    pw: &mut PartialWitness<F>,
+    targets: &ECDSAMessageBytesTargets,
+    msg: &[u8],
+    sig: ECDSASignature<Secp256K1>,
+    pk: ECDSAPublicKey<Secp256K1>,
+) {
+    let limbs = keccak256_padded_limbs(msg);
+    assert_eq!(
+        limbs.len(),
+        targets.msg.len(),
+        "the circuit hashes a different number of blocks"
+    );
+    for (&target, &limb) in targets.msg.iter().zip(&limbs) {
+        pw.set_target(target, F::from_canonical_u32(limb));
+    }
+    pw.set_biguint_target(&targets.sig.r.value, &sig.r.to_canonical_biguint());
+    pw.set_biguint_target(&targets.sig.s.value, &sig.s.to_canonical_biguint());
+    pw.set_biguint_target(&targets.pk.0.x.value, &pk.0.x.to_canonical_biguint());
+    pw.set_biguint_target(&targets.pk.0.y.value, &pk.0.y.to_canonical_biguint());
+}
+
+/// The public inputs of [`verify_message_bytes_circuit`]: the 32-bit limbs of the public key x and
+/// y coordinates, least significant first, followed by the padded message limbs.
+pub fn ecdsa_message_bytes_public_inputs<F: RichField>(
+    msg: &[u8],
+    pk: ECDSAPublicKey<Secp256K1>,
+) -> Vec<F> {
+    limbs(pk.0.x)
+        .chain(limbs(pk.0.y))
+        .chain(
+            keccak256_padded_limbs(msg)
+                .into_iter()
+                .map(F::from_canonical_u32),
+        )
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
@@ -58,23 +482,29 @@ mod tests {
     use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
 
     use super::*;
+    use crate::curve::curve_types::AffinePoint;
     use crate::curve::curve_types::CurveScalar;
-    use crate::curve::ecdsa::{sign_message, ECDSAPublicKey, ECDSASecretKey, ECDSASignature};
+    use crate::curve::ecdsa::{
+        keccak256_msg_hash, sign_message, sign_message_with_nonce_point, ECDSAPublicKey,
+        ECDSASecretKey, ECDSASignature,
+    };
 
     fn test_ecdsa_circuit_with_config(config: CircuitConfig) -> Result<()> {
+        test_ecdsa_circuit_with_curve::<Secp256K1>(config)
+    }
+
+    fn test_ecdsa_circuit_with_curve<Curve: ECDSACurve>(config: CircuitConfig) -> Result<()> {
         const D: usize = 2;
         type C = PoseidonGoldilocksConfig;
         type F = <C as GenericConfig<D>>::F;
 
-        type Curve = Secp256K1;
-
         let pw = PartialWitness::new();
         let mut builder = CircuitBuilder::<F, D>::new(config);
 
-        let msg = Secp256K1Scalar::rand();
+        let msg = Curve::ScalarField::rand();
         let msg_target = builder.constant_nonnative(msg);
 
-        let sk = ECDSASecretKey::<Curve>(Secp256K1Scalar::rand());
+        let sk = ECDSASecretKey::<Curve>(Curve::ScalarField::rand());
         let pk = ECDSAPublicKey((CurveScalar(sk.0) * Curve::GENERATOR_PROJECTIVE).to_affine());
 
         let pk_target = ECDSAPublicKeyTarget(builder.constant_affine_point(pk.0));
@@ -97,6 +527,124 @@ mod tests {
         data.verify(proof)
     }
 
+    #[test]
+    #[ignore]
+    fn test_ecdsa_witness_circuit() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_ecc_config());
+        let targets = verify_message_witness_circuit(&mut builder);
+        let data = builder.build::<C>();
+
+        // The same circuit verifies any signature
+        for _ in 0..2 {
+            let msg = Secp256K1Scalar::rand();
+            let sk = ECDSASecretKey::<Secp256K1>(Secp256K1Scalar::rand());
+            let pk = sk.to_public();
+            let sig = sign_message(msg, sk);
+
+            let mut pw = PartialWitness::new();
+            set_ecdsa_targets(&mut pw, &targets, msg, sig, pk);
+            let proof = data.prove(pw)?;
+            assert_eq!(proof.public_inputs, ecdsa_public_inputs::<F>(msg, pk));
+            data.verify(proof)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_keccak256_padded_limbs() {
+        // One byte short of a block: the two padding bits share the last byte
+        let limbs = keccak256_padded_limbs(&[0; 135]);
+        assert_eq!(limbs.len(), 34);
+        assert_eq!(limbs[33], 0x81 << 24);
+        // A full block spills the padding into a second one
+        let limbs = keccak256_padded_limbs(&[0; 136]);
+        assert_eq!(limbs.len(), 68);
+        assert_eq!((limbs[34], limbs[67]), (0x01, 0x80 << 24));
+    }
+
+    #[test]
+    fn test_keccak256_digest_to_scalar() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let msg = b"zkperf";
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let hash_input = builder.add_virtual_hash_input_target(1, KECCAK256_R);
+        let digest = builder.hash_keccak256(&hash_input);
+        let digest: Vec<_> = digest.limbs.iter().map(|limb| limb.0).collect();
+        let scalar = keccak256_digest_to_scalar(&mut builder, &digest);
+        let expected = builder.constant_nonnative(keccak256_msg_hash::<Secp256K1>(msg));
+        builder.connect_nonnative(&scalar, &expected);
+
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+        for (limb, value) in hash_input
+            .input
+            .limbs
+            .iter()
+            .zip(keccak256_padded_limbs(msg))
+        {
+            pw.set_target(limb.0, F::from_canonical_u32(value));
+        }
+        let proof = data.prove(pw)?;
+        data.verify(proof)
+    }
+
+    #[test]
+    #[ignore]
+    fn test_ecdsa_message_bytes_circuit() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        // About the size of an RLP-encoded Ethereum transfer
+        let msg_len = 110;
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_ecc_config());
+        let targets = verify_message_bytes_circuit(&mut builder, msg_len);
+        let data = builder.build::<C>();
+
+        for seed in 0..2u8 {
+            let msg: Vec<u8> = (0..msg_len as u8).map(|i| i ^ seed).collect();
+            let sk = ECDSASecretKey::<Secp256K1>(Secp256K1Scalar::rand());
+            let pk = sk.to_public();
+            let sig = sign_message(keccak256_msg_hash::<Secp256K1>(&msg), sk);
+
+            let mut pw = PartialWitness::new();
+            set_ecdsa_message_bytes_targets(&mut pw, &targets, &msg, sig, pk);
+            let proof = data.prove(pw)?;
+            assert_eq!(
+                proof.public_inputs,
+                ecdsa_message_bytes_public_inputs::<F>(&msg, pk)
+            );
+            data.verify(proof)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    #[should_panic]
+    fn test_ecdsa_message_bytes_circuit_with_other_message_fails() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_ecc_config());
+        let targets = verify_message_bytes_circuit(&mut builder, 32);
+        let data = builder.build::<C>();
+
+        let sk = ECDSASecretKey::<Secp256K1>(Secp256K1Scalar::rand());
+        let sig = sign_message(keccak256_msg_hash::<Secp256K1>(&[0; 32]), sk);
+        let mut pw = PartialWitness::new();
+        set_ecdsa_message_bytes_targets(&mut pw, &targets, &[1; 32], sig, sk.to_public());
+        data.prove(pw).unwrap();
+    }
+
     #[test]
     #[ignore]
     fn test_ecdsa_circuit_narrow() -> Result<()> {
@@ -108,4 +656,127 @@ mod tests {
     fn test_ecdsa_circuit_wide() -> Result<()> {
         test_ecdsa_circuit_with_config(CircuitConfig::wide_ecc_config())
     }
+
+    #[test]
+    #[ignore]
+    fn test_ecdsa_circuit_p256() -> Result<()> {
+        test_ecdsa_circuit_with_curve::<P256>(CircuitConfig::standard_ecc_config())
+    }
+
+    type Batch = (
+        Vec<Secp256K1Scalar>,
+        Vec<ECDSASignature<Secp256K1>>,
+        Vec<ECDSAPublicKey<Secp256K1>>,
+        Vec<AffinePoint<Secp256K1>>,
+    );
+
+    fn sign_batch(n: usize) -> Batch {
+        let mut batch: Batch = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for _ in 0..n {
+            let msg = Secp256K1Scalar::rand();
+            let sk = ECDSASecretKey::<Secp256K1>(Secp256K1Scalar::rand());
+            let (sig, rr) = sign_message_with_nonce_point(msg, sk);
+            batch.0.push(msg);
+            batch.1.push(sig);
+            batch.2.push(sk.to_public());
+            batch.3.push(rr);
+        }
+        batch
+    }
+
+    fn add_batch_circuit<F: RichField + Extendable<D>, const D: usize>(
+        builder: &mut CircuitBuilder<F, D>,
+        (msgs, sigs, pks, nonce_points): &Batch,
+    ) {
+        let msg_targets: Vec<_> = msgs
+            .iter()
+            .map(|m| builder.constant_nonnative(*m))
+            .collect();
+        let sig_targets: Vec<_> = sigs
+            .iter()
+            .map(|sig| ECDSASignatureTarget {
+                r: builder.constant_nonnative(sig.r),
+                s: builder.constant_nonnative(sig.s),
+            })
+            .collect();
+        let pk_targets: Vec<_> = pks
+            .iter()
+            .map(|pk| ECDSAPublicKeyTarget(builder.constant_affine_point(pk.0)))
+            .collect();
+        let nonce_point_targets: Vec<_> = nonce_points
+            .iter()
+            .map(|rr| builder.constant_affine_point(*rr))
+            .collect();
+        verify_messages_batch_circuit(
+            builder,
+            &msg_targets,
+            &sig_targets,
+            &pk_targets,
+            &nonce_point_targets,
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_batch_ecdsa_circuit() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_ecc_config());
+        add_batch_circuit(&mut builder, &sign_batch(3));
+
+        dbg!(builder.num_gates());
+        let data = builder.build::<C>();
+        let proof = data.prove(PartialWitness::new()).unwrap();
+        data.verify(proof)
+    }
+
+    #[test]
+    #[ignore]
+    #[should_panic]
+    fn test_batch_ecdsa_circuit_with_wrong_nonce_point_fails() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        // `-R` has the same x coordinate as `R`, so only the batch equation can reject it
+        let mut batch = sign_batch(2);
+        batch.3[1] = -batch.3[1];
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_ecc_config());
+        add_batch_circuit(&mut builder, &batch);
+
+        let data = builder.build::<C>();
+        data.prove(PartialWitness::new()).unwrap();
+    }
+
+    #[test]
+    fn test_batch_ecdsa_circuit_is_cheaper() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+
+        let n = 4;
+        let batch = sign_batch(n);
+
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_ecc_config());
+        add_batch_circuit(&mut builder, &batch);
+        let batch_gates = builder.num_gates();
+
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_ecc_config());
+        let (msgs, sigs, pks, _) = &batch;
+        for i in 0..n {
+            let msg_target = builder.constant_nonnative(msgs[i]);
+            let sig_target = ECDSASignatureTarget {
+                r: builder.constant_nonnative(sigs[i].r),
+                s: builder.constant_nonnative(sigs[i].s),
+            };
+            let pk_target = ECDSAPublicKeyTarget(builder.constant_affine_point(pks[i].0));
+            verify_message_circuit(&mut builder, msg_target, sig_target, pk_target);
+        }
+        let independent_gates = builder.num_gates();
+
+        dbg!(batch_gates, independent_gates);
+        assert!(batch_gates < independent_gates);
+    }
 }
@@ -1,8 +1,10 @@
 use alloc::vec;
 use alloc::vec::Vec;
+use core::any::type_name;
 use core::marker::PhantomData;
 
-use alloc::string::{String, ToString};
+use alloc::format;
+use alloc::string::String;
 use num::{BigUint, Integer, One, Zero};
 use plonky2::field::extension::Extendable;
 use plonky2::field::types::{Field, PrimeField};
@@ -490,7 +492,8 @@ impl<F: RichField + Extendable<D>, const D: usize, FF: PrimeField> SimpleGenerat
     }
 
     fn id(&self) -> String {
-        "NonNativeAdditionGenerator".to_string()
+        // The generators of different fields must be told apart by the serializer
+        format!("NonNativeAdditionGenerator<{}>", type_name::<FF>())
     }
 
     fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
@@ -525,8 +528,8 @@ impl<F: RichField + Extendable<D>, const D: usize, FF: PrimeField> SimpleGenerat
     }
 }
 
-#[derive(Debug)]
-struct NonNativeMultipleAddsGenerator<F: RichField + Extendable<D>, const D: usize, FF: PrimeField>
+#[derive(Debug, Default)]
+pub struct NonNativeMultipleAddsGenerator<F: RichField + Extendable<D>, const D: usize, FF: PrimeField>
 {
     summands: Vec<NonNativeTarget<FF>>,
     sum: NonNativeTarget<FF>,
@@ -570,7 +573,7 @@ impl<F: RichField + Extendable<D>, const D: usize, FF: PrimeField> SimpleGenerat
     }
 
     fn id(&self) -> String {
-        "NonNativeMultipleAddsGenerator".to_string()
+        format!("NonNativeMultipleAddsGenerator<{}>", type_name::<FF>())
     }
 
     fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
@@ -649,7 +652,7 @@ impl<F: RichField + Extendable<D>, const D: usize, FF: PrimeField> SimpleGenerat
     }
 
     fn id(&self) -> String {
-        "NonNativeSubtractionGenerator".to_string()
+        format!("NonNativeSubtractionGenerator<{}>", type_name::<FF>())
     }
 
     fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
@@ -723,7 +726,7 @@ impl<F: RichField + Extendable<D>, const D: usize, FF: PrimeField> SimpleGenerat
     }
 
     fn id(&self) -> String {
-        "NonNativeMultiplicationGenerator".to_string()
+        format!("NonNativeMultiplicationGenerator<{}>", type_name::<FF>())
     }
 
     fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
@@ -788,7 +791,7 @@ impl<F: RichField + Extendable<D>, const D: usize, FF: PrimeField> SimpleGenerat
     }
 
     fn id(&self) -> String {
-        "NonNativeInverseGenerator".to_string()
+        format!("NonNativeInverseGenerator<{}>", type_name::<FF>())
     }
 
     fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
@@ -4,4 +4,6 @@
 extern crate alloc;
 
 pub mod curve;
+pub mod field;
 pub mod gadgets;
+pub mod serialization;
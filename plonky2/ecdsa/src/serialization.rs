@@ -0,0 +1,261 @@
+use alloc::vec::Vec;
+
+use plonky2::field::extension::Extendable;
+use plonky2::field::secp256k1_base::Secp256K1Base;
+use plonky2::field::secp256k1_scalar::Secp256K1Scalar;
+use plonky2::gadgets::arithmetic::EqualityGenerator;
+use plonky2::gadgets::arithmetic_extension::QuotientGeneratorExtension;
+use plonky2::gadgets::range_check::LowHighGenerator;
+use plonky2::gadgets::split_base::BaseSumGenerator;
+use plonky2::gadgets::split_join::{SplitGenerator, WireSplitGenerator};
+use plonky2::gates::arithmetic_base::ArithmeticBaseGenerator;
+use plonky2::gates::arithmetic_base::ArithmeticGate;
+use plonky2::gates::arithmetic_extension::ArithmeticExtensionGate;
+use plonky2::gates::arithmetic_extension::ArithmeticExtensionGenerator;
+use plonky2::gates::base_sum::BaseSplitGenerator;
+use plonky2::gates::base_sum::BaseSumGate;
+use plonky2::gates::constant::ConstantGate;
+use plonky2::gates::coset_interpolation::CosetInterpolationGate;
+use plonky2::gates::coset_interpolation::InterpolationGenerator;
+use plonky2::gates::exponentiation::ExponentiationGate;
+use plonky2::gates::exponentiation::ExponentiationGenerator;
+use plonky2::gates::lookup::LookupGate;
+use plonky2::gates::lookup::LookupGenerator;
+use plonky2::gates::lookup_table::LookupTableGate;
+use plonky2::gates::lookup_table::LookupTableGenerator;
+use plonky2::gates::multiplication_extension::MulExtensionGate;
+use plonky2::gates::multiplication_extension::MulExtensionGenerator;
+use plonky2::gates::noop::NoopGate;
+use plonky2::gates::poseidon::PoseidonGate;
+use plonky2::gates::poseidon::PoseidonGenerator;
+use plonky2::gates::poseidon_mds::PoseidonMdsGate;
+use plonky2::gates::poseidon_mds::PoseidonMdsGenerator;
+use plonky2::gates::public_input::PublicInputGate;
+use plonky2::gates::random_access::RandomAccessGate;
+use plonky2::gates::random_access::RandomAccessGenerator;
+use plonky2::gates::reducing::ReducingGate;
+use plonky2::gates::reducing::ReducingGenerator;
+use plonky2::gates::reducing_extension::ReducingExtensionGate;
+use plonky2::gates::reducing_extension::ReducingGenerator as ReducingExtensionGenerator;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::generator::{
+    ConstantGenerator, CopyGenerator, NonzeroTestGenerator, RandomValueGenerator,
+};
+use plonky2::util::serialization::{GateSerializer, WitnessGeneratorSerializer};
+use plonky2::{get_gate_tag_impl, impl_gate_serializer, read_gate_impl};
+use plonky2::{get_generator_tag_impl, impl_generator_serializer, read_generator_impl};
+use plonky2_crypto::u32::gates::add_many_u32::{
+    U32AddManyGate as KeccakU32AddManyGate, U32AddManyGenerator as KeccakU32AddManyGenerator,
+};
+use plonky2_crypto::u32::gates::arithmetic_u32::{
+    U32ArithmeticGate as KeccakU32ArithmeticGate,
+    U32ArithmeticGenerator as KeccakU32ArithmeticGenerator,
+};
+use plonky2_crypto::u32::gates::interleave_u32::{U32InterleaveGate, U32InterleaveGenerator};
+use plonky2_crypto::u32::gates::subtraction_u32::{
+    U32SubtractionGate as KeccakU32SubtractionGate,
+    U32SubtractionGenerator as KeccakU32SubtractionGenerator,
+};
+use plonky2_crypto::u32::gates::uninterleave_to_b32::{
+    UninterleaveToB32Gate, UninterleaveToB32Generator,
+};
+use plonky2_crypto::u32::gates::uninterleave_to_u32::{
+    UninterleaveToU32Gate, UninterleaveToU32Generator,
+};
+use plonky2_u32::gadgets::arithmetic_u32::SplitToU32Generator;
+use plonky2_u32::gates::add_many_u32::U32AddManyGate;
+use plonky2_u32::gates::add_many_u32::U32AddManyGenerator;
+use plonky2_u32::gates::arithmetic_u32::U32ArithmeticGate;
+use plonky2_u32::gates::arithmetic_u32::U32ArithmeticGenerator;
+use plonky2_u32::gates::comparison::ComparisonGate;
+use plonky2_u32::gates::comparison::ComparisonGenerator;
+use plonky2_u32::gates::range_check_u32::U32RangeCheckGate;
+use plonky2_u32::gates::range_check_u32::U32RangeCheckGenerator;
+use plonky2_u32::gates::subtraction_u32::U32SubtractionGate;
+use plonky2_u32::gates::subtraction_u32::U32SubtractionGenerator;
+
+use crate::gadgets::biguint::BigUintDivRemGenerator;
+use crate::gadgets::glv::GLVDecompositionGenerator;
+use crate::gadgets::nonnative::{
+    NonNativeAdditionGenerator, NonNativeInverseGenerator, NonNativeMultipleAddsGenerator,
+    NonNativeMultiplicationGenerator, NonNativeSubtractionGenerator,
+};
+
+/// Serializes the gates of the secp256k1 ECDSA circuits of [`crate::gadgets::ecdsa`], including
+/// the Keccak-256 gadget of [`verify_message_bytes_circuit`].
+///
+/// [`verify_message_bytes_circuit`]: crate::gadgets::ecdsa::verify_message_bytes_circuit
+pub struct ECDSAGateSerializer;
+impl<F: RichField + Extendable<D>, const D: usize> GateSerializer<F, D> for ECDSAGateSerializer {
+    impl_gate_serializer! {
+        DefaultGateSerializer,
+        ArithmeticGate,
+        ArithmeticExtensionGate<D>,
+        BaseSumGate<2>,
+        BaseSumGate<4>,
+        ComparisonGate<F, D>,
+        ConstantGate,
+        CosetInterpolationGate<F, D>,
+        ExponentiationGate<F, D>,
+        LookupGate,
+        LookupTableGate,
+        MulExtensionGate<D>,
+        NoopGate,
+        PoseidonMdsGate<F, D>,
+        PoseidonGate<F, D>,
+        PublicInputGate,
+        RandomAccessGate<F, D>,
+        ReducingExtensionGate<D>,
+        ReducingGate<D>,
+        U32AddManyGate<F, D>,
+        U32ArithmeticGate<F, D>,
+        U32RangeCheckGate<F, D>,
+        U32SubtractionGate<F, D>,
+        KeccakU32AddManyGate<F, D>,
+        KeccakU32ArithmeticGate<F, D>,
+        KeccakU32SubtractionGate<F, D>,
+        U32InterleaveGate,
+        UninterleaveToB32Gate,
+        UninterleaveToU32Gate
+    }
+}
+/// Serializes the witness generators of the same circuits as [`ECDSAGateSerializer`].
+pub struct ECDSAGeneratorSerializer {}
+
+impl<F, const D: usize> WitnessGeneratorSerializer<F, D> for ECDSAGeneratorSerializer
+where
+    F: RichField + Extendable<D>,
+{
+    impl_generator_serializer! {
+        DefaultGeneratorSerializer,
+        ArithmeticBaseGenerator<F, D>,
+        ArithmeticExtensionGenerator<F, D>,
+        BigUintDivRemGenerator<F, D>,
+        BaseSplitGenerator<2>,
+        BaseSplitGenerator<4>,
+        BaseSumGenerator<2>,
+        ComparisonGenerator<F, D>,
+        ConstantGenerator<F>,
+        CopyGenerator,
+        EqualityGenerator,
+        ExponentiationGenerator<F, D>,
+        GLVDecompositionGenerator<F, D>,
+        InterpolationGenerator<F, D>,
+        LookupGenerator,
+        LookupTableGenerator,
+        LowHighGenerator,
+        MulExtensionGenerator<F, D>,
+        NonNativeMultiplicationGenerator<F, D, Secp256K1Base>,
+        NonNativeMultiplicationGenerator<F, D, Secp256K1Scalar>,
+        NonNativeAdditionGenerator<F, D, Secp256K1Base>,
+        NonNativeAdditionGenerator<F, D, Secp256K1Scalar>,
+        NonNativeMultipleAddsGenerator<F, D, Secp256K1Base>,
+        NonNativeMultipleAddsGenerator<F, D, Secp256K1Scalar>,
+        NonNativeInverseGenerator<F, D, Secp256K1Base>,
+        NonNativeInverseGenerator<F, D, Secp256K1Scalar>,
+        NonNativeSubtractionGenerator<F, D, Secp256K1Base>,
+        NonNativeSubtractionGenerator<F, D, Secp256K1Scalar>,
+        NonzeroTestGenerator,
+        PoseidonGenerator<F, D>,
+        PoseidonMdsGenerator<D>,
+        QuotientGeneratorExtension<D>,
+        RandomAccessGenerator<F, D>,
+        RandomValueGenerator,
+        ReducingGenerator<D>,
+        ReducingExtensionGenerator<D>,
+        SplitGenerator,
+        SplitToU32Generator<F, D>,
+        U32AddManyGenerator<F, D>,
+        U32ArithmeticGenerator<F, D>,
+        U32RangeCheckGenerator<F, D>,
+        U32SubtractionGenerator<F, D>,
+        KeccakU32AddManyGenerator<F, D>,
+        KeccakU32ArithmeticGenerator<F, D>,
+        KeccakU32SubtractionGenerator<F, D>,
+        U32InterleaveGenerator,
+        UninterleaveToB32Generator,
+        UninterleaveToU32Generator,
+        WireSplitGenerator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2::field::types::Sample;
+    use plonky2::iop::witness::PartialWitness;
+    use plonky2::plonk::circuit_builder::CircuitBuilder;
+    use plonky2::plonk::circuit_data::{CircuitConfig, CircuitData};
+    use plonky2::plonk::config::{GenericConfig, KeccakGoldilocksConfig};
+    use plonky2::util::serialization::Buffer;
+
+    use super::*;
+    use crate::curve::ecdsa::{keccak256_msg_hash, sign_message, ECDSASecretKey};
+    use crate::curve::secp256k1::Secp256K1;
+    use crate::gadgets::ecdsa::*;
+
+    const D: usize = 2;
+    type C = KeccakGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn round_trip(data: CircuitData<F, C, D>) -> CircuitData<F, C, D> {
+        let gate_serializer = ECDSAGateSerializer {};
+        let generator_serializer = ECDSAGeneratorSerializer {};
+        let bytes = data
+            .to_bytes(&gate_serializer, &generator_serializer)
+            .unwrap();
+        CircuitData::from_bytes(&bytes, &gate_serializer, &generator_serializer).unwrap()
+    }
+
+    // The prove step of the benchmark only has the serialized circuit and targets
+    #[test]
+    #[ignore]
+    fn test_witness_circuit_round_trip() -> Result<()> {
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_ecc_config());
+        let mut target_bytes = Vec::new();
+        verify_message_witness_circuit(&mut builder)
+            .write_to_serializer(&mut target_bytes)
+            .unwrap();
+        let data = round_trip(builder.build::<C>());
+        let targets =
+            ECDSATargets::<Secp256K1>::deserialize(&mut Buffer::new(&target_bytes)).unwrap();
+
+        let msg = Secp256K1Scalar::rand();
+        let sk = ECDSASecretKey::<Secp256K1>(Secp256K1Scalar::rand());
+        let pk = sk.to_public();
+        let sig = sign_message(msg, sk);
+        let mut pw = PartialWitness::new();
+        set_ecdsa_targets(&mut pw, &targets, msg, sig, pk);
+        let proof = data.prove(pw)?;
+        assert_eq!(proof.public_inputs, ecdsa_public_inputs::<F>(msg, pk));
+        data.verify(proof)
+    }
+
+    // The Keccak gadget's generators must be written and read back with their own types
+    #[test]
+    #[ignore]
+    fn test_message_bytes_circuit_round_trip() -> Result<()> {
+        let msg_len = 32;
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_ecc_config());
+        let mut target_bytes = Vec::new();
+        verify_message_bytes_circuit(&mut builder, msg_len)
+            .write_to_serializer(&mut target_bytes)
+            .unwrap();
+        let data = round_trip(builder.build::<C>());
+        let targets =
+            ECDSAMessageBytesTargets::deserialize(&mut Buffer::new(&target_bytes)).unwrap();
+
+        let msg: Vec<u8> = (0..msg_len as u8).collect();
+        let sk = ECDSASecretKey::<Secp256K1>(Secp256K1Scalar::rand());
+        let pk = sk.to_public();
+        let sig = sign_message(keccak256_msg_hash::<Secp256K1>(&msg), sk);
+        let mut pw = PartialWitness::new();
+        set_ecdsa_message_bytes_targets(&mut pw, &targets, &msg, sig, pk);
+        let proof = data.prove(pw)?;
+        assert_eq!(
+            proof.public_inputs,
+            ecdsa_message_bytes_public_inputs::<F>(&msg, pk)
+        );
+        data.verify(proof)
+    }
+}
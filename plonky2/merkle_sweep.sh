@@ -0,0 +1,31 @@
+#!/bin/bash
+
+SCRIPT_DIR=$( cd -- "$( dirname -- "${BASH_SOURCE[0]}" )" &> /dev/null && pwd )
+
+POSEIDON_DIR=$SCRIPT_DIR/merkle_tree/poseidon
+KECCAK_DIR=$SCRIPT_DIR/merkle_tree/keccak
+SWEEP_DIR=$SCRIPT_DIR/logs/merkle_sweep
+
+declare -a depths=(5 10 15 20)
+mkdir -p logs/merkle_sweep
+
+cd $POSEIDON_DIR
+cargo build --release
+for depth in "${depths[@]}"; do
+    touch "$SWEEP_DIR"/"$depth"_poseidon_log
+    "$POSEIDON_DIR"/target/release/standard build "$SWEEP_DIR"/"$depth"_poseidon.json --depth $depth --dynamic-index
+    { RUST_LOG=debug /usr/bin/time -v "$POSEIDON_DIR"/target/release/standard prove "$SWEEP_DIR"/"$depth"_poseidon.json --depth $depth --dynamic-index; } 2> "$SWEEP_DIR"/"$depth"_poseidon_log
+done
+for depth in "${depths[@]}"; do
+    for hash in poseidon poseidon2; do
+        { RUST_LOG=debug /usr/bin/time -v "$POSEIDON_DIR"/target/release/poseidon2 "$SWEEP_DIR"/"$depth"_"$hash"_config.json --hash $hash --depth $depth; } 2> "$SWEEP_DIR"/"$depth"_"$hash"_config_log
+    done
+done
+
+cd $KECCAK_DIR
+cargo build --release
+for depth in "${depths[@]}"; do
+    touch "$SWEEP_DIR"/"$depth"_keccak_log
+    "$KECCAK_DIR"/target/release/standard build "$SWEEP_DIR"/"$depth"_keccak.json --depth $depth --dynamic-index
+    { RUST_LOG=debug /usr/bin/time -v "$KECCAK_DIR"/target/release/standard prove "$SWEEP_DIR"/"$depth"_keccak.json --depth $depth --dynamic-index; } 2> "$SWEEP_DIR"/"$depth"_keccak_log
+done
@@ -1,5 +1,4 @@
 use clap::{App, Arg};
-use log::Level;
 use plonky2::impl_gate_serializer;
 use plonky2::read_gate_impl;
 use plonky2::{get_gate_tag_impl, util::serialization::GateSerializer};
@@ -13,20 +12,21 @@ use plonky2_crypto::u32::gates::uninterleave_to_u32::{
     UninterleaveToU32Gate, UninterleaveToU32Generator,
 };
 use plonky2_field::types::Field;
-use serde_json::json;
-use std::io::Read;
-use std::{fs::File, io::Write, time::Instant};
+use std::{fs::File, time::Instant};
 
 use jemallocator::Jemalloc;
+use zkperf_report::{BenchmarkResult, PhaseTracker};
 use num::BigUint;
 use plonky2::{
+    iop::{
+        target::Target,
+        witness::{PartialWitness, WitnessWrite},
+    },
     plonk::{
         circuit_builder::CircuitBuilder,
         circuit_data::{CircuitConfig, CircuitData},
         config::{GenericConfig, KeccakGoldilocksConfig},
-        prover::prove,
     },
-    util::timing::TimingTree,
 };
 
 use plonky2_crypto::{
@@ -39,7 +39,11 @@ use plonky2_crypto::{
     u32::arithmetic_u32::CircuitBuilderU32,
 };
 use plonky2_field::goldilocks_field::GoldilocksField;
+use plonky2_merkle_trees::proving::{
+    self, check_cols, circuit_config, prove_and_verify, read_targets,
+};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use plonky2::field::extension::Extendable;
 use plonky2::gadgets::arithmetic::EqualityGenerator;
@@ -56,6 +60,7 @@ use plonky2::gates::constant::ConstantGate;
 use plonky2::gates::coset_interpolation::CosetInterpolationGate;
 use plonky2::gates::coset_interpolation::InterpolationGenerator;
 use plonky2::gates::exponentiation::ExponentiationGenerator;
+use plonky2::gates::gate::Gate;
 use plonky2::gates::lookup::LookupGate;
 use plonky2::gates::lookup::LookupGenerator;
 use plonky2::gates::lookup_table::LookupTableGate;
@@ -168,6 +173,9 @@ fn connect_two(
     builder.connect_hash_input(&hash_target, &zero, 17);
 }
 
+// Wires of the standard recursion config
+const DEFAULT_COLS: usize = 135;
+
 fn verify_merkle_proof_circuit(
     leaf_index: usize,
     nr_layers: usize,
@@ -182,10 +190,7 @@ fn verify_merkle_proof_circuit(
 
     let mut targets: Vec<BigUintTarget> = Vec::new();
 
-    let config: CircuitConfig = CircuitConfig {
-        num_routed_wires: cols,
-        ..CircuitConfig::standard_recursion_zk_config()
-    };
+    let config = circuit_config(cols, CircuitConfig::standard_recursion_zk_config());
 
     let mut builder: CircuitBuilder<plonky2::field::goldilocks_field::GoldilocksField, 2> =
         CircuitBuilder::<F, D>::new(config);
@@ -265,9 +270,87 @@ fn verify_merkle_proof_circuit(
     }
     targets.push(next_hash);
 
-    let json_string = serde_json::to_string(&targets).unwrap();
-    let mut file = File::create("merkle_targets").unwrap();
-    file.write_all(json_string.as_bytes()).unwrap();
+    println!("building circuit");
+    builder.print_gate_counts(0);
+    let start = Instant::now();
+    let data = builder.build::<C>();
+    let build_duration = start.elapsed();
+    println!("circuit build duration: {:?}", build_duration);
+    (data, targets)
+}
+
+// Targets of the Merkle proof circuit whose leaf position is part of the witness
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MerkleProofTargets {
+    leaf: HashOutputTarget,
+    leaf_index: Target,
+    // One sibling per level, starting at the lowest level (as returned by `get_merkle_proof`)
+    siblings: Vec<HashOutputTarget>,
+    root: HashOutputTarget,
+}
+
+// Same as verify_merkle_proof_circuit, but the hashing order of each level is picked by the
+// bits of a witness leaf index, so one circuit proves membership of any of the 2^nr_layers leaves.
+// This matches the halo2 merkle_tree_keccak circuit, which uses num_to_bits + select.
+fn verify_merkle_proof_dynamic_index_circuit(
+    nr_layers: usize,
+    cols: usize,
+    public_index: bool,
+) -> (
+    CircuitData<GoldilocksField, KeccakGoldilocksConfig, 2>,
+    MerkleProofTargets,
+) {
+    const D: usize = 2;
+    type C = KeccakGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    let config = circuit_config(cols, CircuitConfig::standard_recursion_zk_config());
+
+    let mut builder: CircuitBuilder<plonky2::field::goldilocks_field::GoldilocksField, 2> =
+        CircuitBuilder::<F, D>::new(config);
+
+    let one = builder.constant_biguint(&BigUint::from(1u32));
+    let last = builder.constant_biguint(&BigUint::from(2147483648u32));
+    let zero = builder.hash_zero(16);
+
+    let leaf = builder.add_virtual_biguint_target(8);
+    let leaf_index = builder.add_virtual_target();
+    // bit i is set if the node at level i is a right child; also enforces leaf_index < 2^nr_layers
+    let index_bits = builder.split_le(leaf_index, nr_layers);
+
+    let mut siblings: Vec<HashOutputTarget> = Vec::new();
+    let mut next_hash = leaf.clone();
+    for is_right in index_bits {
+        let sibling = builder.add_virtual_biguint_target(8);
+        let left = builder.select_biguint(is_right, &sibling, &next_hash);
+        let right = builder.select_biguint(is_right, &next_hash, &sibling);
+
+        let next_hash_inp = builder.add_virtual_hash_input_target(1, KECCAK256_R);
+        connect_two(
+            &mut builder,
+            &next_hash_inp,
+            &left,
+            &right,
+            &one,
+            &last,
+            &zero,
+        );
+        siblings.push(sibling);
+        next_hash = builder.hash_keccak256(&next_hash_inp);
+    }
+    // This is the expected root value
+    for i in 0..next_hash.num_limbs() {
+        builder.register_u32_public_input(next_hash.get_limb(i));
+    }
+    if public_index {
+        builder.register_public_input(leaf_index);
+    }
+    let targets = MerkleProofTargets {
+        leaf,
+        leaf_index,
+        siblings,
+        root: next_hash,
+    };
 
     println!("building circuit");
     builder.print_gate_counts(0);
@@ -275,33 +358,65 @@ fn verify_merkle_proof_circuit(
     let data = builder.build::<C>();
     let build_duration = start.elapsed();
     println!("circuit build duration: {:?}", build_duration);
+    (data, targets)
+}
 
-    let gate_serializer = MerkleGateSerializer {};
-    let generator_serializer = MerkleGeneratorSerializer {};
-    let mut file = File::create("merkle_data").unwrap();
-    let _ = file.write_all(
-        &data
-            .to_bytes(&gate_serializer, &generator_serializer)
-            .unwrap(),
+// Sets the leaf, its index, the siblings on its path and the expected root
+fn set_merkle_proof_targets(
+    pw: &mut PartialWitness<GoldilocksField>,
+    targets: &MerkleProofTargets,
+    tree: &MerkleTree,
+    leaf_index: usize,
+) {
+    pw.set_keccak256_output_target(&targets.leaf, &tree.tree[0][leaf_index]);
+    pw.set_target(
+        targets.leaf_index,
+        GoldilocksField::from_canonical_usize(leaf_index),
     );
-    (data, targets)
+    let merkle_proof = tree.clone().get_merkle_proof(leaf_index);
+    for (sibling, hash) in targets.siblings.iter().zip(&merkle_proof) {
+        pw.set_keccak256_output_target(sibling, hash);
+    }
+    pw.set_keccak256_output_target(&targets.root, &tree.root);
+}
+
+// The circuit files are written with the serializers of this binary
+fn write_circuit(
+    data: &CircuitData<GoldilocksField, KeccakGoldilocksConfig, 2>,
+    targets: &impl Serialize,
+) {
+    proving::write_circuit(
+        data,
+        targets,
+        &MerkleGateSerializer {},
+        &MerkleGeneratorSerializer {},
+    );
+}
+
+fn read_circuit_data() -> CircuitData<GoldilocksField, KeccakGoldilocksConfig, 2> {
+    proving::read_circuit_data(&MerkleGateSerializer {}, &MerkleGeneratorSerializer {})
 }
 
-fn get_tree(nr_leaves: u64) -> MerkleTree {
+// Builds the benchmarked tree of 2^depth leaves. The first leaves are read from `leaf_data`, a JSON
+// array of integers, if given, and are random otherwise; the rest of the tree is padded with zeros.
+fn get_tree(leaf_data: Option<&str>, nr_leaves: usize, depth: usize) -> MerkleTree {
     const GOLDILOCKS_FIELD_ORDER: u64 = 18446744069414584321;
     const D: usize = 2;
     type C = KeccakGoldilocksConfig;
     type F = <C as GenericConfig<D>>::F;
 
-    let mut rng = rand::thread_rng();
-    let mut leaves: Vec<GoldilocksField> = Vec::new();
-    for _ in 0..nr_leaves {
-        leaves.push(F::from_canonical_u64(
-            rng.gen_range(0..GOLDILOCKS_FIELD_ORDER),
-        ));
-    }
-    let tree: MerkleTree = MerkleTree::build(leaves.clone());
-    tree
+    let mut leaves: Vec<GoldilocksField> = if let Some(path) = leaf_data {
+        let values: Vec<u64> = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+        values.into_iter().map(F::from_noncanonical_u64).collect()
+    } else {
+        let mut rng = rand::thread_rng();
+        (0..nr_leaves)
+            .map(|_| F::from_canonical_u64(rng.gen_range(0..GOLDILOCKS_FIELD_ORDER)))
+            .collect()
+    };
+    assert!(leaves.len() <= 1 << depth, "too many leaves for a tree of depth {}", depth);
+    leaves.resize(1 << depth, F::ZERO);
+    MerkleTree::build(leaves)
 }
 
 fn main() {
@@ -321,6 +436,17 @@ fn main() {
                 .help("output json file path")
                 .required(true),
         )
+        .arg(
+            Arg::with_name("dynamic_index")
+                .long("dynamic-index")
+                .help("take the leaf index as a witness instead of fixing it in the circuit"),
+        )
+        .arg(
+            Arg::with_name("public_index")
+                .long("public-index")
+                .requires("dynamic_index")
+                .help("register the leaf index as a public input"),
+        )
         .arg(
             Arg::with_name("cols")
                 .short("c")
@@ -329,10 +455,44 @@ fn main() {
                 .help("number of columns")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("depth")
+                .short("d")
+                .long("depth")
+                .value_name("depth")
+                .help("number of levels of the tree [default: fits the leaves, 10 without leaves]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("leaves")
+                .short("n")
+                .long("leaves")
+                .value_name("leaves")
+                .help("number of leaves, padded with zeros to 2^depth [default: 2^depth]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("index")
+                .short("i")
+                .long("index")
+                .value_name("index")
+                .help("index of the proved leaf")
+                .takes_value(true)
+                .default_value("0"),
+        )
+        .arg(
+            Arg::with_name("leaf_data")
+                .long("leaf-data")
+                .value_name("leaf_data")
+                .help("JSON file with an array of integer leaves; overrides --leaves")
+                .takes_value(true),
+        )
         .get_matches();
 
     let build_prove = matches.value_of("build_prove").unwrap().to_string();
     let outp_json = matches.value_of("output").unwrap().to_string();
+    let dynamic_index = matches.is_present("dynamic_index");
+    let public_index = matches.is_present("public_index");
 
     if build_prove != "build" && build_prove != "prove" {
         panic!("Must specify build or prove");
@@ -341,94 +501,164 @@ fn main() {
     let cols = if let Some(col) = matches.value_of("cols") {
         col.parse::<usize>().unwrap()
     } else {
-        25 as usize
+        DEFAULT_COLS
     };
 
-    if cols < 25 {
-        panic!("Invalid cols")
-    }
+    // The Poseidon gate hashing the public inputs is wider than any gate of the Keccak gadget
+    check_cols(cols, &PoseidonGate::<GoldilocksField, 2>::new());
 
-    const D: usize = 2;
-    type C = KeccakGoldilocksConfig;
-    type F = <C as GenericConfig<D>>::F;
-
-    if build_prove == "build" {
-        verify_merkle_proof_circuit(0, 10, cols);
+    let leaf_data = matches.value_of("leaf_data");
+    let nr_leaves = if let Some(path) = leaf_data {
+        let values: Vec<u64> = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+        Some(values.len())
     } else {
-        let tree: MerkleTree = get_tree(1024);
-
-        let merkle_proof_leaf0 = tree.clone().get_merkle_proof(0);
-        println!("{:?}", merkle_proof_leaf0);
-        let mut target_file = File::open("merkle_targets").unwrap();
-        let mut target_string = String::new();
-
-        target_file.read_to_string(&mut target_string).unwrap();
+        matches.value_of("leaves").map(|n| n.parse::<usize>().unwrap())
+    };
+    let depth = if let Some(depth) = matches.value_of("depth") {
+        depth.parse::<usize>().unwrap()
+    } else {
+        nr_leaves.map_or(10, |n| n.next_power_of_two().trailing_zeros().max(1) as usize)
+    };
+    let max_leaves = match u32::try_from(depth).ok().and_then(|d| 1usize.checked_shl(d)) {
+        Some(max_leaves) if depth > 0 => max_leaves,
+        _ => panic!("Invalid depth {}", depth),
+    };
+    let nr_leaves = nr_leaves.unwrap_or(max_leaves);
+    let leaf_index = matches.value_of("index").unwrap().parse::<usize>().unwrap();
 
-        let targets: Vec<HashOutputTarget> = serde_json::from_str(&target_string).unwrap();
+    if nr_leaves > max_leaves {
+        panic!("Invalid depth {} for {} leaves", depth, nr_leaves)
+    }
+    if leaf_index >= nr_leaves {
+        panic!("Invalid index {} for {} leaves", leaf_index, nr_leaves)
+    }
 
-        let mut file = File::open("merkle_data").expect("File not found");
+    let circuit_name = if dynamic_index {
+        "MerkleTreeDynamicIndex"
+    } else {
+        "MerkleTree"
+    };
 
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)
-            .expect("Failed to read the file");
+    if build_prove == "build" {
+        let mut results = BenchmarkResult::new("plonky2", circuit_name, "Plonk+FRI", "NaN");
+        let tracker = PhaseTracker::start("build");
+        if dynamic_index {
+            let (data, targets) =
+                verify_merkle_proof_dynamic_index_circuit(depth, cols, public_index);
+            write_circuit(&data, &targets);
+        } else {
+            let (data, targets) = verify_merkle_proof_circuit(leaf_index, depth, cols);
+            write_circuit(&data, &targets);
+        }
+        results.record_phase(tracker.finish());
+        results.write(outp_json).unwrap();
+    } else if dynamic_index {
+        let tree: MerkleTree = get_tree(leaf_data, nr_leaves, depth);
+        // any leaf can be proven with the same circuit
+        println!("proving leaf {}", leaf_index);
+
+        let targets: MerkleProofTargets = read_targets();
+        let circuit_data = read_circuit_data();
+
+        let mut pw = PartialWitness::new();
+        set_merkle_proof_targets(&mut pw, &targets, &tree, leaf_index);
+
+        let results =
+            BenchmarkResult::load_or_new(&outp_json, "plonky2", circuit_name, "Plonk+FRI", "NaN");
+        prove_and_verify(circuit_data, pw, results, &outp_json);
+    } else {
+        let tree: MerkleTree = get_tree(leaf_data, nr_leaves, depth);
 
-        let gate_serializer = MerkleGateSerializer {};
-        let generator_serializer = MerkleGeneratorSerializer {};
-        let circuit_data: CircuitData<F, C, D> =
-            CircuitData::from_bytes(&buffer, &gate_serializer, &generator_serializer).unwrap();
+        let merkle_proof = tree.clone().get_merkle_proof(leaf_index);
+        println!("{:?}", merkle_proof);
+        let targets: Vec<HashOutputTarget> = read_targets();
+        let circuit_data = read_circuit_data();
 
-        let mut pw = plonky2::iop::witness::PartialWitness::new();
+        let mut pw = PartialWitness::new();
         // non-public inputs to witness: leaf and elements of merkle proof
-        pw.set_keccak256_output_target(&targets[0], &tree.tree[0][0]);
-        for i in 1..11 {
-            pw.set_keccak256_output_target(&targets[i], &merkle_proof_leaf0[i - 1]);
+        pw.set_keccak256_output_target(&targets[0], &tree.tree[0][leaf_index]);
+        for i in 1..depth + 1 {
+            pw.set_keccak256_output_target(&targets[i], &merkle_proof[i - 1]);
         }
         // public input: root of merkle tree
         let expected_public_inputs = &targets[targets.len() - 1];
 
         pw.set_keccak256_output_target(expected_public_inputs, &tree.root);
 
-        println!("proving circuit");
-        let mut timing = TimingTree::new("prove", Level::Info);
-        let proof = prove::<F, C, D>(
-            &circuit_data.prover_only,
-            &circuit_data.common,
-            pw,
-            &mut timing,
+        let results =
+            BenchmarkResult::load_or_new(&outp_json, "plonky2", circuit_name, "Plonk+FRI", "NaN");
+        prove_and_verify(circuit_data, pw, results, &outp_json);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+    use plonky2_crypto::{hash::keccak256::WitnessHashKeccak, simple_merkle_tree::MerkleTree};
+    use plonky2_field::{goldilocks_field::GoldilocksField, types::Field};
+
+    use crate::{
+        set_merkle_proof_targets, verify_merkle_proof_dynamic_index_circuit, DEFAULT_COLS,
+    };
+
+    fn get_test_tree(nr_leaves: u64) -> MerkleTree {
+        MerkleTree::build(
+            (0..nr_leaves)
+                .map(|i| GoldilocksField::from_canonical_u64(i * 7 + 3))
+                .collect(),
         )
-        .unwrap();
-        timing.pop();
-        timing.print();
-        let proof_duration = timing.duration();
-        println!("Proving time: {:?}", proof_duration);
-
-        let proof_bytes = proof.to_bytes();
-        let proof_len = proof_bytes.len();
-        println!("Proof size: {} bytes", proof_len);
-
-        println!("verifying circuit");
-        let mut timing = TimingTree::new("verify", Level::Info);
-        circuit_data.verify(proof).expect("verify error");
-        timing.pop();
-        timing.print();
-
-        let verify_duration = timing.duration();
-        println!("Verifying time: {:?}", verify_duration);
-
-        println!("writing results");
-        let results = json!({
-          "Framework": "plonky2",
-          "Circuit": "MerkleTree",
-          "Backend": "Plonk+FRI",
-          "Curve": "NaN",
-          "ProverTime": proof_duration.as_secs_f32(),
-          "VerifierTime": verify_duration.as_nanos() as f32 / 1000000.,
-          "ProofSize": proof_len
-        });
-
-        let json_string = serde_json::to_string(&results).unwrap();
-
-        let mut file = File::create(outp_json).unwrap();
-        let _ = file.write_all(json_string.as_bytes());
+    }
+
+    #[test]
+    fn test_one_circuit_for_every_leaf() -> anyhow::Result<()> {
+        let tree = get_test_tree(4);
+        let (circuit_data, targets) =
+            verify_merkle_proof_dynamic_index_circuit(2, DEFAULT_COLS, false);
+
+        for leaf_index in 0..4 {
+            let mut pw = PartialWitness::new();
+            set_merkle_proof_targets(&mut pw, &targets, &tree, leaf_index);
+            let proof = circuit_data.prove(pw)?;
+            circuit_data.verify(proof)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_public_index() -> anyhow::Result<()> {
+        let tree = get_test_tree(4);
+        let (circuit_data, targets) =
+            verify_merkle_proof_dynamic_index_circuit(2, DEFAULT_COLS, true);
+
+        let mut pw = PartialWitness::new();
+        set_merkle_proof_targets(&mut pw, &targets, &tree, 2);
+        let proof = circuit_data.prove(pw)?;
+        assert_eq!(
+            *proof.public_inputs.last().unwrap(),
+            GoldilocksField::from_canonical_u64(2)
+        );
+        circuit_data.verify(proof)
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_wrong_index_fails() {
+        let tree = get_test_tree(4);
+        let (circuit_data, targets) =
+            verify_merkle_proof_dynamic_index_circuit(2, DEFAULT_COLS, false);
+
+        // The proof of leaf 2 does not lead to the root when hashed in the order of leaf 3
+        let mut pw = PartialWitness::new();
+        pw.set_keccak256_output_target(&targets.leaf, &tree.tree[0][2]);
+        pw.set_target(targets.leaf_index, GoldilocksField::from_canonical_u64(3));
+        for (sibling, hash) in targets
+            .siblings
+            .iter()
+            .zip(&tree.clone().get_merkle_proof(2))
+        {
+            pw.set_keccak256_output_target(sibling, hash);
+        }
+        pw.set_keccak256_output_target(&targets.root, &tree.root);
+        circuit_data.prove(pw).unwrap();
     }
 }
@@ -65,6 +65,14 @@ pub trait CircuitBuilderBiguint<F: RichField + Extendable<D>, const D: usize> {
 
     fn mul_biguint_by_bool(&mut self, a: &BigUintTarget, b: BoolTarget) -> BigUintTarget;
 
+    /// Returns `x` if `b` is true, `y` otherwise. Both must have the same number of limbs.
+    fn select_biguint(
+        &mut self,
+        b: BoolTarget,
+        x: &BigUintTarget,
+        y: &BigUintTarget,
+    ) -> BigUintTarget;
+
     /// Returns x * y + z. This is no more efficient than mul-then-add; it's purely for convenience (only need to call one CircuitBuilder function).
     fn mul_add_biguint(
         &mut self,
@@ -320,6 +328,25 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderBiguint<F, D>
         }
     }
 
+    fn select_biguint(
+        &mut self,
+        b: BoolTarget,
+        x: &BigUintTarget,
+        y: &BigUintTarget,
+    ) -> BigUintTarget {
+        assert_eq!(x.num_limbs(), y.num_limbs());
+
+        // Each limb is one of two range-checked limbs, so no new range check is needed.
+        BigUintTarget {
+            limbs: x
+                .limbs
+                .iter()
+                .zip(&y.limbs)
+                .map(|(&xl, &yl)| U32Target(self.select(b, xl.0, yl.0)))
+                .collect(),
+        }
+    }
+
     fn mul_add_biguint(
         &mut self,
         x: &BigUintTarget,
@@ -640,6 +667,35 @@ mod tests {
         data.verify(proof)
     }
 
+    #[test]
+    fn test_biguint_select() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let mut rng = OsRng;
+
+        let x_value = BigUint::from_u128(rng.gen()).unwrap();
+        let y_value = BigUint::from_u128(rng.gen()).unwrap();
+
+        let config = CircuitConfig::standard_recursion_config();
+        let pw = PartialWitness::new();
+        let mut builder = CircuitBuilder::<F, D>::new(config);
+
+        let x = builder.constant_biguint(&x_value);
+        let y = builder.constant_biguint(&y_value);
+        let (x, y) = builder.pad_biguints(&x, &y);
+        let b_true = builder._true();
+        let b_false = builder._false();
+        let picked_x = builder.select_biguint(b_true, &x, &y);
+        let picked_y = builder.select_biguint(b_false, &x, &y);
+        builder.connect_biguint(&picked_x, &x);
+        builder.connect_biguint(&picked_y, &y);
+
+        let data = builder.build::<C>();
+        let proof = data.prove(pw).unwrap();
+        data.verify(proof)
+    }
+
     #[test]
     fn test_biguint_div_rem() -> Result<()> {
         const D: usize = 2;
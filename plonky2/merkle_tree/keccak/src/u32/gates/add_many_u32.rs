@@ -300,7 +300,7 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
     for U32AddManyGenerator<F, D>
 {
     fn id(&self) -> String {
-        "U32AddManyGenerator".to_string()
+        "plonky2_crypto::U32AddManyGenerator".to_string()
     }
 
     fn dependencies(&self) -> Vec<Target> {
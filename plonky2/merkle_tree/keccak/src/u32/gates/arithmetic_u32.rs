@@ -366,7 +366,7 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
     for U32ArithmeticGenerator<F, D>
 {
     fn id(&self) -> String {
-        "U32ArithmeticGenerator".to_string()
+        "plonky2_crypto::U32ArithmeticGenerator".to_string()
     }
 
     fn dependencies(&self) -> Vec<Target> {
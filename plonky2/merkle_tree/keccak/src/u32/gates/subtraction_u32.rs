@@ -289,7 +289,7 @@ impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
     for U32SubtractionGenerator<F, D>
 {
     fn id(&self) -> String {
-        "U32SubtractionGenerator".to_string()
+        "plonky2_crypto::U32SubtractionGenerator".to_string()
     }
 
     fn dependencies(&self) -> Vec<Target> {
@@ -0,0 +1,220 @@
+use clap::{App, Arg};
+use log::Level;
+use plonky2::{
+    field::{
+        goldilocks_field::GoldilocksField,
+        types::{Field, Sample},
+    },
+    gates::{poseidon::PoseidonGate, poseidon2::Poseidon2Gate},
+    hash::{merkle_proofs::MerkleProofTarget, merkle_tree::MerkleTree},
+    iop::{
+        generator::generate_partial_witness,
+        witness::{PartialWitness, WitnessWrite},
+    },
+    plonk::{
+        circuit_builder::CircuitBuilder,
+        circuit_data::CircuitConfig,
+        config::{GenericConfig, Poseidon2GoldilocksConfig, PoseidonGoldilocksConfig},
+        prover::prove_with_partition_witness,
+    },
+    util::timing::TimingTree,
+};
+use plonky2_merkle_trees::proving::{check_cols, circuit_config};
+use std::time::Instant;
+
+use jemallocator::Jemalloc;
+use zkperf_report::{BenchmarkResult, PhaseTracker};
+
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+const D: usize = 2;
+type F = GoldilocksField;
+
+// Builds, proves and verifies a membership proof for the leaf at `leaf_index` of a random tree.
+// The tree and the circuit both use `C::InnerHasher`, so the hash is swapped in the commitments
+// (`C::Hasher`) and in the circuit at once.
+fn prove_membership<C: GenericConfig<D, F = F> + 'static>(
+    circuit_name: &str,
+    depth: usize,
+    leaf_index: usize,
+    cols: usize,
+    outp_json: &str,
+) {
+    let mut results = BenchmarkResult::new("plonky2", circuit_name, "Plonk+FRI", "NaN");
+
+    println!("building tree");
+    let tracker = PhaseTracker::start("tree");
+    let start = Instant::now();
+    let leaves: Vec<Vec<F>> = (0..1 << depth).map(|_| F::rand_vec(4)).collect();
+    let tree = MerkleTree::<F, C::InnerHasher>::new(leaves.clone(), 0);
+    let merkle_proof = tree.prove(leaf_index);
+    results.record_phase(tracker.finish());
+    println!("Tree construction time: {:?}", start.elapsed());
+
+    println!("building circuit");
+    let tracker = PhaseTracker::start("build");
+    let mut builder = CircuitBuilder::<F, D>::new(circuit_config(
+        cols,
+        CircuitConfig::standard_recursion_config(),
+    ));
+    let leaf_data = builder.add_virtual_targets(4);
+    let index = builder.add_virtual_target();
+    let index_bits = builder.split_le(index, depth);
+    let root = builder.add_virtual_hash();
+    builder.register_public_inputs(&root.elements);
+    let proof_target = MerkleProofTarget {
+        siblings: builder.add_virtual_hashes(depth),
+    };
+    builder.verify_merkle_proof::<C::InnerHasher>(
+        leaf_data.clone(),
+        &index_bits,
+        root,
+        &proof_target,
+    );
+    let circuit_data = builder.build::<C>();
+    results.record_phase(tracker.finish());
+
+    let mut pw = PartialWitness::new();
+    pw.set_target_arr(&leaf_data, &leaves[leaf_index]);
+    pw.set_target(index, F::from_canonical_usize(leaf_index));
+    pw.set_hash_target(root, tree.cap.0[0]);
+    for (target, sibling) in proof_target.siblings.iter().zip(merkle_proof.siblings) {
+        pw.set_hash_target(*target, sibling);
+    }
+
+    println!("generating witness");
+    let tracker = PhaseTracker::start("witness");
+    let mut timing = TimingTree::new("witness", Level::Info);
+    let partition_witness =
+        generate_partial_witness(pw, &circuit_data.prover_only, &circuit_data.common);
+    timing.pop();
+    results.record_phase(tracker.finish());
+    results.record_timing("witness", &timing).unwrap();
+    let witness_duration = timing.duration();
+
+    println!("proving circuit");
+    let tracker = PhaseTracker::start("prove");
+    let mut timing = TimingTree::new("prove", Level::Info);
+    let proof = prove_with_partition_witness::<F, C, D>(
+        &circuit_data.prover_only,
+        &circuit_data.common,
+        partition_witness,
+        &mut timing,
+    )
+    .unwrap();
+    timing.pop();
+    results.record_phase(tracker.finish());
+    timing.print();
+    results.record_timing("prove", &timing).unwrap();
+    let proof_duration = timing.duration();
+    println!("Proving time: {:?}", proof_duration);
+
+    let proof_len = proof.to_bytes().len();
+    println!("Proof size: {} bytes", proof_len);
+
+    println!("verifying circuit");
+    let tracker = PhaseTracker::start("verify");
+    let mut timing = TimingTree::new("verify", Level::Info);
+    circuit_data.verify(proof).expect("verify error");
+    timing.pop();
+    results.record_phase(tracker.finish());
+    timing.print();
+    results.record_timing("verify", &timing).unwrap();
+
+    let verify_duration = timing.duration();
+    println!("Verifying time: {:?}", verify_duration);
+
+    println!("writing results");
+    results.set_shape(
+        circuit_data.common.degree(),
+        circuit_data.common.config.num_wires,
+    );
+    results.set_prover_time(witness_duration + proof_duration);
+    results.set_verifier_time(verify_duration);
+    results.set_proof_size(proof_len);
+    results.record_peak_rss();
+    results.write(outp_json).unwrap();
+}
+
+fn main() {
+    env_logger::init();
+    let matches = App::new("poseidon2")
+        .arg(
+            Arg::with_name("output")
+                .index(1)
+                .value_name("outp_json")
+                .help("output json file path")
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("hash")
+                .long("hash")
+                .value_name("hash")
+                .help("hash used for the tree, the circuit and the FRI commitments")
+                .possible_values(&["poseidon", "poseidon2"])
+                .takes_value(true)
+                .default_value("poseidon2"),
+        )
+        .arg(
+            Arg::with_name("cols")
+                .short("c")
+                .long("cols")
+                .value_name("cols")
+                .help("number of columns")
+                .takes_value(true)
+                .default_value("135"),
+        )
+        .arg(
+            Arg::with_name("depth")
+                .short("d")
+                .long("depth")
+                .value_name("depth")
+                .help("number of levels of the tree")
+                .takes_value(true)
+                .default_value("10"),
+        )
+        .arg(
+            Arg::with_name("index")
+                .short("i")
+                .long("index")
+                .value_name("index")
+                .help("index of the proved leaf")
+                .takes_value(true)
+                .default_value("0"),
+        )
+        .get_matches();
+
+    let outp_json = matches.value_of("output").unwrap();
+    let cols = matches.value_of("cols").unwrap().parse::<usize>().unwrap();
+    let depth = matches.value_of("depth").unwrap().parse::<usize>().unwrap();
+    let leaf_index = matches.value_of("index").unwrap().parse::<usize>().unwrap();
+
+    if depth == 0 || depth >= usize::BITS as usize || leaf_index >> depth != 0 {
+        panic!("Invalid index {} for depth {}", leaf_index, depth)
+    }
+
+    // The gate of the hash is the widest gate of the circuit
+    match matches.value_of("hash").unwrap() {
+        "poseidon" => {
+            check_cols(cols, &PoseidonGate::<F, D>::new());
+            prove_membership::<PoseidonGoldilocksConfig>(
+                "MerkleTreePoseidon",
+                depth,
+                leaf_index,
+                cols,
+                outp_json,
+            )
+        }
+        _ => {
+            check_cols(cols, &Poseidon2Gate::<F, D>::new());
+            prove_membership::<Poseidon2GoldilocksConfig>(
+                "MerkleTreePoseidon2",
+                depth,
+                leaf_index,
+                cols,
+                outp_json,
+            )
+        }
+    }
+}
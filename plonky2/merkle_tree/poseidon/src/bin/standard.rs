@@ -1,7 +1,5 @@
 use clap::{App, Arg};
-use log::Level;
 use plonky2::impl_gate_serializer;
-use plonky2::plonk::prover::prove;
 use plonky2::read_gate_impl;
 use plonky2::{
     field::{goldilocks_field::GoldilocksField, types::Field},
@@ -9,24 +7,40 @@ use plonky2::{
         hash_types::{HashOut, HashOutTarget, RichField},
         poseidon::PoseidonHash,
     },
-    iop::witness::{PartialWitness, WitnessWrite},
+    iop::witness::WitnessWrite,
     plonk::{
         circuit_builder::CircuitBuilder,
         circuit_data::{CircuitConfig, CircuitData, CommonCircuitData, VerifierOnlyCircuitData},
         config::{GenericConfig, KeccakGoldilocksConfig},
         proof,
     },
-    util::timing::TimingTree,
 };
 use plonky2::{get_gate_tag_impl, util::serialization::GateSerializer};
+use plonky2_merkle_trees::proving::{
+    self, check_cols, circuit_config, prove_and_verify, read_targets,
+};
+use plonky2_merkle_trees::simple_merkle_tree::merkle_plonky2_verifier::{
+    set_batch_merkle_proof_targets, set_merkle_proof_targets,
+    verify_batch_merkle_proof_dynamic_circuit, verify_merkle_multiproof_circuit,
+    set_merkle_update_targets, verify_merkle_proof_dynamic_circuit, verify_merkle_update_circuit,
+    BatchMerkleProofTargets, MerkleProofTargets, MerkleUpdateTargets,
+};
 use plonky2_merkle_trees::simple_merkle_tree::simple_merkle_tree::MerkleTree;
-use serde_json::json;
-use std::io::Read;
-use std::{fs::File, io::Write, time::Instant};
+use plonky2_merkle_trees::sparse_merkle_tree::smt_plonky2_verifier::{
+    set_smt_proof_targets, verify_smt_proof_circuit, SmtProofTargets,
+};
+use plonky2_merkle_trees::sparse_merkle_tree::sparse_merkle_tree::{
+    Key, SparseMerkleTree, KEY_BITS,
+};
+use std::{fs::File, time::Instant};
 
 use jemallocator::Jemalloc;
+use zkperf_report::{BenchmarkResult, PhaseTracker};
 
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
 
 use plonky2::field::extension::Extendable;
 use plonky2::gadgets::arithmetic::EqualityGenerator;
@@ -43,6 +57,7 @@ use plonky2::gates::constant::ConstantGate;
 use plonky2::gates::coset_interpolation::CosetInterpolationGate;
 use plonky2::gates::coset_interpolation::InterpolationGenerator;
 use plonky2::gates::exponentiation::ExponentiationGenerator;
+use plonky2::gates::gate::Gate;
 use plonky2::gates::lookup::LookupGate;
 use plonky2::gates::lookup::LookupGenerator;
 use plonky2::gates::lookup_table::LookupTableGate;
@@ -126,6 +141,11 @@ where
     }
 }
 
+// Wires of the standard recursion config, which this benchmark has always used
+const DEFAULT_COLS: usize = 135;
+// Number of keys of a sparse tree without --leaves
+const DEFAULT_SPARSE_LEAVES: usize = 1024;
+
 /**
  * zkp for veryfing merkle proof
  */
@@ -135,6 +155,7 @@ where
 pub fn verify_merkle_proof_circuit(
     leaf_index: usize,
     nr_layers: usize,
+    cols: usize,
 ) -> (
     CircuitData<GoldilocksField, KeccakGoldilocksConfig, 2>,
     Vec<HashOutTarget>,
@@ -145,7 +166,7 @@ pub fn verify_merkle_proof_circuit(
 
     let mut targets: Vec<HashOutTarget> = Vec::new();
 
-    let config = CircuitConfig::standard_recursion_config();
+    let config = circuit_config(cols, CircuitConfig::standard_recursion_config());
     let mut builder: CircuitBuilder<plonky2::field::goldilocks_field::GoldilocksField, 2> =
         CircuitBuilder::<F, D>::new(config);
 
@@ -204,10 +225,6 @@ pub fn verify_merkle_proof_circuit(
     // This is the expected root value
     builder.register_public_inputs(&next_hash.elements);
 
-    let json_string = serde_json::to_string(&targets).unwrap();
-    let mut file = File::create("merkle_targets").unwrap();
-    file.write_all(json_string.as_bytes()).unwrap();
-
     println!("building circuit");
     builder.print_gate_counts(0);
     let start = Instant::now();
@@ -215,32 +232,84 @@ pub fn verify_merkle_proof_circuit(
     let build_duration = start.elapsed();
     println!("circuit build duration: {:?}", build_duration);
 
-    let gate_serializer = MerkleGateSerializer {};
-    let generator_serializer = MerkleGeneratorSerializer {};
-    let mut file = File::create("merkle_data").unwrap();
-    let _ = file.write_all(
-        &data
-            .to_bytes(&gate_serializer, &generator_serializer)
-            .unwrap(),
-    );
-
+    write_circuit(&data, &targets);
     (data, targets)
 }
 
-fn get_tree(nr_leaves: u64) -> MerkleTree {
+// Builds the benchmarked tree of 2^depth leaves. The first leaves are read from `leaf_data`, a JSON
+// array of integers, if given, and are random otherwise; the rest of the tree is padded with zeros.
+fn get_tree(leaf_data: Option<&str>, nr_leaves: usize, depth: usize) -> MerkleTree {
     const GOLDILOCKS_FIELD_ORDER: u64 = 18446744069414584321;
     const D: usize = 2;
     type C = KeccakGoldilocksConfig;
     type F = <C as GenericConfig<D>>::F;
-    let mut rng = rand::thread_rng();
-    let mut leaves: Vec<GoldilocksField> = Vec::new();
-    for i in 0..nr_leaves {
-        leaves.push(F::from_canonical_u64(
-            rng.gen_range(0..GOLDILOCKS_FIELD_ORDER),
-        ));
+    let mut leaves: Vec<GoldilocksField> = if let Some(path) = leaf_data {
+        let values: Vec<u64> = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+        values.into_iter().map(F::from_noncanonical_u64).collect()
+    } else {
+        let mut rng = rand::thread_rng();
+        (0..nr_leaves)
+            .map(|_| F::from_canonical_u64(rng.gen_range(0..GOLDILOCKS_FIELD_ORDER)))
+            .collect()
+    };
+    assert!(leaves.len() <= 1 << depth, "too many leaves for a tree of depth {}", depth);
+    leaves.resize(1 << depth, F::ZERO);
+    MerkleTree::build(leaves)
+}
+
+// Returns a random key of [depth] bits
+fn random_key(rng: &mut impl Rng, depth: usize) -> Key {
+    let mut key: Key = rng.gen();
+    for i in depth..KEY_BITS {
+        key[i / 32] &= !(1 << (i % 32));
+    }
+    key
+}
+
+// Returns a sparse tree with nr_leaves values and their keys. The keys are drawn with a fixed seed,
+// the values come from leaf_data or are random.
+fn get_sparse_tree(
+    leaf_data: Option<&str>,
+    nr_leaves: usize,
+    depth: usize,
+) -> (SparseMerkleTree, Vec<Key>) {
+    const GOLDILOCKS_FIELD_ORDER: u64 = 18446744069414584321;
+    let values: Vec<GoldilocksField> = if let Some(path) = leaf_data {
+        let values: Vec<u64> = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+        values.into_iter().map(GoldilocksField::from_noncanonical_u64).collect()
+    } else {
+        let mut rng = rand::thread_rng();
+        (0..nr_leaves)
+            .map(|_| GoldilocksField::from_canonical_u64(rng.gen_range(0..GOLDILOCKS_FIELD_ORDER)))
+            .collect()
+    };
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut tree = SparseMerkleTree::new(depth);
+    let mut keys = Vec::new();
+    for value in values {
+        let key = random_key(&mut rng, depth);
+        tree.insert(key, value);
+        keys.push(key);
     }
-    let tree: MerkleTree = MerkleTree::build(leaves.clone());
-    tree
+    (tree, keys)
+}
+
+// The circuit files are written with the serializers of this binary
+fn write_circuit(
+    data: &CircuitData<GoldilocksField, KeccakGoldilocksConfig, 2>,
+    targets: &impl Serialize,
+) {
+    proving::write_circuit(
+        data,
+        targets,
+        &MerkleGateSerializer {},
+        &MerkleGeneratorSerializer {},
+    );
+}
+
+fn read_circuit_data() -> CircuitData<GoldilocksField, KeccakGoldilocksConfig, 2> {
+    proving::read_circuit_data(&MerkleGateSerializer {}, &MerkleGeneratorSerializer {})
 }
 
 fn main() {
@@ -260,6 +329,44 @@ fn main() {
                 .help("output json file path")
                 .required(true),
         )
+        .arg(
+            Arg::with_name("dynamic_index")
+                .long("dynamic-index")
+                .help("take the leaf index as a witness instead of fixing it in the circuit"),
+        )
+        .arg(
+            Arg::with_name("public_index")
+                .long("public-index")
+                .requires("dynamic_index")
+                .conflicts_with("batch")
+                .help("register the leaf index as a public input"),
+        )
+        .arg(
+            Arg::with_name("update")
+                .long("update")
+                .conflicts_with_all(&["dynamic_index", "batch"])
+                .help("prove that replacing the leaf at --index turns the root into a new root"),
+        )
+        .arg(
+            Arg::with_name("new_leaf")
+                .long("new-leaf")
+                .value_name("new_leaf")
+                .requires("update")
+                .help("new value of the updated leaf (random by default)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("sparse")
+                .long("sparse")
+                .conflicts_with_all(&["dynamic_index", "batch", "update"])
+                .help("prove membership of the key at --index in a sparse tree with --leaves random keys"),
+        )
+        .arg(
+            Arg::with_name("non_membership")
+                .long("non-membership")
+                .requires("sparse")
+                .help("prove that a random key is not in the sparse tree instead"),
+        )
         .arg(
             Arg::with_name("cols")
                 .short("c")
@@ -268,10 +375,57 @@ fn main() {
                 .help("number of columns")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("depth")
+                .short("d")
+                .long("depth")
+                .value_name("depth")
+                .help("number of levels of the tree [default: fits the leaves, 10 without leaves, 256 with --sparse]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("leaves")
+                .short("n")
+                .long("leaves")
+                .value_name("leaves")
+                .help("number of leaves, padded with zeros to 2^depth [default: 2^depth]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("index")
+                .short("i")
+                .long("index")
+                .value_name("index")
+                .help("index of the proved leaf")
+                .takes_value(true)
+                .default_value("0"),
+        )
+        .arg(
+            Arg::with_name("batch")
+                .short("b")
+                .long("batch")
+                .value_name("batch")
+                .help("number of leaves proven against the root at once. Without --dynamic-index this is a multiproof \
+                       whose leaf indices (drawn with a fixed seed) are fixed in the circuit, so other indices need \
+                       a new build; --dynamic-index takes them as witnesses with one path per leaf")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("leaf_data")
+                .long("leaf-data")
+                .value_name("leaf_data")
+                .help("JSON file with an array of integer leaves; overrides --leaves")
+                .takes_value(true),
+        )
         .get_matches();
 
     let build_prove = matches.value_of("build_prove").unwrap().to_string();
     let outp_json = matches.value_of("output").unwrap().to_string();
+    let dynamic_index = matches.is_present("dynamic_index");
+    let public_index = matches.is_present("public_index");
+    let update = matches.is_present("update");
+    let sparse = matches.is_present("sparse");
+    let non_membership = matches.is_present("non_membership");
 
     if build_prove != "build" && build_prove != "prove" {
         panic!("Must specify build or prove");
@@ -280,52 +434,252 @@ fn main() {
     let cols = if let Some(col) = matches.value_of("cols") {
         col.parse::<usize>().unwrap()
     } else {
-        25 as usize
+        DEFAULT_COLS
+    };
+
+    // The Poseidon gate, which hashes the tree and the public inputs, is the widest gate
+    check_cols(cols, &PoseidonGate::<GoldilocksField, 2>::new());
+
+    let leaf_data = matches.value_of("leaf_data");
+    let nr_leaves = if let Some(path) = leaf_data {
+        let values: Vec<u64> = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+        Some(values.len())
+    } else {
+        matches.value_of("leaves").map(|n| n.parse::<usize>().unwrap())
+    };
+    let depth = if let Some(depth) = matches.value_of("depth") {
+        depth.parse::<usize>().unwrap()
+    } else if sparse {
+        KEY_BITS
+    } else {
+        nr_leaves.map_or(10, |n| n.next_power_of_two().trailing_zeros().max(1) as usize)
     };
+    // A sparse tree over 256-bit keys has more slots than fit in a usize, the others are built in full
+    let max_leaves = u32::try_from(depth).ok().and_then(|d| 1usize.checked_shl(d));
+    if depth == 0 || (sparse && depth > KEY_BITS) || (!sparse && max_leaves.is_none()) {
+        panic!("Invalid depth {}", depth)
+    }
+    let nr_leaves = nr_leaves.unwrap_or_else(|| {
+        if sparse {
+            DEFAULT_SPARSE_LEAVES
+        } else {
+            max_leaves.unwrap()
+        }
+    });
+    let leaf_index = matches.value_of("index").unwrap().parse::<usize>().unwrap();
+
+    if max_leaves.map_or(false, |max_leaves| nr_leaves > max_leaves) {
+        panic!("Invalid depth {} for {} leaves", depth, nr_leaves)
+    }
+    if leaf_index >= nr_leaves {
+        panic!("Invalid index {} for {} leaves", leaf_index, nr_leaves)
+    }
 
-    if cols < 25 {
-        panic!("Invalid cols")
+    let batch = matches.value_of("batch").unwrap_or("1").parse::<usize>().unwrap();
+    if batch == 0 || batch > nr_leaves {
+        panic!("Invalid batch size {} for {} leaves", batch, nr_leaves)
     }
+    // The batch is drawn with a fixed seed so that build and prove agree on the leaves. The
+    // multiproof circuit only verifies these indices; --dynamic-index proves any batch of this size.
+    let mut batch_indices = sample(&mut StdRng::seed_from_u64(0), nr_leaves, batch).into_vec();
+    batch_indices.sort();
 
     const D: usize = 2;
     type C = KeccakGoldilocksConfig;
     type F = <C as GenericConfig<D>>::F;
 
+    let circuit_name = if sparse && non_membership {
+        "SparseMerkleTreeNonMembership".to_string()
+    } else if sparse {
+        "SparseMerkleTree".to_string()
+    } else {
+        match (update, batch > 1, dynamic_index) {
+            (true, _, _) => "MerkleTreeUpdate".to_string(),
+            (false, true, true) => format!("MerkleTreeBatchDynamicIndex_{}", batch),
+            (false, true, false) => format!("MerkleTreeMultiproof_{}", batch),
+            (false, false, true) => "MerkleTreeDynamicIndex".to_string(),
+            (false, false, false) => "MerkleTree".to_string(),
+        }
+    };
+
     if build_prove == "build" {
-        let tree: MerkleTree = get_tree(1024);
-        let merkle_proof_leaf0 = tree.clone().get_merkle_proof(0);
-        println!("{:?}", merkle_proof_leaf0);
+        let mut results = BenchmarkResult::new("plonky2", &circuit_name, "Plonk+FRI", "NaN");
+        let tracker = PhaseTracker::start("build");
+        let config = circuit_config(cols, CircuitConfig::standard_recursion_config());
+        if sparse {
+            let (data, targets) = verify_smt_proof_circuit::<C>(config, depth, !non_membership);
+            write_circuit(&data, &targets);
+        } else if update {
+            let (data, targets) = verify_merkle_update_circuit::<C>(config, depth);
+            write_circuit(&data, &targets);
+        } else if batch > 1 && dynamic_index {
+            let (data, targets) =
+                verify_batch_merkle_proof_dynamic_circuit::<C>(config, batch, depth);
+            write_circuit(&data, &targets);
+        } else if batch > 1 {
+            let (data, targets) =
+                verify_merkle_multiproof_circuit::<C>(config, &batch_indices, depth);
+            write_circuit(&data, &targets);
+        } else if dynamic_index {
+            let (data, targets) =
+                verify_merkle_proof_dynamic_circuit::<C>(config, depth, public_index);
+            write_circuit(&data, &targets);
+        } else {
+            verify_merkle_proof_circuit(leaf_index, depth, cols);
+        }
+        results.record_phase(tracker.finish());
+        results.write(outp_json).unwrap();
+    } else if sparse {
+        let (tree, keys) = get_sparse_tree(leaf_data, nr_leaves, depth);
+        let key = if non_membership {
+            let mut rng = rand::thread_rng();
+            loop {
+                let key = random_key(&mut rng, depth);
+                if tree.get(&key).is_none() {
+                    break key;
+                }
+            }
+        } else {
+            keys[leaf_index]
+        };
+        println!("proving key {:?}", key);
 
-        let (circuit_data, targets) = verify_merkle_proof_circuit(0, 10);
-    } else {
-        let tree: MerkleTree = get_tree(1024);
+        let targets: SmtProofTargets = read_targets();
+        let circuit_data = read_circuit_data();
 
-        let merkle_proof_leaf0 = tree.clone().get_merkle_proof(0);
-        println!("{:?}", merkle_proof_leaf0);
-        let mut target_file = File::open("merkle_targets").unwrap();
-        let mut target_string = String::new();
+        // the key and the value are public inputs of their own targets
+        let mut pw = plonky2::iop::witness::PartialWitness::new();
+        set_smt_proof_targets(&mut pw, &targets, &key, tree.get(&key), &tree.get_proof(&key));
+
+        // public input: root of merkle tree
+        let expected_public_inputs = circuit_data.prover_only.public_inputs.clone();
+        for i in 0..4 {
+            pw.set_target(expected_public_inputs[i], tree.root.elements[i]);
+        }
+
+        let results =
+            BenchmarkResult::load_or_new(&outp_json, "plonky2", &circuit_name, "Plonk+FRI", "NaN");
+        prove_and_verify(circuit_data, pw, results, &outp_json);
+    } else if update {
+        let mut tree: MerkleTree = get_tree(leaf_data, nr_leaves, depth);
+        let new_leaf = if let Some(value) = matches.value_of("new_leaf") {
+            F::from_noncanonical_u64(value.parse::<u64>().unwrap())
+        } else {
+            F::from_noncanonical_u64(rand::thread_rng().gen())
+        };
+        // the same circuit proves an update of any leaf
+        let update_proof = tree.update_leaf(leaf_index, new_leaf);
+        println!("updating leaf {}", leaf_index);
+
+        let targets: MerkleUpdateTargets = read_targets();
+        let circuit_data = read_circuit_data();
+
+        let mut pw = plonky2::iop::witness::PartialWitness::new();
+        set_merkle_update_targets(&mut pw, &targets, &update_proof);
+
+        // public inputs: old and new root of merkle tree
+        let expected_public_inputs = circuit_data.prover_only.public_inputs.clone();
+        for i in 0..4 {
+            pw.set_target(expected_public_inputs[i], update_proof.old_root.elements[i]);
+            pw.set_target(expected_public_inputs[4 + i], update_proof.new_root.elements[i]);
+        }
+
+        let results =
+            BenchmarkResult::load_or_new(&outp_json, "plonky2", &circuit_name, "Plonk+FRI", "NaN");
+        prove_and_verify(circuit_data, pw, results, &outp_json);
+    } else if batch > 1 && dynamic_index {
+        let tree: MerkleTree = get_tree(leaf_data, nr_leaves, depth);
+        println!("proving leaves {:?}", batch_indices);
+
+        let targets: Vec<MerkleProofTargets> = read_targets();
+        let circuit_data = read_circuit_data();
 
-        target_file.read_to_string(&mut target_string).unwrap();
+        let mut pw = plonky2::iop::witness::PartialWitness::new();
+        for (proof_targets, &leaf_index) in targets.iter().zip(&batch_indices) {
+            set_merkle_proof_targets(
+                &mut pw,
+                proof_targets,
+                tree.tree[0][leaf_index],
+                leaf_index,
+                &tree.clone().get_merkle_proof(leaf_index),
+            );
+        }
+
+        // public input: root of merkle tree
+        let expected_public_inputs = circuit_data.prover_only.public_inputs.clone();
+        for i in 0..4 {
+            pw.set_target(expected_public_inputs[i], tree.root.elements[i]);
+        }
+
+        let results =
+            BenchmarkResult::load_or_new(&outp_json, "plonky2", &circuit_name, "Plonk+FRI", "NaN");
+        prove_and_verify(circuit_data, pw, results, &outp_json);
+    } else if batch > 1 {
+        let tree: MerkleTree = get_tree(leaf_data, nr_leaves, depth);
+        let batch_proof = tree.get_batch_proof(&batch_indices);
+        println!(
+            "proving leaves {:?} with {} hashes",
+            batch_indices,
+            batch_proof.hashes.len()
+        );
+
+        let targets: BatchMerkleProofTargets = read_targets();
+        let circuit_data = read_circuit_data();
+
+        let mut pw = plonky2::iop::witness::PartialWitness::new();
+        let leaves: Vec<_> = batch_indices.iter().map(|&i| tree.tree[0][i]).collect();
+        set_batch_merkle_proof_targets(&mut pw, &targets, &leaves, &batch_proof);
+
+        // public input: root of merkle tree
+        let expected_public_inputs = circuit_data.prover_only.public_inputs.clone();
+        for i in 0..4 {
+            pw.set_target(expected_public_inputs[i], tree.root.elements[i]);
+        }
 
-        let targets: Vec<HashOutTarget> = serde_json::from_str(&target_string).unwrap();
+        let results =
+            BenchmarkResult::load_or_new(&outp_json, "plonky2", &circuit_name, "Plonk+FRI", "NaN");
+        prove_and_verify(circuit_data, pw, results, &outp_json);
+    } else if dynamic_index {
+        let tree: MerkleTree = get_tree(leaf_data, nr_leaves, depth);
+        // any leaf can be proven with the same circuit
+        let merkle_proof = tree.clone().get_merkle_proof(leaf_index);
+        println!("proving leaf {}", leaf_index);
+
+        let targets: MerkleProofTargets = read_targets();
+        let circuit_data = read_circuit_data();
+
+        let mut pw = plonky2::iop::witness::PartialWitness::new();
+        set_merkle_proof_targets(
+            &mut pw,
+            &targets,
+            tree.tree[0][leaf_index],
+            leaf_index,
+            &merkle_proof,
+        );
 
-        let mut file = File::open("merkle_data").expect("File not found");
+        // public input: root of merkle tree
+        let expected_public_inputs = circuit_data.prover_only.public_inputs.clone();
+        for i in 0..4 {
+            pw.set_target(expected_public_inputs[i], tree.root.elements[i]);
+        }
 
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)
-            .expect("Failed to read the file");
+        let results =
+            BenchmarkResult::load_or_new(&outp_json, "plonky2", &circuit_name, "Plonk+FRI", "NaN");
+        prove_and_verify(circuit_data, pw, results, &outp_json);
+    } else {
+        let tree: MerkleTree = get_tree(leaf_data, nr_leaves, depth);
 
-        let gate_serializer = MerkleGateSerializer {};
-        let generator_serializer = MerkleGeneratorSerializer {};
-        let circuit_data: CircuitData<F, C, D> =
-            CircuitData::from_bytes(&buffer, &gate_serializer, &generator_serializer).unwrap();
+        let merkle_proof = tree.clone().get_merkle_proof(leaf_index);
+        println!("{:?}", merkle_proof);
+        let targets: Vec<HashOutTarget> = read_targets();
+        let circuit_data = read_circuit_data();
 
         let mut pw = plonky2::iop::witness::PartialWitness::new();
         // non-public inputs to witness: leaf and elements of merkle proof
-        pw.set_hash_target(targets[0], tree.tree[0][0]); // leaf index 0
+        pw.set_hash_target(targets[0], tree.tree[0][leaf_index]);
 
-        for i in 1..11 {
-          pw.set_hash_target(targets[i], merkle_proof_leaf0[i-1]);
+        for i in 1..depth + 1 {
+          pw.set_hash_target(targets[i], merkle_proof[i-1]);
         }
 
         // public input: root of merkle tree
@@ -334,48 +688,9 @@ fn main() {
             pw.set_target(expected_public_inputs[i], tree.root.elements[i]);
         }
 
-        println!("proving circuit");
-        let mut timing = TimingTree::new("prove", Level::Info);
-        let proof = prove::<F, C, D>(
-            &circuit_data.prover_only,
-            &circuit_data.common,
-            pw,
-            &mut timing,
-        )
-        .unwrap();
-        timing.pop();
-        timing.print();
-        let proof_duration = timing.duration();
-        println!("Proving time: {:?}", proof_duration);
-
-        let proof_bytes = proof.to_bytes();
-        let proof_len = proof_bytes.len();
-        println!("Proof size: {} bytes", proof_len);
-
-        println!("verifying circuit");
-        let mut timing = TimingTree::new("verify", Level::Info);
-        circuit_data.verify(proof).expect("verify error");
-        timing.pop();
-        timing.print();
-
-        let verify_duration = timing.duration();
-        println!("Verifying time: {:?}", verify_duration);
-
-        println!("writing results");
-        let results = json!({
-          "Framework": "plonky2",
-          "Circuit": "MerkleTree",
-          "Backend": "Plonk+FRI",
-          "Curve": "NaN",
-          "ProverTime": proof_duration.as_secs_f32(),
-          "VerifierTime": verify_duration.as_nanos() as f32 / 1000000.,
-          "ProofSize": proof_len
-        });
-
-        let json_string = serde_json::to_string(&results).unwrap();
-
-        let mut file = File::create(outp_json).unwrap();
-        let _ = file.write_all(json_string.as_bytes());
+        let results =
+            BenchmarkResult::load_or_new(&outp_json, "plonky2", &circuit_name, "Plonk+FRI", "NaN");
+        prove_and_verify(circuit_data, pw, results, &outp_json);
     }
 }
 
@@ -395,7 +710,7 @@ mod tests {
     use plonky2_merkle_trees::simple_merkle_tree::simple_merkle_tree::MerkleTree;
     use rand::Rng;
 
-    use crate::verify_merkle_proof_circuit;
+    use crate::{verify_merkle_proof_circuit, DEFAULT_COLS};
     const GOLDILOCKS_FIELD_ORDER: u64 = 18446744069414584321;
     const D: usize = 2;
     type C = PoseidonGoldilocksConfig;
@@ -438,7 +753,7 @@ mod tests {
         // H1 = Hash(H0||N1)
         // CHECK Root = H1 equals R ?
 
-        let (circuit_data, targets) = verify_merkle_proof_circuit(0, 2);
+        let (circuit_data, targets) = verify_merkle_proof_circuit(0, 2, DEFAULT_COLS);
 
         /* The witness needs the following inputs:
           - leaf_to_prove
@@ -491,7 +806,7 @@ mod tests {
         // H1 = Hash(N0||H0)
         // CHECK Root = H1 equals R ?
 
-        let (circuit_data, targets) = verify_merkle_proof_circuit(3, 2);
+        let (circuit_data, targets) = verify_merkle_proof_circuit(3, 2, DEFAULT_COLS);
 
         /* The witness needs the following inputs:
           - leaf_to_prove
@@ -529,7 +844,7 @@ mod tests {
         let merkle_proof_leaf0 = tree.clone().get_merkle_proof(0);
         println!("{:?}", merkle_proof_leaf0);
 
-        let (circuit_data, targets) = verify_merkle_proof_circuit(0, 4);
+        let (circuit_data, targets) = verify_merkle_proof_circuit(0, 4, DEFAULT_COLS);
 
         /* The witness needs the following inputs:
           - leaf_to_prove
@@ -568,7 +883,7 @@ mod tests {
         let merkle_proof_leaf7 = tree.clone().get_merkle_proof(7);
         println!("{:?}", merkle_proof_leaf7);
 
-        let (circuit_data, targets) = verify_merkle_proof_circuit(7, 4);
+        let (circuit_data, targets) = verify_merkle_proof_circuit(7, 4, DEFAULT_COLS);
 
         /* The witness needs the following inputs:
           - leaf_to_prove
@@ -1,2 +1,4 @@
 pub mod simple_merkle_tree;
-pub mod mmr;
\ No newline at end of file
+pub mod sparse_merkle_tree;
+pub mod mmr;
+pub mod proving;
@@ -0,0 +1,125 @@
+use std::{fs::File, io::{Read, Write}};
+
+use log::Level;
+use plonky2::{field::goldilocks_field::GoldilocksField, gates::gate::Gate, iop::{generator::generate_partial_witness, witness::PartialWitness}, plonk::{circuit_data::{CircuitConfig, CircuitData}, config::KeccakGoldilocksConfig, prover::prove_with_partition_witness}, util::{serialization::{GateSerializer, WitnessGeneratorSerializer}, timing::TimingTree}};
+use serde::{de::DeserializeOwned, Serialize};
+use zkperf_report::{BenchmarkResult, PhaseTracker};
+
+// The build and prove steps of the Merkle benchmarks (the Poseidon and the Keccak `standard`
+// binaries) run as separate processes and share the circuit through these files
+const CIRCUIT_DATA_FILE: &str = "merkle_data";
+const TARGETS_FILE: &str = "merkle_targets";
+
+const D: usize = 2;
+type C = KeccakGoldilocksConfig;
+type F = GoldilocksField;
+
+// `cols` sets the number of wires, all of them routed, as in the column sweep of the other plonky2
+// benchmarks
+pub fn circuit_config(cols: usize, standard: CircuitConfig) -> CircuitConfig {
+  CircuitConfig {
+    num_wires: cols,
+    num_routed_wires: cols,
+    ..standard
+  }
+}
+
+// The widest gate of the circuit must fit in the `cols` wires. This is the gate of the hash that
+// commits to the public inputs, unless the circuit uses a wider one.
+pub fn check_cols(cols: usize, widest_gate: &impl Gate<F, D>) {
+  let min_cols = widest_gate.num_wires();
+  if cols < min_cols {
+    panic!("Invalid cols {}, the circuit needs at least {}", cols, min_cols)
+  }
+}
+
+// Writes the circuit and its targets for the prove step
+pub fn write_circuit(
+  data: &CircuitData<F, C, D>,
+  targets: &impl Serialize,
+  gate_serializer: &impl GateSerializer<F, D>,
+  generator_serializer: &impl WitnessGeneratorSerializer<F, D>,
+) {
+  let json_string = serde_json::to_string(targets).unwrap();
+  let mut file = File::create(TARGETS_FILE).unwrap();
+  file.write_all(json_string.as_bytes()).unwrap();
+
+  let mut file = File::create(CIRCUIT_DATA_FILE).unwrap();
+  file.write_all(&data.to_bytes(gate_serializer, generator_serializer).unwrap()).unwrap();
+}
+
+pub fn read_targets<T: DeserializeOwned>() -> T {
+  serde_json::from_reader(File::open(TARGETS_FILE).unwrap()).unwrap()
+}
+
+pub fn read_circuit_data(
+  gate_serializer: &impl GateSerializer<F, D>,
+  generator_serializer: &impl WitnessGeneratorSerializer<F, D>,
+) -> CircuitData<F, C, D> {
+  let mut file = File::open(CIRCUIT_DATA_FILE).expect("File not found");
+
+  let mut buffer = Vec::new();
+  file.read_to_end(&mut buffer).expect("Failed to read the file");
+
+  CircuitData::from_bytes(&buffer, gate_serializer, generator_serializer).unwrap()
+}
+
+// Proves and verifies with a timing tree and a memory phase per step, and writes the results.
+// The prover time is witness generation plus proving.
+pub fn prove_and_verify(
+  circuit_data: CircuitData<F, C, D>,
+  pw: PartialWitness<F>,
+  mut results: BenchmarkResult,
+  outp_json: &str,
+) {
+  println!("generating witness");
+  let tracker = PhaseTracker::start("witness");
+  let mut timing = TimingTree::new("witness", Level::Info);
+  let partition_witness =
+    generate_partial_witness(pw, &circuit_data.prover_only, &circuit_data.common);
+  timing.pop();
+  results.record_phase(tracker.finish());
+  results.record_timing("witness", &timing).unwrap();
+  let witness_duration = timing.duration();
+
+  println!("proving circuit");
+  let tracker = PhaseTracker::start("prove");
+  let mut timing = TimingTree::new("prove", Level::Info);
+  let proof = prove_with_partition_witness::<F, C, D>(
+    &circuit_data.prover_only,
+    &circuit_data.common,
+    partition_witness,
+    &mut timing,
+  )
+  .unwrap();
+  timing.pop();
+  results.record_phase(tracker.finish());
+  timing.print();
+  results.record_timing("prove", &timing).unwrap();
+  let proof_duration = timing.duration();
+  println!("Proving time: {:?}", proof_duration);
+
+  let proof_bytes = proof.to_bytes();
+  let proof_len = proof_bytes.len();
+  println!("Proof size: {} bytes", proof_len);
+
+  println!("verifying circuit");
+  let tracker = PhaseTracker::start("verify");
+  let mut timing = TimingTree::new("verify", Level::Info);
+  circuit_data.verify(proof).expect("verify error");
+  timing.pop();
+  results.record_phase(tracker.finish());
+  timing.print();
+  results.record_timing("verify", &timing).unwrap();
+
+  let verify_duration = timing.duration();
+  println!("Verifying time: {:?}", verify_duration);
+
+  println!("writing results");
+  results.set_shape(circuit_data.common.degree(), circuit_data.common.config.num_wires);
+  results.set_prover_time(witness_duration + proof_duration);
+  results.set_verifier_time(verify_duration);
+  results.set_proof_size(proof_len);
+  results.record_peak_rss();
+  results.write(outp_json).unwrap();
+}
@@ -0,0 +1,416 @@
+use plonky2::{hash::{poseidon::PoseidonHash, hash_types::{HashOut, HashOutTarget}}, plonk::{config::GenericConfig, circuit_data::{CircuitData, CircuitConfig}, circuit_builder::CircuitBuilder}, iop::{target::{BoolTarget, Target}, witness::{PartialWitness, WitnessWrite}}};
+use plonky2_field::{goldilocks_field::GoldilocksField, types::Field};
+use serde::{Deserialize, Serialize};
+
+use crate::mmr::common::pick_hash;
+use crate::simple_merkle_tree::simple_merkle_tree::{BatchMerkleProof, MerkleUpdateProof};
+
+// Targets of a Merkle proof whose leaf position is part of the witness
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MerkleProofTargets {
+  // The hashed leaf, as stored in level 0 of `MerkleTree::tree`
+  pub leaf: HashOutTarget,
+  // Position of the leaf; its bits pick the hashing order per level
+  pub leaf_index: Target,
+  // One sibling per level, starting at the lowest level (as returned by `get_merkle_proof`)
+  pub siblings: Vec<HashOutTarget>,
+}
+
+// Hashes [leaf] with the siblings up to the root. Bit i of the index is 1 if the node at level i is a right child,
+// in which case the sibling and the current hash are swapped before hashing.
+pub fn compute_root_target(
+  builder: &mut CircuitBuilder<GoldilocksField, 2>,
+  leaf: HashOutTarget,
+  index_bits: &[BoolTarget],
+  siblings: &[HashOutTarget]
+) -> HashOutTarget {
+  let mut next_hash = leaf;
+  for (is_right, sibling) in index_bits.iter().zip(siblings) {
+    // Conditional swap: (left, right) = is_right ? (sibling, next_hash) : (next_hash, sibling)
+    let left = pick_hash(builder, *sibling, next_hash, *is_right);
+    let right = pick_hash(builder, next_hash, *sibling, *is_right);
+    next_hash = builder.hash_or_noop::<PoseidonHash>([
+      left.elements.to_vec(),
+      right.elements.to_vec()
+    ].concat());
+  }
+  next_hash
+}
+
+// Adds the hashing of a Merkle proof with a witness leaf index to the builder and returns the targets
+// together with the computed root.
+// The decomposition into [nr_layers] bits also enforces leaf_index < 2^nr_layers.
+pub fn add_merkle_proof_target(
+  builder: &mut CircuitBuilder<GoldilocksField, 2>,
+  nr_layers: usize
+) -> (MerkleProofTargets, HashOutTarget) {
+  let leaf = builder.add_virtual_hash();
+  let leaf_index = builder.add_virtual_target();
+  let index_bits: Vec<BoolTarget> = builder.split_le(leaf_index, nr_layers);
+  let siblings: Vec<HashOutTarget> = (0..nr_layers).map(|_| builder.add_virtual_hash()).collect();
+
+  let root = compute_root_target(builder, leaf, &index_bits, &siblings);
+  (MerkleProofTargets { leaf, leaf_index, siblings }, root)
+}
+
+// Returns a circuit with the given config that verifies a Merkle proof of [nr_layers] siblings for any leaf of the tree.
+// Public inputs: the root (4 elements), followed by the leaf index if [public_index] is set.
+pub fn verify_merkle_proof_dynamic_circuit<C: GenericConfig<2, F = GoldilocksField>>(
+  config: CircuitConfig,
+  nr_layers: usize,
+  public_index: bool
+) -> (CircuitData<GoldilocksField, C, 2>, MerkleProofTargets) {
+  const D: usize = 2;
+  type F = GoldilocksField;
+
+  let mut builder = CircuitBuilder::<F, D>::new(config);
+  let (targets, root) = add_merkle_proof_target(&mut builder, nr_layers);
+
+  // This is the expected root value
+  builder.register_public_inputs(&root.elements);
+  if public_index {
+    builder.register_public_input(targets.leaf_index);
+  }
+
+  let data = builder.build::<C>();
+  (data, targets)
+}
+
+// Sets the leaf, its index and the Merkle proof (as returned by `get_merkle_proof`) in the witness
+pub fn set_merkle_proof_targets(
+  pw: &mut PartialWitness<GoldilocksField>,
+  targets: &MerkleProofTargets,
+  leaf: HashOut<GoldilocksField>,
+  leaf_index: usize,
+  proof: &[HashOut<GoldilocksField>]
+) {
+  assert_eq!(proof.len(), targets.siblings.len());
+  pw.set_hash_target(targets.leaf, leaf);
+  pw.set_target(targets.leaf_index, GoldilocksField::from_canonical_usize(leaf_index));
+  for (sibling, hash) in targets.siblings.iter().zip(proof) {
+    pw.set_hash_target(*sibling, *hash);
+  }
+}
+
+// Returns a circuit that verifies [nr_proofs] Merkle proofs with witness leaf indices against the same root.
+// Public inputs: the root (4 elements).
+pub fn verify_batch_merkle_proof_dynamic_circuit<C: GenericConfig<2, F = GoldilocksField>>(
+  config: CircuitConfig,
+  nr_proofs: usize,
+  nr_layers: usize
+) -> (CircuitData<GoldilocksField, C, 2>, Vec<MerkleProofTargets>) {
+  assert!(nr_proofs > 0);
+  let mut builder = CircuitBuilder::<GoldilocksField, 2>::new(config);
+
+  let mut targets = Vec::new();
+  let (first_targets, root) = add_merkle_proof_target(&mut builder, nr_layers);
+  targets.push(first_targets);
+  for _ in 1..nr_proofs {
+    let (proof_targets, proof_root) = add_merkle_proof_target(&mut builder, nr_layers);
+    builder.connect_hashes(root, proof_root);
+    targets.push(proof_targets);
+  }
+
+  // This is the expected root value
+  builder.register_public_inputs(&root.elements);
+
+  let data = builder.build::<C>();
+  (data, targets)
+}
+
+// Targets of a multiproof, in the order of `BatchMerkleProof`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchMerkleProofTargets {
+  // The hashed leaves, ordered by their index
+  pub leaves: Vec<HashOutTarget>,
+  pub hashes: Vec<HashOutTarget>,
+}
+
+// Returns a circuit that verifies a multiproof (see `MerkleTree::get_batch_proof`) for a fixed set of leaf indices.
+// Every node on the paths of the leaves is hashed once, so the number of hashes grows with the union of
+// the paths instead of with [leaf_indices].len() * [nr_layers]. Since the shared nodes depend on the
+// indices, the circuit only proves this set of leaves; `verify_batch_merkle_proof_dynamic_circuit`
+// takes the indices as witnesses, with one full path per leaf.
+// Public inputs: the root (4 elements).
+pub fn verify_merkle_multiproof_circuit<C: GenericConfig<2, F = GoldilocksField>>(
+  config: CircuitConfig,
+  leaf_indices: &[usize],
+  nr_layers: usize
+) -> (CircuitData<GoldilocksField, C, 2>, BatchMerkleProofTargets) {
+  let mut indices = leaf_indices.to_vec();
+  indices.sort();
+  indices.dedup();
+  assert!(!indices.is_empty());
+  assert!(*indices.last().unwrap() < 1 << nr_layers);
+
+  let mut builder = CircuitBuilder::<GoldilocksField, 2>::new(config);
+  let leaves: Vec<HashOutTarget> = indices.iter().map(|_| builder.add_virtual_hash()).collect();
+  let mut hashes: Vec<HashOutTarget> = Vec::new();
+
+  // Same walk as `verify_batch_proof`, but the structure is fixed by the indices at build time
+  let mut nodes: Vec<(usize, HashOutTarget)> = indices.into_iter().zip(leaves.clone()).collect();
+  for _ in 0..nr_layers {
+    let mut next_nodes = Vec::new();
+    let mut j = 0;
+    while j < nodes.len() {
+      let (index, hash) = nodes[j];
+      let (left, right) = if j + 1 < nodes.len() && nodes[j + 1].0 == (index ^ 1) {
+        j += 2;
+        (hash, nodes[j - 1].1)
+      } else {
+        let sibling = builder.add_virtual_hash();
+        hashes.push(sibling);
+        j += 1;
+        if index % 2 == 0 {
+          (hash, sibling)
+        } else {
+          (sibling, hash)
+        }
+      };
+      let next_hash = builder.hash_or_noop::<PoseidonHash>([
+        left.elements.to_vec(),
+        right.elements.to_vec()
+      ].concat());
+      next_nodes.push((index / 2, next_hash));
+    }
+    nodes = next_nodes;
+  }
+
+  // This is the expected root value
+  builder.register_public_inputs(&nodes[0].1.elements);
+
+  let data = builder.build::<C>();
+  (data, BatchMerkleProofTargets { leaves, hashes })
+}
+
+// Sets the hashed leaves (ordered by their index in the proof) and the multiproof hashes in the witness
+pub fn set_batch_merkle_proof_targets(
+  pw: &mut PartialWitness<GoldilocksField>,
+  targets: &BatchMerkleProofTargets,
+  leaves: &[HashOut<GoldilocksField>],
+  proof: &BatchMerkleProof
+) {
+  assert_eq!(leaves.len(), targets.leaves.len());
+  assert_eq!(proof.hashes.len(), targets.hashes.len());
+  for (target, leaf) in targets.leaves.iter().zip(leaves) {
+    pw.set_hash_target(*target, *leaf);
+  }
+  for (target, hash) in targets.hashes.iter().zip(&proof.hashes) {
+    pw.set_hash_target(*target, *hash);
+  }
+}
+
+// Targets of a leaf update, see `MerkleTree::update_leaf`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MerkleUpdateTargets {
+  pub old_leaf: HashOutTarget,
+  pub new_leaf: HashOutTarget,
+  pub leaf_index: Target,
+  // Shared by the old and the new path, starting at the lowest level
+  pub siblings: Vec<HashOutTarget>,
+}
+
+// Adds the state transition of replacing one leaf to the builder and returns the targets together with the
+// old and the new root. Both paths are hashed with the same index bits and siblings, so every other leaf
+// is left unchanged.
+pub fn add_merkle_update_target(
+  builder: &mut CircuitBuilder<GoldilocksField, 2>,
+  nr_layers: usize
+) -> (MerkleUpdateTargets, HashOutTarget, HashOutTarget) {
+  let old_leaf = builder.add_virtual_hash();
+  let new_leaf = builder.add_virtual_hash();
+  let leaf_index = builder.add_virtual_target();
+  let index_bits: Vec<BoolTarget> = builder.split_le(leaf_index, nr_layers);
+  let siblings: Vec<HashOutTarget> = (0..nr_layers).map(|_| builder.add_virtual_hash()).collect();
+
+  let old_root = compute_root_target(builder, old_leaf, &index_bits, &siblings);
+  let new_root = compute_root_target(builder, new_leaf, &index_bits, &siblings);
+  (MerkleUpdateTargets { old_leaf, new_leaf, leaf_index, siblings }, old_root, new_root)
+}
+
+// Returns a circuit that proves that replacing a leaf of a tree with [nr_layers] levels turns the old root into the new root.
+// Public inputs: the old root (4 elements), followed by the new root (4 elements).
+pub fn verify_merkle_update_circuit<C: GenericConfig<2, F = GoldilocksField>>(
+  config: CircuitConfig,
+  nr_layers: usize
+) -> (CircuitData<GoldilocksField, C, 2>, MerkleUpdateTargets) {
+  let mut builder = CircuitBuilder::<GoldilocksField, 2>::new(config);
+  let (targets, old_root, new_root) = add_merkle_update_target(&mut builder, nr_layers);
+
+  builder.register_public_inputs(&old_root.elements);
+  builder.register_public_inputs(&new_root.elements);
+
+  let data = builder.build::<C>();
+  (data, targets)
+}
+
+// Sets the leaves, the index and the siblings of an update proof (as returned by `update_leaf`) in the witness
+pub fn set_merkle_update_targets(
+  pw: &mut PartialWitness<GoldilocksField>,
+  targets: &MerkleUpdateTargets,
+  proof: &MerkleUpdateProof
+) {
+  assert_eq!(proof.siblings.len(), targets.siblings.len());
+  pw.set_hash_target(targets.old_leaf, proof.old_leaf);
+  pw.set_hash_target(targets.new_leaf, proof.new_leaf);
+  pw.set_target(targets.leaf_index, GoldilocksField::from_canonical_usize(proof.leaf_index));
+  for (sibling, hash) in targets.siblings.iter().zip(&proof.siblings) {
+    pw.set_hash_target(*sibling, *hash);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anyhow::Result;
+  use plonky2::{iop::witness::{PartialWitness, WitnessWrite}, plonk::{config::PoseidonGoldilocksConfig, circuit_data::CircuitConfig}};
+  use plonky2_field::{goldilocks_field::GoldilocksField, types::Field};
+  use rand::Rng;
+
+  use crate::mmr::common::GOLDILOCKS_FIELD_ORDER;
+  use crate::simple_merkle_tree::{simple_merkle_tree::MerkleTree, merkle_plonky2_verifier::{verify_merkle_proof_dynamic_circuit, set_merkle_proof_targets, verify_batch_merkle_proof_dynamic_circuit, verify_merkle_multiproof_circuit, set_batch_merkle_proof_targets, verify_merkle_update_circuit, set_merkle_update_targets}};
+
+  fn get_test_tree(nr_leaves: usize) -> MerkleTree {
+    let mut rng = rand::thread_rng();
+    let leaves = (0..nr_leaves)
+      .map(|_| GoldilocksField::from_canonical_u64(rng.gen_range(0..GOLDILOCKS_FIELD_ORDER)))
+      .collect();
+    MerkleTree::build(leaves)
+  }
+
+  #[test]
+  fn test_one_circuit_for_every_leaf() -> Result<()> {
+    let tree = get_test_tree(16);
+    let (circuit_data, targets) = verify_merkle_proof_dynamic_circuit::<PoseidonGoldilocksConfig>(CircuitConfig::standard_recursion_config(), 4, false);
+
+    for leaf_index in 0..16 {
+      let mut pw = PartialWitness::new();
+      set_merkle_proof_targets(&mut pw, &targets, tree.tree[0][leaf_index], leaf_index, &tree.clone().get_merkle_proof(leaf_index));
+      let public_inputs = circuit_data.prover_only.public_inputs.clone();
+      for i in 0..4 {
+        pw.set_target(public_inputs[i], tree.root.elements[i]);
+      }
+      let proof = circuit_data.prove(pw)?;
+      circuit_data.verify(proof)?;
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_public_index() -> Result<()> {
+    let tree = get_test_tree(8);
+    let (circuit_data, targets) = verify_merkle_proof_dynamic_circuit::<PoseidonGoldilocksConfig>(CircuitConfig::standard_recursion_config(), 3, true);
+
+    let mut pw = PartialWitness::new();
+    set_merkle_proof_targets(&mut pw, &targets, tree.tree[0][5], 5, &tree.clone().get_merkle_proof(5));
+    let public_inputs = circuit_data.prover_only.public_inputs.clone();
+    for i in 0..4 {
+      pw.set_target(public_inputs[i], tree.root.elements[i]);
+    }
+    let proof = circuit_data.prove(pw)?;
+    assert_eq!(proof.public_inputs[4], GoldilocksField::from_canonical_u64(5));
+    circuit_data.verify(proof)
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_wrong_index_fails() {
+    let tree = get_test_tree(8);
+    let (circuit_data, targets) = verify_merkle_proof_dynamic_circuit::<PoseidonGoldilocksConfig>(CircuitConfig::standard_recursion_config(), 3, false);
+
+    // The proof of leaf 2 does not lead to the root when hashed in the order of leaf 3
+    let mut pw = PartialWitness::new();
+    set_merkle_proof_targets(&mut pw, &targets, tree.tree[0][2], 3, &tree.clone().get_merkle_proof(2));
+    let public_inputs = circuit_data.prover_only.public_inputs.clone();
+    for i in 0..4 {
+      pw.set_target(public_inputs[i], tree.root.elements[i]);
+    }
+    circuit_data.prove(pw).unwrap();
+  }
+
+  #[test]
+  fn test_batch_dynamic_index() -> Result<()> {
+    let tree = get_test_tree(16);
+    let (circuit_data, targets) = verify_batch_merkle_proof_dynamic_circuit::<PoseidonGoldilocksConfig>(CircuitConfig::standard_recursion_config(), 3, 4);
+
+    let mut pw = PartialWitness::new();
+    for (proof_targets, leaf_index) in targets.iter().zip([1, 6, 13]) {
+      set_merkle_proof_targets(&mut pw, proof_targets, tree.tree[0][leaf_index], leaf_index, &tree.clone().get_merkle_proof(leaf_index));
+    }
+    let public_inputs = circuit_data.prover_only.public_inputs.clone();
+    for i in 0..4 {
+      pw.set_target(public_inputs[i], tree.root.elements[i]);
+    }
+    let proof = circuit_data.prove(pw)?;
+    circuit_data.verify(proof)
+  }
+
+  #[test]
+  fn test_multiproof() -> Result<()> {
+    let tree = get_test_tree(16);
+    let batch_proof = tree.get_batch_proof(&[2, 3, 9, 14]);
+    let (circuit_data, targets) = verify_merkle_multiproof_circuit::<PoseidonGoldilocksConfig>(CircuitConfig::standard_recursion_config(), &batch_proof.leaf_indices, 4);
+
+    let mut pw = PartialWitness::new();
+    let leaves: Vec<_> = batch_proof.leaf_indices.iter().map(|i| tree.tree[0][*i]).collect();
+    set_batch_merkle_proof_targets(&mut pw, &targets, &leaves, &batch_proof);
+    let public_inputs = circuit_data.prover_only.public_inputs.clone();
+    for i in 0..4 {
+      pw.set_target(public_inputs[i], tree.root.elements[i]);
+    }
+    let proof = circuit_data.prove(pw)?;
+    circuit_data.verify(proof)
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_multiproof_wrong_leaf_fails() {
+    let tree = get_test_tree(16);
+    let batch_proof = tree.get_batch_proof(&[0, 5]);
+    let (circuit_data, targets) = verify_merkle_multiproof_circuit::<PoseidonGoldilocksConfig>(CircuitConfig::standard_recursion_config(), &batch_proof.leaf_indices, 4);
+
+    let mut pw = PartialWitness::new();
+    set_batch_merkle_proof_targets(&mut pw, &targets, &[tree.tree[0][0], tree.tree[0][4]], &batch_proof);
+    let public_inputs = circuit_data.prover_only.public_inputs.clone();
+    for i in 0..4 {
+      pw.set_target(public_inputs[i], tree.root.elements[i]);
+    }
+    circuit_data.prove(pw).unwrap();
+  }
+
+  #[test]
+  fn test_update() -> Result<()> {
+    let mut tree = get_test_tree(16);
+    let (circuit_data, targets) = verify_merkle_update_circuit::<PoseidonGoldilocksConfig>(CircuitConfig::standard_recursion_config(), 4);
+    let update_proof = tree.update_leaf(11, GoldilocksField::from_canonical_u64(42));
+
+    let mut pw = PartialWitness::new();
+    set_merkle_update_targets(&mut pw, &targets, &update_proof);
+    let public_inputs = circuit_data.prover_only.public_inputs.clone();
+    for i in 0..4 {
+      pw.set_target(public_inputs[i], update_proof.old_root.elements[i]);
+      pw.set_target(public_inputs[4 + i], update_proof.new_root.elements[i]);
+    }
+    let proof = circuit_data.prove(pw)?;
+    assert_eq!(&proof.public_inputs[4..], &tree.root.elements);
+    circuit_data.verify(proof)
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_update_of_other_leaf_fails() {
+    let mut tree = get_test_tree(16);
+    let (circuit_data, targets) = verify_merkle_update_circuit::<PoseidonGoldilocksConfig>(CircuitConfig::standard_recursion_config(), 4);
+    let mut update_proof = tree.update_leaf(11, GoldilocksField::from_canonical_u64(42));
+    // The siblings of leaf 11 don't lead to the new root from leaf 10
+    update_proof.leaf_index = 10;
+
+    let mut pw = PartialWitness::new();
+    set_merkle_update_targets(&mut pw, &targets, &update_proof);
+    let public_inputs = circuit_data.prover_only.public_inputs.clone();
+    for i in 0..4 {
+      pw.set_target(public_inputs[i], update_proof.old_root.elements[i]);
+      pw.set_target(public_inputs[4 + i], update_proof.new_root.elements[i]);
+    }
+    circuit_data.prove(pw).unwrap();
+  }
+}
@@ -1 +1,2 @@
-pub mod simple_merkle_tree;
\ No newline at end of file
+pub mod simple_merkle_tree;
+pub mod merkle_plonky2_verifier;
\ No newline at end of file
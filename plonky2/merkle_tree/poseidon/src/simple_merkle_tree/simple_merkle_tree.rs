@@ -8,6 +8,27 @@ use plonky2::hash::poseidon::PoseidonHash;
 use plonky2::plonk::config::Hasher;
 use plonky2_util::log2_strict;
 
+// Proof that several leaves are part of the tree (a multiproof). Nodes that can be computed from the proven
+// leaves are left out, so the upper part of the paths that the leaves have in common is only included once.
+#[derive(Debug, Clone)]
+pub struct BatchMerkleProof {
+  pub count_levels: usize,
+  pub leaf_indices: Vec<usize>, // sorted and without duplicates
+  pub hashes: Vec<HashOut<GoldilocksField>>, // missing siblings, level by level starting at the lowest level, left to right
+}
+
+// Proof that replacing one leaf turns the old root into the new root. The leaves are hashed, as in level 0
+// of the tree. Only the path of the leaf changes, so the old and the new path share the siblings.
+#[derive(Debug, Clone)]
+pub struct MerkleUpdateProof {
+  pub leaf_index: usize,
+  pub old_leaf: HashOut<GoldilocksField>,
+  pub new_leaf: HashOut<GoldilocksField>,
+  pub siblings: Vec<HashOut<GoldilocksField>>, // starts at the lowest level, as in `get_merkle_proof`
+  pub old_root: HashOut<GoldilocksField>,
+  pub new_root: HashOut<GoldilocksField>,
+}
+
 #[derive(Debug, Clone)]
 pub struct MerkleTree {
     pub count_levels: usize,
@@ -73,6 +94,41 @@ impl MerkleTree {
     proof_hashes
   }
 
+  // Returns the multiproof for the given leaves. Per level, a sibling is only added if it can't be computed
+  // from the proven leaves, i.e. if it is not itself on the path of one of them.
+  pub fn get_batch_proof(&self, leaf_indices: &[usize]) -> BatchMerkleProof {
+    let mut indices = leaf_indices.to_vec();
+    indices.sort();
+    indices.dedup();
+    assert!(!indices.is_empty());
+    assert!(*indices.last().unwrap() < self.tree[0].len());
+
+    let mut hashes = Vec::new();
+    let mut known = indices.clone();
+    for i in 0..self.count_levels {
+      let mut next_known = Vec::new();
+      let mut j = 0;
+      while j < known.len() {
+        let index = known[j];
+        if j + 1 < known.len() && known[j + 1] == (index ^ 1) {
+          // Both children are known
+          j += 2;
+        } else {
+          hashes.push(self.tree[i][index ^ 1]);
+          j += 1;
+        }
+        next_known.push(index / 2);
+      }
+      known = next_known;
+    }
+
+    BatchMerkleProof {
+      count_levels: self.count_levels,
+      leaf_indices: indices,
+      hashes
+    }
+  }
+
   pub fn get_in_between_hashes(self, leaf_index: usize) -> Vec<HashOut<GoldilocksField>>{
     assert!(leaf_index < self.tree[0].len());
     let mut index = leaf_index / 2;
@@ -84,6 +140,53 @@ impl MerkleTree {
     hashes.push(self.root);
     hashes
   }
+
+  // Replaces the leaf at [leaf_index] by [new_leaf], rehashing its path up to the root, and returns the proof of the update
+  pub fn update_leaf(&mut self, leaf_index: usize, new_leaf: GoldilocksField) -> MerkleUpdateProof {
+    assert!(leaf_index < self.tree[0].len());
+    let old_leaf = self.tree[0][leaf_index];
+    let old_root = self.root;
+
+    let mut siblings = Vec::new();
+    let mut next_hash: HashOut<GoldilocksField> = PoseidonHash::hash_or_noop(&[new_leaf]);
+    let mut updated_index = leaf_index;
+    for i in 0..self.count_levels {
+      self.tree[i][updated_index] = next_hash;
+      let sibling = self.tree[i][updated_index ^ 1];
+      siblings.push(sibling);
+      if updated_index.is_even() {
+        next_hash = PoseidonHash::two_to_one(next_hash, sibling);
+      } else {
+        next_hash = PoseidonHash::two_to_one(sibling, next_hash);
+      }
+      updated_index = updated_index/2;
+    }
+    self.root = next_hash;
+
+    MerkleUpdateProof {
+      leaf_index,
+      old_leaf,
+      new_leaf: self.tree[0][leaf_index],
+      siblings,
+      old_root,
+      new_root: self.root
+    }
+  }
+}
+
+// Hashes a hashed leaf with the given siblings up to the root
+fn compute_root(leaf: HashOut<GoldilocksField>, leaf_index: usize, siblings: &[HashOut<GoldilocksField>]) -> HashOut<GoldilocksField> {
+  let mut next_hash = leaf;
+  let mut updated_index = leaf_index;
+  for sibling in siblings {
+    if updated_index.is_even() {
+      next_hash = PoseidonHash::two_to_one(next_hash, *sibling);
+    } else {
+      next_hash = PoseidonHash::two_to_one(*sibling, next_hash);
+    }
+    updated_index = updated_index/2;
+  }
+  next_hash
 }
 
 
@@ -108,11 +211,58 @@ pub fn verify_merkle_proof(leaf: GoldilocksField, leaf_index: usize, root: HashO
   next_hash == root
 }
 
+// Returns true if the old leaf leads to the old root and the new leaf to the new root, using the same siblings
+pub fn verify_merkle_update_proof(proof: &MerkleUpdateProof) -> bool {
+  compute_root(proof.old_leaf, proof.leaf_index, &proof.siblings) == proof.old_root
+    && compute_root(proof.new_leaf, proof.leaf_index, &proof.siblings) == proof.new_root
+}
+
+// Returns true if the given leaves (ordered by their index in the proof) and the multiproof lead to the root
+pub fn verify_batch_proof(leaves: &[GoldilocksField], root: HashOut<GoldilocksField>, proof: &BatchMerkleProof) -> bool {
+  if leaves.len() != proof.leaf_indices.len() {
+    return false;
+  }
+
+  let mut nodes: Vec<(usize, HashOut<GoldilocksField>)> = proof.leaf_indices.iter()
+    .zip(leaves)
+    .map(|(index, leaf)| (*index, PoseidonHash::hash_or_noop(&[*leaf])))
+    .collect();
+  let mut hashes = proof.hashes.iter();
+
+  for _ in 0..proof.count_levels {
+    let mut next_nodes = Vec::new();
+    let mut j = 0;
+    while j < nodes.len() {
+      let (index, hash) = nodes[j];
+      let next_hash = if j + 1 < nodes.len() && nodes[j + 1].0 == (index ^ 1) {
+        j += 2;
+        PoseidonHash::two_to_one(hash, nodes[j - 1].1)
+      } else {
+        let sibling = match hashes.next() {
+          Some(sibling) => *sibling,
+          None => return false,
+        };
+        j += 1;
+        if index.is_even() {
+          PoseidonHash::two_to_one(hash, sibling)
+        } else {
+          PoseidonHash::two_to_one(sibling, hash)
+        }
+      };
+      next_nodes.push((index / 2, next_hash));
+    }
+    nodes = next_nodes;
+  }
+
+  // All hashes are used and everything was hashed up into the root
+  hashes.next().is_none() && nodes == [(0, root)]
+}
+
 #[cfg(test)]
 mod tests {
   use anyhow::Result;
   use plonky2::{plonk::config::{GenericConfig, PoseidonGoldilocksConfig}, hash::hash_types::HashOut, field::{goldilocks_field::GoldilocksField, types::Field}};
-  use crate::simple_merkle_tree::simple_merkle_tree::{MerkleTree, verify_merkle_proof};
+  use crate::simple_merkle_tree::simple_merkle_tree::{MerkleTree, verify_merkle_proof, verify_batch_proof, verify_merkle_update_proof};
 
   #[test]
   fn test_build_merkle_tree_4_leaves() -> Result<()> {
@@ -308,4 +458,67 @@ mod tests {
     Ok(())
   }
 
+  #[test]
+  fn test_verify_batch_proof() -> Result<()> {
+    let leaves: Vec<GoldilocksField> = (0..16).map(|i| GoldilocksField::from_canonical_u64(1000 + i)).collect();
+    let tree: MerkleTree = MerkleTree::build(leaves.clone());
+
+    let proof = tree.get_batch_proof(&[9, 2, 3, 14]);
+    assert_eq!(proof.leaf_indices, [2, 3, 9, 14]);
+    let proven_leaves: Vec<GoldilocksField> = proof.leaf_indices.iter().map(|i| leaves[*i]).collect();
+    assert!(verify_batch_proof(&proven_leaves, tree.root, &proof));
+
+    // Shared nodes are left out: 4 single proofs would need 16 hashes
+    let single_proofs: usize = proof.leaf_indices.iter().map(|i| tree.clone().get_merkle_proof(*i).len()).sum();
+    assert!(proof.hashes.len() < single_proofs);
+
+    // wrong leaf
+    let mut wrong_leaves = proven_leaves.clone();
+    wrong_leaves[1] = leaves[4];
+    assert!(!verify_batch_proof(&wrong_leaves, tree.root, &proof));
+    // missing hash
+    let mut short_proof = proof.clone();
+    short_proof.hashes.pop();
+    assert!(!verify_batch_proof(&proven_leaves, tree.root, &short_proof));
+    // wrong root
+    assert!(!verify_batch_proof(&proven_leaves, tree.tree[0][0], &proof));
+    Ok(())
+  }
+
+  #[test]
+  fn test_batch_proof_of_all_leaves_is_empty() -> Result<()> {
+    let leaves: Vec<GoldilocksField> = (0..8).map(|i| GoldilocksField::from_canonical_u64(i)).collect();
+    let tree: MerkleTree = MerkleTree::build(leaves.clone());
+
+    let proof = tree.get_batch_proof(&(0..8).collect::<Vec<usize>>());
+    assert!(proof.hashes.is_empty());
+    assert!(verify_batch_proof(&leaves, tree.root, &proof));
+    Ok(())
+  }
+
+  #[test]
+  fn test_update_leaf() -> Result<()> {
+    let mut leaves: Vec<GoldilocksField> = (0..16).map(|i| GoldilocksField::from_canonical_u64(1000 + i)).collect();
+    let mut tree: MerkleTree = MerkleTree::build(leaves.clone());
+    let old_root = tree.root;
+
+    let proof = tree.update_leaf(5, GoldilocksField::from_canonical_u64(42));
+    assert_eq!(proof.old_root, old_root);
+    assert_eq!(proof.new_root, tree.root);
+    assert!(verify_merkle_update_proof(&proof));
+
+    // The updated tree is the same as a tree built from the new leaves
+    leaves[5] = GoldilocksField::from_canonical_u64(42);
+    let rebuilt: MerkleTree = MerkleTree::build(leaves.clone());
+    assert_eq!(tree.tree, rebuilt.tree);
+    assert_eq!(tree.root, rebuilt.root);
+    assert!(verify_merkle_proof(leaves[5], 5, tree.root, tree.clone().get_merkle_proof(5)));
+
+    // The roots can't be swapped
+    let mut wrong_proof = proof.clone();
+    wrong_proof.new_root = proof.old_root;
+    assert!(!verify_merkle_update_proof(&wrong_proof));
+    Ok(())
+  }
+
 }
\ No newline at end of file
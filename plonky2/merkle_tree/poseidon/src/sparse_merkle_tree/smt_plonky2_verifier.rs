@@ -0,0 +1,157 @@
+use plonky2::{hash::{poseidon::PoseidonHash, hash_types::{HashOut, HashOutTarget}}, plonk::{config::GenericConfig, circuit_data::{CircuitData, CircuitConfig}, circuit_builder::CircuitBuilder}, iop::{target::{BoolTarget, Target}, witness::{PartialWitness, WitnessWrite}}};
+use plonky2_field::{goldilocks_field::GoldilocksField, types::Field};
+use serde::{Deserialize, Serialize};
+
+use crate::simple_merkle_tree::merkle_plonky2_verifier::compute_root_target;
+use crate::sparse_merkle_tree::sparse_merkle_tree::{Key, KEY_LIMBS};
+
+// Targets of a (non-)membership proof of a key in a sparse Merkle tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmtProofTargets {
+  // The key as little-endian 32-bit limbs, see `Key`
+  pub key: Vec<Target>,
+  // The stored value; None for a non-membership proof
+  pub value: Option<Target>,
+  // One sibling per level, starting at the lowest level (as returned by `get_proof`)
+  pub siblings: Vec<HashOutTarget>,
+}
+
+// Adds the key limbs to the builder and returns them with the [depth] bits that pick the path.
+// Each limb is range checked to 32 bits and the bits above [depth] must be zero.
+fn add_key_target(
+  builder: &mut CircuitBuilder<GoldilocksField, 2>,
+  depth: usize
+) -> (Vec<Target>, Vec<BoolTarget>) {
+  let key: Vec<Target> = builder.add_virtual_targets(KEY_LIMBS);
+  let mut key_bits: Vec<BoolTarget> = Vec::new();
+  for limb in key.iter() {
+    key_bits.extend(builder.split_le(*limb, 32));
+  }
+  for bit in key_bits.drain(depth..) {
+    builder.assert_zero(bit.target);
+  }
+  (key, key_bits)
+}
+
+// Returns a circuit that proves that a key is stored in (or, if not [membership], absent from) a sparse Merkle tree
+// over keys of [depth] bits. An absent key has an empty leaf, which hashes to zero.
+// Public inputs: the root (4 elements), followed by the key limbs (8 elements) and, for membership, the value.
+pub fn verify_smt_proof_circuit<C: GenericConfig<2, F = GoldilocksField>>(
+  config: CircuitConfig,
+  depth: usize,
+  membership: bool
+) -> (CircuitData<GoldilocksField, C, 2>, SmtProofTargets) {
+  let mut builder = CircuitBuilder::<GoldilocksField, 2>::new(config);
+
+  let (key, key_bits) = add_key_target(&mut builder, depth);
+  let siblings: Vec<HashOutTarget> = (0..depth).map(|_| builder.add_virtual_hash()).collect();
+  let (value, leaf) = if membership {
+    // Same as `leaf_hash`
+    let value = builder.add_virtual_target();
+    (Some(value), builder.hash_n_to_hash_no_pad::<PoseidonHash>(vec![value]))
+  } else {
+    (None, builder.constant_hash(HashOut::ZERO))
+  };
+  let root = compute_root_target(&mut builder, leaf, &key_bits, &siblings);
+
+  // This is the expected root value
+  builder.register_public_inputs(&root.elements);
+  builder.register_public_inputs(&key);
+  if let Some(value) = value {
+    builder.register_public_input(value);
+  }
+
+  let data = builder.build::<C>();
+  (data, SmtProofTargets { key, value, siblings })
+}
+
+// Sets the key, the value (for a membership proof) and the proof (as returned by `get_proof`) in the witness
+pub fn set_smt_proof_targets(
+  pw: &mut PartialWitness<GoldilocksField>,
+  targets: &SmtProofTargets,
+  key: &Key,
+  value: Option<GoldilocksField>,
+  proof: &[HashOut<GoldilocksField>]
+) {
+  assert_eq!(proof.len(), targets.siblings.len());
+  assert_eq!(value.is_some(), targets.value.is_some());
+  for (target, limb) in targets.key.iter().zip(key) {
+    pw.set_target(*target, GoldilocksField::from_canonical_u32(*limb));
+  }
+  if let (Some(target), Some(value)) = (targets.value, value) {
+    pw.set_target(target, value);
+  }
+  for (sibling, hash) in targets.siblings.iter().zip(proof) {
+    pw.set_hash_target(*sibling, *hash);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anyhow::Result;
+  use plonky2::{iop::witness::{PartialWitness, WitnessWrite}, plonk::{config::PoseidonGoldilocksConfig, circuit_data::CircuitConfig}};
+  use plonky2_field::{goldilocks_field::GoldilocksField, types::Field};
+  use rand::Rng;
+
+  use crate::sparse_merkle_tree::{sparse_merkle_tree::{Key, SparseMerkleTree, KEY_BITS}, smt_plonky2_verifier::{verify_smt_proof_circuit, set_smt_proof_targets}};
+
+  fn get_test_tree(nr_keys: usize) -> (SparseMerkleTree, Vec<Key>) {
+    let mut rng = rand::thread_rng();
+    let mut tree = SparseMerkleTree::new(KEY_BITS);
+    let keys: Vec<Key> = (0..nr_keys).map(|_| rng.gen()).collect();
+    for (i, key) in keys.iter().enumerate() {
+      tree.insert(*key, GoldilocksField::from_canonical_usize(i));
+    }
+    (tree, keys)
+  }
+
+  #[test]
+  fn test_membership() -> Result<()> {
+    let (tree, keys) = get_test_tree(8);
+    let (circuit_data, targets) = verify_smt_proof_circuit::<PoseidonGoldilocksConfig>(CircuitConfig::standard_recursion_config(), KEY_BITS, true);
+
+    // any key can be proven with the same circuit
+    for key in keys.iter().take(2) {
+      let mut pw = PartialWitness::new();
+      set_smt_proof_targets(&mut pw, &targets, key, tree.get(key), &tree.get_proof(key));
+      let public_inputs = circuit_data.prover_only.public_inputs.clone();
+      for i in 0..4 {
+        pw.set_target(public_inputs[i], tree.root.elements[i]);
+      }
+      let proof = circuit_data.prove(pw)?;
+      circuit_data.verify(proof)?;
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_non_membership() -> Result<()> {
+    let (tree, _) = get_test_tree(8);
+    let (circuit_data, targets) = verify_smt_proof_circuit::<PoseidonGoldilocksConfig>(CircuitConfig::standard_recursion_config(), KEY_BITS, false);
+
+    let missing_key: Key = rand::thread_rng().gen();
+    let mut pw = PartialWitness::new();
+    set_smt_proof_targets(&mut pw, &targets, &missing_key, None, &tree.get_proof(&missing_key));
+    let public_inputs = circuit_data.prover_only.public_inputs.clone();
+    for i in 0..4 {
+      pw.set_target(public_inputs[i], tree.root.elements[i]);
+    }
+    let proof = circuit_data.prove(pw)?;
+    circuit_data.verify(proof)
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_non_membership_of_stored_key_fails() {
+    let (tree, keys) = get_test_tree(8);
+    let (circuit_data, targets) = verify_smt_proof_circuit::<PoseidonGoldilocksConfig>(CircuitConfig::standard_recursion_config(), KEY_BITS, false);
+
+    let mut pw = PartialWitness::new();
+    set_smt_proof_targets(&mut pw, &targets, &keys[0], None, &tree.get_proof(&keys[0]));
+    let public_inputs = circuit_data.prover_only.public_inputs.clone();
+    for i in 0..4 {
+      pw.set_target(public_inputs[i], tree.root.elements[i]);
+    }
+    circuit_data.prove(pw).unwrap();
+  }
+}
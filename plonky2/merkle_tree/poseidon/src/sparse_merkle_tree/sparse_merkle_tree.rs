@@ -0,0 +1,224 @@
+// Sparse Merkle Tree impl
+
+use std::collections::HashMap;
+
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::hash::hash_types::HashOut;
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::plonk::config::Hasher;
+
+// Number of bits of a key, i.e. the depth of a full tree
+pub const KEY_BITS: usize = 256;
+// Number of 32-bit limbs of a key; a limb fits in a field element
+pub const KEY_LIMBS: usize = KEY_BITS / 32;
+
+// A 256-bit key as little-endian 32-bit limbs. Bit i of the key picks the child at level i, so
+// the key is the index of its leaf.
+pub type Key = [u32; KEY_LIMBS];
+
+pub fn key_bit(key: &Key, i: usize) -> bool {
+  (key[i / 32] >> (i % 32)) & 1 == 1
+}
+
+// Returns the key with the lowest [level] bits cleared, which identifies the node at [level] on the path of the key
+fn node_key(key: &Key, level: usize) -> Key {
+  let mut node = *key;
+  for (j, limb) in node.iter_mut().enumerate() {
+    let cleared_bits = level.saturating_sub(32 * j).min(32);
+    if cleared_bits == 32 {
+      *limb = 0;
+    } else {
+      *limb &= !((1u32 << cleared_bits) - 1);
+    }
+  }
+  node
+}
+
+fn sibling_key(key: &Key, level: usize) -> Key {
+  let mut sibling = node_key(key, level);
+  sibling[level / 32] ^= 1 << (level % 32);
+  sibling
+}
+
+// Hash of a leaf holding [value]. Empty leaves hash to zero, which is why the value is always hashed
+// (`hash_or_noop` would map the value 0 to the empty leaf).
+pub fn leaf_hash(value: GoldilocksField) -> HashOut<GoldilocksField> {
+  PoseidonHash::hash_no_pad(&[value])
+}
+
+// Merkle tree over a 2^depth key space in which only the non-empty nodes are stored.
+// A subtree without leaves has the default hash of its level.
+#[derive(Debug, Clone)]
+pub struct SparseMerkleTree {
+  pub depth: usize,
+  pub leaves: HashMap<Key, GoldilocksField>,
+  pub nodes: Vec<HashMap<Key, HashOut<GoldilocksField>>>, // non-empty nodes per level, keyed by `node_key`, level 0 are the leaves
+  pub default_hashes: Vec<HashOut<GoldilocksField>>, // hash of an empty subtree per level, the last one is the root of the empty tree
+  pub root: HashOut<GoldilocksField>
+}
+
+impl SparseMerkleTree {
+
+  // Create an empty tree over keys of [depth] bits
+  pub fn new(depth: usize) -> Self {
+    assert!(depth > 0 && depth <= KEY_BITS);
+
+    let mut default_hashes = vec![HashOut::ZERO];
+    for i in 0..depth {
+      default_hashes.push(PoseidonHash::two_to_one(default_hashes[i], default_hashes[i]));
+    }
+
+    SparseMerkleTree {
+      depth,
+      leaves: HashMap::new(),
+      nodes: vec![HashMap::new(); depth],
+      root: default_hashes[depth],
+      default_hashes
+    }
+  }
+
+  fn check_key(&self, key: &Key) {
+    assert!(self.depth == KEY_BITS || node_key(key, self.depth) == [0; KEY_LIMBS], "key out of range for depth {}", self.depth);
+  }
+
+  fn get_node(&self, level: usize, node: &Key) -> HashOut<GoldilocksField> {
+    *self.nodes[level].get(node).unwrap_or(&self.default_hashes[level])
+  }
+
+  // Sets the value of [key], replacing an earlier value, and rehashes its path up to the root
+  pub fn insert(&mut self, key: Key, value: GoldilocksField) {
+    self.check_key(&key);
+    self.leaves.insert(key, value);
+
+    let mut next_hash = leaf_hash(value);
+    for i in 0..self.depth {
+      self.nodes[i].insert(node_key(&key, i), next_hash);
+      let sibling = self.get_node(i, &sibling_key(&key, i));
+      if key_bit(&key, i) {
+        next_hash = PoseidonHash::two_to_one(sibling, next_hash);
+      } else {
+        next_hash = PoseidonHash::two_to_one(next_hash, sibling);
+      }
+    }
+    self.root = next_hash;
+  }
+
+  pub fn get(&self, key: &Key) -> Option<GoldilocksField> {
+    self.leaves.get(key).copied()
+  }
+
+  // Returns [depth] siblings, starting at the lowest level. The same proof shows membership of a stored key
+  // and non-membership of a missing key.
+  pub fn get_proof(&self, key: &Key) -> Vec<HashOut<GoldilocksField>> {
+    self.check_key(key);
+    (0..self.depth).map(|i| self.get_node(i, &sibling_key(key, i))).collect()
+  }
+}
+
+fn compute_root(leaf: HashOut<GoldilocksField>, key: &Key, siblings: &[HashOut<GoldilocksField>]) -> HashOut<GoldilocksField> {
+  let mut next_hash = leaf;
+  for (i, sibling) in siblings.iter().enumerate() {
+    if key_bit(key, i) {
+      next_hash = PoseidonHash::two_to_one(*sibling, next_hash);
+    } else {
+      next_hash = PoseidonHash::two_to_one(next_hash, *sibling);
+    }
+  }
+  next_hash
+}
+
+// Returns true if [key] holds [value] in the tree with the given root
+pub fn verify_membership(key: &Key, value: GoldilocksField, root: HashOut<GoldilocksField>, siblings: &[HashOut<GoldilocksField>]) -> bool {
+  compute_root(leaf_hash(value), key, siblings) == root
+}
+
+// Returns true if [key] is empty in the tree with the given root
+pub fn verify_non_membership(key: &Key, root: HashOut<GoldilocksField>, siblings: &[HashOut<GoldilocksField>]) -> bool {
+  compute_root(HashOut::ZERO, key, siblings) == root
+}
+
+#[cfg(test)]
+mod tests {
+  use plonky2::field::{goldilocks_field::GoldilocksField, types::Field};
+  use rand::Rng;
+
+  use crate::sparse_merkle_tree::sparse_merkle_tree::{Key, SparseMerkleTree, KEY_BITS, verify_membership, verify_non_membership};
+
+  fn random_key() -> Key {
+    rand::thread_rng().gen()
+  }
+
+  #[test]
+  fn test_insert_and_get() {
+    let mut tree = SparseMerkleTree::new(KEY_BITS);
+    let empty_root = tree.root;
+    let key = random_key();
+    assert_eq!(tree.get(&key), None);
+
+    tree.insert(key, GoldilocksField::from_canonical_u64(7));
+    assert_eq!(tree.get(&key), Some(GoldilocksField::from_canonical_u64(7)));
+    assert_ne!(tree.root, empty_root);
+
+    tree.insert(key, GoldilocksField::from_canonical_u64(8));
+    assert_eq!(tree.get(&key), Some(GoldilocksField::from_canonical_u64(8)));
+  }
+
+  #[test]
+  fn test_root_is_independent_of_insertion_order() {
+    let keys: Vec<Key> = (0..10).map(|_| random_key()).collect();
+    let mut tree = SparseMerkleTree::new(KEY_BITS);
+    let mut reversed_tree = SparseMerkleTree::new(KEY_BITS);
+    for (i, key) in keys.iter().enumerate() {
+      tree.insert(*key, GoldilocksField::from_canonical_usize(i));
+    }
+    for (i, key) in keys.iter().enumerate().rev() {
+      reversed_tree.insert(*key, GoldilocksField::from_canonical_usize(i));
+    }
+    assert_eq!(tree.root, reversed_tree.root);
+  }
+
+  #[test]
+  fn test_membership_and_non_membership() {
+    let mut tree = SparseMerkleTree::new(KEY_BITS);
+    let keys: Vec<Key> = (0..10).map(|_| random_key()).collect();
+    for (i, key) in keys.iter().enumerate() {
+      tree.insert(*key, GoldilocksField::from_canonical_usize(i));
+    }
+
+    for (i, key) in keys.iter().enumerate() {
+      let proof = tree.get_proof(key);
+      assert!(verify_membership(key, GoldilocksField::from_canonical_usize(i), tree.root, &proof));
+      assert!(!verify_membership(key, GoldilocksField::from_canonical_usize(i + 1), tree.root, &proof));
+      assert!(!verify_non_membership(key, tree.root, &proof));
+    }
+
+    let missing_key = random_key();
+    let proof = tree.get_proof(&missing_key);
+    assert!(verify_non_membership(&missing_key, tree.root, &proof));
+    // A stored value of 0 is not an empty leaf
+    tree.insert(missing_key, GoldilocksField::ZERO);
+    assert!(!verify_non_membership(&missing_key, tree.root, &tree.get_proof(&missing_key)));
+  }
+
+  #[test]
+  fn test_neighbouring_keys() {
+    let mut tree = SparseMerkleTree::new(8);
+    let mut key: Key = [0; 8];
+    tree.insert(key, GoldilocksField::ONE);
+    key[0] = 1;
+    tree.insert(key, GoldilocksField::TWO);
+
+    assert!(verify_membership(&key, GoldilocksField::TWO, tree.root, &tree.get_proof(&key)));
+    key[0] = 2;
+    assert!(verify_non_membership(&key, tree.root, &tree.get_proof(&key)));
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_key_out_of_range() {
+    let mut tree = SparseMerkleTree::new(8);
+    let mut key: Key = [0; 8];
+    key[0] = 1 << 8;
+    tree.insert(key, GoldilocksField::ONE);
+  }
+}
@@ -0,0 +1,178 @@
+use std::{collections::HashMap, fs::File, rc::Rc, time::Instant};
+
+use clap::{App, Arg};
+use ndarray::{Array, IxDyn};
+use plonky2::{
+  field::types::Field,
+  iop::witness::{PartialWitness, WitnessWrite},
+  plonk::{
+    circuit_builder::CircuitBuilder,
+    circuit_data::CircuitConfig,
+    config::{GenericConfig, KeccakGoldilocksConfig},
+  },
+};
+use rand::Rng;
+use serde_derive::Serialize;
+use zkml::{
+  gadgets::gadget::GadgetConfig,
+  gates::dot_prod::DotProductGate,
+  layers::{
+    fully_connected::{FullyConnectedCircuit, FullyConnectedConfig},
+    layer::{Layer, LayerConfig, MatMulCheck},
+  },
+};
+
+use jemallocator::Jemalloc;
+
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+const D: usize = 2;
+type C = KeccakGoldilocksConfig;
+type F = <C as GenericConfig<D>>::F;
+
+#[derive(Debug, Serialize)]
+struct MatMulResult {
+  check: String,
+  m: usize,
+  n: usize,
+  p: usize,
+  estimated_rows: usize,
+  gates: usize,
+  degree: usize,
+  build_ms: f64,
+  prove_ms: f64,
+}
+
+fn circuit_config(cols: usize) -> CircuitConfig {
+  CircuitConfig {
+    num_constants: 3,
+    num_routed_wires: cols,
+    ..CircuitConfig::standard_recursion_zk_config()
+  }
+}
+
+// Proves a single (m x n) * (n x p) matrix multiplication with the given check
+fn bench(m: usize, n: usize, p: usize, cols: usize, matmul_check: MatMulCheck) -> MatMulResult {
+  let config = circuit_config(cols);
+  let estimated_rows = matmul_check.estimated_rows(m, n, p, DotProductGate::num_ops(&config));
+  let mut builder = CircuitBuilder::<F, D>::new(config);
+  let mut pw = PartialWitness::<F>::new();
+
+  let mut tensor = |shape: &[usize]| {
+    let targets = builder.add_virtual_targets(shape.iter().product());
+    for t in targets.iter() {
+      // Small values, like the quantized activations and weights
+      pw.set_target(
+        *t,
+        F::from_canonical_u64(rand::thread_rng().gen_range(0..512)),
+      );
+    }
+    Array::from_shape_vec(IxDyn(shape), targets.into_iter().map(Rc::new).collect()).unwrap()
+  };
+  // Fully connected weights are stored as (out x in)
+  let tensors = vec![tensor(&[m, n]), tensor(&[p, n])];
+  let constants = HashMap::from([(0, Rc::new(F::ZERO))]);
+
+  let fc_circuit =
+    FullyConnectedCircuit::<F, C, D>::construct(FullyConnectedConfig::construct(false));
+  let layer_config = LayerConfig {
+    layer_params: vec![0],
+    matmul_check,
+    ..LayerConfig::default()
+  };
+  fc_circuit.make_circuit(
+    &mut builder,
+    &tensors,
+    &constants,
+    Rc::new(GadgetConfig::default()),
+    &layer_config,
+    &mut vec![],
+  );
+  let gates = builder.num_gates();
+
+  let start = Instant::now();
+  let data = builder.build::<C>();
+  let build_ms = start.elapsed().as_secs_f64() * 1000.;
+
+  let start = Instant::now();
+  let proof = data.prove(pw).unwrap();
+  let prove_ms = start.elapsed().as_secs_f64() * 1000.;
+  data.verify(proof).unwrap();
+
+  MatMulResult {
+    check: format!("{:?}", matmul_check),
+    m,
+    n,
+    p,
+    estimated_rows,
+    gates,
+    degree: data.common.degree(),
+    build_ms,
+    prove_ms,
+  }
+}
+
+fn main() {
+  env_logger::init();
+  let matches = App::new("matmul_bench")
+    .arg(
+      Arg::with_name("sizes")
+        .index(1)
+        .value_name("sizes")
+        .help("comma separated m,n,p")
+        .required(true),
+    )
+    .arg(
+      Arg::with_name("cols")
+        .short("c")
+        .long("cols")
+        .value_name("cols")
+        .help("number of columns")
+        .takes_value(true),
+    )
+    .arg(
+      Arg::with_name("output")
+        .long("json")
+        .value_name("outp_json")
+        .help("output json file path")
+        .takes_value(true),
+    )
+    .get_matches();
+  let sizes = matches
+    .value_of("sizes")
+    .unwrap()
+    .split(',')
+    .map(|x| x.parse::<usize>().unwrap())
+    .collect::<Vec<_>>();
+  assert_eq!(sizes.len(), 3, "sizes must be m,n,p");
+  let cols = matches
+    .value_of("cols")
+    .map_or(60, |col| col.parse::<usize>().unwrap());
+  let (m, n, p) = (sizes[0], sizes[1], sizes[2]);
+  let dp_size = DotProductGate::num_ops(&circuit_config(cols));
+
+  let results = [MatMulCheck::Naive, MatMulCheck::Freivalds]
+    .into_iter()
+    .map(|check| bench(m, n, p, cols, check))
+    .collect::<Vec<_>>();
+  for result in results.iter() {
+    println!(
+      "{} {}x{}x{}: {} gates ({} estimated, degree {}), build {:.1} ms, prove {:.1} ms",
+      result.check,
+      result.m,
+      result.n,
+      result.p,
+      result.gates,
+      result.estimated_rows,
+      result.degree,
+      result.build_ms,
+      result.prove_ms
+    );
+  }
+  println!("auto picks {:?}", MatMulCheck::Auto.resolve(m, n, p, dp_size));
+
+  if let Some(outp_json) = matches.value_of("output") {
+    serde_json::to_writer_pretty(File::create(outp_json).unwrap(), &results).unwrap();
+  }
+}
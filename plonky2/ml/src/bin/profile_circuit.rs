@@ -0,0 +1,122 @@
+use std::fs::File;
+
+use clap::{App, Arg};
+use plonky2::plonk::config::{GenericConfig, KeccakGoldilocksConfig};
+use zkml::{layers::layer::MatMulCheck, model::ModelCircuit, utils::profiler::profile_circuit};
+
+use jemallocator::Jemalloc;
+
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+fn main() {
+  env_logger::init();
+  let matches = App::new("profile_circuit")
+    .arg(
+      Arg::with_name("config")
+        .index(1)
+        .value_name("config_fname")
+        .help("config file path")
+        .required(true),
+    )
+    .arg(
+      Arg::with_name("input")
+        .index(2)
+        .value_name("inp_fname")
+        .help("input file path")
+        .required(true),
+    )
+    .arg(
+      Arg::with_name("cols")
+        .short("c")
+        .long("cols")
+        .value_name("cols")
+        .help("number of columns")
+        .takes_value(true),
+    )
+    .arg(
+      Arg::with_name("no_lookups")
+        .long("no-lookups")
+        .value_name("no_lookups")
+        .help("no lookups")
+        .takes_value(false),
+    )
+    .arg(
+      Arg::with_name("json")
+        .long("json")
+        .value_name("outp_json")
+        .help("output json file path")
+        .takes_value(true),
+    )
+    .arg(
+      Arg::with_name("csv")
+        .long("csv")
+        .value_name("outp_csv")
+        .help("output csv file path")
+        .takes_value(true),
+    )
+    .arg(
+      Arg::with_name("matmul")
+        .long("matmul")
+        .value_name("matmul")
+        .help("how matrix multiplications are checked: auto (default), naive or freivalds")
+        .takes_value(true),
+    )
+    .get_matches();
+  let config_fname = matches.value_of("config").unwrap().to_string();
+  let inp_fname = matches.value_of("input").unwrap().to_string();
+  let no_lookups = matches.is_present("no_lookups");
+  let matmul_check = matches
+    .value_of("matmul")
+    .map_or(MatMulCheck::default(), |x| x.parse().unwrap());
+  let col = matches
+    .value_of("cols")
+    .map_or(60, |col| col.parse::<usize>().unwrap());
+
+  const D: usize = 2;
+  type C = KeccakGoldilocksConfig;
+  type F = <C as GenericConfig<D>>::F;
+
+  let (circuit, builder, pw) = ModelCircuit::generate_from_file::<F, C, D>(
+    &config_fname,
+    &inp_fname,
+    &col,
+    no_lookups,
+    matmul_check,
+  );
+  let report = profile_circuit::<F, C, D>(circuit, builder, pw);
+
+  println!(
+    "gates: {}, degree: {}, lookups: {}, witness: {:.3} ms (outside layers: {:.3} ms)",
+    report.gates, report.degree, report.lookups, report.witness_time_ms, report.other_time_ms
+  );
+  for layer in report.layers.iter() {
+    println!(
+      "  layer {}: {} gates, {} lookups, {:.3} ms ({:.1}%)",
+      layer.name,
+      layer.gates,
+      layer.lookups,
+      layer.witness_time_ms,
+      layer.time_share * 100.
+    );
+  }
+  for (gadget, lookups) in report.gadget_lookups.iter() {
+    println!("  gadget {}: {} lookups", gadget, lookups);
+  }
+  for generator in report.generators.iter() {
+    println!(
+      "  generator {}: {} runs, {:.3} ms ({:.1}%)",
+      generator.name,
+      generator.runs,
+      generator.witness_time_ms,
+      generator.time_share * 100.
+    );
+  }
+
+  if let Some(outp_json) = matches.value_of("json") {
+    serde_json::to_writer_pretty(File::create(outp_json).unwrap(), &report).unwrap();
+  }
+  if let Some(outp_csv) = matches.value_of("csv") {
+    std::fs::write(outp_csv, report.to_csv()).unwrap();
+  }
+}
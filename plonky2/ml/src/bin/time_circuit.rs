@@ -1,6 +1,10 @@
 use clap::{Arg, App};
 use plonky2::plonk::config::{GenericConfig, KeccakGoldilocksConfig};
-use zkml::{model::ModelCircuit, utils::proving::time_circuit};
+use zkml::{
+  layers::layer::MatMulCheck,
+  model::ModelCircuit,
+  utils::proving::{time_circuit, verify_circuit},
+};
 
 use jemallocator::Jemalloc;
 
@@ -14,7 +18,7 @@ fn main() {
       Arg::with_name("type")
         .index(1)
         .value_name("circuit_type")
-        .help("circuit_name")
+        .help("circuit name, used to name the circuit data, proof and public input files")
         .required(true),
     )
     .arg(
@@ -35,7 +39,7 @@ fn main() {
       Arg::with_name("build_prove")
         .index(4)
         .value_name("build_prove")
-        .help("build or prove")
+        .help("build, prove or verify")
         .required(true),
     )
     .arg(
@@ -50,7 +54,7 @@ fn main() {
         .short("c")
         .long("cols")
         .value_name("cols")
-        .help("number of columns")
+        .help("number of columns, required to build or prove")
         .takes_value(true),
     )
     .arg(
@@ -60,6 +64,20 @@ fn main() {
         .help("no lookups")
         .takes_value(false),
     )
+    .arg(
+      Arg::with_name("public_outputs")
+        .long("public-outputs")
+        .value_name("public_outputs")
+        .help("register the model output as public inputs and write the proof")
+        .takes_value(false),
+    )
+    .arg(
+      Arg::with_name("matmul")
+        .long("matmul")
+        .value_name("matmul")
+        .help("how matrix multiplications are checked: auto (default), naive or freivalds")
+        .takes_value(true),
+    )
     .get_matches();
   let circuit_type = matches.value_of("type").unwrap().to_string();
   let config_fname = matches.value_of("config").unwrap().to_string();
@@ -68,28 +86,44 @@ fn main() {
   let outp_json = matches.value_of("output").unwrap().to_string();
 
   let no_lookups = matches.is_present("no_lookups");
-  if circuit_type != "mnist" && circuit_type != "dlrm" {
-    panic!("Unsupported circuit type");
+  let matmul_check = matches
+    .value_of("matmul")
+    .map_or(MatMulCheck::default(), |x| x.parse().unwrap());
+  let public_outputs = matches.is_present("public_outputs");
+  if build_prove != "build" && build_prove != "prove" && build_prove != "verify" {
+    panic!("Must specify build, prove or verify");
   }
 
-  if build_prove != "build" && build_prove != "prove" {
-    panic!("Must specify build or prove");
-  }
-
-  let col = if let Some(col) = matches.value_of("cols") {
-    col.parse::<usize>().unwrap()
-  } else {
-    if circuit_type == "mnist" {
-      60 as usize
-    } else {
-      110 as usize
-    }
-  };
-
   const D: usize = 2;
   type C = KeccakGoldilocksConfig;
   type F = <C as GenericConfig<D>>::F;
-  let (circuit, builder, pw) =
-    ModelCircuit::generate_from_file::<F, C, D>(&config_fname, &inp_fname, &col, no_lookups);
-  time_circuit::<F, C, D>(circuit, builder, pw, circuit_type, build_prove, outp_json);
+  // Verification only needs the serialized verifier data and proof
+  if build_prove == "verify" {
+    verify_circuit::<F, C, D>(circuit_type, outp_json);
+    return;
+  }
+
+  // The num_cols of the model configs is sized for halo2, so the plonky2 width is given here
+  let col = matches
+    .value_of("cols")
+    .expect("--cols is required to build or prove")
+    .parse::<usize>()
+    .unwrap();
+
+  let (circuit, builder, pw) = ModelCircuit::generate_from_file::<F, C, D>(
+    &config_fname,
+    &inp_fname,
+    &col,
+    no_lookups,
+    matmul_check,
+  );
+  time_circuit::<F, C, D>(
+    circuit,
+    builder,
+    pw,
+    circuit_type,
+    build_prove,
+    outp_json,
+    public_outputs,
+  );
 }
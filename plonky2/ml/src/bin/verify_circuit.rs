@@ -0,0 +1,95 @@
+use std::{fs::File, io::BufReader, io::Read, time::Instant};
+
+use clap::{App, Arg};
+use plonky2::plonk::{
+  circuit_data::VerifierCircuitData,
+  config::{GenericConfig, KeccakGoldilocksConfig},
+  proof::ProofWithPublicInputs,
+};
+use zkml::utils::{
+  loader::TensorMsgpack,
+  proving::{verify_with_public_values, MLGateSerializer, PublicInputLayout},
+};
+
+fn read_bytes(fname: &str) -> Vec<u8> {
+  let mut file = File::open(fname).expect("File not found");
+  let mut buffer = Vec::new();
+  file
+    .read_to_end(&mut buffer)
+    .expect("Failed to read the file");
+  buffer
+}
+
+fn main() {
+  env_logger::init();
+  let matches = App::new("verify_circuit")
+    .arg(
+      Arg::with_name("type")
+        .index(1)
+        .value_name("circuit_type")
+        .help("circuit_name")
+        .required(true),
+    )
+    .arg(
+      Arg::with_name("proof")
+        .index(2)
+        .value_name("proof_fname")
+        .help("proof file path")
+        .required(true),
+    )
+    .arg(
+      Arg::with_name("output")
+        .index(3)
+        .value_name("outp_fname")
+        .help("claimed output file path")
+        .required(true),
+    )
+    .arg(
+      Arg::with_name("input")
+        .long("input")
+        .value_name("inp_fname")
+        .help("claimed input file path, checked against the proof's input segment")
+        .takes_value(true),
+    )
+    .get_matches();
+  let circuit_type = matches.value_of("type").unwrap().to_string();
+  let proof_fname = matches.value_of("proof").unwrap().to_string();
+  let outp_fname = matches.value_of("output").unwrap().to_string();
+
+  const D: usize = 2;
+  type C = KeccakGoldilocksConfig;
+  type F = <C as GenericConfig<D>>::F;
+
+  let gate_serializer = MLGateSerializer {};
+  let data = VerifierCircuitData::<F, C, D>::from_bytes(
+    read_bytes(&format!("{}_verifier_data", circuit_type)),
+    &gate_serializer,
+  )
+  .unwrap();
+  let layout = PublicInputLayout::read(&circuit_type).expect("public input layout not found");
+  let proof =
+    ProofWithPublicInputs::<F, C, D>::from_bytes(read_bytes(&proof_fname), &data.common).unwrap();
+
+  let claimed_outputs: Vec<i64> = {
+    let mut reader = BufReader::new(File::open(outp_fname).unwrap());
+    rmp_serde::from_read(&mut reader).unwrap()
+  };
+  // The input tensors are registered as public inputs in file order
+  let claimed_inputs: Option<Vec<i64>> = matches.value_of("input").map(|inp_fname| {
+    let mut reader = BufReader::new(File::open(inp_fname).unwrap());
+    let tensors: Vec<TensorMsgpack> = rmp_serde::from_read(&mut reader).unwrap();
+    tensors.into_iter().flat_map(|t| t.data).collect()
+  });
+
+  let start = Instant::now();
+  verify_with_public_values(
+    &data,
+    &layout,
+    proof,
+    claimed_inputs.as_ref(),
+    &claimed_outputs,
+  )
+  .expect("verify error");
+  println!("Verifying time: {:?}", start.elapsed());
+  println!("Proof verified");
+}
@@ -0,0 +1,2 @@
+pub mod packer;
+pub mod poseidon_commit;
@@ -0,0 +1,115 @@
+use std::{collections::BTreeMap, rc::Rc};
+
+use ndarray::{Array, IxDyn};
+use plonky2::{
+  field::extension::Extendable, hash::hash_types::RichField, iop::target::Target,
+  plonk::circuit_builder::CircuitBuilder,
+};
+
+#[derive(Clone, Debug)]
+pub struct PackerConfig {
+  pub num_bits_per_elem: usize,
+  pub num_elem_per_packed: usize,
+}
+
+// Packs several signed tensor elements into a single field element, so fewer elements need to be
+// hashed for the commitment
+pub struct PackerCircuit {
+  pub config: PackerConfig,
+}
+
+impl PackerCircuit {
+  pub fn construct<F: RichField>(num_bits_per_elem: usize) -> PackerConfig {
+    // Leave the top bit free so the packed value never wraps around the modulus
+    let num_elem_per_packed = (F::BITS - 1) / num_bits_per_elem;
+    assert!(num_elem_per_packed > 0);
+
+    PackerConfig {
+      num_bits_per_elem,
+      num_elem_per_packed,
+    }
+  }
+
+  pub fn pack<F: RichField + Extendable<D>, const D: usize>(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    values: &BTreeMap<i64, Array<Rc<Target>, IxDyn>>,
+  ) -> Vec<Target> {
+    let num_bits_per_elem = self.config.num_bits_per_elem;
+    let shift_val = F::from_canonical_u64(1 << (num_bits_per_elem - 1));
+    let mul_val = F::from_canonical_u64(1 << num_bits_per_elem);
+
+    let flat = values
+      .values()
+      .flat_map(|tensor| tensor.iter().map(|x| *x.as_ref()))
+      .collect::<Vec<_>>();
+
+    flat
+      .chunks(self.config.num_elem_per_packed)
+      .map(|chunk| {
+        // packed = sum_i (x_i + shift) * 2^(i * num_bits_per_elem)
+        let mut packed = builder.zero();
+        let mut exponent = F::ONE;
+        for x in chunk {
+          let shifted = builder.add_const(*x, shift_val);
+          // Ensure that the weights/inputs are in the correct range
+          builder.range_check(shifted, num_bits_per_elem);
+          packed = builder.mul_const_add(exponent, shifted, packed);
+          exponent = exponent * mul_val;
+        }
+        packed
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{collections::BTreeMap, rc::Rc};
+
+  use ndarray::{Array, IxDyn};
+  use plonky2::{iop::witness::PartialWitness, plonk::circuit_builder::CircuitBuilder};
+
+  use crate::utils::testing::{add_inputs, circuit_config, prove_and_verify, F};
+
+  use super::PackerCircuit;
+
+  // Packs `values` as one tensor with `num_bits_per_elem` bits per element
+  fn pack(values: &[i64], num_bits_per_elem: usize) -> anyhow::Result<Vec<i64>> {
+    let mut builder = CircuitBuilder::new(circuit_config());
+    let mut pw = PartialWitness::<F>::new();
+    let targets = add_inputs(&mut builder, &mut pw, values);
+    let tensor = Array::from_shape_vec(
+      IxDyn(&[values.len()]),
+      targets.into_iter().map(Rc::new).collect(),
+    )
+    .unwrap();
+
+    let packer = PackerCircuit {
+      config: PackerCircuit::construct::<F>(num_bits_per_elem),
+    };
+    let packed = packer.pack(&mut builder, &BTreeMap::from([(0, tensor)]));
+    prove_and_verify(builder, pw, &packed, &[])
+  }
+
+  #[test]
+  fn packs_shifted_elements() {
+    // 4 bits per element, 15 elements per packed value: sum_i (x_i + 8) * 16^i
+    let packed = pack(&[-8, 7, 0, 3], 4).unwrap();
+    assert_eq!(packed, vec![15 * 16 + 8 * 256 + 11 * 4096]);
+  }
+
+  #[test]
+  fn starts_a_new_element_when_full() {
+    let packed = pack(&[1; 16], 4).unwrap();
+    let full = (0..15).map(|i| 9 << (4 * i)).sum::<i64>();
+    assert_eq!(packed, vec![full, 9]);
+  }
+
+  #[test]
+  #[should_panic]
+  fn out_of_range_element_fails() {
+    // 8 + 8 doesn't fit in 4 bits
+    pack(&[8], 4).unwrap();
+  }
+}
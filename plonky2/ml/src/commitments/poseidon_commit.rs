@@ -0,0 +1,108 @@
+use std::{collections::BTreeMap, rc::Rc};
+
+use ndarray::{Array, IxDyn};
+use plonky2::{
+  field::extension::Extendable,
+  hash::{hash_types::RichField, poseidon::PoseidonHash},
+  iop::target::Target,
+  plonk::circuit_builder::CircuitBuilder,
+};
+
+use super::packer::PackerCircuit;
+
+pub struct PoseidonCommitCircuit {
+  pub packer: PackerCircuit,
+}
+
+impl PoseidonCommitCircuit {
+  pub fn construct<F: RichField>(num_bits_per_elem: usize) -> Self {
+    Self {
+      packer: PackerCircuit {
+        config: PackerCircuit::construct::<F>(num_bits_per_elem),
+      },
+    }
+  }
+
+  // Packs the tensors (in index order) and hashes them with Poseidon. The digest is registered as
+  // public inputs.
+  pub fn commit<F: RichField + Extendable<D>, const D: usize>(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    values: &BTreeMap<i64, Array<Rc<Target>, IxDyn>>,
+  ) -> Vec<Target> {
+    let packed = self.packer.pack(builder, values);
+    let commitment = builder.hash_n_to_hash_no_pad::<PoseidonHash>(packed);
+    builder.register_public_inputs(&commitment.elements);
+    commitment.elements.to_vec()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{collections::BTreeMap, rc::Rc};
+
+  use ndarray::{Array, IxDyn};
+  use plonky2::{
+    field::types::Field,
+    hash::poseidon::PoseidonHash,
+    iop::witness::PartialWitness,
+    plonk::{circuit_builder::CircuitBuilder, config::Hasher},
+  };
+
+  use crate::utils::proving::field_to_i64;
+  use crate::utils::testing::{add_inputs, circuit_config, prove_and_verify, D, F, TEST_K};
+
+  use super::PoseidonCommitCircuit;
+
+  fn commit(tensors: &[&[i64]]) -> anyhow::Result<Vec<i64>> {
+    let mut builder = CircuitBuilder::new(circuit_config());
+    let mut pw = PartialWitness::<F>::new();
+    let mut values = BTreeMap::new();
+    for (idx, tensor) in tensors.iter().enumerate() {
+      let targets = add_inputs(&mut builder, &mut pw, tensor);
+      let targets = targets.into_iter().map(Rc::new).collect();
+      values.insert(
+        idx as i64,
+        Array::from_shape_vec(IxDyn(&[tensor.len()]), targets).unwrap(),
+      );
+    }
+
+    let commitment =
+      PoseidonCommitCircuit::construct::<F>(TEST_K as usize).commit(&mut builder, &values);
+    prove_and_verify(builder, pw, &commitment, &[])
+  }
+
+  #[test]
+  fn matches_native_hash() {
+    let tensors: [&[i64]; 2] = [&[-512, 511, 0, 3, -1], &[7, 8, -9]];
+    let commitment = commit(&tensors).unwrap();
+
+    // 6 elements of 10 bits per packed value, over the concatenated tensors
+    let shift = 1 << (TEST_K - 1);
+    let flat = tensors.concat();
+    let packed = flat
+      .chunks(6)
+      .map(|chunk| {
+        let packed = chunk
+          .iter()
+          .rev()
+          .fold(0, |acc, x| (acc << TEST_K) + (x + shift) as u64);
+        F::from_canonical_u64(packed)
+      })
+      .collect::<Vec<_>>();
+    let expected = PoseidonHash::hash_no_pad(&packed)
+      .elements
+      .iter()
+      .map(|x| field_to_i64::<F, D>(x))
+      .collect::<Vec<_>>();
+    assert_eq!(commitment, expected);
+  }
+
+  #[test]
+  #[should_panic]
+  fn out_of_range_element_fails() {
+    // 512 + 512 doesn't fit in 10 bits
+    let tensor: &[i64] = &[0, 1 << (TEST_K - 1)];
+    commit(&[tensor]).unwrap();
+  }
+}
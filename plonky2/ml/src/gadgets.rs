@@ -3,5 +3,7 @@ pub mod gadget;
 pub mod bias_div_round_relu6;
 pub mod dot_prod;
 pub mod input_lookup;
+pub mod max;
 pub mod nonlinear;
 pub mod var_div;
+pub mod var_div_big;
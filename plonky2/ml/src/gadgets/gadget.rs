@@ -13,8 +13,15 @@ pub enum GadgetType {
   BiasDivRoundRelu6,
   DivRound,
   DotProduct,
+  Exp,
   Logistic,
+  Max,
+  Pow,
   Relu,
+  Rsqrt,
+  Sqrt,
+  Tanh,
+  VarDivRoundBig,
   InputLookup, // Dummy placeholder for the input lookup
 }
 
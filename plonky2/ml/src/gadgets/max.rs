@@ -0,0 +1,113 @@
+use std::rc::Rc;
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use crate::gates::comparison::ComparisonGate;
+
+use super::gadget::{Gadget, GadgetConfig};
+
+type MaxConfig = GadgetConfig;
+
+pub struct MaxCircuit {
+  pub _config: Rc<MaxConfig>,
+}
+
+impl MaxCircuit {
+  pub fn construct(config: Rc<MaxConfig>) -> Self {
+    Self { _config: config }
+  }
+
+  pub fn configure<F: RichField + Extendable<D>, const D: usize>(
+    _builder: &mut CircuitBuilder<F, D>,
+    gadget_config: GadgetConfig,
+  ) -> GadgetConfig {
+    gadget_config
+  }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gadget<F, D> for MaxCircuit {
+  fn load_lookups(_builder: &mut CircuitBuilder<F, D>, _config: GadgetConfig) -> Option<usize> {
+    None
+  }
+
+  // Returns the max of vec_inputs[0]
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    vec_inputs: &Vec<Vec<&Target>>,
+    _single_inputs: &Vec<F>,
+    gadget_config: Rc<GadgetConfig>,
+  ) -> Vec<Target> {
+    let inps = &vec_inputs[0];
+    assert!(inps.len() > 0);
+
+    // The comparison gate works on canonical u64s, so shift [min_val, max_val] to [0, 2^k)
+    let neg_min_val = builder.constant(F::from_canonical_i64(-gadget_config.min_val));
+    let shifted = inps
+      .iter()
+      .map(|x| builder.add(**x, neg_min_val))
+      .collect::<Vec<_>>();
+
+    let mut max = shifted[0];
+    for x in shifted.iter().skip(1) {
+      let comp_gate = ComparisonGate::<F, D>::new(32);
+      let row = builder.add_gate(comp_gate, vec![]);
+      builder.connect(
+        max,
+        Target::wire(row, ComparisonGate::<F, D>::wire_first_input()),
+      );
+      builder.connect(
+        *x,
+        Target::wire(row, ComparisonGate::<F, D>::wire_second_input()),
+      );
+      // max(a, b) = a + b - min(a, b)
+      let min = Target::wire(row, ComparisonGate::<F, D>::wire_result());
+      let sum = builder.add(max, *x);
+      max = builder.sub(sum, min);
+    }
+
+    vec![builder.sub(max, neg_min_val)]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use plonky2::iop::witness::PartialWitness;
+
+  use crate::gadgets::gadget::Gadget;
+  use crate::utils::testing::{add_inputs, gadget_builder, prove_and_verify, D, F};
+
+  use super::MaxCircuit;
+
+  fn max(inps: &[i64], output_override: Option<i64>) -> anyhow::Result<i64> {
+    let (mut builder, gadget_config) = gadget_builder(&[]);
+    let mut pw = PartialWitness::<F>::new();
+    let inp_targets = add_inputs(&mut builder, &mut pw, inps);
+    let gadget = MaxCircuit::construct(gadget_config.clone());
+    let outp = <MaxCircuit as Gadget<F, D>>::make_circuit(
+      &gadget,
+      &mut builder,
+      &vec![inp_targets.iter().collect()],
+      &vec![],
+      gadget_config,
+    );
+    let overrides = output_override.map_or(vec![], |v| vec![(outp[0], v)]);
+    Ok(prove_and_verify(builder, pw, &outp, &overrides)?[0])
+  }
+
+  #[test]
+  fn max_of_mixed_signs() {
+    assert_eq!(max(&[3, -7, 12, 5], None).unwrap(), 12);
+    assert_eq!(max(&[-100, -5, -300], None).unwrap(), -5);
+    assert_eq!(max(&[-512, 502], None).unwrap(), 502);
+    assert_eq!(max(&[4], None).unwrap(), 4);
+  }
+
+  #[test]
+  fn rejects_wrong_max() {
+    assert!(max(&[3, -7, 12, 5], Some(5)).is_err());
+  }
+}
@@ -1,3 +1,8 @@
+pub mod exp;
 pub mod logistic;
 pub mod non_linearity;
+pub mod pow;
 pub mod relu;
+pub mod rsqrt;
+pub mod sqrt;
+pub mod tanh;
@@ -0,0 +1,84 @@
+use std::{collections::HashMap, rc::Rc};
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+type ExpConfig = GadgetConfig;
+
+use super::{
+  super::gadget::{Gadget, GadgetConfig, GadgetType},
+  non_linearity::NonLinearGadget,
+};
+
+pub struct ExpGadgetCircuit {
+  config: Rc<ExpConfig>,
+}
+
+impl ExpGadgetCircuit {
+  pub fn construct(config: Rc<ExpConfig>) -> Self {
+    Self { config }
+  }
+
+  pub fn configure<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    gadget_config: GadgetConfig,
+  ) -> GadgetConfig {
+    <ExpGadgetCircuit as NonLinearGadget<F, D>>::configure(builder, gadget_config, GadgetType::Exp)
+  }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> NonLinearGadget<F, D> for ExpGadgetCircuit {
+  fn generate_map(scale_factor: u64, min_val: i64, num_rows: i64) -> HashMap<i64, i64> {
+    let mut map = HashMap::new();
+    for i in 0..num_rows {
+      let shifted = i + min_val;
+      let x = (shifted as f64) / (scale_factor as f64);
+      let exp = x.exp();
+      let exp = (exp * ((scale_factor * scale_factor) as f64)).round() as i64;
+      map.insert(i as i64, exp);
+    }
+
+    map
+  }
+
+  fn get_map(&self) -> &HashMap<i64, i64> {
+    &self.config.maps.get(&GadgetType::Exp).unwrap()[0]
+  }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gadget<F, D> for ExpGadgetCircuit {
+  fn load_lookups(_builder: &mut CircuitBuilder<F, D>, _config: GadgetConfig) -> Option<usize> {
+    None
+  }
+
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    vec_inputs: &Vec<Vec<&Target>>,
+    _single_inputs: &Vec<F>,
+    gadget_config: Rc<GadgetConfig>,
+  ) -> Vec<Target> {
+    NonLinearGadget::make_circuit(self, builder, vec_inputs, gadget_config, GadgetType::Exp)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::testing::{eval_gadget, D, F};
+
+  use super::ExpGadgetCircuit;
+
+  #[test]
+  fn matches_scaled_exp() {
+    // sf = 16, scaled by sf^2 as in halo2
+    let outp = eval_gadget(
+      ExpGadgetCircuit::construct,
+      &[ExpGadgetCircuit::configure::<F, D>],
+      &[0, -16, 16, 48, 80, -400],
+    )
+    .unwrap();
+    assert_eq!(outp, vec![256, 94, 696, 5142, 37994, 0]);
+  }
+}
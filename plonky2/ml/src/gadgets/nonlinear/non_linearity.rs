@@ -9,6 +9,17 @@ use plonky2::plonk::circuit_builder::CircuitBuilder;
 use super::super::gadget::Gadget;
 use super::super::gadget::{GadgetConfig, GadgetType};
 
+const LIMB_BITS: usize = 16;
+// The outputs are saturated to 48 bits, far from the field's modulus. halo2 keeps them up to
+// i64::MAX, so only outputs that would overflow the next layers anyway (e.g. exp of large positive
+// inputs) differ
+pub(crate) const OUTPUT_BITS: usize = 48;
+
+pub(crate) fn saturate_output(v: i64) -> i64 {
+  v.clamp(-(1 << (OUTPUT_BITS - 1)), (1 << (OUTPUT_BITS - 1)) - 1)
+}
+
+// The Gadget::load_lookups of the nonlinearities load nothing: configure loads their tables
 pub trait NonLinearGadget<F: RichField + Extendable<D>, const D: usize>: Gadget<F, D> {
   fn generate_map(scale_factor: u64, min_val: i64, num_rows: i64) -> HashMap<i64, i64>;
 
@@ -23,21 +34,25 @@ pub trait NonLinearGadget<F: RichField + Extendable<D>, const D: usize>: Gadget<
     let mut tables = gadget_config.tables;
 
     let mut maps = gadget_config.maps;
-    let non_linear_map = Self::generate_map(
+    let mut non_linear_map = Self::generate_map(
       gadget_config.scale_factor,
       gadget_config.min_val,
       gadget_config.num_rows as i64,
     );
+    for v in non_linear_map.values_mut() {
+      *v = saturate_output(*v);
+    }
     maps.insert(gadget_type, vec![non_linear_map]);
     let map_config = GadgetConfig {
       maps,
       ..cloned_gadget
     };
     let cloned_map_config = map_config.clone();
-    let outp_lookup = <Self as NonLinearGadget<F, D>>::load_lookups(builder, Rc::new(map_config), gadget_type).unwrap();
+    let outp_lookups =
+      <Self as NonLinearGadget<F, D>>::load_lookups(builder, Rc::new(map_config), gadget_type);
 
     let inp_lookup = tables.get(&GadgetType::InputLookup).unwrap()[0];
-    tables.insert(gadget_type, vec![inp_lookup, outp_lookup]);
+    tables.insert(gadget_type, [vec![inp_lookup], outp_lookups].concat());
 
     GadgetConfig {
       tables,
@@ -45,34 +60,48 @@ pub trait NonLinearGadget<F: RichField + Extendable<D>, const D: usize>: Gadget<
     }
   }
 
-  fn load_lookups(builder: &mut CircuitBuilder<F, D>, config: Rc<GadgetConfig>, gadget_type: GadgetType) -> Option<usize> {
+  // One table per 16-bit limb of the shifted outputs, as the tables only hold u16 values
+  fn load_lookups(
+    builder: &mut CircuitBuilder<F, D>,
+    config: Rc<GadgetConfig>,
+    gadget_type: GadgetType,
+  ) -> Vec<usize> {
     // TODO may need to refactor, currently each of the nonlinearities have exactly one map
     println!("nl load lookups, num_rows: {}", config.num_rows);
     let map = &config.maps.get(&gadget_type).unwrap()[0];
 
-    let shift_pos_i64 = -config.shift_min_val;
-
-    let nl_table = Arc::new({
-      (0..config.num_rows)
-        .map(|i| {
-          let i = i as i64;
-          // FIXME: refactor this
-          let tmp = *map.get(&i).unwrap();
-          let val = if i == 0 {
-            0
-          } else {
-            if tmp >= 0 {
-              tmp as u64
-            } else {
-              let tmp = tmp + shift_pos_i64;
-              tmp as u64 - shift_pos_i64 as u64
-            }
-          };
-          (i as u16, val as u16)
-        })
-        .collect::<Vec<_>>()
-    });
-    Some(builder.add_lookup_table_from_pairs(nl_table))
+    // Negative outputs (e.g. tanh) are stored shifted
+    let outp_shift = Self::output_shift(map);
+    // The first row is loaded as zero, as in halo2
+    let shifted = (0..config.num_rows as i64)
+      .map(|i| {
+        let val = if i == 0 { 0 } else { map[&i] };
+        (val + outp_shift) as u64
+      })
+      .collect::<Vec<_>>();
+
+    (0..Self::num_output_limbs(map))
+      .map(|limb| {
+        let nl_table = Arc::new(
+          shifted
+            .iter()
+            .enumerate()
+            .map(|(i, val)| (i as u16, (val >> (LIMB_BITS * limb)) as u16))
+            .collect::<Vec<_>>(),
+        );
+        builder.add_lookup_table_from_pairs(nl_table)
+      })
+      .collect()
+  }
+
+  fn output_shift(map: &HashMap<i64, i64>) -> i64 {
+    -map.values().min().unwrap().min(&0)
+  }
+
+  fn num_output_limbs(map: &HashMap<i64, i64>) -> usize {
+    let max_shifted = (map.values().max().unwrap() + Self::output_shift(map)) as u64;
+    let bits = 64 - max_shifted.leading_zeros() as usize;
+    ((bits + LIMB_BITS - 1) / LIMB_BITS).max(1)
   }
 
   fn make_circuit(
@@ -83,7 +112,7 @@ pub trait NonLinearGadget<F: RichField + Extendable<D>, const D: usize>: Gadget<
     gadget_type: GadgetType,
   ) -> Vec<Target> {
     let inps = &vec_inputs[0];
-    let nl_table = gadget_config.tables.get(&gadget_type).unwrap()[1];
+    let limb_tables = &gadget_config.tables.get(&gadget_type).unwrap()[1..];
 
     let min_val = gadget_config.min_val;
     let zero = builder.zero();
@@ -96,10 +125,24 @@ pub trait NonLinearGadget<F: RichField + Extendable<D>, const D: usize>: Gadget<
         builder.sub(**x, min_val_t)
       }).collect::<Vec<_>>();
 
+    let outp_shift = Self::output_shift(&gadget_config.maps.get(&gadget_type).unwrap()[0]);
+    let outp_shift_t = builder.constant(F::from_canonical_i64(outp_shift));
+    let limb_base = F::from_canonical_u64(1 << LIMB_BITS);
+
     let mut outps = vec![];
 
     for i in 0..inps.len() {
-      let outp = builder.add_lookup_from_index(shifted_inps[i], nl_table);
+      // Recombine the limbs, most significant first
+      let mut outp = builder.add_lookup_from_index(shifted_inps[i], *limb_tables.last().unwrap());
+      for table in limb_tables.iter().rev().skip(1) {
+        let limb = builder.add_lookup_from_index(shifted_inps[i], *table);
+        outp = builder.mul_const_add(limb_base, outp, limb);
+      }
+      let outp = if outp_shift == 0 {
+        outp
+      } else {
+        builder.sub(outp, outp_shift_t)
+      };
       outps.push(outp);
     }
 
@@ -0,0 +1,86 @@
+use std::{collections::HashMap, rc::Rc};
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+type PowConfig = GadgetConfig;
+
+use super::{
+  super::gadget::{Gadget, GadgetConfig, GadgetType},
+  non_linearity::NonLinearGadget,
+};
+
+pub struct PowGadgetCircuit {
+  config: Rc<PowConfig>,
+}
+
+impl PowGadgetCircuit {
+  pub fn construct(config: Rc<PowConfig>) -> Self {
+    Self { config }
+  }
+
+  pub fn configure<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    gadget_config: GadgetConfig,
+  ) -> GadgetConfig {
+    <PowGadgetCircuit as NonLinearGadget<F, D>>::configure(builder, gadget_config, GadgetType::Pow)
+  }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> NonLinearGadget<F, D> for PowGadgetCircuit {
+  fn generate_map(scale_factor: u64, min_val: i64, num_rows: i64) -> HashMap<i64, i64> {
+    let power = 3.; // FIXME: same fixed power as the halo2 gadget
+
+    let mut map = HashMap::new();
+    for i in 0..num_rows {
+      let shifted = i + min_val;
+      let x = (shifted as f64) / (scale_factor as f64);
+      let y = x.powf(power);
+      let y = (y * ((scale_factor) as f64)).round() as i64;
+      map.insert(i as i64, y);
+    }
+
+    map
+  }
+
+  fn get_map(&self) -> &HashMap<i64, i64> {
+    &self.config.maps.get(&GadgetType::Pow).unwrap()[0]
+  }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gadget<F, D> for PowGadgetCircuit {
+  fn load_lookups(_builder: &mut CircuitBuilder<F, D>, _config: GadgetConfig) -> Option<usize> {
+    None
+  }
+
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    vec_inputs: &Vec<Vec<&Target>>,
+    _single_inputs: &Vec<F>,
+    gadget_config: Rc<GadgetConfig>,
+  ) -> Vec<Target> {
+    NonLinearGadget::make_circuit(self, builder, vec_inputs, gadget_config, GadgetType::Pow)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::testing::{eval_gadget, D, F};
+
+  use super::PowGadgetCircuit;
+
+  #[test]
+  fn matches_scaled_cube() {
+    // sf = 16, 4^3 and (-4)^3 are kept outside of the input range
+    let outp = eval_gadget(
+      PowGadgetCircuit::construct,
+      &[PowGadgetCircuit::configure::<F, D>],
+      &[32, -16, 8, 64, -64],
+    )
+    .unwrap();
+    assert_eq!(outp, vec![128, -16, 2, 1024, -1024]);
+  }
+}
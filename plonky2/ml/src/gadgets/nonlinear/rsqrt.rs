@@ -0,0 +1,89 @@
+use std::{collections::HashMap, rc::Rc};
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+type RsqrtConfig = GadgetConfig;
+
+use super::{
+  super::gadget::{Gadget, GadgetConfig, GadgetType},
+  non_linearity::NonLinearGadget,
+};
+
+pub struct RsqrtGadgetCircuit {
+  config: Rc<RsqrtConfig>,
+}
+
+impl RsqrtGadgetCircuit {
+  pub fn construct(config: Rc<RsqrtConfig>) -> Self {
+    Self { config }
+  }
+
+  pub fn configure<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    gadget_config: GadgetConfig,
+  ) -> GadgetConfig {
+    <RsqrtGadgetCircuit as NonLinearGadget<F, D>>::configure(
+      builder,
+      gadget_config,
+      GadgetType::Rsqrt,
+    )
+  }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> NonLinearGadget<F, D> for RsqrtGadgetCircuit {
+  fn generate_map(scale_factor: u64, min_val: i64, num_rows: i64) -> HashMap<i64, i64> {
+    let mut map = HashMap::new();
+    for i in 0..num_rows {
+      let shifted = i + min_val;
+      let x = (shifted as f64) / (scale_factor as f64);
+      let sqrt = x.sqrt();
+      let rsqrt = 1.0 / sqrt;
+      let rsqrt = (rsqrt * ((scale_factor) as f64)).round() as i64;
+      map.insert(i as i64, rsqrt);
+    }
+
+    map
+  }
+
+  fn get_map(&self) -> &HashMap<i64, i64> {
+    &self.config.maps.get(&GadgetType::Rsqrt).unwrap()[0]
+  }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gadget<F, D> for RsqrtGadgetCircuit {
+  fn load_lookups(_builder: &mut CircuitBuilder<F, D>, _config: GadgetConfig) -> Option<usize> {
+    None
+  }
+
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    vec_inputs: &Vec<Vec<&Target>>,
+    _single_inputs: &Vec<F>,
+    gadget_config: Rc<GadgetConfig>,
+  ) -> Vec<Target> {
+    NonLinearGadget::make_circuit(self, builder, vec_inputs, gadget_config, GadgetType::Rsqrt)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::testing::{eval_gadget, D, F};
+
+  use super::RsqrtGadgetCircuit;
+
+  #[test]
+  fn matches_scaled_rsqrt() {
+    // sf = 16, 1 / sqrt(0) saturates at the largest output
+    let outp = eval_gadget(
+      RsqrtGadgetCircuit::construct,
+      &[RsqrtGadgetCircuit::configure::<F, D>],
+      &[64, 16, 4, 0],
+    )
+    .unwrap();
+    assert_eq!(outp, vec![8, 16, 32, (1 << 47) - 1]);
+  }
+}
@@ -0,0 +1,88 @@
+use std::{collections::HashMap, rc::Rc};
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+type SqrtConfig = GadgetConfig;
+
+use super::{
+  super::gadget::{Gadget, GadgetConfig, GadgetType},
+  non_linearity::NonLinearGadget,
+};
+
+pub struct SqrtGadgetCircuit {
+  config: Rc<SqrtConfig>,
+}
+
+impl SqrtGadgetCircuit {
+  pub fn construct(config: Rc<SqrtConfig>) -> Self {
+    Self { config }
+  }
+
+  pub fn configure<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    gadget_config: GadgetConfig,
+  ) -> GadgetConfig {
+    <SqrtGadgetCircuit as NonLinearGadget<F, D>>::configure(
+      builder,
+      gadget_config,
+      GadgetType::Sqrt,
+    )
+  }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> NonLinearGadget<F, D> for SqrtGadgetCircuit {
+  fn generate_map(scale_factor: u64, min_val: i64, num_rows: i64) -> HashMap<i64, i64> {
+    let mut map = HashMap::new();
+    for i in 0..num_rows {
+      let shifted = i + min_val;
+      let x = (shifted as f64) / (scale_factor as f64);
+      let sqrt = x.sqrt();
+      let sqrt = (sqrt * ((scale_factor) as f64)).round() as i64;
+      map.insert(i as i64, sqrt);
+    }
+
+    map
+  }
+
+  fn get_map(&self) -> &HashMap<i64, i64> {
+    &self.config.maps.get(&GadgetType::Sqrt).unwrap()[0]
+  }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gadget<F, D> for SqrtGadgetCircuit {
+  fn load_lookups(_builder: &mut CircuitBuilder<F, D>, _config: GadgetConfig) -> Option<usize> {
+    None
+  }
+
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    vec_inputs: &Vec<Vec<&Target>>,
+    _single_inputs: &Vec<F>,
+    gadget_config: Rc<GadgetConfig>,
+  ) -> Vec<Target> {
+    NonLinearGadget::make_circuit(self, builder, vec_inputs, gadget_config, GadgetType::Sqrt)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::testing::{eval_gadget, D, F};
+
+  use super::SqrtGadgetCircuit;
+
+  #[test]
+  fn matches_scaled_sqrt() {
+    // sf = 16
+    let outp = eval_gadget(
+      SqrtGadgetCircuit::construct,
+      &[SqrtGadgetCircuit::configure::<F, D>],
+      &[64, 16, 4, 36, 0],
+    )
+    .unwrap();
+    assert_eq!(outp, vec![32, 16, 8, 24, 0]);
+  }
+}
@@ -100,3 +100,55 @@ impl<F: RichField + Extendable<D>, const D: usize> Gadget<F, D> for DivRoundCirc
       .collect::<Vec<_>>()
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use std::rc::Rc;
+
+  use plonky2::iop::witness::PartialWitness;
+  use plonky2_field::types::Field;
+
+  use crate::gadgets::gadget::Gadget;
+  use crate::utils::testing::{add_inputs, gadget_builder, prove_and_verify, D, F};
+
+  use super::{DivRoundCircuit, DivRoundConfig};
+
+  fn divide(
+    inps: &[i64],
+    div: u64,
+    no_lookups: bool,
+    output_override: Option<i64>,
+  ) -> anyhow::Result<Vec<i64>> {
+    let (mut builder, gadget_config) = gadget_builder(&[]);
+    let mut pw = PartialWitness::<F>::new();
+    let inp_targets = add_inputs(&mut builder, &mut pw, inps);
+
+    let gadget = DivRoundCircuit::construct(Rc::new(DivRoundConfig {
+      _gadget: gadget_config.clone(),
+      no_lookups,
+    }));
+    let outp = <DivRoundCircuit as Gadget<F, D>>::make_circuit(
+      &gadget,
+      &mut builder,
+      &vec![inp_targets.iter().collect()],
+      &vec![F::from_canonical_u64(div)],
+      gadget_config,
+    );
+    let overrides = output_override.map_or(vec![], |v| vec![(outp[0], v)]);
+    prove_and_verify(builder, pw, &outp, &overrides)
+  }
+
+  #[test]
+  fn rounds_to_nearest() {
+    for no_lookups in [false, true] {
+      let outp = divide(&[7, -7, 24, -24, 0], 16, no_lookups, None).unwrap();
+      assert_eq!(outp, vec![0, 0, 2, -1, 0]);
+    }
+  }
+
+  #[test]
+  fn rejects_wrong_output() {
+    assert!(divide(&[100], 16, true, Some(6)).is_ok());
+    assert!(divide(&[100], 16, true, Some(7)).is_err());
+  }
+}
@@ -0,0 +1,217 @@
+use std::rc::Rc;
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+
+use crate::gates::var_div_big::VarDivRoundBigGate;
+
+use super::gadget::{Gadget, GadgetConfig};
+
+type VarDivRoundBigConfig = GadgetConfig;
+
+// Bits of the divisor; the remainder and 2 * div - r - 1 take one more
+pub(crate) const DIV_BITS: usize = 32;
+// Signed bits of the output. (DIV_BITS + 1) + OUTPUT_BITS must stay below the 64 bits of the field
+pub(crate) const OUTPUT_BITS: usize = 30;
+
+pub struct VarDivRoundBigCircuit {
+  _config: Rc<VarDivRoundBigConfig>,
+}
+
+impl VarDivRoundBigCircuit {
+  pub fn construct(config: Rc<VarDivRoundBigConfig>) -> Self {
+    Self { _config: config }
+  }
+
+  pub fn configure<F: RichField + Extendable<D>, const D: usize>(
+    _builder: &mut CircuitBuilder<F, D>,
+    gadget_config: GadgetConfig,
+  ) -> GadgetConfig {
+    gadget_config
+  }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gadget<F, D> for VarDivRoundBigCircuit {
+  fn load_lookups(_builder: &mut CircuitBuilder<F, D>, _config: GadgetConfig) -> Option<usize> {
+    None
+  }
+
+  // Divides every element of vec_inputs[0] by the single target in vec_inputs[1], rounding
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    vec_inputs: &Vec<Vec<&Target>>,
+    _single_inputs: &Vec<F>,
+    _gadget_config: Rc<GadgetConfig>,
+  ) -> Vec<Target> {
+    let inp = &vec_inputs[0];
+    let div = *vec_inputs[1][0];
+
+    // The divisor is not known when building, so check 0 < div < 2^32 in the circuit.
+    // The divisor may be larger than the lookup tables (e.g. the sum in softmax)
+    builder.range_check(div, DIV_BITS);
+    builder.inverse(div);
+
+    let num_ops = VarDivRoundBigGate::num_ops(&builder.config);
+    let mut div_gates = vec![];
+    for i in 0..inp.len() {
+      let wire_idx = i % num_ops;
+      if wire_idx == 0 {
+        let dr_gate =
+          builder.add_gate(VarDivRoundBigGate::new_from_config(&builder.config), vec![]);
+        div_gates.push(dr_gate);
+      }
+      let gate_idx = i / num_ops;
+      builder.connect(
+        *inp[i],
+        Target::wire(
+          div_gates[gate_idx],
+          VarDivRoundBigGate::wire_ith_input(wire_idx),
+        ),
+      );
+      builder.connect(
+        div,
+        Target::wire(
+          div_gates[gate_idx],
+          VarDivRoundBigGate::wire_ith_divisor(wire_idx),
+        ),
+      );
+    }
+
+    // Unused slots divide zero by the same divisor
+    let zero = builder.zero();
+    if inp.len() % num_ops != 0 {
+      for i in (inp.len() % num_ops)..num_ops {
+        let gate = div_gates[div_gates.len() - 1];
+        builder.connect(
+          zero,
+          Target::wire(gate, VarDivRoundBigGate::wire_ith_input(i)),
+        );
+        builder.connect(
+          div,
+          Target::wire(gate, VarDivRoundBigGate::wire_ith_divisor(i)),
+        );
+      }
+    }
+
+    // The gate only checks the division equations in the field. r and 2 * div - r - 1 must be
+    // in [0, 2 * div), and the output in a range small enough that 2 * div * c can't wrap around,
+    // otherwise c + k / (2 * div) satisfies them as well for small k
+    let out_offset = builder.constant(F::from_canonical_u64(1 << (OUTPUT_BITS - 1)));
+    for gate in div_gates.iter() {
+      for i in 0..num_ops {
+        let r = Target::wire(*gate, VarDivRoundBigGate::wire_ith_remainder(i));
+        let diff = Target::wire(*gate, VarDivRoundBigGate::wire_ith_div_rem_diff(i));
+        builder.range_check(r, DIV_BITS + 1);
+        builder.range_check(diff, DIV_BITS + 1);
+        let c = Target::wire(*gate, VarDivRoundBigGate::wire_ith_output(i));
+        let c_shifted = builder.add(c, out_offset);
+        builder.range_check(c_shifted, OUTPUT_BITS);
+      }
+    }
+
+    (0..inp.len())
+      .map(|i| {
+        Target::wire(
+          div_gates[i / num_ops],
+          VarDivRoundBigGate::wire_ith_output(i % num_ops),
+        )
+      })
+      .collect::<Vec<_>>()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use plonky2::iop::target::Target;
+  use plonky2::iop::wire::Wire;
+  use plonky2::iop::witness::PartialWitness;
+
+  use crate::gadgets::gadget::Gadget;
+  use crate::gates::var_div_big::VarDivRoundBigGate;
+  use crate::utils::testing::{add_inputs, gadget_builder, prove_and_verify, D, F};
+
+  use super::VarDivRoundBigCircuit;
+
+  // Divides `inps` by `div` and proves it with `overrides` applied to the first slot's
+  // (output, remainder, div_rem_diff), or to the divisor
+  fn divide(
+    inps: &[i64],
+    div: i64,
+    slot_overrides: Option<(i64, i64, i64)>,
+    div_override: Option<i64>,
+  ) -> anyhow::Result<Vec<i64>> {
+    let (mut builder, gadget_config) = gadget_builder(&[]);
+    let mut pw = PartialWitness::<F>::new();
+    let inp_targets = add_inputs(&mut builder, &mut pw, inps);
+    let div_target = add_inputs(&mut builder, &mut pw, &[div])[0];
+
+    let gadget = VarDivRoundBigCircuit::construct(gadget_config.clone());
+    let outp = <VarDivRoundBigCircuit as Gadget<F, D>>::make_circuit(
+      &gadget,
+      &mut builder,
+      &vec![inp_targets.iter().collect(), vec![&div_target]],
+      &vec![],
+      gadget_config,
+    );
+
+    let mut overrides = vec![];
+    if let Some((c, r, diff)) = slot_overrides {
+      let row = match outp[0] {
+        Target::Wire(Wire { row, .. }) => row,
+        _ => unreachable!(),
+      };
+      overrides.push((outp[0], c));
+      overrides.push((
+        Target::wire(row, VarDivRoundBigGate::wire_ith_remainder(0)),
+        r,
+      ));
+      overrides.push((
+        Target::wire(row, VarDivRoundBigGate::wire_ith_div_rem_diff(0)),
+        diff,
+      ));
+    }
+    if let Some(div) = div_override {
+      overrides.push((div_target, div));
+    }
+    prove_and_verify(builder, pw, &outp, &overrides)
+  }
+
+  #[test]
+  fn rounds_to_nearest() {
+    let outp = divide(&[7, -7, 5, 0, -300, 301], 3, None, None).unwrap();
+    assert_eq!(outp, vec![2, -2, 2, 0, -100, 100]);
+    // Ties round up
+    let outp = divide(&[3, -3], 2, None, None).unwrap();
+    assert_eq!(outp, vec![2, -1]);
+  }
+
+  #[test]
+  fn divisor_larger_than_tables() {
+    // e.g. the sum of the exponentials in softmax
+    let outp = divide(&[1 << 20, -(1 << 20)], 100_000, None, None).unwrap();
+    assert_eq!(outp, vec![10, -10]);
+  }
+
+  #[test]
+  fn rejects_wrong_output() {
+    // 7 / 3 = 2 with r = 2 * 7 + 3 - 6 * 2 = 5 and diff = 6 - 5 - 1 = 0
+    assert!(divide(&[7], 3, Some((2, 5, 0)), None).is_ok());
+    assert!(divide(&[7], 3, Some((3, 5, 0)), None).is_err());
+  }
+
+  #[test]
+  fn rejects_negative_remainder() {
+    // 6 * 3 + (-1) = 17 and 6 - (-1) - 1 = 6 satisfy both gate equations, only the range check
+    // on the remainder rules this out
+    assert!(divide(&[7], 3, Some((3, -1, 6)), None).is_err());
+  }
+
+  #[test]
+  fn rejects_zero_divisor() {
+    // With div = 0 the equations read 2 * a = r and -r - 1 = diff
+    assert!(divide(&[7], 3, Some((0, 14, -15)), Some(0)).is_err());
+  }
+}
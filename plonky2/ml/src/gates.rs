@@ -1,4 +1,5 @@
 pub mod bias_div_round;
 pub mod dot_prod;
 pub mod var_div;
+pub mod var_div_big;
 pub mod comparison;
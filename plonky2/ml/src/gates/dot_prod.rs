@@ -30,7 +30,7 @@ impl DotProductGate {
     }
   }
 
-  pub(crate) fn num_ops(config: &CircuitConfig) -> usize {
+  pub fn num_ops(config: &CircuitConfig) -> usize {
     let wires_per_entry = 2;
     (config.num_routed_wires - 1) / wires_per_entry
   }
@@ -0,0 +1,305 @@
+use plonky2::field::extension::Extendable;
+use plonky2::field::packed::PackedField;
+use plonky2::gates::gate::Gate;
+use plonky2::gates::packed_util::PackedEvaluableBase;
+use plonky2::gates::util::StridedConstraintConsumer;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::{CircuitConfig, CommonCircuitData};
+use plonky2::plonk::vars::{
+  EvaluationTargets, EvaluationVars, EvaluationVarsBase, EvaluationVarsBaseBatch,
+  EvaluationVarsBasePacked,
+};
+use plonky2::util::serialization::{Buffer, IoResult, Read, Write};
+use plonky2_field::types::Field;
+
+// Same as DivRoundGate, but the divisor is a wire instead of a constant
+#[derive(Debug, Clone)]
+pub struct VarDivRoundBigGate {
+  pub num_ops: usize,
+}
+
+impl VarDivRoundBigGate {
+  pub fn new_from_config(config: &CircuitConfig) -> Self {
+    Self {
+      num_ops: Self::num_ops(config),
+    }
+  }
+
+  pub(crate) fn num_ops(config: &CircuitConfig) -> usize {
+    let wires_per_entry = 5;
+    config.num_routed_wires / wires_per_entry
+  }
+
+  pub fn wire_ith_input(i: usize) -> usize {
+    5 * i
+  }
+
+  pub fn wire_ith_divisor(i: usize) -> usize {
+    5 * i + 1
+  }
+
+  pub fn wire_ith_output(i: usize) -> usize {
+    5 * i + 2
+  }
+
+  pub fn wire_ith_remainder(i: usize) -> usize {
+    5 * i + 3
+  }
+
+  pub fn wire_ith_div_rem_diff(i: usize) -> usize {
+    5 * i + 4
+  }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for VarDivRoundBigGate {
+  fn id(&self) -> String {
+    format!("{self:?}")
+  }
+
+  fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+    dst.write_usize(self.num_ops)
+  }
+
+  fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+    let num_ops = src.read_usize()?;
+    Ok(Self { num_ops })
+  }
+
+  fn eval_unfiltered(&self, vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
+    let two = F::Extension::from_canonical_u64(2);
+    let mut constraints = vec![];
+
+    for i in 0..self.num_ops {
+      let a = vars.local_wires[Self::wire_ith_input(i)];
+      let b = vars.local_wires[Self::wire_ith_divisor(i)];
+      let c = vars.local_wires[Self::wire_ith_output(i)];
+      let diff = vars.local_wires[Self::wire_ith_div_rem_diff(i)];
+      let r = vars.local_wires[Self::wire_ith_remainder(i)];
+
+      // (2 * a + b) = (2 * b) * c + r
+      let lhs = a * two + b;
+      let rhs = b * two * c + r;
+      constraints.push(lhs - rhs);
+
+      // 2 * b - r >= 1 => exists diff >= 0 s.t. 2 * b - r - 1 = diff
+      constraints.push(two * b - r - F::Extension::ONE - diff);
+    }
+
+    constraints
+  }
+
+  fn eval_unfiltered_base_one(
+    &self,
+    _vars: EvaluationVarsBase<F>,
+    _yield_constr: StridedConstraintConsumer<F>,
+  ) {
+    panic!("use eval_unfiltered_base_packed instead");
+  }
+
+  fn eval_unfiltered_base_batch(&self, vars_base: EvaluationVarsBaseBatch<F>) -> Vec<F> {
+    self.eval_unfiltered_base_batch_packed(vars_base)
+  }
+
+  fn eval_unfiltered_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    vars: EvaluationTargets<D>,
+  ) -> Vec<ExtensionTarget<D>> {
+    let one = builder.constant_extension(F::Extension::ONE);
+    let two = builder.constant_extension(F::Extension::from_canonical_u64(2));
+    let mut constraints = Vec::with_capacity(2 * self.num_ops);
+    for i in 0..self.num_ops {
+      let a = vars.local_wires[Self::wire_ith_input(i)];
+      let b = vars.local_wires[Self::wire_ith_divisor(i)];
+      let c = vars.local_wires[Self::wire_ith_output(i)];
+      let diff = vars.local_wires[Self::wire_ith_div_rem_diff(i)];
+      let r = vars.local_wires[Self::wire_ith_remainder(i)];
+
+      // 2 * b
+      let bb = builder.mul_extension(b, two);
+
+      // 2 * a + b = 2 * b * c + r
+      let constr0 = {
+        let lhs = builder.mul_add_extension(a, two, b);
+        let rhs = builder.mul_add_extension(bb, c, r);
+        builder.sub_extension(lhs, rhs)
+      };
+      // 2 * b - r >= 1
+      let constr1 = {
+        let u = builder.add_many_extension([r, one, diff]);
+        builder.sub_extension(bb, u)
+      };
+      constraints.extend([constr0, constr1].iter());
+    }
+
+    constraints
+  }
+
+  fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F, D>> {
+    (0..self.num_ops)
+      .map(|i| WitnessGeneratorRef::new(VarDivRoundBigGenerator { row, i }.adapter()))
+      .collect()
+  }
+
+  fn num_wires(&self) -> usize {
+    self.num_ops * 5
+  }
+
+  fn num_constants(&self) -> usize {
+    0
+  }
+
+  fn degree(&self) -> usize {
+    2
+  }
+
+  fn num_constraints(&self) -> usize {
+    self.num_ops * 2
+  }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> PackedEvaluableBase<F, D>
+  for VarDivRoundBigGate
+{
+  fn eval_unfiltered_base_packed<P: PackedField<Scalar = F>>(
+    &self,
+    vars: EvaluationVarsBasePacked<P>,
+    mut yield_constr: StridedConstraintConsumer<P>,
+  ) {
+    let two = P::ONES + P::ONES;
+    for i in 0..self.num_ops {
+      let a = vars.local_wires[Self::wire_ith_input(i)];
+      let b = vars.local_wires[Self::wire_ith_divisor(i)];
+      let c = vars.local_wires[Self::wire_ith_output(i)];
+      let diff = vars.local_wires[Self::wire_ith_div_rem_diff(i)];
+      let r = vars.local_wires[Self::wire_ith_remainder(i)];
+
+      let lhs = a * two + b;
+      let rhs = b * two * c + r;
+      yield_constr.one(lhs - rhs);
+      yield_constr.one(two * b - r - P::ONES - diff);
+    }
+  }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct VarDivRoundBigGenerator {
+  row: usize,
+  i: usize,
+}
+
+impl VarDivRoundBigGenerator {
+  // Values above p / 2 are negative
+  fn to_i128<F: RichField>(x: F) -> i128 {
+    let x = x.to_canonical_u64();
+    if x > F::ORDER / 2 {
+      x as i128 - F::ORDER as i128
+    } else {
+      x as i128
+    }
+  }
+
+  fn from_i128<F: RichField>(x: i128) -> F {
+    if x < 0 {
+      -F::from_canonical_u64((-x) as u64)
+    } else {
+      F::from_canonical_u64(x as u64)
+    }
+  }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+  for VarDivRoundBigGenerator
+{
+  fn id(&self) -> String {
+    "VarDivRoundBigGenerator".to_string()
+  }
+
+  fn dependencies(&self) -> Vec<Target> {
+    vec![
+      VarDivRoundBigGate::wire_ith_input(self.i),
+      VarDivRoundBigGate::wire_ith_divisor(self.i),
+    ]
+    .iter()
+    .map(|&i| Target::wire(self.row, i))
+    .collect()
+  }
+
+  fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+    let get_wire = |wire: usize| -> F { witness.get_target(Target::wire(self.row, wire)) };
+    let a = Self::to_i128(get_wire(VarDivRoundBigGate::wire_ith_input(self.i)));
+    let b = Self::to_i128(get_wire(VarDivRoundBigGate::wire_ith_divisor(self.i)));
+    assert!(b > 0, "divisor must be positive");
+
+    // c = (2 * a + b) / (2 * b), rounded towards negative infinity
+    let c = (2 * a + b).div_euclid(2 * b);
+    // r = (2 * a + b) % (2 * b)
+    let r = (2 * a + b).rem_euclid(2 * b);
+    let diff = 2 * b - r - 1;
+
+    let output_target = Target::wire(self.row, VarDivRoundBigGate::wire_ith_output(self.i));
+    let div_rem_diff_target =
+      Target::wire(self.row, VarDivRoundBigGate::wire_ith_div_rem_diff(self.i));
+    let remainder_target = Target::wire(self.row, VarDivRoundBigGate::wire_ith_remainder(self.i));
+
+    out_buffer.set_target(div_rem_diff_target, Self::from_i128(diff));
+    out_buffer.set_target(remainder_target, Self::from_i128(r));
+    out_buffer.set_target(output_target, Self::from_i128(c))
+  }
+
+  fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+    dst.write_usize(self.row)?;
+    dst.write_usize(self.i)
+  }
+
+  fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+    let row = src.read_usize()?;
+    let i = src.read_usize()?;
+    Ok(Self { row, i })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anyhow::Result;
+  use plonky2::gates::gate_testing::{test_eval_fns, test_low_degree};
+  use plonky2::plonk::circuit_data::CircuitConfig;
+  use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+  use plonky2_field::goldilocks_field::GoldilocksField;
+
+  use crate::gates::var_div_big::VarDivRoundBigGate;
+
+  #[test]
+  fn wire_indices() {
+    let gate = VarDivRoundBigGate::new_from_config(&CircuitConfig::standard_recursion_config());
+    assert_eq!(gate.num_ops, 16);
+    assert_eq!(VarDivRoundBigGate::wire_ith_input(0), 0);
+    assert_eq!(VarDivRoundBigGate::wire_ith_divisor(0), 1);
+    assert_eq!(VarDivRoundBigGate::wire_ith_output(0), 2);
+    assert_eq!(VarDivRoundBigGate::wire_ith_remainder(0), 3);
+    assert_eq!(VarDivRoundBigGate::wire_ith_div_rem_diff(0), 4);
+    assert_eq!(VarDivRoundBigGate::wire_ith_input(15), 75);
+    assert_eq!(VarDivRoundBigGate::wire_ith_div_rem_diff(15), 79);
+  }
+
+  #[test]
+  fn low_degree() {
+    let gate = VarDivRoundBigGate::new_from_config(&CircuitConfig::standard_recursion_config());
+    test_low_degree::<GoldilocksField, _, 4>(gate)
+  }
+
+  #[test]
+  fn eval_fns() -> Result<()> {
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    let gate = VarDivRoundBigGate::new_from_config(&CircuitConfig::standard_recursion_config());
+    test_eval_fns::<F, C, _, D>(gate)
+  }
+}
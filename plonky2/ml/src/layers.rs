@@ -2,10 +2,21 @@ pub mod arithmetic;
 pub mod avg_pool_2d;
 pub mod batch_mat_mul;
 pub mod conv2d;
+pub mod div_fixed;
 pub mod fully_connected;
 pub mod logistic;
+pub mod max_pool_2d;
+pub mod mean;
 pub mod noop;
+pub mod pow;
+pub mod rsqrt;
 pub mod shape;
+pub mod softmax;
+pub mod sqrt;
+pub mod square;
+pub mod squared_diff;
+pub mod tanh;
+pub mod update;
 
 // Special: dag
 pub mod dag;
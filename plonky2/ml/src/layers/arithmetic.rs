@@ -1,2 +1,4 @@
 pub mod add;
+pub mod div_var;
 pub mod mul;
+pub mod sub;
@@ -0,0 +1,84 @@
+use std::{collections::HashMap, rc::Rc, vec};
+
+use ndarray::{Array, IxDyn};
+
+use plonky2::{
+  field::extension::Extendable, hash::hash_types::RichField, iop::target::Target,
+  plonk::circuit_builder::CircuitBuilder,
+};
+
+use crate::{
+  gadgets::{
+    gadget::{Gadget, GadgetConfig, GadgetType},
+    var_div_big::VarDivRoundBigCircuit,
+  },
+  layers::layer::GadgetConsumer,
+};
+
+use super::super::super::layers::layer::{Layer, LayerConfig};
+
+#[derive(Clone, Debug)]
+pub struct DivVarCircuit {}
+
+impl<F: RichField + Extendable<D>, const D: usize> Layer<F, D> for DivVarCircuit {
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    _constants: &HashMap<i64, Rc<F>>,
+    gadget_config: Rc<GadgetConfig>,
+    _layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    assert_eq!(tensors.len(), 2);
+    // TODO: We only support dividing by a single number for now
+    assert_eq!(tensors[1].shape().len(), 1);
+    assert_eq!(tensors[1].shape()[0], 1);
+
+    let inp = &tensors[0];
+    let div = tensors[1].iter().next().unwrap().as_ref();
+
+    // out = inp * SF / div
+    let sf = F::from_canonical_u64(gadget_config.scale_factor);
+    let mul_outp = inp
+      .iter()
+      .map(|x| builder.mul_const(sf, *x.as_ref()))
+      .collect::<Vec<_>>();
+    let mul_ref = mul_outp.iter().collect::<Vec<_>>();
+
+    let div_gadget = VarDivRoundBigCircuit::construct(gadget_config.clone());
+    let div_outp =
+      div_gadget.make_circuit(builder, &vec![mul_ref, vec![div]], &vec![], gadget_config);
+
+    let outp = div_outp.iter().map(|t| Rc::new(*t)).collect::<Vec<_>>();
+
+    vec![Array::from_shape_vec(IxDyn(inp.shape()), outp).unwrap()]
+  }
+}
+
+impl GadgetConsumer for DivVarCircuit {
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![GadgetType::VarDivRoundBig]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn divides_by_scalar_tensor() {
+    let inp = tensor(0, &[1, 4], &[7, -7, 32, 100]);
+    let div = tensor(1, &[1], &[3]);
+    let div_var = layer("DivVar", &[], &[&inp, &div], 2, &[1, 4]);
+    let outp = prove_model(
+      model(vec![inp, div], vec![div_var]),
+      true,
+      MatMulCheck::Naive,
+    )
+    .unwrap();
+    // inp * sf / 3, rounded
+    assert_eq!(outp, vec![37, -37, 171, 533]);
+  }
+}
@@ -0,0 +1,61 @@
+use std::{collections::HashMap, rc::Rc, vec};
+
+use ndarray::{Array, IxDyn};
+
+use plonky2::{
+  field::extension::Extendable, hash::hash_types::RichField, iop::target::Target,
+  plonk::circuit_builder::CircuitBuilder,
+};
+
+use crate::{
+  gadgets::gadget::GadgetConfig, layers::layer::GadgetConsumer, utils::helpers::broadcast,
+};
+
+use super::super::super::layers::layer::{Layer, LayerConfig};
+
+#[derive(Clone, Debug)]
+pub struct SubCircuit {}
+
+impl<F: RichField + Extendable<D>, const D: usize> Layer<F, D> for SubCircuit {
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    _constants: &HashMap<i64, Rc<F>>,
+    _gadget_config: Rc<GadgetConfig>,
+    _layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    assert_eq!(tensors.len(), 2);
+    let (inp, sub) = broadcast(&tensors[0], &tensors[1]);
+
+    let outp = inp
+      .iter()
+      .zip(sub.iter())
+      .map(|(x, y)| Rc::new(builder.sub(*x.as_ref(), *y.as_ref())))
+      .collect::<Vec<_>>();
+
+    vec![Array::from_shape_vec(IxDyn(inp.shape()), outp).unwrap()]
+  }
+}
+
+impl GadgetConsumer for SubCircuit {
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn subtracts_elementwise() {
+    let a = tensor(0, &[1, 3], &[5, -3, 0]);
+    let b = tensor(1, &[1, 3], &[2, 2, -4]);
+    let sub = layer("Sub", &[], &[&a, &b], 2, &[1, 3]);
+    let outp = prove_model(model(vec![a, b], vec![sub]), true, MatMulCheck::Naive).unwrap();
+    assert_eq!(outp, vec![3, -5, 4]);
+  }
+}
@@ -106,3 +106,31 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> G
     vec![]
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn naive_and_freivalds_agree() {
+    let inp = tensor(
+      0,
+      &[2, 2, 3],
+      &[16, 32, -16, 8, 0, 48, 16, 16, 16, -16, -16, -16],
+    );
+    // adj_y, so both batches are (out x in)
+    let weight = tensor(
+      1,
+      &[2, 2, 3],
+      &[16, 0, 16, 32, -16, 8, 16, 0, 16, 32, -16, 8],
+    );
+    let bmm = layer("BatchMatMul", &[0, 1], &[&inp, &weight], 2, &[2, 2, 2]);
+    let config = model(vec![inp, weight], vec![bmm]);
+
+    let naive = prove_model(config.clone(), true, MatMulCheck::Naive).unwrap();
+    let freivalds = prove_model(config, true, MatMulCheck::Freivalds).unwrap();
+    assert_eq!(naive, vec![0, -8, 56, 40, 32, 24, -32, -24]);
+    assert_eq!(freivalds, naive);
+  }
+}
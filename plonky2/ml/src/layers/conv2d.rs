@@ -390,4 +390,37 @@ impl <F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
       vec![GadgetType::BiasDivRoundRelu6, GadgetType::InputLookup]
     }
   }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn naive_and_freivalds_agree() {
+    // 1..9 in a 3x3 image with one channel
+    let inp = tensor(
+      0,
+      &[1, 3, 3, 1],
+      &(1..10).map(|x| x * 16).collect::<Vec<_>>(),
+    );
+    // Two 2x2 filters: the main diagonal and the anti-diagonal difference
+    let weight = tensor(1, &[2, 2, 2, 1], &[16, 0, 0, 16, 0, -16, 16, 0]);
+    let bias = tensor(2, &[2], &[1, -1]);
+    // Valid padding, no activation, stride 1
+    let conv = layer(
+      "Conv2D",
+      &[0, 1, 0, 1, 1],
+      &[&inp, &weight, &bias],
+      3,
+      &[1, 2, 2, 2],
+    );
+    let config = model(vec![inp, weight, bias], vec![conv]);
+
+    let naive = prove_model(config.clone(), false, MatMulCheck::Naive).unwrap();
+    let freivalds = prove_model(config, false, MatMulCheck::Freivalds).unwrap();
+    assert_eq!(naive, vec![97, 31, 129, 31, 193, 31, 225, 31]);
+    assert_eq!(freivalds, naive);
+  }
+}
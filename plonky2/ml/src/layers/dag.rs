@@ -8,8 +8,26 @@ use crate::{
     avg_pool_2d::AvgPool2DCircuit,
     conv2d::Conv2DCircuit,
     layer::{Layer, LayerType}, shape::{concatenation::ConcatenationCircuit, reshape::ReshapeCircuit, transpose::TransposeCircuit, pack::PackCircuit, split::SplitCircuit, gather::GatherCircuit}, fully_connected::{FullyConnectedCircuit, FullyConnectedConfig}, noop::NoopCircuit, logistic::LogisticCircuit, batch_mat_mul::BatchMatMulCircuit,
+    arithmetic::{div_var::DivVarCircuit, sub::SubCircuit},
+    div_fixed::DivFixedCircuit,
+    max_pool_2d::MaxPool2DCircuit,
+    mean::MeanCircuit,
+    pow::PowCircuit,
+    rsqrt::RsqrtCircuit,
+    shape::{
+      broadcast::BroadcastCircuit, mask_neg_inf::MaskNegInfCircuit, pad::PadCircuit,
+      permute::PermuteCircuit, resize_nn::ResizeNNCircuit, rotate::RotateCircuit,
+      slice::SliceCircuit,
+    },
+    softmax::SoftmaxCircuit,
+    sqrt::SqrtCircuit,
+    square::SquareCircuit,
+    squared_diff::SquaredDiffCircuit,
+    tanh::TanhCircuit,
+    update::UpdateCircuit,
   },
 };
+use log::Level;
 use ndarray::{Array, IxDyn};
 use plonky2::{
   field::extension::Extendable, hash::hash_types::RichField, iop::target::Target,
@@ -17,6 +35,7 @@ use plonky2::{
 };
 
 use super::layer::LayerConfig;
+use crate::utils::profiler::LayerStart;
 
 #[derive(Clone, Debug, Default)]
 pub struct DAGLayerConfig {
@@ -47,7 +66,11 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>  + 'static, const
     constants: &HashMap<i64, Rc<F>>,
     gadget_config: Rc<GadgetConfig>,
     _layer_config: &LayerConfig,
-  ) -> (Vec<Array<Rc<Target>, IxDyn>>, Vec<Target>) {
+  ) -> (
+    HashMap<usize, Array<Rc<Target>, IxDyn>>,
+    Vec<Array<Rc<Target>, IxDyn>>,
+    Vec<Target>,
+  ) {
     // Tensor map
     let mut tensor_map = HashMap::new();
     for (idx, tensor) in tensors.iter().enumerate() {
@@ -68,6 +91,10 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>  + 'static, const
         .iter()
         .map(|idx| tensor_map.get(idx).unwrap().clone())
         .collect::<Vec<_>>();
+      // Shows up in `print_gate_counts` and the per-layer profile
+      let layer_name = format!("{} {:?}", layer_idx, layer_type);
+      let layer_start = LayerStart::new(builder);
+      builder.push_context(Level::Debug, &layer_name);
       let out = match layer_type {
         LayerType::Add => {
           let add_circuit = AddCircuit {};
@@ -106,6 +133,17 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>  + 'static, const
             &mut rand_targets
           )
         }
+        LayerType::Broadcast => {
+          let broadcast_circuit = BroadcastCircuit {};
+          broadcast_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
         LayerType::Concatenation => {
           let concat_circuit = ConcatenationCircuit {};
           concat_circuit.make_circuit(
@@ -131,6 +169,28 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>  + 'static, const
             &mut rand_targets
           )
         }
+        LayerType::DivFixed => {
+          let div_fixed_circuit = DivFixedCircuit {};
+          div_fixed_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
+        LayerType::DivVar => {
+          let div_var_circuit = DivVarCircuit {};
+          div_var_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
         LayerType::FullyConnected => {
           let fc_circuit = FullyConnectedCircuit::<F, C, D> {
             config: FullyConnectedConfig::construct(true),
@@ -167,6 +227,41 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>  + 'static, const
             &mut rand_targets
           )
         }
+        LayerType::MaskNegInf => {
+          let mask_neg_inf_circuit = MaskNegInfCircuit {};
+          mask_neg_inf_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
+        LayerType::MaxPool2D => {
+          let max_pool_2d_circuit = MaxPool2DCircuit::<F, C, D> {
+            _marker: PhantomData,
+          };
+          max_pool_2d_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
+        LayerType::Mean => {
+          let mean_circuit = MeanCircuit {};
+          mean_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
         LayerType::Mul => {
           let mul_circuit = MulCircuit {};
           mul_circuit.make_circuit(
@@ -200,6 +295,41 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>  + 'static, const
             &mut rand_targets
           )
         }
+        LayerType::Pad => {
+          let pad_circuit = PadCircuit::<F, C, D> {
+            _marker: PhantomData,
+          };
+          pad_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
+        LayerType::Permute => {
+          let permute_circuit = PermuteCircuit {};
+          permute_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
+        LayerType::Pow => {
+          let pow_circuit = PowCircuit {};
+          pow_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
         LayerType::Reshape => {
           let reshape_circuit = ReshapeCircuit {};
           reshape_circuit.make_circuit(
@@ -211,6 +341,61 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>  + 'static, const
             &mut rand_targets
           )
         }
+        LayerType::ResizeNN => {
+          let resize_nn_circuit = ResizeNNCircuit {};
+          resize_nn_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
+        LayerType::Rotate => {
+          let rotate_circuit = RotateCircuit {};
+          rotate_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
+        LayerType::Rsqrt => {
+          let rsqrt_circuit = RsqrtCircuit {};
+          rsqrt_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
+        LayerType::Slice => {
+          let slice_circuit = SliceCircuit {};
+          slice_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
+        LayerType::Softmax => {
+          let softmax_circuit = SoftmaxCircuit {};
+          softmax_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
         LayerType::Split => {
           let split_circuit = SplitCircuit {};
           split_circuit.make_circuit(
@@ -222,6 +407,61 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>  + 'static, const
             &mut rand_targets
           )
         }
+        LayerType::Sqrt => {
+          let sqrt_circuit = SqrtCircuit {};
+          sqrt_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
+        LayerType::Square => {
+          let square_circuit = SquareCircuit {};
+          square_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
+        LayerType::SquaredDifference => {
+          let squared_diff_circuit = SquaredDiffCircuit {};
+          squared_diff_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
+        LayerType::Sub => {
+          let sub_circuit = SubCircuit {};
+          sub_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
+        LayerType::Tanh => {
+          let tanh_circuit = TanhCircuit {};
+          tanh_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
         LayerType::Transpose => {
           let transpose_circuit = TransposeCircuit {};
           transpose_circuit.make_circuit(
@@ -233,7 +473,20 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>  + 'static, const
             &mut rand_targets
           )
         }
+        LayerType::Update => {
+          let update_circuit = UpdateCircuit {};
+          update_circuit.make_circuit(
+            builder,
+            &vec_inps,
+            constants,
+            gadget_config.clone(),
+            &layer_config,
+            &mut rand_targets
+          )
+        }
       };
+      builder.pop_context();
+      layer_start.finish(builder, layer_name);
 
       for (idx, tensor_idx) in out_idxes.iter().enumerate() {
         println!("Out {} shape: {:?}", idx, out[idx].shape());
@@ -247,6 +500,28 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>  + 'static, const
       final_out.push(tensor_map.get(idx).unwrap().clone());
     }
 
-    (final_out, rand_targets)
+    (tensor_map, final_out, rand_targets)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn chains_layers() {
+    let a = tensor(0, &[1, 2], &[20, 4]);
+    let b = tensor(1, &[1, 2], &[0, 8]);
+    let sub = layer("Sub", &[], &[&a, &b], 2, &[1, 2]);
+    let diff = tensor(2, &[1, 2], &[]);
+    let square = layer("Square", &[], &[&diff], 3, &[1, 2]);
+    let outp = prove_model(
+      model(vec![a, b], vec![sub, square]),
+      true,
+      MatMulCheck::Naive,
+    )
+    .unwrap();
+    assert_eq!(outp, vec![25, 1]);
   }
 }
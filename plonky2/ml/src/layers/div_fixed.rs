@@ -0,0 +1,74 @@
+use std::{collections::HashMap, rc::Rc};
+
+use ndarray::{Array, IxDyn};
+use plonky2::{
+  hash::hash_types::RichField, iop::target::Target, plonk::circuit_builder::CircuitBuilder,
+};
+use plonky2_field::extension::Extendable;
+
+use crate::gadgets::{
+  gadget::{Gadget, GadgetConfig, GadgetType},
+  var_div::{DivRoundCircuit, DivRoundConfig},
+};
+use crate::layers::layer::GadgetConsumer;
+
+use crate::layers::layer::{Layer, LayerConfig};
+
+#[derive(Clone, Debug)]
+pub struct DivFixedCircuit {}
+
+impl<F: RichField + Extendable<D>, const D: usize> Layer<F, D> for DivFixedCircuit {
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    _constants: &HashMap<i64, Rc<F>>,
+    gadget_config: Rc<GadgetConfig>,
+    layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    let inp = &tensors[0];
+    let inp_vec = inp.iter().map(|x| x.as_ref()).collect::<Vec<_>>();
+
+    // FIXME: this needs to be revealed
+    let div = layer_config.layer_params[0];
+
+    let div_config = DivRoundConfig {
+      _gadget: gadget_config.clone(),
+      no_lookups: layer_config.no_lookups,
+    };
+    let div_gadget = DivRoundCircuit::construct(Rc::new(div_config));
+    let div_outp = div_gadget.make_circuit(
+      builder,
+      &vec![inp_vec],
+      &vec![F::from_canonical_u64(div as u64)],
+      gadget_config,
+    );
+
+    let outp = div_outp.iter().map(|t| Rc::new(*t)).collect::<Vec<_>>();
+
+    vec![Array::from_shape_vec(IxDyn(inp.shape()), outp).unwrap()]
+  }
+}
+
+impl GadgetConsumer for DivFixedCircuit {
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![GadgetType::DivRound]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn divides_by_constant() {
+    for no_lookups in [false, true] {
+      let inp = tensor(0, &[1, 3], &[7, 24, -24]);
+      let div = layer("Div", &[16], &[&inp], 1, &[1, 3]);
+      let outp = prove_model(model(vec![inp], vec![div]), no_lookups, MatMulCheck::Naive).unwrap();
+      assert_eq!(outp, vec![0, 2, -1]);
+    }
+  }
+}
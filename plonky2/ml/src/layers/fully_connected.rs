@@ -2,8 +2,9 @@ use std::{collections::HashMap, marker::PhantomData, rc::Rc};
 
 use ndarray::{Array, ArrayView, Axis, IxDyn};
 use plonky2::{
-  hash::hash_types::RichField,
+  hash::{hash_types::RichField, poseidon::PoseidonHash},
   iop::{
+    challenger::RecursiveChallenger,
     generator::{GeneratedValues, SimpleGenerator},
     target::Target,
     witness::{PartitionWitness, Witness, WitnessWrite},
@@ -22,7 +23,8 @@ use crate::{
     nonlinear::relu::ReluCircuit,
     var_div::{DivRoundCircuit, DivRoundConfig},
   },
-  layers::layer::ActivationType,
+  gates::dot_prod::DotProductGate,
+  layers::layer::{ActivationType, MatMulCheck},
 };
 
 use super::layer::{GadgetConsumer, Layer, LayerConfig};
@@ -49,6 +51,13 @@ pub struct FullyConnectedCircuit<
 impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
   FullyConnectedCircuit<F, C, D>
 {
+  pub fn construct(config: FullyConnectedConfig) -> Self {
+    Self {
+      config,
+      _marker: PhantomData,
+    }
+  }
+
   fn get_activation(&self, layer_params: &Vec<i64>) -> ActivationType {
     let activation = layer_params[0];
     match activation {
@@ -57,77 +66,80 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
       _ => panic!("Unsupported activation type for fully connected"),
     }
   }
-}
 
-impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F> + 'static, const D: usize> Layer<F, D>
-  for FullyConnectedCircuit<F, C, D>
-{
-  fn make_circuit(
+  // Only the first batch is multiplied, see `MatMulGenerator`
+  fn first_batch(input: &Array<Target, IxDyn>) -> ArrayView<Target, IxDyn> {
+    if input.ndim() == 2 {
+      input.view()
+    } else {
+      input.index_axis(Axis(0), 0)
+    }
+  }
+
+  // Computes every entry of input * weight with its own dot product
+  fn naive_matmul(
     &self,
     builder: &mut CircuitBuilder<F, D>,
-    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
-    constants: &HashMap<i64, Rc<F>>,
+    input: &Array<Target, IxDyn>,
+    weight: &Array<Target, IxDyn>,
+    shape: &[usize; 2],
+    zero: F,
     gadget_config: Rc<GadgetConfig>,
-    layer_config: &LayerConfig,
-    rand_targets: &mut Vec<Target>,
-  ) -> Vec<Array<Rc<Target>, IxDyn>> {
-    assert!(tensors.len() <= 3);
-    let activation = self.get_activation(&layer_config.layer_params);
-
-    let input = tensors[0].map(|t| **t);
-    let weight = tensors[1].t().into_owned().map(|t| **t);
-    let shape = [input.shape()[0], weight.shape()[1]];
-    let mm_outp_flat = builder.add_virtual_targets(shape[0] * shape[1]);
-    let mm_result = Array::from_shape_vec(IxDyn(&shape), mm_outp_flat).unwrap();
-    let zero = constants.get(&0).unwrap().as_ref();
-
-    // Compute and assign the result
-    let dim = [input.shape()[0], input.shape()[1], weight.shape()[1]];
-    builder.add_simple_generator(MatMulGenerator {
-      dim,
-      input: input.clone(),
-      weight: weight.clone(),
-      outp: mm_result.clone(),
-    });
-
-    // Fill random values
-    let r1_ref = {
-      let mut ts = vec![];
-      for _ in 0..mm_result.shape()[0] {
-        let t = builder.add_virtual_public_input();
-        ts.push(t);
-      }
-      builder.register_public_inputs(&ts);
-      ts
-    };
-    let r2_ref = {
-      let mut ts = vec![];
-      for _ in 0..mm_result.shape()[1] {
-        let t = builder.add_virtual_public_input();
-        ts.push(t);
+  ) -> Array<Target, IxDyn> {
+    let input = Self::first_batch(input);
+    let dot_prod_circuit = DotProductCircuit::construct(gadget_config.clone());
+    let mut outp = vec![];
+    for i in 0..shape[0] {
+      let input_ri = input.index_axis(Axis(0), i);
+      let input_ri = input_ri.iter().collect::<Vec<_>>();
+      for j in 0..shape[1] {
+        let weight_cj = weight.index_axis(Axis(1), j);
+        let weight_cj = weight_cj.iter().collect::<Vec<_>>();
+        let outp_ij = dot_prod_circuit.make_circuit(
+          builder,
+          &vec![input_ri.clone(), weight_cj],
+          &vec![zero],
+          gadget_config.clone(),
+        );
+        outp.push(outp_ij[0]);
       }
-      builder.register_public_inputs(&ts);
-      ts
-    };
-    rand_targets.extend(r1_ref.iter());
-    rand_targets.extend(r2_ref.iter());
-
-    let r1_ref = r1_ref.iter().collect::<Vec<_>>();
-    let r2_ref = r2_ref.iter().collect::<Vec<_>>();
+    }
+    Array::from_shape_vec(IxDyn(shape), outp).unwrap()
+  }
 
-    // // Compute r1 * result
+  // Checks r1 * result * r2 == (r1 * input) * (weight * r2). The challenges are squeezed from a
+  // Poseidon sponge that absorbs the input, the weight and the claimed result, so the prover can't
+  // pick the result after seeing them. A wrong result passes with probability at most 2 / |F|.
+  fn freivalds_check(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    input: &Array<Target, IxDyn>,
+    weight: &Array<Target, IxDyn>,
+    mm_result: &Array<Target, IxDyn>,
+    zero: F,
+    gadget_config: Rc<GadgetConfig>,
+  ) {
+    let input = Self::first_batch(input);
+
+    let mut challenger = RecursiveChallenger::<F, PoseidonHash, D>::new(builder);
+    challenger.observe_elements(&input.iter().cloned().collect::<Vec<_>>());
+    challenger.observe_elements(&weight.iter().cloned().collect::<Vec<_>>());
+    challenger.observe_elements(&mm_result.iter().cloned().collect::<Vec<_>>());
+    let r1 = challenger.get_n_challenges(builder, mm_result.shape()[0]);
+    let r2 = challenger.get_n_challenges(builder, mm_result.shape()[1]);
+    let r1_ref = r1.iter().collect::<Vec<_>>();
+    let r2_ref = r2.iter().collect::<Vec<_>>();
+
+    // Compute r1 * result
     let dot_prod_circuit = DotProductCircuit::construct(gadget_config.clone());
     let mut r1_res = vec![];
-    // println!("r1_ref: {:?}", r1_ref.len());
-    // println!("r2_ref: {:?}", r2_ref.len());
-    // println!("mm_result: {:?}", mm_result.shape());
     for i in 0..mm_result.shape()[1] {
       let tmp = mm_result.index_axis(Axis(1), i);
       let mm_ci = tmp.iter().collect::<Vec<_>>();
       let r1_res_i = dot_prod_circuit.make_circuit(
         builder,
         &vec![mm_ci, r1_ref.clone()],
-        &vec![*zero],
+        &vec![zero],
         gadget_config.clone(),
       );
       r1_res.push(r1_res_i[0].clone());
@@ -138,31 +150,20 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F> + 'static, const D
     let r1_res_r2 = dot_prod_circuit.make_circuit(
       builder,
       &vec![r1_res_ref, r2_ref.clone()],
-      &vec![*zero],
+      &vec![zero],
       gadget_config.clone(),
     );
     let r1_res_r2 = r1_res_r2[0].clone();
-    // println!("r1_res_r2: {:?}", r1_res_r2);
 
     // Compute r1 * input
     let mut r1_input = vec![];
-
-    let ndim = input.ndim();
-    let input = if ndim == 2 {
-      ArrayView::from(&input)
-    } else {
-      input.index_axis(Axis(0), 0)
-    };
-    // println!("input: {:?}", input.shape());
-
-    // println!("r1_ref: {:?}", r1_ref.len());
     for i in 0..input.shape()[1] {
       let tmp = input.index_axis(Axis(1), i);
       let input_ci = tmp.iter().collect::<Vec<_>>();
       let r1_input_i = dot_prod_circuit.make_circuit(
         builder,
         &vec![input_ci, r1_ref.clone()],
-        &vec![*zero],
+        &vec![zero],
         gadget_config.clone(),
       );
       r1_input.push(r1_input_i[0].clone());
@@ -176,7 +177,7 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F> + 'static, const D
       let weight_r2_i = dot_prod_circuit.make_circuit(
         builder,
         &vec![weight_ci, r2_ref.clone()],
-        &vec![*zero],
+        &vec![zero],
         gadget_config.clone(),
       );
       weight_r2.push(weight_r2_i[0].clone());
@@ -188,14 +189,73 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F> + 'static, const D
     let r1_inp_weight_r2 = dot_prod_circuit.make_circuit(
       builder,
       &vec![r1_input_ref, weight_r2_ref],
-      &vec![*zero],
-      gadget_config.clone(),
+      &vec![zero],
+      gadget_config,
     );
-
     let r1_inp_weight_r2 = r1_inp_weight_r2[0].clone();
 
     // check equality
     builder.connect(r1_res_r2, r1_inp_weight_r2);
+  }
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F> + 'static, const D: usize> Layer<F, D>
+  for FullyConnectedCircuit<F, C, D>
+{
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    constants: &HashMap<i64, Rc<F>>,
+    gadget_config: Rc<GadgetConfig>,
+    layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    assert!(tensors.len() <= 3);
+    let activation = self.get_activation(&layer_config.layer_params);
+
+    let input = tensors[0].map(|t| **t);
+    let weight = tensors[1].t().into_owned().map(|t| **t);
+    let shape = [input.shape()[0], weight.shape()[1]];
+    let zero = constants.get(&0).unwrap().as_ref();
+
+    let dp_size = DotProductGate::num_ops(&builder.config);
+    let (m, n, p) = (shape[0], weight.shape()[0], shape[1]);
+    let matmul_check = layer_config.matmul_check.resolve(m, n, p, dp_size);
+    let mm_result = match matmul_check {
+      MatMulCheck::Auto => unreachable!(),
+      MatMulCheck::Naive => self.naive_matmul(
+        builder,
+        &input,
+        &weight,
+        &shape,
+        *zero,
+        gadget_config.clone(),
+      ),
+      MatMulCheck::Freivalds => {
+        let mm_outp_flat = builder.add_virtual_targets(shape[0] * shape[1]);
+        let mm_result = Array::from_shape_vec(IxDyn(&shape), mm_outp_flat).unwrap();
+
+        // Compute and assign the result
+        let dim = [input.shape()[0], input.shape()[1], weight.shape()[1]];
+        builder.add_simple_generator(MatMulGenerator {
+          dim,
+          input: input.clone(),
+          weight: weight.clone(),
+          outp: mm_result.clone(),
+        });
+
+        self.freivalds_check(
+          builder,
+          &input,
+          &weight,
+          &mm_result,
+          *zero,
+          gadget_config.clone(),
+        );
+        mm_result
+      }
+    };
 
     let final_result_flat = if self.config.normalize {
       let mm_flat = mm_result.iter().map(|t| &*t).collect::<Vec<_>>();
@@ -346,3 +406,84 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> G
     outp
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use std::{collections::HashMap, rc::Rc};
+
+  use ndarray::{Array, IxDyn};
+  use plonky2::{field::types::Field, iop::witness::PartialWitness};
+
+  use crate::{
+    layers::layer::{Layer, LayerConfig, MatMulCheck},
+    utils::testing::{
+      add_inputs, gadget_builder, layer, model, prove_and_verify, prove_model, tensor, C, D, F,
+    },
+  };
+
+  use super::{FullyConnectedCircuit, FullyConnectedConfig};
+
+  const INPUT: [i64; 6] = [16, 32, -16, 8, 0, 48];
+  // Stored as (out x in)
+  const WEIGHT: [i64; 6] = [16, 0, 16, 32, -16, 8];
+
+  // Multiplies INPUT (2 x 3) by WEIGHT transposed without normalizing, replacing the first entry
+  // of the product with `first_override` after witness generation
+  fn matmul(matmul_check: MatMulCheck, first_override: Option<i64>) -> anyhow::Result<Vec<i64>> {
+    let (mut builder, gadget_config) = gadget_builder(&[]);
+    let mut pw = PartialWitness::<F>::new();
+    let mut operand = |data: &[i64]| {
+      let targets = add_inputs(&mut builder, &mut pw, data);
+      Array::from_shape_vec(IxDyn(&[2, 3]), targets.into_iter().map(Rc::new).collect()).unwrap()
+    };
+    let tensors = vec![operand(&INPUT), operand(&WEIGHT)];
+    let constants = HashMap::from([(0, Rc::new(F::ZERO))]);
+    let layer_config = LayerConfig {
+      layer_params: vec![0],
+      matmul_check,
+      ..LayerConfig::default()
+    };
+
+    let fc_circuit =
+      FullyConnectedCircuit::<F, C, D>::construct(FullyConnectedConfig::construct(false));
+    let outp = fc_circuit.make_circuit(
+      &mut builder,
+      &tensors,
+      &constants,
+      gadget_config,
+      &layer_config,
+      &mut vec![],
+    );
+    let outp = outp[0].iter().map(|t| **t).collect::<Vec<_>>();
+    let overrides = first_override.map_or(vec![], |v| vec![(outp[0], v)]);
+    prove_and_verify(builder, pw, &outp, &overrides)
+  }
+
+  #[test]
+  fn multiplies_with_both_checks() {
+    for check in [MatMulCheck::Naive, MatMulCheck::Freivalds] {
+      assert_eq!(matmul(check, None).unwrap(), vec![0, -128, 896, 640]);
+    }
+  }
+
+  #[test]
+  fn freivalds_rejects_wrong_product() {
+    // The product is a witness computed by MatMulGenerator, only the check constrains it
+    assert!(matmul(MatMulCheck::Freivalds, Some(1)).is_err());
+  }
+
+  #[test]
+  fn naive_and_freivalds_agree() {
+    let inp = tensor(0, &[2, 3], &INPUT);
+    let weight = tensor(1, &[2, 3], &WEIGHT);
+    let bias = tensor(2, &[2], &[1, -1]);
+    let fc = layer("FullyConnected", &[0], &[&inp, &weight, &bias], 3, &[2, 2]);
+    let config = model(vec![inp, weight, bias], vec![fc]);
+
+    let naive = prove_model(config.clone(), true, MatMulCheck::Naive).unwrap();
+    let freivalds = prove_model(config, true, MatMulCheck::Freivalds).unwrap();
+    // [0, -128, 896, 640] / 16, plus the bias
+    assert_eq!(naive, vec![1, -9, 57, 39]);
+    assert_eq!(freivalds, naive);
+  }
+}
@@ -1,9 +1,11 @@
-use std::{collections::HashMap, rc::Rc};
+use std::{collections::HashMap, rc::Rc, str::FromStr};
 
 use ndarray::{Array, IxDyn};
 
 use plonky2::{
-  field::extension::Extendable, hash::hash_types::RichField, iop::target::Target,
+  field::extension::Extendable,
+  hash::{hash_types::RichField, poseidon::SPONGE_RATE},
+  iop::target::Target,
   plonk::circuit_builder::CircuitBuilder,
 };
 
@@ -15,18 +17,83 @@ pub enum LayerType {
   Add,
   AvgPool2D,
   BatchMatMul,
+  Broadcast,
   Concatenation,
   Conv2D,
+  DivFixed,
+  DivVar,
   FullyConnected,
   Logistic,
   Gather,
+  MaskNegInf,
+  MaxPool2D,
+  Mean,
   Mul,
   #[default]
   Noop,
   Pack,
+  Pad,
+  Permute,
+  Pow,
   Reshape,
+  ResizeNN,
+  Rotate,
+  Rsqrt,
+  Slice,
+  Softmax,
   Split,
+  Sqrt,
+  Square,
+  SquaredDifference,
+  Sub,
+  Tanh,
   Transpose,
+  Update,
+}
+
+// The op names used in the msgpack model config
+impl FromStr for LayerType {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "Add" => Ok(LayerType::Add),
+      "AveragePool2D" => Ok(LayerType::AvgPool2D),
+      "BatchMatMul" => Ok(LayerType::BatchMatMul),
+      "Broadcast" => Ok(LayerType::Broadcast),
+      "Concatenation" => Ok(LayerType::Concatenation),
+      "Conv2D" => Ok(LayerType::Conv2D),
+      "Div" => Ok(LayerType::DivFixed),
+      "DivVar" => Ok(LayerType::DivVar),
+      "FullyConnected" => Ok(LayerType::FullyConnected),
+      "Gather" => Ok(LayerType::Gather),
+      "Logistic" => Ok(LayerType::Logistic),
+      "MaskNegInf" => Ok(LayerType::MaskNegInf),
+      "MaxPool2D" => Ok(LayerType::MaxPool2D),
+      "Mean" => Ok(LayerType::Mean),
+      "Mul" => Ok(LayerType::Mul),
+      "Noop" => Ok(LayerType::Noop),
+      "Pack" => Ok(LayerType::Pack),
+      "Pad" => Ok(LayerType::Pad),
+      "Permute" => Ok(LayerType::Permute),
+      "Pow" => Ok(LayerType::Pow),
+      "Reshape" => Ok(LayerType::Reshape),
+      "ResizeNearestNeighbor" => Ok(LayerType::ResizeNN),
+      "Rotate" => Ok(LayerType::Rotate),
+      "Rsqrt" => Ok(LayerType::Rsqrt),
+      "Slice" => Ok(LayerType::Slice),
+      "Softmax" => Ok(LayerType::Softmax),
+      "Split" => Ok(LayerType::Split),
+      "Sqrt" => Ok(LayerType::Sqrt),
+      "Square" => Ok(LayerType::Square),
+      "SquaredDifference" => Ok(LayerType::SquaredDifference),
+      "Sub" => Ok(LayerType::Sub),
+      "Tanh" => Ok(LayerType::Tanh),
+      "Transpose" => Ok(LayerType::Transpose),
+      "Update" => Ok(LayerType::Update),
+      _ => Err(format!("unknown op: {}", s)),
+    }
+  }
 }
 
 // NOTE: This is the same order as the TFLite schema
@@ -39,6 +106,73 @@ pub enum ActivationType {
   Relu,
 }
 
+// How a matrix multiplication (fully connected, batch matmul and conv2d via im2col) is checked
+#[derive(Clone, Copy, Debug, Default, Hash, Eq, PartialEq)]
+pub enum MatMulCheck {
+  // Whichever of the two below is estimated to take fewer rows for the layer's shapes
+  #[default]
+  Auto,
+  // One dot product per output entry
+  Naive,
+  // Freivalds' check with challenges derived in-circuit from the operands and the product
+  Freivalds,
+}
+
+impl MatMulCheck {
+  // Rough number of rows to check an (m x n) * (n x p) product with `dp_size` products per dot
+  // product row. The Freivalds estimate counts its five dot product batches and the Poseidon
+  // permutations that absorb the operands and the product and squeeze the m + p challenges.
+  pub fn estimated_rows(self, m: usize, n: usize, p: usize, dp_size: usize) -> usize {
+    let dp_rows = |len: usize| (len + dp_size - 1) / dp_size;
+    let sponge_rows = |len: usize| (len + SPONGE_RATE - 1) / SPONGE_RATE;
+    match self {
+      MatMulCheck::Auto => self
+        .resolve(m, n, p, dp_size)
+        .estimated_rows(m, n, p, dp_size),
+      MatMulCheck::Naive => m * p * dp_rows(n),
+      MatMulCheck::Freivalds => {
+        p * dp_rows(m)
+          + dp_rows(p)
+          + n * dp_rows(m)
+          + n * dp_rows(p)
+          + dp_rows(n)
+          + sponge_rows(m * n + n * p + m * p)
+          + sponge_rows(m + p)
+      }
+    }
+  }
+
+  // Picks the check for a layer. Naive wins for a single row (m = 1), where Freivalds still
+  // multiplies the weight by r2 and hashes all of it on top
+  pub fn resolve(self, m: usize, n: usize, p: usize, dp_size: usize) -> MatMulCheck {
+    match self {
+      MatMulCheck::Auto => {
+        let naive = MatMulCheck::Naive.estimated_rows(m, n, p, dp_size);
+        let freivalds = MatMulCheck::Freivalds.estimated_rows(m, n, p, dp_size);
+        if naive <= freivalds {
+          MatMulCheck::Naive
+        } else {
+          MatMulCheck::Freivalds
+        }
+      }
+      check => check,
+    }
+  }
+}
+
+impl FromStr for MatMulCheck {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "auto" => Ok(MatMulCheck::Auto),
+      "naive" => Ok(MatMulCheck::Naive),
+      "freivalds" => Ok(MatMulCheck::Freivalds),
+      _ => Err(format!("Unknown matmul check: {}", s)),
+    }
+  }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct LayerConfig {
   pub layer_type: LayerType,
@@ -46,7 +180,8 @@ pub struct LayerConfig {
   pub inp_shapes: Vec<Vec<usize>>,
   pub out_shapes: Vec<Vec<usize>>,
   pub mask: Vec<i64>,
-  pub no_lookups: bool
+  pub no_lookups: bool,
+  pub matmul_check: MatMulCheck,
 }
 
 // General issue with rust: I'm not sure how to pass named arguments to a trait...
@@ -66,3 +201,41 @@ pub trait Layer<F: RichField + Extendable<D>, const D: usize> {
 pub trait GadgetConsumer {
   fn used_gadgets(&self, layer_params: Vec<i64>) -> Vec<GadgetType>;
 }
+
+#[cfg(test)]
+mod tests {
+  use super::MatMulCheck;
+
+  // DotProductGate::num_ops at 80 routed wires
+  const DP_SIZE: usize = 39;
+
+  #[test]
+  fn auto_picks_naive_for_a_single_row() {
+    let check = MatMulCheck::Auto.resolve(1, 1000, 1000, DP_SIZE);
+    assert_eq!(check, MatMulCheck::Naive);
+    assert_eq!(check.estimated_rows(1, 1000, 1000, DP_SIZE), 26000);
+  }
+
+  #[test]
+  fn auto_picks_freivalds_for_large_products() {
+    let check = MatMulCheck::Auto.resolve(100, 1000, 1000, DP_SIZE);
+    assert_eq!(check, MatMulCheck::Freivalds);
+    assert!(
+      MatMulCheck::Auto.estimated_rows(100, 1000, 1000, DP_SIZE)
+        < MatMulCheck::Naive.estimated_rows(100, 1000, 1000, DP_SIZE)
+    );
+  }
+
+  #[test]
+  fn explicit_checks_are_kept() {
+    assert_eq!(
+      MatMulCheck::Freivalds.resolve(1, 4, 4, DP_SIZE),
+      MatMulCheck::Freivalds
+    );
+    assert_eq!(
+      MatMulCheck::Naive.resolve(64, 64, 64, DP_SIZE),
+      MatMulCheck::Naive
+    );
+    assert_eq!("auto".parse::<MatMulCheck>(), Ok(MatMulCheck::Auto));
+  }
+}
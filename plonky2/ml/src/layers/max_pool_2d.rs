@@ -0,0 +1,106 @@
+use std::{collections::HashMap, marker::PhantomData, rc::Rc};
+
+use ndarray::{Array, IxDyn};
+
+use plonky2::{
+  field::extension::Extendable,
+  hash::hash_types::RichField,
+  iop::target::Target,
+  plonk::{circuit_builder::CircuitBuilder, config::GenericConfig},
+};
+
+use crate::{
+  gadgets::{
+    gadget::{Gadget, GadgetConfig, GadgetType},
+    max::MaxCircuit,
+  },
+  layers::{conv2d::Conv2DCircuit, conv2d::PaddingEnum, layer::LayerConfig},
+};
+
+use super::layer::{GadgetConsumer, Layer};
+
+pub struct MaxPool2DCircuit<
+  F: RichField + Extendable<D>,
+  C: GenericConfig<D, F = F>,
+  const D: usize,
+> {
+  pub(crate) _marker: PhantomData<C>,
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F> + 'static, const D: usize> Layer<F, D>
+  for MaxPool2DCircuit<F, C, D>
+{
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    _constants: &HashMap<i64, Rc<F>>,
+    gadget_config: Rc<GadgetConfig>,
+    layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    let inp = &tensors[0];
+    assert_eq!(inp.shape().len(), 4);
+    // Don't support batch size > 1 yet
+    assert_eq!(inp.shape()[0], 1);
+
+    let h = inp.shape()[1];
+    let w = inp.shape()[2];
+    let c = inp.shape()[3];
+
+    let (fx, fy) = (
+      layer_config.layer_params[0] as usize,
+      layer_config.layer_params[1] as usize,
+    );
+    let (sh, sw) = (
+      layer_config.layer_params[2] as usize,
+      layer_config.layer_params[3] as usize,
+    );
+
+    let (oh, ow) = Conv2DCircuit::<F, C, D>::out_hw(h, w, sh, sw, fx, fy, PaddingEnum::Valid);
+    let batch_size = inp.shape()[0];
+
+    let max_gadget = MaxCircuit::construct(gadget_config.clone());
+    let mut outp = vec![];
+    for i in 0..oh {
+      for j in 0..ow {
+        for chan in 0..c {
+          let pool_targets = (0..fx)
+            .map(|x| (0..fy).map(move |y| (x + i * sh, y + j * sw)))
+            .flatten()
+            .filter(|(x, y)| *x < h && *y < w)
+            .map(|(x, y)| inp[[0, x, y, chan]].as_ref())
+            .collect::<Vec<_>>();
+          let max =
+            max_gadget.make_circuit(builder, &vec![pool_targets], &vec![], gadget_config.clone());
+          outp.push(Rc::new(max[0]));
+        }
+      }
+    }
+
+    vec![Array::from_shape_vec(IxDyn(&vec![batch_size, oh, ow, c]), outp).unwrap()]
+  }
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> GadgetConsumer
+  for MaxPool2DCircuit<F, C, D>
+{
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![GadgetType::Max]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn pools_2x2_windows() {
+    let data = (0..16).map(|x| x - 8).collect::<Vec<_>>();
+    let inp = tensor(0, &[1, 4, 4, 1], &data);
+    let pool = layer("MaxPool2D", &[2, 2, 2, 2], &[&inp], 1, &[1, 2, 2, 1]);
+    let outp = prove_model(model(vec![inp], vec![pool]), true, MatMulCheck::Naive).unwrap();
+    assert_eq!(outp, vec![-3, -1, 5, 7]);
+  }
+}
@@ -0,0 +1,100 @@
+use std::{collections::HashMap, rc::Rc};
+
+use ndarray::{Array, Axis, IxDyn};
+use plonky2::{
+  hash::hash_types::RichField, iop::target::Target, plonk::circuit_builder::CircuitBuilder,
+};
+use plonky2_field::extension::Extendable;
+
+use crate::gadgets::{
+  gadget::{Gadget, GadgetConfig, GadgetType},
+  var_div::{DivRoundCircuit, DivRoundConfig},
+};
+use crate::layers::layer::GadgetConsumer;
+
+use crate::layers::layer::{Layer, LayerConfig};
+
+#[derive(Clone, Debug)]
+pub struct MeanCircuit {}
+
+impl MeanCircuit {
+  pub fn get_keep_axis(&self, layer_config: &LayerConfig) -> usize {
+    let inp_shape = &layer_config.inp_shapes[0];
+    let out_shape = &layer_config.out_shapes[0];
+    assert_eq!(inp_shape[0], 1);
+    assert_eq!(out_shape[0], 1);
+
+    // Skip the batch axis
+    let mut keep_axes = (1..inp_shape.len()).collect::<Vec<_>>();
+    for mean_axis in layer_config.layer_params.iter() {
+      keep_axes.retain(|&x| x != *mean_axis as usize);
+    }
+    assert_eq!(keep_axes.len(), 1);
+    keep_axes[0]
+  }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Layer<F, D> for MeanCircuit {
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    _constants: &HashMap<i64, Rc<F>>,
+    gadget_config: Rc<GadgetConfig>,
+    layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    let inp = &tensors[0];
+    // Only support batch size = 1
+    assert_eq!(inp.shape()[0], 1);
+    let keep_axis = self.get_keep_axis(layer_config);
+
+    let mut sum_outp = vec![];
+    for i in 0..inp.shape()[keep_axis] {
+      let slice = inp.index_axis(Axis(keep_axis), i);
+      sum_outp.push(builder.add_many(slice.iter().map(|x| x.as_ref())));
+    }
+
+    let div = inp.len() / inp.shape()[keep_axis];
+
+    let sum_ref = sum_outp.iter().collect::<Vec<_>>();
+
+    let div_config = DivRoundConfig {
+      _gadget: gadget_config.clone(),
+      no_lookups: layer_config.no_lookups,
+    };
+    let div_gadget = DivRoundCircuit::construct(Rc::new(div_config));
+    let div_outp = div_gadget.make_circuit(
+      builder,
+      &vec![sum_ref],
+      &vec![F::from_canonical_usize(div)],
+      gadget_config,
+    );
+
+    let outp = div_outp.iter().map(|t| Rc::new(*t)).collect::<Vec<_>>();
+    let out_shape = layer_config.out_shapes[0].clone();
+
+    vec![Array::from_shape_vec(IxDyn(&out_shape), outp).unwrap()]
+  }
+}
+
+impl GadgetConsumer for MeanCircuit {
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![GadgetType::DivRound]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn averages_over_axis() {
+    let inp = tensor(0, &[1, 2, 3], &[1, 2, 4, 4, 5, 7]);
+    let mean = layer("Mean", &[2], &[&inp], 1, &[1, 2]);
+    let outp = prove_model(model(vec![inp], vec![mean]), true, MatMulCheck::Naive).unwrap();
+    // 7 / 3 and 16 / 3, rounded
+    assert_eq!(outp, vec![2, 5]);
+  }
+}
@@ -0,0 +1,60 @@
+use std::{collections::HashMap, rc::Rc};
+
+use ndarray::{Array, IxDyn};
+use plonky2::{
+  hash::hash_types::RichField, iop::target::Target, plonk::circuit_builder::CircuitBuilder,
+};
+use plonky2_field::extension::Extendable;
+
+use crate::gadgets::gadget::{Gadget, GadgetType};
+use crate::gadgets::nonlinear::pow::PowGadgetCircuit;
+use crate::{gadgets::gadget::GadgetConfig, layers::layer::GadgetConsumer};
+
+use crate::layers::layer::{Layer, LayerConfig};
+#[derive(Clone, Debug)]
+pub struct PowCircuit {}
+
+impl<F: RichField + Extendable<D>, const D: usize> Layer<F, D> for PowCircuit {
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    _constants: &HashMap<i64, Rc<F>>,
+    gadget_config: Rc<GadgetConfig>,
+    _layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    let inp = &tensors[0];
+    let inp_vec = inp.iter().map(|x| x.as_ref()).collect::<Vec<_>>();
+
+    let pow_gadget = PowGadgetCircuit::construct(gadget_config.clone());
+    let vec_inps = vec![inp_vec];
+    let out = pow_gadget.make_circuit(builder, &vec_inps, &vec![], gadget_config);
+
+    let out = out.into_iter().map(|x| Rc::new(x)).collect::<Vec<_>>();
+    let out = Array::from_shape_vec(IxDyn(inp.shape()), out).unwrap();
+
+    vec![out]
+  }
+}
+
+impl GadgetConsumer for PowCircuit {
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![GadgetType::Pow]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn cubes() {
+    let inp = tensor(0, &[1, 2], &[32, -16]);
+    let pow = layer("Pow", &[], &[&inp], 1, &[1, 2]);
+    let outp = prove_model(model(vec![inp], vec![pow]), false, MatMulCheck::Naive).unwrap();
+    // 2^3 and (-1)^3 at sf = 16
+    assert_eq!(outp, vec![128, -16]);
+  }
+}
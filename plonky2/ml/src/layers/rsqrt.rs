@@ -0,0 +1,80 @@
+use std::{collections::HashMap, rc::Rc};
+
+use ndarray::{Array, IxDyn};
+use plonky2::{
+  hash::hash_types::RichField, iop::target::Target, plonk::circuit_builder::CircuitBuilder,
+};
+use plonky2_field::extension::Extendable;
+
+use crate::gadgets::gadget::{Gadget, GadgetType};
+use crate::gadgets::nonlinear::rsqrt::RsqrtGadgetCircuit;
+use crate::{gadgets::gadget::GadgetConfig, layers::layer::GadgetConsumer};
+
+use crate::layers::layer::{Layer, LayerConfig};
+#[derive(Clone, Debug)]
+pub struct RsqrtCircuit {}
+
+impl<F: RichField + Extendable<D>, const D: usize> Layer<F, D> for RsqrtCircuit {
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    constants: &HashMap<i64, Rc<F>>,
+    gadget_config: Rc<GadgetConfig>,
+    layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    let inp = &tensors[0];
+
+    // Masked inputs are out of the table's range and clamped to min_val / max_val
+    let mask = &layer_config.mask;
+    let mut mask_map = HashMap::new();
+    for i in 0..mask.len() / 2 {
+      mask_map.insert(mask[2 * i], mask[2 * i + 1]);
+    }
+    let min_val_t = builder.constant(*constants.get(&gadget_config.min_val).unwrap().as_ref());
+    let max_val_t = builder.constant(*constants.get(&gadget_config.max_val).unwrap().as_ref());
+
+    let inp_vec = inp
+      .iter()
+      .enumerate()
+      .map(|(i, x)| match mask_map.get(&(i as i64)).copied() {
+        None => x.as_ref(),
+        Some(1) => &max_val_t,
+        Some(-1) => &min_val_t,
+        Some(_) => panic!(),
+      })
+      .collect::<Vec<_>>();
+
+    let rsqrt_gadget = RsqrtGadgetCircuit::construct(gadget_config.clone());
+    let vec_inps = vec![inp_vec];
+    let out = rsqrt_gadget.make_circuit(builder, &vec_inps, &vec![], gadget_config);
+
+    let out = out.into_iter().map(|x| Rc::new(x)).collect::<Vec<_>>();
+    let out = Array::from_shape_vec(IxDyn(inp.shape()), out).unwrap();
+
+    vec![out]
+  }
+}
+
+impl GadgetConsumer for RsqrtCircuit {
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![GadgetType::Rsqrt]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn masked_inputs_take_max_val() {
+    let inp = tensor(0, &[1, 3], &[64, 16, -5]);
+    let mut rsqrt = layer("Rsqrt", &[], &[&inp], 1, &[1, 3]);
+    rsqrt.mask = vec![2, 1];
+    let outp = prove_model(model(vec![inp], vec![rsqrt]), false, MatMulCheck::Naive).unwrap();
+    // 1 / sqrt(502 / 16) * 16 = 2.86 for the masked element
+    assert_eq!(outp, vec![8, 16, 3]);
+  }
+}
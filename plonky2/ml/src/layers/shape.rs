@@ -1,6 +1,13 @@
+pub mod broadcast;
 pub mod concatenation;
 pub mod gather;
+pub mod mask_neg_inf;
 pub mod pack;
+pub mod pad;
+pub mod permute;
 pub mod reshape;
+pub mod resize_nn;
+pub mod rotate;
+pub mod slice;
 pub mod split;
 pub mod transpose;
@@ -0,0 +1,77 @@
+use std::{collections::HashMap, rc::Rc};
+
+use ndarray::{Array, IxDyn};
+use plonky2::{
+  hash::hash_types::RichField, iop::target::Target, plonk::circuit_builder::CircuitBuilder,
+};
+use plonky2_field::extension::Extendable;
+
+use crate::{gadgets::gadget::GadgetConfig, layers::layer::GadgetConsumer};
+
+use super::super::layer::{Layer, LayerConfig};
+
+pub struct BroadcastCircuit {}
+
+impl<F: RichField + Extendable<D>, const D: usize> Layer<F, D> for BroadcastCircuit {
+  fn make_circuit(
+    &self,
+    _builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    _constants: &HashMap<i64, Rc<F>>,
+    _gadget_config: Rc<GadgetConfig>,
+    layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    let inp = &tensors[0];
+    let shape = inp.shape();
+    let output_shape = layer_config.out_shapes[0].clone();
+
+    // Check that we only broadcast dimensions with shape 1
+    assert!(shape.len() == output_shape.len());
+    assert!(shape.len() == 4);
+    for (inp, outp) in shape.iter().zip(output_shape.iter()) {
+      if *inp != *outp && !(*inp == 1) {
+        panic!();
+      }
+    }
+
+    let mut output_flat = vec![];
+    for i in 0..output_shape[0] {
+      for j in 0..output_shape[1] {
+        for k in 0..output_shape[2] {
+          for l in 0..output_shape[3] {
+            let indexes = [i, j, k, l]
+              .iter()
+              .enumerate()
+              .map(|(idx, x)| if shape[idx] == 1 { 0 } else { *x })
+              .collect::<Vec<_>>();
+            output_flat.push(inp[[indexes[0], indexes[1], indexes[2], indexes[3]]].clone());
+          }
+        }
+      }
+    }
+
+    let out = Array::from_shape_vec(IxDyn(&output_shape), output_flat).unwrap();
+    vec![out]
+  }
+}
+
+impl GadgetConsumer for BroadcastCircuit {
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn repeats_unit_axes() {
+    let inp = tensor(0, &[1, 1, 2, 1], &[1, 2]);
+    let broadcast = layer("Broadcast", &[], &[&inp], 1, &[1, 2, 2, 1]);
+    let outp = prove_model(model(vec![inp], vec![broadcast]), true, MatMulCheck::Naive).unwrap();
+    assert_eq!(outp, vec![1, 2, 1, 2]);
+  }
+}
@@ -0,0 +1,70 @@
+use std::{collections::HashMap, rc::Rc};
+
+use ndarray::{Array, IxDyn};
+use plonky2::{
+  hash::hash_types::RichField, iop::target::Target, plonk::circuit_builder::CircuitBuilder,
+};
+use plonky2_field::extension::Extendable;
+
+use crate::{gadgets::gadget::GadgetConfig, layers::layer::GadgetConsumer};
+
+use super::super::layer::{Layer, LayerConfig};
+
+pub struct MaskNegInfCircuit {}
+
+impl<F: RichField + Extendable<D>, const D: usize> Layer<F, D> for MaskNegInfCircuit {
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    constants: &HashMap<i64, Rc<F>>,
+    gadget_config: Rc<GadgetConfig>,
+    layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    let inp = &tensors[0];
+    let mask_ndim = layer_config.layer_params[0] as usize;
+    let mask_shape = layer_config.layer_params[1..mask_ndim + 1]
+      .iter()
+      .map(|x| *x as usize)
+      .collect::<Vec<_>>();
+    let mask_vec = layer_config.layer_params[mask_ndim + 1..].to_vec();
+    let mask = Array::from_shape_vec(IxDyn(&mask_shape), mask_vec).unwrap();
+    let mask = mask.broadcast(inp.raw_dim()).unwrap();
+
+    let min_val =
+      Rc::new(builder.constant(*constants.get(&gadget_config.min_val).unwrap().as_ref()));
+
+    let mut out_vec = vec![];
+    for (val, to_mask) in inp.iter().zip(mask.iter()) {
+      if *to_mask == 0 {
+        out_vec.push(val.clone());
+      } else {
+        out_vec.push(min_val.clone());
+      }
+    }
+
+    let outp = Array::from_shape_vec(inp.raw_dim(), out_vec).unwrap();
+    vec![outp]
+  }
+}
+
+impl GadgetConsumer for MaskNegInfCircuit {
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn masks_to_min_val() {
+    let inp = tensor(0, &[1, 3], &[5, 6, 7]);
+    let mask = layer("MaskNegInf", &[2, 1, 3, 0, 1, 0], &[&inp], 1, &[1, 3]);
+    let outp = prove_model(model(vec![inp], vec![mask]), true, MatMulCheck::Naive).unwrap();
+    assert_eq!(outp, vec![5, -512, 7]);
+  }
+}
@@ -0,0 +1,81 @@
+use std::{collections::HashMap, marker::PhantomData, rc::Rc};
+
+use ndarray::{Array, IxDyn};
+use plonky2::{
+  hash::hash_types::RichField,
+  iop::target::Target,
+  plonk::{circuit_builder::CircuitBuilder, config::GenericConfig},
+};
+use plonky2_field::extension::Extendable;
+
+use crate::{
+  gadgets::gadget::GadgetConfig,
+  layers::{conv2d::Conv2DCircuit, layer::GadgetConsumer},
+};
+
+use super::super::layer::{Layer, LayerConfig};
+
+pub struct PadConfig {
+  pub padding: Vec<[usize; 2]>,
+}
+
+pub struct PadCircuit<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> {
+  pub(crate) _marker: PhantomData<C>,
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> PadCircuit<F, C, D> {
+  pub fn param_vec_to_config(layer_params: Vec<i64>) -> PadConfig {
+    assert!(layer_params.len() % 2 == 0);
+
+    let padding = layer_params
+      .chunks(2)
+      .map(|chunk| [chunk[0] as usize, chunk[1] as usize])
+      .collect();
+    PadConfig { padding }
+  }
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F> + 'static, const D: usize> Layer<F, D>
+  for PadCircuit<F, C, D>
+{
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    _constants: &HashMap<i64, Rc<F>>,
+    _gadget_config: Rc<GadgetConfig>,
+    layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    // FIXME: the pad from tflite is actually two, but mine is one
+    let input = &tensors[0];
+
+    let zero = Rc::new(builder.zero());
+    let padding = Self::param_vec_to_config(layer_config.layer_params.clone());
+    let padded = Conv2DCircuit::<F, C, D>::pad(input, padding.padding, &zero);
+
+    vec![padded]
+  }
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> GadgetConsumer
+  for PadCircuit<F, C, D>
+{
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn pads_with_zeros() {
+    let inp = tensor(0, &[1, 2], &[3, 4]);
+    let pad = layer("Pad", &[0, 0, 1, 1], &[&inp], 1, &[1, 4]);
+    let outp = prove_model(model(vec![inp], vec![pad]), true, MatMulCheck::Naive).unwrap();
+    assert_eq!(outp, vec![0, 3, 4, 0]);
+  }
+}
@@ -0,0 +1,58 @@
+use std::{collections::HashMap, rc::Rc};
+
+use ndarray::{Array, IxDyn};
+use plonky2::{
+  hash::hash_types::RichField, iop::target::Target, plonk::circuit_builder::CircuitBuilder,
+};
+use plonky2_field::extension::Extendable;
+
+use crate::{gadgets::gadget::GadgetConfig, layers::layer::GadgetConsumer};
+
+use super::super::layer::{Layer, LayerConfig};
+
+pub struct PermuteCircuit {}
+
+impl<F: RichField + Extendable<D>, const D: usize> Layer<F, D> for PermuteCircuit {
+  fn make_circuit(
+    &self,
+    _builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    _constants: &HashMap<i64, Rc<F>>,
+    _gadget_config: Rc<GadgetConfig>,
+    layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    let inp = &tensors[0];
+    let params = &layer_config
+      .layer_params
+      .iter()
+      .map(|x| *x as usize)
+      .collect::<Vec<_>>()[..];
+
+    assert!(inp.ndim() == params.len());
+
+    let out = inp.clone();
+    let out = out.permuted_axes(IxDyn(params));
+    vec![out]
+  }
+}
+
+impl GadgetConsumer for PermuteCircuit {
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn swaps_axes() {
+    let inp = tensor(0, &[1, 2, 3], &[1, 2, 3, 4, 5, 6]);
+    let permute = layer("Permute", &[0, 2, 1], &[&inp], 1, &[1, 3, 2]);
+    let outp = prove_model(model(vec![inp], vec![permute]), true, MatMulCheck::Naive).unwrap();
+    assert_eq!(outp, vec![1, 4, 2, 5, 3, 6]);
+  }
+}
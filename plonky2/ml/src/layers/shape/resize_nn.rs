@@ -0,0 +1,70 @@
+use std::{collections::HashMap, rc::Rc};
+
+use ndarray::{Array, IxDyn};
+use plonky2::{
+  hash::hash_types::RichField, iop::target::Target, plonk::circuit_builder::CircuitBuilder,
+};
+use plonky2_field::extension::Extendable;
+
+use crate::{gadgets::gadget::GadgetConfig, layers::layer::GadgetConsumer};
+
+use super::super::layer::{Layer, LayerConfig};
+
+pub struct ResizeNNCircuit {}
+
+impl<F: RichField + Extendable<D>, const D: usize> Layer<F, D> for ResizeNNCircuit {
+  fn make_circuit(
+    &self,
+    _builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    _constants: &HashMap<i64, Rc<F>>,
+    _gadget_config: Rc<GadgetConfig>,
+    layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    let inp = &tensors[0];
+    let output_shape = layer_config.out_shapes[0].clone();
+
+    assert_eq!(inp.ndim(), 4);
+    assert_eq!(inp.shape()[0], 1);
+    assert_eq!(inp.shape()[3], output_shape[3]);
+
+    let mut flat = vec![];
+    // Do nearest neighbor interpolation over batch, h, w, c
+    // The interpolation is over h and w
+    for b in 0..inp.shape()[0] {
+      for h in 0..output_shape[1] {
+        let h_in = (h as f64 * (inp.shape()[1] as f64 / output_shape[1] as f64)) as usize;
+        for w in 0..output_shape[2] {
+          let w_in = (w as f64 * (inp.shape()[2] as f64 / output_shape[2] as f64)) as usize;
+          for c in 0..inp.shape()[3] {
+            flat.push(inp[[b, h_in, w_in, c]].clone());
+          }
+        }
+      }
+    }
+
+    let outp = Array::from_shape_vec(IxDyn(&output_shape), flat).unwrap();
+    vec![outp]
+  }
+}
+
+impl GadgetConsumer for ResizeNNCircuit {
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn upsamples_nearest() {
+    let inp = tensor(0, &[1, 1, 2, 1], &[1, 2]);
+    let resize = layer("ResizeNearestNeighbor", &[], &[&inp], 1, &[1, 2, 4, 1]);
+    let outp = prove_model(model(vec![inp], vec![resize]), true, MatMulCheck::Naive).unwrap();
+    assert_eq!(outp, vec![1, 1, 2, 2, 1, 1, 2, 2]);
+  }
+}
@@ -0,0 +1,76 @@
+use std::{collections::HashMap, rc::Rc};
+
+use ndarray::{Array, IxDyn};
+use plonky2::{
+  hash::hash_types::RichField, iop::target::Target, plonk::circuit_builder::CircuitBuilder,
+};
+use plonky2_field::extension::Extendable;
+
+use crate::{gadgets::gadget::GadgetConfig, layers::layer::GadgetConsumer};
+
+use super::super::layer::{Layer, LayerConfig};
+
+pub struct RotateCircuit {}
+
+impl<F: RichField + Extendable<D>, const D: usize> Layer<F, D> for RotateCircuit {
+  fn make_circuit(
+    &self,
+    _builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    _constants: &HashMap<i64, Rc<F>>,
+    _gadget_config: Rc<GadgetConfig>,
+    layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    let inp = &tensors[0];
+    let params = &layer_config.layer_params;
+
+    assert!(inp.shape().len() == 4);
+
+    let mut flip = vec![false; 4];
+    for p in params {
+      flip[*p as usize] = true;
+    }
+    let shape = inp.shape();
+
+    let mut out = inp.clone();
+    for i in 0..shape[0] {
+      for j in 0..shape[1] {
+        for k in 0..shape[2] {
+          for l in 0..shape[3] {
+            let [ix, jx, kx, lx]: [usize; 4] = [i, j, k, l]
+              .iter()
+              .enumerate()
+              .map(|(idx, x)| if flip[idx] { shape[idx] - 1 - *x } else { *x })
+              .collect::<Vec<_>>()
+              .try_into()
+              .unwrap();
+            out[[ix, jx, kx, lx]] = inp[[i, j, k, l]].clone();
+          }
+        }
+      }
+    }
+
+    vec![out]
+  }
+}
+
+impl GadgetConsumer for RotateCircuit {
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn flips_axis() {
+    let inp = tensor(0, &[1, 1, 3, 1], &[1, 2, 3]);
+    let rotate = layer("Rotate", &[2], &[&inp], 1, &[1, 1, 3, 1]);
+    let outp = prove_model(model(vec![inp], vec![rotate]), true, MatMulCheck::Naive).unwrap();
+    assert_eq!(outp, vec![3, 2, 1]);
+  }
+}
@@ -0,0 +1,63 @@
+use std::{collections::HashMap, rc::Rc};
+
+use ndarray::{Array, IxDyn, Slice};
+use plonky2::{
+  hash::hash_types::RichField, iop::target::Target, plonk::circuit_builder::CircuitBuilder,
+};
+use plonky2_field::extension::Extendable;
+
+use crate::{gadgets::gadget::GadgetConfig, layers::layer::GadgetConsumer};
+
+use super::super::layer::{Layer, LayerConfig};
+
+pub struct SliceCircuit {}
+
+impl<F: RichField + Extendable<D>, const D: usize> Layer<F, D> for SliceCircuit {
+  fn make_circuit(
+    &self,
+    _builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    _constants: &HashMap<i64, Rc<F>>,
+    _gadget_config: Rc<GadgetConfig>,
+    layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    let params = &layer_config.layer_params;
+    assert_eq!(params.len() % 2, 0);
+    let num_axes = params.len() / 2;
+    let starts = &params[0..num_axes];
+    let sizes = &params[num_axes..];
+
+    let inp = &tensors[0];
+    let outp = inp.slice_each_axis(|ax| {
+      let start = starts[ax.axis.0] as usize;
+      let size = sizes[ax.axis.0];
+      if size == -1 {
+        Slice::from(start..)
+      } else {
+        Slice::from(start..(start + size as usize))
+      }
+    });
+    vec![outp.to_owned()]
+  }
+}
+
+impl GadgetConsumer for SliceCircuit {
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn slices_to_end() {
+    let inp = tensor(0, &[1, 4], &[1, 2, 3, 4]);
+    let slice = layer("Slice", &[0, 1, 1, -1], &[&inp], 1, &[1, 3]);
+    let outp = prove_model(model(vec![inp], vec![slice]), true, MatMulCheck::Naive).unwrap();
+    assert_eq!(outp, vec![2, 3, 4]);
+  }
+}
@@ -0,0 +1,198 @@
+use std::{collections::HashMap, rc::Rc};
+
+use ndarray::{s, Array, IxDyn};
+use plonky2::{
+  hash::hash_types::RichField, iop::target::Target, plonk::circuit_builder::CircuitBuilder,
+};
+use plonky2_field::extension::Extendable;
+
+use crate::gadgets::{
+  gadget::{Gadget, GadgetConfig, GadgetType},
+  max::MaxCircuit,
+  nonlinear::exp::ExpGadgetCircuit,
+  var_div_big::VarDivRoundBigCircuit,
+};
+use crate::layers::layer::GadgetConsumer;
+
+use crate::layers::layer::{Layer, LayerConfig};
+
+#[derive(Clone, Debug)]
+pub struct SoftmaxCircuit {}
+
+impl SoftmaxCircuit {
+  pub fn softmax_flat<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    inp_flat: Vec<&Target>,
+    gadget_config: Rc<GadgetConfig>,
+    mask: &Vec<i64>,
+  ) -> Vec<Target> {
+    let max_gadget = MaxCircuit::construct(gadget_config.clone());
+    let exp_gadget = ExpGadgetCircuit::construct(gadget_config.clone());
+    let var_div_big_gadget = VarDivRoundBigCircuit::construct(gadget_config.clone());
+
+    // Mask the input for max computation and subtraction
+    let inp_take = inp_flat
+      .iter()
+      .enumerate()
+      .filter(|(i, _)| mask[*i] == 0) // Awkwardly, 1 = take negative infinity
+      .map(|(_, x)| *x)
+      .collect::<Vec<_>>();
+
+    // Compute the max
+    let max = max_gadget.make_circuit(
+      builder,
+      &vec![inp_take.clone()],
+      &vec![],
+      gadget_config.clone(),
+    )[0];
+
+    // Subtract the max
+    let sub = inp_take
+      .iter()
+      .map(|x| builder.sub(**x, max))
+      .collect::<Vec<_>>();
+
+    // Compute the exp, scaled by sf^2
+    let exp_slice = exp_gadget.make_circuit(
+      builder,
+      &vec![sub.iter().collect()],
+      &vec![],
+      gadget_config.clone(),
+    );
+
+    // Compute the sum
+    let sum = builder.add_many(exp_slice.iter());
+
+    // Divide the sum by sf, as in halo2
+    let sf = builder.constant(F::from_canonical_u64(gadget_config.scale_factor));
+    let sum_div_sf = var_div_big_gadget.make_circuit(
+      builder,
+      &vec![vec![&sum], vec![&sf]],
+      &vec![],
+      gadget_config.clone(),
+    )[0];
+
+    // exp / (sum / sf), scaled by sf
+    let dived = var_div_big_gadget.make_circuit(
+      builder,
+      &vec![exp_slice.iter().collect(), vec![&sum_div_sf]],
+      &vec![],
+      gadget_config,
+    );
+
+    // Take either zero (softmax(-inf)) or the result
+    let zero = builder.zero();
+    let mut div_idx = 0;
+    mask
+      .iter()
+      .map(|x| {
+        if *x == 1 {
+          zero
+        } else {
+          let tmp = dived[div_idx];
+          div_idx = div_idx + 1;
+          tmp
+        }
+      })
+      .collect()
+  }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Layer<F, D> for SoftmaxCircuit {
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    _constants: &HashMap<i64, Rc<F>>,
+    gadget_config: Rc<GadgetConfig>,
+    layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    let inp = &tensors[0];
+    assert!(inp.ndim() == 2 || inp.ndim() == 3 || inp.ndim() == 4);
+    if inp.ndim() == 4 {
+      assert_eq!(inp.shape()[0], 1);
+    }
+
+    let inp_shape = inp.shape().iter().map(|x| *x).collect::<Vec<_>>();
+    let mask = if layer_config.layer_params.len() == 0 {
+      Array::from_shape_fn(IxDyn(&inp_shape), |_| 0)
+    } else {
+      let mask_shape_len = layer_config.layer_params[0] as usize;
+      let mask_shape = layer_config.layer_params[1..(1 + mask_shape_len)]
+        .iter()
+        .map(|x| *x as usize)
+        .collect::<Vec<_>>();
+      let mask = layer_config.layer_params[(1 + mask_shape_len)..].to_vec();
+      let mask = Array::from_shape_vec(IxDyn(&mask_shape), mask).unwrap();
+      let mask = mask.broadcast(IxDyn(&inp_shape)).unwrap().to_owned();
+      mask
+    };
+
+    let shape = if inp.ndim() == 2 || inp.ndim() == 3 {
+      inp_shape.clone()
+    } else {
+      vec![inp.shape()[1], inp.shape()[2], inp.shape()[3]]
+    };
+    // Required because of memory layout issues
+    let inp_flat = inp.iter().cloned().collect::<Vec<_>>();
+    let inp = Array::from_shape_vec(IxDyn(&shape), inp_flat).unwrap();
+    let mask_flat = mask.iter().cloned().collect::<Vec<_>>();
+    let mask = Array::from_shape_vec(IxDyn(&shape), mask_flat).unwrap();
+
+    // Softmax over the last axis
+    let mut outp = vec![];
+    if inp.ndim() == 2 {
+      for i in 0..shape[0] {
+        let inp_slice = inp.slice(s![i, ..]);
+        let inp_flat = inp_slice.iter().map(|x| x.as_ref()).collect::<Vec<_>>();
+        let mask_flat = mask.slice(s![i, ..]).iter().cloned().collect::<Vec<_>>();
+        let dived = Self::softmax_flat(builder, inp_flat, gadget_config.clone(), &mask_flat);
+        outp.extend(dived);
+      }
+    } else {
+      for i in 0..shape[0] {
+        for j in 0..shape[1] {
+          let inp_slice = inp.slice(s![i, j, ..]);
+          let inp_flat = inp_slice.iter().map(|x| x.as_ref()).collect::<Vec<_>>();
+          let mask_flat = mask.slice(s![i, j, ..]).iter().cloned().collect::<Vec<_>>();
+          let dived = Self::softmax_flat(builder, inp_flat, gadget_config.clone(), &mask_flat);
+          outp.extend(dived);
+        }
+      }
+    }
+
+    let outp = outp.into_iter().map(|x| Rc::new(x)).collect::<Vec<_>>();
+    vec![Array::from_shape_vec(IxDyn(&inp_shape), outp).unwrap()]
+  }
+}
+
+impl GadgetConsumer for SoftmaxCircuit {
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![GadgetType::Exp, GadgetType::Max, GadgetType::VarDivRoundBig]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn normalizes_last_axis() {
+    let inp = tensor(0, &[1, 3], &[0, 16, 0]);
+    let softmax = layer("Softmax", &[], &[&inp], 1, &[1, 3]);
+    let outp = prove_model(model(vec![inp], vec![softmax]), false, MatMulCheck::Naive).unwrap();
+    // exp(x - max) = [94, 256, 94] at sf^2, and their sum / sf = 28
+    assert_eq!(outp, vec![3, 9, 3]);
+  }
+
+  #[test]
+  fn masked_entries_are_zero() {
+    let inp = tensor(0, &[1, 3], &[0, 16, 0]);
+    let softmax = layer("Softmax", &[2, 1, 3, 0, 0, 1], &[&inp], 1, &[1, 3]);
+    let outp = prove_model(model(vec![inp], vec![softmax]), false, MatMulCheck::Naive).unwrap();
+    // Only [94, 256] enter the sum, so sum / sf = 22
+    assert_eq!(outp, vec![4, 12, 0]);
+  }
+}
@@ -0,0 +1,76 @@
+use std::{collections::HashMap, rc::Rc};
+
+use ndarray::{Array, IxDyn};
+use plonky2::{
+  hash::hash_types::RichField, iop::target::Target, plonk::circuit_builder::CircuitBuilder,
+};
+use plonky2_field::extension::Extendable;
+
+use crate::gadgets::{
+  gadget::{Gadget, GadgetConfig, GadgetType},
+  var_div::{DivRoundCircuit, DivRoundConfig},
+};
+use crate::layers::layer::GadgetConsumer;
+
+use crate::layers::layer::{Layer, LayerConfig};
+
+#[derive(Clone, Debug)]
+pub struct SquareCircuit {}
+
+impl<F: RichField + Extendable<D>, const D: usize> Layer<F, D> for SquareCircuit {
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    _constants: &HashMap<i64, Rc<F>>,
+    gadget_config: Rc<GadgetConfig>,
+    layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    assert_eq!(tensors.len(), 1);
+    let inp = &tensors[0];
+
+    let square_outp = inp
+      .iter()
+      .map(|x| builder.square(*x.as_ref()))
+      .collect::<Vec<_>>();
+    let square_ref = square_outp.iter().collect::<Vec<_>>();
+
+    let div_config = DivRoundConfig {
+      _gadget: gadget_config.clone(),
+      no_lookups: layer_config.no_lookups,
+    };
+    let div_gadget = DivRoundCircuit::construct(Rc::new(div_config));
+    let div_outp = div_gadget.make_circuit(
+      builder,
+      &vec![square_ref],
+      &vec![F::from_canonical_u64(gadget_config.scale_factor)],
+      gadget_config,
+    );
+
+    let outp = div_outp.iter().map(|t| Rc::new(*t)).collect::<Vec<_>>();
+
+    vec![Array::from_shape_vec(IxDyn(inp.shape()), outp).unwrap()]
+  }
+}
+
+impl GadgetConsumer for SquareCircuit {
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![GadgetType::DivRound]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn squares_and_rescales() {
+    let inp = tensor(0, &[1, 3], &[20, -20, 4]);
+    let square = layer("Square", &[], &[&inp], 1, &[1, 3]);
+    let outp = prove_model(model(vec![inp], vec![square]), true, MatMulCheck::Naive).unwrap();
+    // 400 / 16 and 16 / 16
+    assert_eq!(outp, vec![25, 25, 1]);
+  }
+}
@@ -0,0 +1,82 @@
+use std::{collections::HashMap, rc::Rc};
+
+use ndarray::{Array, IxDyn};
+use plonky2::{
+  hash::hash_types::RichField, iop::target::Target, plonk::circuit_builder::CircuitBuilder,
+};
+use plonky2_field::extension::Extendable;
+
+use crate::gadgets::{
+  gadget::{Gadget, GadgetConfig, GadgetType},
+  var_div::{DivRoundCircuit, DivRoundConfig},
+};
+use crate::layers::layer::GadgetConsumer;
+use crate::utils::helpers::broadcast;
+
+use crate::layers::layer::{Layer, LayerConfig};
+
+#[derive(Clone, Debug)]
+pub struct SquaredDiffCircuit {}
+
+impl<F: RichField + Extendable<D>, const D: usize> Layer<F, D> for SquaredDiffCircuit {
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    _constants: &HashMap<i64, Rc<F>>,
+    gadget_config: Rc<GadgetConfig>,
+    layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    assert_eq!(tensors.len(), 2);
+    // Broadcasting allowed... can't check shapes easily
+    let (inp1, inp2) = broadcast(&tensors[0], &tensors[1]);
+
+    let sq_diff_outp = inp1
+      .iter()
+      .zip(inp2.iter())
+      .map(|(x, y)| {
+        let diff = builder.sub(*x.as_ref(), *y.as_ref());
+        builder.square(diff)
+      })
+      .collect::<Vec<_>>();
+    let sq_diff_ref = sq_diff_outp.iter().collect::<Vec<_>>();
+
+    let div_config = DivRoundConfig {
+      _gadget: gadget_config.clone(),
+      no_lookups: layer_config.no_lookups,
+    };
+    let div_gadget = DivRoundCircuit::construct(Rc::new(div_config));
+    let div_outp = div_gadget.make_circuit(
+      builder,
+      &vec![sq_diff_ref],
+      &vec![F::from_canonical_u64(gadget_config.scale_factor)],
+      gadget_config,
+    );
+
+    let outp = div_outp.iter().map(|t| Rc::new(*t)).collect::<Vec<_>>();
+
+    vec![Array::from_shape_vec(IxDyn(inp1.shape()), outp).unwrap()]
+  }
+}
+
+impl GadgetConsumer for SquaredDiffCircuit {
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![GadgetType::DivRound]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn squares_the_difference() {
+    let a = tensor(0, &[1, 2], &[20, 4]);
+    let b = tensor(1, &[1, 2], &[0, 8]);
+    let sq_diff = layer("SquaredDifference", &[], &[&a, &b], 2, &[1, 2]);
+    let outp = prove_model(model(vec![a, b], vec![sq_diff]), true, MatMulCheck::Naive).unwrap();
+    assert_eq!(outp, vec![25, 1]);
+  }
+}
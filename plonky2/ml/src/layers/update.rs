@@ -0,0 +1,91 @@
+use std::{collections::HashMap, rc::Rc};
+
+use ndarray::{Array, IxDyn};
+use plonky2::{
+  hash::hash_types::RichField, iop::target::Target, plonk::circuit_builder::CircuitBuilder,
+};
+use plonky2_field::extension::Extendable;
+
+use crate::gadgets::{
+  gadget::{Gadget, GadgetConfig, GadgetType},
+  var_div::{DivRoundCircuit, DivRoundConfig},
+};
+use crate::layers::layer::GadgetConsumer;
+
+use crate::layers::layer::{Layer, LayerConfig};
+
+// SGD step w - eta * dw, with the same fixed eta = sf / 1000 as the halo2 update gadget
+#[derive(Clone, Debug)]
+pub struct UpdateCircuit {}
+
+impl<F: RichField + Extendable<D>, const D: usize> Layer<F, D> for UpdateCircuit {
+  fn make_circuit(
+    &self,
+    builder: &mut CircuitBuilder<F, D>,
+    tensors: &Vec<Array<Rc<Target>, IxDyn>>,
+    _constants: &HashMap<i64, Rc<F>>,
+    gadget_config: Rc<GadgetConfig>,
+    layer_config: &LayerConfig,
+    _rand_targets: &mut Vec<Target>,
+  ) -> Vec<Array<Rc<Target>, IxDyn>> {
+    let w = &tensors[0];
+    let dw = &tensors[1];
+    assert_eq!(w.len(), dw.len());
+
+    let sf = F::from_canonical_u64(gadget_config.scale_factor);
+    let eta = F::from_canonical_u64(gadget_config.scale_factor / 1000);
+
+    // w * sf - dw * eta
+    let scaled_outp = w
+      .iter()
+      .zip(dw.iter())
+      .map(|(w, dw)| {
+        let w_sf = builder.mul_const(sf, *w.as_ref());
+        let dw_eta = builder.mul_const(eta, *dw.as_ref());
+        builder.sub(w_sf, dw_eta)
+      })
+      .collect::<Vec<_>>();
+    let scaled_ref = scaled_outp.iter().collect::<Vec<_>>();
+
+    // Rounds, where the halo2 gadget floors
+    let div_config = DivRoundConfig {
+      _gadget: gadget_config.clone(),
+      no_lookups: layer_config.no_lookups,
+    };
+    let div_gadget = DivRoundCircuit::construct(Rc::new(div_config));
+    let div_outp = div_gadget.make_circuit(builder, &vec![scaled_ref], &vec![sf], gadget_config);
+
+    let outp = div_outp.iter().map(|t| Rc::new(*t)).collect::<Vec<_>>();
+
+    vec![Array::from_shape_vec(IxDyn(w.shape()), outp).unwrap()]
+  }
+}
+
+impl GadgetConsumer for UpdateCircuit {
+  fn used_gadgets(&self, _layer_params: Vec<i64>) -> Vec<crate::gadgets::gadget::GadgetType> {
+    vec![GadgetType::DivRound]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::loader::ModelMsgpack;
+  use crate::utils::testing::{layer, model, prove_model, tensor};
+
+  #[test]
+  fn steps_against_gradient() {
+    let w = tensor(0, &[1, 2], &[5, -3]);
+    let dw = tensor(1, &[1, 2], &[600, 1000]);
+    let update = layer("Update", &[], &[&w, &dw], 2, &[1, 2]);
+    // eta = sf / 1000 is zero at the default sf
+    let config = ModelMsgpack {
+      global_sf: 1024,
+      k: 12,
+      ..model(vec![w, dw], vec![update])
+    };
+    let outp = prove_model(config, true, MatMulCheck::Naive).unwrap();
+    // (w * 1024 - dw) / 1024, rounded
+    assert_eq!(outp, vec![4, -4]);
+  }
+}
@@ -1,5 +1,6 @@
 #![feature(int_roundings)]
 
+pub mod commitments;
 pub mod gadgets;
 pub mod gates;
 pub mod layers;
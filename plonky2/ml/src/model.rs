@@ -15,28 +15,48 @@ use plonky2::{hash::hash_types::RichField, iop::witness::WitnessWrite};
 use plonky2::{iop::target::Target, plonk::config::GenericConfig};
 
 use crate::{
+  commitments::poseidon_commit::PoseidonCommitCircuit,
   gadgets::{
     bias_div_round_relu6::BiasDivRoundRelu6Circuit,
     dot_prod::DotProductCircuit,
     gadget::{GadgetConfig, GadgetType},
     input_lookup::InputLookupCircuit,
-    nonlinear::{logistic::LogisticGadgetCircuit, relu::ReluCircuit},
+    max::MaxCircuit,
+    nonlinear::{
+      exp::ExpGadgetCircuit, logistic::LogisticGadgetCircuit, pow::PowGadgetCircuit,
+      relu::ReluCircuit, rsqrt::RsqrtGadgetCircuit, sqrt::SqrtGadgetCircuit,
+      tanh::TanhGadgetCircuit,
+    },
     var_div::DivRoundCircuit,
+    var_div_big::VarDivRoundBigCircuit,
   },
   layers::{
-    arithmetic::{add::AddCircuit, mul::MulCircuit},
+    arithmetic::{add::AddCircuit, div_var::DivVarCircuit, mul::MulCircuit, sub::SubCircuit},
     avg_pool_2d::AvgPool2DCircuit,
     batch_mat_mul::BatchMatMulCircuit,
     conv2d::Conv2DCircuit,
     dag::{DAGLayerCircuit, DAGLayerConfig},
+    div_fixed::DivFixedCircuit,
     fully_connected::{FullyConnectedCircuit, FullyConnectedConfig},
-    layer::{GadgetConsumer, LayerConfig, LayerType},
+    layer::{GadgetConsumer, LayerConfig, LayerType, MatMulCheck},
     logistic::LogisticCircuit,
+    max_pool_2d::MaxPool2DCircuit,
+    mean::MeanCircuit,
     noop::NoopCircuit,
+    pow::PowCircuit,
+    rsqrt::RsqrtCircuit,
     shape::{
-      concatenation::ConcatenationCircuit, gather::GatherCircuit, pack::PackCircuit,
-      reshape::ReshapeCircuit, split::SplitCircuit, transpose::TransposeCircuit,
+      broadcast::BroadcastCircuit, concatenation::ConcatenationCircuit, gather::GatherCircuit,
+      mask_neg_inf::MaskNegInfCircuit, pack::PackCircuit, pad::PadCircuit, permute::PermuteCircuit,
+      reshape::ReshapeCircuit, resize_nn::ResizeNNCircuit, rotate::RotateCircuit,
+      slice::SliceCircuit, split::SplitCircuit, transpose::TransposeCircuit,
     },
+    softmax::SoftmaxCircuit,
+    sqrt::SqrtCircuit,
+    square::SquareCircuit,
+    squared_diff::SquaredDiffCircuit,
+    tanh::TanhCircuit,
+    update::UpdateCircuit,
   },
   utils::loader::{load_model_msgpack, ModelMsgpack},
 };
@@ -52,6 +72,8 @@ pub struct ModelCircuit {
   pub k: usize,
   pub bits_per_elem: usize,
   pub inp_idxes: Vec<i64>,
+  pub commit_before: Vec<Vec<i64>>,
+  pub commit_after: Vec<Vec<i64>>,
 }
 
 impl ModelCircuit {
@@ -110,9 +132,10 @@ impl ModelCircuit {
     inp_file: &str,
     sweep_variable: &usize,
     no_lookups: bool,
+    matmul_check: MatMulCheck,
   ) -> (ModelCircuit, CircuitBuilder<F, D>, PartialWitness<F>) {
     let config = load_model_msgpack(config_file, inp_file);
-    Self::generate_from_msgpack::<F, C, D>(config, true, sweep_variable, no_lookups)
+    Self::generate_from_msgpack::<F, C, D>(config, true, sweep_variable, no_lookups, matmul_check)
   }
 
   pub fn generate_from_msgpack<
@@ -124,6 +147,7 @@ impl ModelCircuit {
     panic_empty_tensor: bool,
     sweep_variable: &usize,
     no_lookups: bool,
+    matmul_check: MatMulCheck,
   ) -> (ModelCircuit, CircuitBuilder<F, D>, PartialWitness<F>) {
     let to_field = |x: i64| {
       let bias = 1 << 31;
@@ -131,24 +155,6 @@ impl ModelCircuit {
       F::from_canonical_u64(x_pos as u64) - F::from_canonical_u64(bias as u64)
     };
 
-    let match_layer = |x: &str| match x {
-      "Add" => LayerType::Add,
-      "AveragePool2D" => LayerType::AvgPool2D,
-      "BatchMatMul" => LayerType::BatchMatMul,
-      "Concatenation" => LayerType::Concatenation,
-      "Conv2D" => LayerType::Conv2D,
-      "FullyConnected" => LayerType::FullyConnected,
-      "Gather" => LayerType::Gather,
-      "Logistic" => LayerType::Logistic,
-      "Mul" => LayerType::Mul,
-      "Noop" => LayerType::Noop,
-      "Pack" => LayerType::Pack,
-      "Reshape" => LayerType::Reshape,
-      "Split" => LayerType::Split,
-      "Transpose" => LayerType::Transpose,
-      _ => panic!("unknown op: {}", x),
-    };
-
     if *sweep_variable < 25 {
       panic!("Invalid Sweep Variable")
     }
@@ -161,15 +167,25 @@ impl ModelCircuit {
     let mut builder = CircuitBuilder::<F, D>::new(mnist_config);
     let mut pw = PartialWitness::<F>::new();
 
+    let commit_before = config.commit_before.clone().unwrap_or(vec![]);
+    let commit_after = config.commit_after.clone().unwrap_or(vec![]);
+    let committed_idxes = commit_before
+      .iter()
+      .flatten()
+      .cloned()
+      .collect::<BTreeSet<_>>();
+
     let mut tensors = BTreeMap::new();
     for flat in config.tensors {
       let value_flat = flat.data.iter().map(|x| to_field(*x)).collect::<Vec<_>>();
       let target_flat = builder.add_virtual_targets(flat.data.len());
       let inp_idxes = config.inp_idxes.clone();
+      // Committed tensors are exposed through their commitment instead
       let is_public = inp_idxes
         .into_iter()
         .find(|&x| x == flat.idx as i64)
-        .is_some();
+        .is_some()
+        && !committed_idxes.contains(&flat.idx);
       for j in 0..target_flat.len() {
         // for now set inp idxs to be public
         pw.set_target(target_flat[j], value_flat[j]);
@@ -203,7 +219,7 @@ impl ModelCircuit {
         .layers
         .iter()
         .map(|layer| {
-          let layer_type = match_layer(&layer.layer_type);
+          let layer_type = layer.layer_type.parse::<LayerType>().unwrap();
           let layer_gadgets = match layer_type {
             LayerType::Add => Box::new(AddCircuit {}) as Box<dyn GadgetConsumer>,
             LayerType::AvgPool2D => Box::new(AvgPool2DCircuit::<F, C, D> {
@@ -212,6 +228,7 @@ impl ModelCircuit {
             LayerType::BatchMatMul => Box::new(BatchMatMulCircuit::<F, C, D> {
               _marker: PhantomData,
             }) as Box<dyn GadgetConsumer>,
+            LayerType::Broadcast => Box::new(BroadcastCircuit {}) as Box<dyn GadgetConsumer>,
             LayerType::Concatenation => {
               Box::new(ConcatenationCircuit {}) as Box<dyn GadgetConsumer>
             }
@@ -219,18 +236,43 @@ impl ModelCircuit {
               config: LayerConfig::default(),
               _marker: PhantomData,
             }) as Box<dyn GadgetConsumer>,
+            LayerType::DivFixed => Box::new(DivFixedCircuit {}) as Box<dyn GadgetConsumer>,
+            LayerType::DivVar => Box::new(DivVarCircuit {}) as Box<dyn GadgetConsumer>,
             LayerType::FullyConnected => Box::new(FullyConnectedCircuit::<F, C, D> {
               config: FullyConnectedConfig { normalize: true },
               _marker: PhantomData,
             }) as Box<dyn GadgetConsumer>,
             LayerType::Gather => Box::new(GatherCircuit {}) as Box<dyn GadgetConsumer>,
             LayerType::Logistic => Box::new(LogisticCircuit {}) as Box<dyn GadgetConsumer>,
+            LayerType::MaskNegInf => Box::new(MaskNegInfCircuit {}) as Box<dyn GadgetConsumer>,
+            LayerType::MaxPool2D => Box::new(MaxPool2DCircuit::<F, C, D> {
+              _marker: PhantomData,
+            }) as Box<dyn GadgetConsumer>,
+            LayerType::Mean => Box::new(MeanCircuit {}) as Box<dyn GadgetConsumer>,
             LayerType::Mul => Box::new(MulCircuit {}) as Box<dyn GadgetConsumer>,
             LayerType::Noop => Box::new(NoopCircuit {}) as Box<dyn GadgetConsumer>,
             LayerType::Pack => Box::new(PackCircuit {}) as Box<dyn GadgetConsumer>,
+            LayerType::Pad => Box::new(PadCircuit::<F, C, D> {
+              _marker: PhantomData,
+            }) as Box<dyn GadgetConsumer>,
+            LayerType::Permute => Box::new(PermuteCircuit {}) as Box<dyn GadgetConsumer>,
+            LayerType::Pow => Box::new(PowCircuit {}) as Box<dyn GadgetConsumer>,
             LayerType::Reshape => Box::new(ReshapeCircuit {}) as Box<dyn GadgetConsumer>,
+            LayerType::ResizeNN => Box::new(ResizeNNCircuit {}) as Box<dyn GadgetConsumer>,
+            LayerType::Rotate => Box::new(RotateCircuit {}) as Box<dyn GadgetConsumer>,
+            LayerType::Rsqrt => Box::new(RsqrtCircuit {}) as Box<dyn GadgetConsumer>,
+            LayerType::Slice => Box::new(SliceCircuit {}) as Box<dyn GadgetConsumer>,
+            LayerType::Softmax => Box::new(SoftmaxCircuit {}) as Box<dyn GadgetConsumer>,
             LayerType::Split => Box::new(SplitCircuit {}) as Box<dyn GadgetConsumer>,
+            LayerType::Sqrt => Box::new(SqrtCircuit {}) as Box<dyn GadgetConsumer>,
+            LayerType::Square => Box::new(SquareCircuit {}) as Box<dyn GadgetConsumer>,
+            LayerType::SquaredDifference => {
+              Box::new(SquaredDiffCircuit {}) as Box<dyn GadgetConsumer>
+            }
+            LayerType::Sub => Box::new(SubCircuit {}) as Box<dyn GadgetConsumer>,
+            LayerType::Tanh => Box::new(TanhCircuit {}) as Box<dyn GadgetConsumer>,
             LayerType::Transpose => Box::new(TransposeCircuit {}) as Box<dyn GadgetConsumer>,
+            LayerType::Update => Box::new(UpdateCircuit {}) as Box<dyn GadgetConsumer>,
           }
           .used_gadgets(layer.params.clone());
           for gadget in layer_gadgets {
@@ -243,6 +285,11 @@ impl ModelCircuit {
             out_shapes: layer.out_shapes.iter().map(|x| i64_to_usize(x)).collect(),
             mask: layer.mask.clone(),
             no_lookups,
+            // The model config can override the check for individual layers
+            matmul_check: layer
+              .matmul_check
+              .as_ref()
+              .map_or(matmul_check, |x| x.parse().unwrap()),
           }
         })
         .collect::<Vec<_>>();
@@ -298,8 +345,17 @@ impl ModelCircuit {
           }
           GadgetType::DivRound => DivRoundCircuit::configure(&mut builder, gadget_config),
           GadgetType::DotProduct => DotProductCircuit::configure(&mut builder, gadget_config),
+          GadgetType::Exp => ExpGadgetCircuit::configure(&mut builder, gadget_config),
           GadgetType::Logistic => LogisticGadgetCircuit::configure(&mut builder, gadget_config),
+          GadgetType::Max => MaxCircuit::configure(&mut builder, gadget_config),
+          GadgetType::Pow => PowGadgetCircuit::configure(&mut builder, gadget_config),
           GadgetType::Relu => ReluCircuit::configure(&mut builder, gadget_config),
+          GadgetType::Rsqrt => RsqrtGadgetCircuit::configure(&mut builder, gadget_config),
+          GadgetType::Sqrt => SqrtGadgetCircuit::configure(&mut builder, gadget_config),
+          GadgetType::Tanh => TanhGadgetCircuit::configure(&mut builder, gadget_config),
+          GadgetType::VarDivRoundBig => {
+            VarDivRoundBigCircuit::configure(&mut builder, gadget_config)
+          }
           GadgetType::InputLookup => gadget_config, // This is always loaded
         };
       }
@@ -320,6 +376,8 @@ impl ModelCircuit {
         k: config.k as usize,
         bits_per_elem: config.bits_per_elem.unwrap_or(config.k) as usize,
         inp_idxes: config.inp_idxes.clone(),
+        commit_before,
+        commit_after,
       },
       builder,
       pw,
@@ -338,15 +396,41 @@ impl ModelCircuit {
     let cloned_gadget = gadget.lock().unwrap().clone();
     let constants = self.assign_constants(Rc::new(cloned_gadget.clone()));
 
+    let commit_circuit =
+      PoseidonCommitCircuit::construct::<F>(cloned_gadget.num_bits_per_elem as usize);
+
+    // Commit to the tensors before the DAG
+    for commit_idxes in self.commit_before.iter() {
+      let to_commit = BTreeMap::from_iter(
+        commit_idxes
+          .iter()
+          .map(|idx| (*idx, self.tensors.get(idx).unwrap().clone())),
+      );
+      commit_circuit.commit(builder, &to_commit);
+    }
+
     // make the circuit
     let tensors_vec = self.tensor_map_to_vec(&self.tensors);
     let dag_circuit = DAGLayerCircuit::<F, C, D>::construct(self.dag_config.clone());
-    dag_circuit.make_circuit(
+    let (final_tensor_map, result, rand_targets) = dag_circuit.make_circuit(
       builder,
       &tensors_vec,
       &constants,
       Rc::new(cloned_gadget),
       &LayerConfig::default(),
-    )
+    );
+
+    // Commit to the tensors after the DAG
+    for commit_idxes in self.commit_after.iter() {
+      let to_commit = BTreeMap::from_iter(commit_idxes.iter().map(|idx| {
+        (
+          *idx,
+          final_tensor_map.get(&(*idx as usize)).unwrap().clone(),
+        )
+      }));
+      commit_circuit.commit(builder, &to_commit);
+    }
+
+    (result, rand_targets)
   }
 }
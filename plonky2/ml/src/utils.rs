@@ -1,2 +1,7 @@
+pub mod evaluator;
+pub mod helpers;
 pub mod loader;
+pub mod profiler;
 pub mod proving;
+#[cfg(test)]
+pub(crate) mod testing;
@@ -0,0 +1,959 @@
+use std::{
+  cell::RefCell,
+  collections::{BTreeMap, HashMap},
+};
+
+use anyhow::{bail, ensure, Context, Result};
+use ndarray::{concatenate, Array, ArrayView, Axis, IxDyn, Slice};
+use plonky2::{field::goldilocks_field::GoldilocksField, plonk::config::PoseidonGoldilocksConfig};
+
+use crate::{
+  gadgets::{
+    bias_div_round_relu6::BiasDivRoundRelu6Circuit,
+    gadget::GadgetType,
+    nonlinear::{
+      exp::ExpGadgetCircuit,
+      logistic::LogisticGadgetCircuit,
+      non_linearity::{saturate_output, NonLinearGadget},
+      pow::PowGadgetCircuit,
+      relu::ReluCircuit,
+      rsqrt::RsqrtGadgetCircuit,
+      sqrt::SqrtGadgetCircuit,
+      tanh::TanhGadgetCircuit,
+    },
+  },
+  layers::{
+    conv2d::{Conv2DCircuit, PaddingEnum},
+    dag::DAGLayerConfig,
+    layer::{ActivationType, LayerConfig, LayerType},
+    shape::pad::PadCircuit,
+  },
+  utils::{
+    helpers::broadcast,
+    loader::{ModelMsgpack, TensorMsgpack},
+  },
+};
+
+pub type Tensor = Array<i64, IxDyn>;
+
+// The tables and the conv helpers are generic over the field, which never shows up in them
+type F = GoldilocksField;
+type C = PoseidonGoldilocksConfig;
+const D: usize = 2;
+type Conv2D = Conv2DCircuit<F, C, D>;
+
+// Fixed-point parameters, derived from the msgpack exactly like the GadgetConfig in
+// ModelCircuit::generate_from_msgpack. The lookup tables are built on first use from the
+// gadgets' own maps.
+#[derive(Clone, Debug)]
+pub struct FixedPointConfig {
+  pub scale_factor: i64,
+  pub min_val: i64,
+  pub max_val: i64,
+  pub num_rows: i64,
+  tables: RefCell<HashMap<GadgetType, Vec<i64>>>,
+}
+
+impl FixedPointConfig {
+  pub fn from_msgpack(config: &ModelMsgpack) -> Self {
+    Self {
+      scale_factor: config.global_sf,
+      min_val: -(1 << (config.k - 1)),
+      max_val: (1 << (config.k - 1)) - 10,
+      num_rows: (1 << config.k) - 10 + 1,
+      tables: RefCell::new(HashMap::new()),
+    }
+  }
+
+  // DivRoundGate and VarDivRoundBigGate: c = (2 * a + b) / (2 * b), rounded towards -inf
+  pub fn div_round(&self, a: i64, b: i64) -> i64 {
+    (2 * a + b).div_euclid(2 * b)
+  }
+
+  // The outputs of NonLinearGadget::make_circuit: the saturated map, with the first row loaded as
+  // zero
+  fn nonlinear_table<G: NonLinearGadget<F, D>>(&self) -> Vec<i64> {
+    let map = G::generate_map(self.scale_factor as u64, self.min_val, self.num_rows);
+    (0..self.num_rows)
+      .map(|i| if i == 0 { 0 } else { saturate_output(map[&i]) })
+      .collect()
+  }
+
+  fn table(&self, gadget_type: GadgetType) -> Vec<i64> {
+    match gadget_type {
+      GadgetType::BiasDivRoundRelu6 => {
+        let map = BiasDivRoundRelu6Circuit::generate_map(
+          self.scale_factor as u64,
+          self.min_val,
+          self.num_rows,
+        );
+        (0..self.num_rows).map(|i| map[&i]).collect()
+      }
+      GadgetType::Exp => self.nonlinear_table::<ExpGadgetCircuit>(),
+      GadgetType::Logistic => self.nonlinear_table::<LogisticGadgetCircuit>(),
+      GadgetType::Pow => self.nonlinear_table::<PowGadgetCircuit>(),
+      GadgetType::Relu => self.nonlinear_table::<ReluCircuit>(),
+      GadgetType::Rsqrt => self.nonlinear_table::<RsqrtGadgetCircuit>(),
+      GadgetType::Sqrt => self.nonlinear_table::<SqrtGadgetCircuit>(),
+      GadgetType::Tanh => self.nonlinear_table::<TanhGadgetCircuit>(),
+      _ => panic!("{:?} has no lookup table", gadget_type),
+    }
+  }
+
+  // Lookups are over [min_val, min_val + num_rows)
+  pub fn lookup(&self, gadget_type: GadgetType, x: i64) -> Result<i64> {
+    let idx = x - self.min_val;
+    ensure!(
+      idx >= 0 && idx < self.num_rows,
+      "{:?} input {} is outside the lookup table [{}, {})",
+      gadget_type,
+      x,
+      self.min_val,
+      self.min_val + self.num_rows
+    );
+    let mut tables = self.tables.borrow_mut();
+    let table = tables
+      .entry(gadget_type)
+      .or_insert_with(|| self.table(gadget_type));
+    Ok(table[idx as usize])
+  }
+
+  // BiasDivRoundRelu6: returns (relu6, div_res), the bias is added after the division. With
+  // lookups the relu6 table constrains div_res to the table's range.
+  pub fn bias_div_round_relu6(&self, x: i64, bias: i64) -> Result<(i64, i64)> {
+    let div_res = self.div_round(x, self.scale_factor) + bias;
+    let relu6 = self.lookup(GadgetType::BiasDivRoundRelu6, div_res)?;
+    Ok((relu6, div_res))
+  }
+}
+
+// Evaluates the model natively over i64, reproducing the fixed-point semantics of the circuit.
+// Every tensor the DAG produces can be compared against the circuit witness.
+pub struct ModelEvaluator {
+  pub fixed_point: FixedPointConfig,
+  pub dag_config: DAGLayerConfig,
+  pub tensors: BTreeMap<i64, Tensor>,
+}
+
+impl ModelEvaluator {
+  pub fn from_msgpack(config: &ModelMsgpack) -> Result<Self> {
+    let mut tensors = BTreeMap::new();
+    for flat in config.tensors.iter() {
+      let shape = flat.shape.iter().map(|x| *x as usize).collect::<Vec<_>>();
+      let tensor =
+        reshape(flat.data.clone(), &shape).with_context(|| format!("tensor {}", flat.idx))?;
+      tensors.insert(flat.idx, tensor);
+    }
+
+    let i64_to_usize = |x: &Vec<i64>| x.iter().map(|x| *x as usize).collect::<Vec<_>>();
+    let ops = config
+      .layers
+      .iter()
+      .map(|layer| -> Result<LayerConfig> {
+        Ok(LayerConfig {
+          layer_type: layer.layer_type.parse().map_err(anyhow::Error::msg)?,
+          layer_params: layer.params.clone(),
+          inp_shapes: layer.inp_shapes.iter().map(|x| i64_to_usize(x)).collect(),
+          out_shapes: layer.out_shapes.iter().map(|x| i64_to_usize(x)).collect(),
+          mask: layer.mask.clone(),
+          ..LayerConfig::default()
+        })
+      })
+      .collect::<Result<Vec<_>>>()?;
+    let dag_config = DAGLayerConfig {
+      ops,
+      inp_idxes: config
+        .layers
+        .iter()
+        .map(|layer| i64_to_usize(&layer.inp_idxes))
+        .collect(),
+      out_idxes: config
+        .layers
+        .iter()
+        .map(|layer| i64_to_usize(&layer.out_idxes))
+        .collect(),
+      final_out_idxes: i64_to_usize(&config.out_idxes),
+    };
+
+    Ok(Self {
+      fixed_point: FixedPointConfig::from_msgpack(config),
+      dag_config,
+      tensors,
+    })
+  }
+
+  // Mirrors DAGLayerCircuit::make_circuit: returns the tensor map and the final outputs
+  pub fn forward(&self) -> Result<(HashMap<usize, Tensor>, Vec<Tensor>)> {
+    let mut tensor_map = HashMap::new();
+    for (idx, tensor) in self.tensors.iter() {
+      tensor_map.insert(*idx as usize, tensor.clone());
+    }
+
+    for (layer_idx, layer_config) in self.dag_config.ops.iter().enumerate() {
+      let inp_idxes = &self.dag_config.inp_idxes[layer_idx];
+      let out_idxes = &self.dag_config.out_idxes[layer_idx];
+      let out = inp_idxes
+        .iter()
+        .map(|idx| get_tensor(&tensor_map, *idx))
+        .collect::<Result<Vec<_>>>()
+        .and_then(|inps| self.forward_layer(&inps, layer_config))
+        .with_context(|| format!("layer {} ({:?})", layer_idx, layer_config.layer_type))?;
+      for (idx, tensor_idx) in out_idxes.iter().enumerate() {
+        tensor_map.insert(*tensor_idx, out[idx].clone());
+      }
+    }
+
+    let final_out = self
+      .dag_config
+      .final_out_idxes
+      .iter()
+      .map(|idx| get_tensor(&tensor_map, *idx))
+      .collect::<Result<Vec<_>>>()?;
+
+    Ok((tensor_map, final_out))
+  }
+
+  pub fn forward_layer(
+    &self,
+    tensors: &Vec<Tensor>,
+    layer_config: &LayerConfig,
+  ) -> Result<Vec<Tensor>> {
+    let fp = &self.fixed_point;
+    let sf = fp.scale_factor;
+    let params = &layer_config.layer_params;
+
+    let out = match layer_config.layer_type {
+      // Add and Mul repeat the second input over the flattened first one
+      LayerType::Add => vec![self.zip_repeat(tensors, |a, b| a + b)?],
+      LayerType::Mul => vec![self.zip_repeat(tensors, |a, b| fp.div_round(a * b, sf))?],
+      LayerType::Sub => vec![self.zip_broadcast(tensors, |a, b| a - b)?],
+      LayerType::SquaredDifference => {
+        vec![self.zip_broadcast(tensors, |a, b| fp.div_round((a - b) * (a - b), sf))?]
+      }
+      LayerType::DivVar => {
+        ensure!(
+          tensors[1].shape() == [1],
+          "the divisor must have shape [1], not {:?}",
+          tensors[1].shape()
+        );
+        let div = tensors[1][[0]];
+        ensure!(div > 0, "the divisor must be positive, not {}", div);
+        vec![tensors[0].mapv(|x| fp.div_round(x * sf, div))]
+      }
+      LayerType::DivFixed => {
+        ensure!(
+          params[0] > 0,
+          "the divisor must be positive, not {}",
+          params[0]
+        );
+        vec![tensors[0].mapv(|x| fp.div_round(x, params[0]))]
+      }
+      LayerType::Square => vec![tensors[0].mapv(|x| fp.div_round(x * x, sf))],
+      LayerType::Update => {
+        // Rounded division, with eta = sf / 1000
+        let (w, dw) = (&tensors[0], &tensors[1]);
+        ensure!(
+          w.len() == dw.len(),
+          "weights of shape {:?} don't match the update of shape {:?}",
+          w.shape(),
+          dw.shape()
+        );
+        let eta = sf / 1000;
+        let out = w
+          .iter()
+          .zip(dw.iter())
+          .map(|(w, dw)| fp.div_round(w * sf - dw * eta, sf))
+          .collect::<Vec<_>>();
+        vec![Array::from_shape_vec(w.raw_dim(), out).unwrap()]
+      }
+      LayerType::Logistic => vec![self.map(&tensors[0], GadgetType::Logistic)?],
+      LayerType::Pow => vec![self.map(&tensors[0], GadgetType::Pow)?],
+      LayerType::Tanh => vec![self.map(&tensors[0], GadgetType::Tanh)?],
+      LayerType::Sqrt => vec![self.map(
+        &self.apply_mask(&tensors[0], layer_config)?,
+        GadgetType::Sqrt,
+      )?],
+      LayerType::Rsqrt => vec![self.map(
+        &self.apply_mask(&tensors[0], layer_config)?,
+        GadgetType::Rsqrt,
+      )?],
+      LayerType::AvgPool2D => vec![self.pool(&tensors[0], layer_config, |window| {
+        fp.div_round(window.iter().sum(), params[0] * params[1])
+      })?],
+      LayerType::MaxPool2D => vec![self.pool(&tensors[0], layer_config, |window| {
+        *window.iter().max().unwrap()
+      })?],
+      LayerType::Mean => {
+        let inp = &tensors[0];
+        check_batch(inp)?;
+        // MeanCircuit::get_keep_axis: every axis but the batch and the averaged ones
+        let keep_axes = (1..inp.ndim())
+          .filter(|ax| !params.contains(&(*ax as i64)))
+          .collect::<Vec<_>>();
+        ensure!(
+          keep_axes.len() == 1,
+          "averaging {:?} of shape {:?} must keep exactly one axis",
+          params,
+          inp.shape()
+        );
+        let keep_axis = keep_axes[0];
+        let div = inp.len() / inp.shape()[keep_axis];
+        let out = inp
+          .axis_iter(Axis(keep_axis))
+          .map(|x| fp.div_round(x.sum(), div as i64))
+          .collect::<Vec<_>>();
+        vec![reshape(out, &layer_config.out_shapes[0])?]
+      }
+      LayerType::FullyConnected => {
+        let activation = match params[0] {
+          0 => ActivationType::None,
+          1 => ActivationType::Relu,
+          _ => bail!("unsupported activation {}", params[0]),
+        };
+        let mm = self.fully_connected(&tensors[0], &tensors[1].view())?;
+        let mm = if tensors.len() == 3 {
+          &mm + &broadcast_to(&tensors[2], mm.shape())?
+        } else {
+          mm
+        };
+        if activation == ActivationType::Relu {
+          vec![self.map(&mm, GadgetType::Relu)?]
+        } else {
+          vec![mm]
+        }
+      }
+      LayerType::BatchMatMul => {
+        let (inp1, inp2) = (&tensors[0], &tensors[1]);
+        check_ndim(inp1, &[3])?;
+        check_ndim(inp2, &[3])?;
+        ensure!(
+          inp1.shape()[0] == inp2.shape()[0],
+          "batch sizes {} and {} differ",
+          inp1.shape()[0],
+          inp2.shape()[0]
+        );
+        let adj_y = params[1] == 1;
+        let mut outp = vec![];
+        for i in 0..inp1.shape()[0] {
+          let inp2_slice = inp2.index_axis(Axis(0), i);
+          let weight = if adj_y {
+            inp2_slice
+          } else {
+            inp2_slice.reversed_axes()
+          };
+          let out = self.fully_connected(&inp1.index_axis(Axis(0), i).to_owned(), &weight)?;
+          outp.extend(out.iter().cloned());
+        }
+        let out_shape = if adj_y {
+          vec![inp1.shape()[0], inp1.shape()[1], inp2.shape()[1]]
+        } else {
+          vec![inp1.shape()[0], inp1.shape()[1], inp2.shape()[2]]
+        };
+        vec![Array::from_shape_vec(IxDyn(&out_shape), outp).unwrap()]
+      }
+      LayerType::Conv2D => vec![self.conv2d(tensors, layer_config)?],
+      LayerType::Softmax => vec![self.softmax(&tensors[0], layer_config)?],
+      _ => shape_forward(tensors, layer_config, fp)?,
+    };
+    Ok(out)
+  }
+
+  fn map(&self, inp: &Tensor, gadget_type: GadgetType) -> Result<Tensor> {
+    let out = inp
+      .iter()
+      .map(|x| self.fixed_point.lookup(gadget_type, *x))
+      .collect::<Result<Vec<_>>>()?;
+    Ok(Array::from_shape_vec(inp.raw_dim(), out).unwrap())
+  }
+
+  fn zip_repeat(&self, tensors: &Vec<Tensor>, f: impl Fn(i64, i64) -> i64) -> Result<Tensor> {
+    ensure!(
+      tensors.len() == 2,
+      "expected 2 inputs, got {}",
+      tensors.len()
+    );
+    let (inp, other) = (&tensors[0], &tensors[1]);
+    check_ndim(inp, &[4])?;
+    ensure!(
+      inp.shape()[3] == other.shape()[0],
+      "input of shape {:?} doesn't match {:?}",
+      inp.shape(),
+      other.shape()
+    );
+    let other = other.iter().collect::<Vec<_>>();
+    let out = inp
+      .iter()
+      .enumerate()
+      .map(|(i, x)| f(*x, *other[i % other.len()]))
+      .collect::<Vec<_>>();
+    Ok(Array::from_shape_vec(inp.raw_dim(), out).unwrap())
+  }
+
+  fn zip_broadcast(&self, tensors: &Vec<Tensor>, f: impl Fn(i64, i64) -> i64) -> Result<Tensor> {
+    ensure!(
+      tensors.len() == 2,
+      "expected 2 inputs, got {}",
+      tensors.len()
+    );
+    let (inp1, inp2) = broadcast(&tensors[0], &tensors[1]);
+    let out = inp1
+      .iter()
+      .zip(inp2.iter())
+      .map(|(a, b)| f(*a, *b))
+      .collect::<Vec<_>>();
+    Ok(Array::from_shape_vec(inp1.raw_dim(), out).unwrap())
+  }
+
+  // Masked entries of sqrt / rsqrt are replaced by max_val (1) or min_val (-1)
+  fn apply_mask(&self, inp: &Tensor, layer_config: &LayerConfig) -> Result<Tensor> {
+    let mask = &layer_config.mask;
+    let mut mask_map = HashMap::new();
+    for i in 0..mask.len() / 2 {
+      mask_map.insert(mask[2 * i], mask[2 * i + 1]);
+    }
+    let out = inp
+      .iter()
+      .enumerate()
+      .map(|(i, x)| match mask_map.get(&(i as i64)).copied() {
+        None => Ok(*x),
+        Some(1) => Ok(self.fixed_point.max_val),
+        Some(-1) => Ok(self.fixed_point.min_val),
+        Some(m) => bail!("invalid mask value {} at {}", m, i),
+      })
+      .collect::<Result<Vec<_>>>()?;
+    Ok(Array::from_shape_vec(inp.raw_dim(), out).unwrap())
+  }
+
+  // FullyConnectedCircuit with normalization: the first batch of the input times weight^T,
+  // divided by the scale factor, with as many rows as the input's first axis
+  fn fully_connected(&self, input: &Tensor, weight_t: &ArrayView<i64, IxDyn>) -> Result<Tensor> {
+    let fp = &self.fixed_point;
+    check_ndim(input, &[2, 3])?;
+    let first_batch = if input.ndim() == 2 {
+      input.view()
+    } else {
+      input.index_axis(Axis(0), 0)
+    };
+    ensure!(
+      first_batch.shape()[1] == weight_t.shape()[1] && input.shape()[0] <= first_batch.shape()[0],
+      "input of shape {:?} doesn't match weights of shape {:?}",
+      input.shape(),
+      weight_t.shape()
+    );
+    let (n, m) = (input.shape()[0], weight_t.shape()[0]);
+    let mut out = vec![];
+    for i in 0..n {
+      for j in 0..m {
+        let row = first_batch.index_axis(Axis(0), i);
+        let col = weight_t.index_axis(Axis(0), j);
+        let dot: i64 = row.iter().zip(col.iter()).map(|(a, b)| a * b).sum();
+        out.push(fp.div_round(dot, fp.scale_factor));
+      }
+    }
+    Ok(Array::from_shape_vec(IxDyn(&[n, m]), out).unwrap())
+  }
+
+  // Windows in the same order as the pooling circuits, with valid padding
+  fn pool(
+    &self,
+    inp: &Tensor,
+    layer_config: &LayerConfig,
+    f: impl Fn(&Vec<i64>) -> i64,
+  ) -> Result<Tensor> {
+    let params = &layer_config.layer_params;
+    let (fx, fy) = (params[0] as usize, params[1] as usize);
+    let (sx, sy) = (params[2] as usize, params[3] as usize);
+    check_ndim(inp, &[4])?;
+    check_batch(inp)?;
+    let (h, w, c) = (inp.shape()[1], inp.shape()[2], inp.shape()[3]);
+    ensure!(
+      fx <= h && fy <= w,
+      "the {}x{} window doesn't fit an input of shape {:?}",
+      fx,
+      fy,
+      inp.shape()
+    );
+    let (oh, ow) = Conv2D::out_hw(h, w, sx, sy, fx, fy, PaddingEnum::Valid);
+
+    let mut out = vec![];
+    for i in 0..oh {
+      for j in 0..ow {
+        for k in 0..c {
+          let mut window = vec![];
+          for x in 0..fx {
+            for y in 0..fy {
+              window.push(inp[[0, i * sx + x, j * sy + y, k]]);
+            }
+          }
+          out.push(f(&window));
+        }
+      }
+    }
+    Ok(Array::from_shape_vec(IxDyn(&[1, oh, ow, c]), out).unwrap())
+  }
+
+  // Conv2DCircuit only supports plain convolutions with valid padding over square inputs and
+  // filters. The products are accumulated without dividing, BiasDivRoundRelu6 divides.
+  fn conv2d(&self, tensors: &Vec<Tensor>, layer_config: &LayerConfig) -> Result<Tensor> {
+    let fp = &self.fixed_point;
+    let params = &layer_config.layer_params;
+    ensure!(params[0] == 0, "depthwise convolutions are unimplemented");
+    ensure!(params[1] == 1, "only valid padding is implemented");
+    ensure!(
+      params[2] == 0 || params[2] == 3,
+      "unsupported activation {}",
+      params[2]
+    );
+    let conv_config = Conv2D::param_vec_to_config(params.clone());
+
+    let inp = &tensors[0];
+    let weights = &tensors[1];
+    check_ndim(inp, &[4])?;
+    check_ndim(weights, &[4])?;
+    let (h, w) = (inp.shape()[1], inp.shape()[2]);
+    let (ch, cw) = (weights.shape()[1], weights.shape()[2]);
+    ensure!(
+      h == w && ch == cw && ch <= h,
+      "a filter of shape {:?} doesn't fit an input of shape {:?}",
+      weights.shape(),
+      inp.shape()
+    );
+    let (si, sj) = conv_config.stride;
+    let (oh, ow) = Conv2D::out_hw(h, w, si, sj, ch, cw, conv_config.padding);
+    let batch_size = inp.shape()[0];
+    let oc = weights.shape()[0];
+
+    let mut outp = vec![];
+    for batch in 0..batch_size {
+      for i in 0..oh {
+        for j in 0..ow {
+          for chan_out in 0..oc {
+            let mut dot = 0;
+            for ci in 0..ch {
+              for cj in 0..cw {
+                for ck in 0..weights.shape()[3] {
+                  dot +=
+                    inp[[batch, i * si + ci, j * sj + cj, ck]] * weights[[chan_out, ci, cj, ck]];
+                }
+              }
+            }
+            let bias = if tensors.len() == 3 {
+              tensors[2][chan_out]
+            } else {
+              0
+            };
+            let (relu6, div_res) = fp.bias_div_round_relu6(dot, bias)?;
+            outp.push(if conv_config.activation == ActivationType::Relu6 {
+              relu6
+            } else {
+              div_res
+            });
+          }
+        }
+      }
+    }
+    Ok(Array::from_shape_vec(IxDyn(&[batch_size, oh, ow, oc]), outp).unwrap())
+  }
+
+  // The exponentials are scaled by sf^2, so they are divided by their sum / sf
+  fn softmax_flat(&self, inp: &Vec<i64>, mask: &Vec<i64>) -> Result<Vec<i64>> {
+    let fp = &self.fixed_point;
+    // 1 = take negative infinity
+    let inp_take = inp
+      .iter()
+      .zip(mask.iter())
+      .filter(|(_, m)| **m == 0)
+      .map(|(x, _)| *x)
+      .collect::<Vec<_>>();
+    let max = *inp_take
+      .iter()
+      .max()
+      .context("softmax over a fully masked row")?;
+    let exp = inp_take
+      .iter()
+      .map(|x| fp.lookup(GadgetType::Exp, x - max))
+      .collect::<Result<Vec<_>>>()?;
+    let sum: i64 = exp.iter().sum();
+    ensure!(sum > 0, "the exponentials of {:?} sum to zero", inp_take);
+    let sum_div_sf = fp.div_round(sum, fp.scale_factor);
+    let mut dived = exp.iter().map(|x| fp.div_round(*x, sum_div_sf));
+    Ok(
+      mask
+        .iter()
+        .map(|m| if *m == 1 { 0 } else { dived.next().unwrap() })
+        .collect(),
+    )
+  }
+
+  fn softmax(&self, inp: &Tensor, layer_config: &LayerConfig) -> Result<Tensor> {
+    check_ndim(inp, &[2, 3, 4])?;
+    if inp.ndim() == 4 {
+      check_batch(inp)?;
+    }
+    let params = &layer_config.layer_params;
+    let mask = if params.len() == 0 {
+      Array::zeros(inp.raw_dim())
+    } else {
+      let mask_shape_len = params[0] as usize;
+      let mask_shape = params[1..(1 + mask_shape_len)]
+        .iter()
+        .map(|x| *x as usize)
+        .collect::<Vec<_>>();
+      let mask = reshape(params[(1 + mask_shape_len)..].to_vec(), &mask_shape)?;
+      broadcast_to(&mask, inp.shape())?
+    };
+
+    // Over the last axis, the output keeps the shape of the input
+    let last = inp.ndim() - 1;
+    let mut outp = vec![];
+    for (inp_row, mask_row) in inp
+      .lanes(Axis(last))
+      .into_iter()
+      .zip(mask.lanes(Axis(last)))
+    {
+      outp.extend(self.softmax_flat(&inp_row.to_vec(), &mask_row.to_vec())?);
+    }
+    Ok(Array::from_shape_vec(inp.raw_dim(), outp).unwrap())
+  }
+}
+
+fn get_tensor(tensor_map: &HashMap<usize, Tensor>, idx: usize) -> Result<Tensor> {
+  tensor_map
+    .get(&idx)
+    .cloned()
+    .with_context(|| format!("tensor {} is read before it is computed", idx))
+}
+
+fn check_ndim(inp: &Tensor, ndims: &[usize]) -> Result<()> {
+  ensure!(
+    ndims.contains(&inp.ndim()),
+    "expected an input with {:?} dimensions, got shape {:?}",
+    ndims,
+    inp.shape()
+  );
+  Ok(())
+}
+
+fn check_batch(inp: &Tensor) -> Result<()> {
+  ensure!(
+    inp.shape()[0] == 1,
+    "only a batch size of 1 is supported, got shape {:?}",
+    inp.shape()
+  );
+  Ok(())
+}
+
+fn reshape(flat: Vec<i64>, shape: &[usize]) -> Result<Tensor> {
+  let len = flat.len();
+  Array::from_shape_vec(IxDyn(shape), flat)
+    .with_context(|| format!("{} values don't fit the shape {:?}", len, shape))
+}
+
+fn broadcast_to(inp: &Tensor, shape: &[usize]) -> Result<Tensor> {
+  inp
+    .broadcast(IxDyn(shape))
+    .map(|x| x.to_owned())
+    .with_context(|| format!("shape {:?} doesn't broadcast to {:?}", inp.shape(), shape))
+}
+
+fn pad(inp: &Tensor, padding: &Vec<[usize; 2]>) -> Result<Tensor> {
+  ensure!(
+    padding.len() == inp.ndim(),
+    "padding {:?} doesn't match the shape {:?}",
+    padding,
+    inp.shape()
+  );
+  let mut padded_shape = inp.raw_dim();
+  for (ax, [pad_lo, pad_hi]) in padding.iter().enumerate() {
+    padded_shape[ax] += pad_lo + pad_hi;
+  }
+  let mut padded = Array::zeros(padded_shape);
+  padded
+    .slice_each_axis_mut(|ax| {
+      let [pad_lo, _] = padding[ax.axis.0];
+      Slice::from(pad_lo..pad_lo + inp.shape()[ax.axis.0])
+    })
+    .assign(inp);
+  Ok(padded)
+}
+
+// Layers that only move values around, with the same semantics as the circuits in layers::shape
+fn shape_forward(
+  tensors: &Vec<Tensor>,
+  layer_config: &LayerConfig,
+  fp: &FixedPointConfig,
+) -> Result<Vec<Tensor>> {
+  let params = &layer_config.layer_params;
+  let inp = &tensors[0];
+
+  let out = match layer_config.layer_type {
+    LayerType::Noop => tensors[params[0] as usize].clone(),
+    LayerType::Broadcast => {
+      check_ndim(inp, &[4])?;
+      broadcast_to(inp, &layer_config.out_shapes[0])?
+    }
+    LayerType::Concatenation => {
+      let views = tensors.iter().map(|x| x.view()).collect::<Vec<_>>();
+      concatenate(Axis(params[0] as usize), views.as_slice()).unwrap_or(inp.clone())
+    }
+    LayerType::Pack => {
+      let axis = params[0] as usize;
+      ensure!(axis <= 1, "only axis 0 or 1 is supported, not {}", axis);
+      let expanded = tensors
+        .iter()
+        .map(|x| x.clone().insert_axis(Axis(axis)))
+        .collect::<Vec<_>>();
+      let views = expanded.iter().map(|x| x.view()).collect::<Vec<_>>();
+      concatenate(Axis(axis), views.as_slice()).unwrap_or(inp.clone())
+    }
+    LayerType::Gather => {
+      let axis = params[0] as usize;
+      let pos = params[1..].iter().map(|x| *x as usize).collect::<Vec<_>>();
+      ensure!(
+        axis < inp.ndim() && pos.iter().all(|x| *x < inp.shape()[axis]),
+        "positions {:?} on axis {} are out of range for shape {:?}",
+        pos,
+        axis,
+        inp.shape()
+      );
+      inp.select(Axis(axis), &pos)
+    }
+    LayerType::MaskNegInf => {
+      let mask_ndim = params[0] as usize;
+      let mask_shape = params[1..mask_ndim + 1]
+        .iter()
+        .map(|x| *x as usize)
+        .collect::<Vec<_>>();
+      let mask = reshape(params[mask_ndim + 1..].to_vec(), &mask_shape)?;
+      let mask = broadcast_to(&mask, inp.shape())?;
+      let out = inp
+        .iter()
+        .zip(mask.iter())
+        .map(|(x, m)| if *m == 0 { *x } else { fp.min_val })
+        .collect::<Vec<_>>();
+      Array::from_shape_vec(inp.raw_dim(), out).unwrap()
+    }
+    LayerType::Pad => {
+      ensure!(
+        params.len() % 2 == 0,
+        "expected an even number of params, got {}",
+        params.len()
+      );
+      let padding = PadCircuit::<F, C, D>::param_vec_to_config(params.clone()).padding;
+      pad(inp, &padding)?
+    }
+    LayerType::Permute => {
+      let params = params.iter().map(|x| *x as usize).collect::<Vec<_>>();
+      check_ndim(inp, &[params.len()])?;
+      inp.clone().permuted_axes(IxDyn(&params))
+    }
+    LayerType::Reshape => reshape(inp.iter().cloned().collect(), &layer_config.out_shapes[0])?,
+    LayerType::ResizeNN => {
+      let output_shape = &layer_config.out_shapes[0];
+      check_ndim(inp, &[4])?;
+      check_batch(inp)?;
+      ensure!(
+        output_shape.len() == 4 && inp.shape()[3] == output_shape[3],
+        "can't resize shape {:?} to {:?}",
+        inp.shape(),
+        output_shape
+      );
+      let mut flat = vec![];
+      for h in 0..output_shape[1] {
+        let h_in = (h as f64 * (inp.shape()[1] as f64 / output_shape[1] as f64)) as usize;
+        for w in 0..output_shape[2] {
+          let w_in = (w as f64 * (inp.shape()[2] as f64 / output_shape[2] as f64)) as usize;
+          for c in 0..inp.shape()[3] {
+            flat.push(inp[[0, h_in, w_in, c]]);
+          }
+        }
+      }
+      Array::from_shape_vec(IxDyn(output_shape), flat).unwrap()
+    }
+    LayerType::Rotate => {
+      check_ndim(inp, &[4])?;
+      let mut out = inp.clone();
+      for p in params.iter() {
+        out.invert_axis(Axis(*p as usize));
+      }
+      out
+    }
+    LayerType::Slice => {
+      ensure!(
+        params.len() % 2 == 0,
+        "expected an even number of params, got {}",
+        params.len()
+      );
+      let num_axes = params.len() / 2;
+      let (starts, sizes) = (&params[0..num_axes], &params[num_axes..]);
+      inp
+        .slice_each_axis(|ax| {
+          let start = starts[ax.axis.0] as usize;
+          let size = sizes[ax.axis.0];
+          if size == -1 {
+            Slice::from(start..)
+          } else {
+            Slice::from(start..(start + size as usize))
+          }
+        })
+        .to_owned()
+    }
+    LayerType::Split => {
+      let axis = params[0] as usize;
+      let num_splits = params[1] as usize;
+      let inp = &tensors[1];
+      let split_len = inp.shape()[axis] / num_splits;
+      return Ok(
+        (0..num_splits)
+          .map(|i| {
+            inp
+              .slice_axis(
+                Axis(axis),
+                Slice::from((i * split_len)..((i + 1) * split_len)),
+              )
+              .to_owned()
+          })
+          .collect(),
+      );
+    }
+    LayerType::Transpose => {
+      ensure!(
+        params.len() % 2 == 0,
+        "expected an even number of params, got {}",
+        params.len()
+      );
+      let ndim = params.len() / 2;
+      let inp_shape = params[0..ndim]
+        .iter()
+        .map(|x| *x as usize)
+        .collect::<Vec<_>>();
+      let permutation = params[ndim..]
+        .iter()
+        .map(|x| *x as usize)
+        .collect::<Vec<_>>();
+      reshape(inp.iter().cloned().collect(), &inp_shape)?.permuted_axes(IxDyn(&permutation))
+    }
+    _ => panic!("{:?} is not a shape layer", layer_config.layer_type),
+  };
+  Ok(vec![out])
+}
+
+// Flattens the tensors into the same format as the msgpack inputs
+pub fn tensors_to_msgpack(tensor_map: &HashMap<usize, Tensor>) -> Vec<TensorMsgpack> {
+  let tensor_map = tensor_map.iter().collect::<BTreeMap<_, _>>();
+  tensor_map
+    .into_iter()
+    .map(|(idx, tensor)| TensorMsgpack {
+      idx: *idx as i64,
+      shape: tensor.shape().iter().map(|x| *x as i64).collect(),
+      data: tensor.iter().cloned().collect(),
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{FixedPointConfig, ModelEvaluator};
+  use crate::layers::layer::MatMulCheck;
+  use crate::utils::loader::ModelMsgpack;
+  use crate::utils::testing::{layer, model, prove_model, tensor, TEST_SF};
+
+  fn fc_model(activation: i64) -> ModelMsgpack {
+    let inp = tensor(0, &[2, 3], &[16, 32, -16, 8, 0, 48]);
+    let weight = tensor(1, &[2, 3], &[16, 0, 16, 32, -16, 8]);
+    let bias = tensor(2, &[2], &[1, -1]);
+    let fc = layer(
+      "FullyConnected",
+      &[activation],
+      &[&inp, &weight, &bias],
+      3,
+      &[2, 2],
+    );
+    model(vec![inp, weight, bias], vec![fc])
+  }
+
+  fn eval(config: &ModelMsgpack) -> anyhow::Result<Vec<i64>> {
+    let (_, final_out) = ModelEvaluator::from_msgpack(config)?.forward()?;
+    Ok(final_out[0].iter().cloned().collect())
+  }
+
+  #[test]
+  fn div_round_rounds_half_up() {
+    let fp = FixedPointConfig::from_msgpack(&fc_model(0));
+    assert_eq!(fp.div_round(7, 4), 2);
+    assert_eq!(fp.div_round(6, 4), 2);
+    assert_eq!(fp.div_round(-6, 4), -1);
+    assert_eq!(fp.div_round(-7, 4), -2);
+    assert_eq!(fp.div_round(-3, 2), -1);
+    assert_eq!(fp.div_round(-1, TEST_SF), 0);
+  }
+
+  #[test]
+  fn bias_div_round_relu6_clamps_after_the_bias() {
+    let fp = FixedPointConfig::from_msgpack(&fc_model(0));
+    // (relu6, div_res)
+    let cases = [
+      ((10 * TEST_SF * TEST_SF, 0), (6 * TEST_SF, 160)),
+      ((-2 * TEST_SF, 0), (0, -2)),
+      ((-2 * TEST_SF, 5), (3, 3)),
+      ((5 * TEST_SF, 1), (6, 6)),
+    ];
+    for ((x, bias), expected) in cases {
+      assert_eq!(fp.bias_div_round_relu6(x, bias).unwrap(), expected);
+    }
+    // The division result must fit the lookup table
+    assert!(fp.bias_div_round_relu6(3000 * TEST_SF, 0).is_err());
+  }
+
+  #[test]
+  fn softmax_masks_entries() {
+    // Same model as the circuit's masked_entries_are_zero
+    let inp = tensor(0, &[1, 3], &[0, 16, 0]);
+    let softmax = layer("Softmax", &[2, 1, 3, 0, 0, 1], &[&inp], 1, &[1, 3]);
+    assert_eq!(
+      eval(&model(vec![inp], vec![softmax])).unwrap(),
+      vec![4, 12, 0]
+    );
+  }
+
+  #[test]
+  fn fully_connected_matches_circuit() {
+    let config = fc_model(1);
+    let out = eval(&config).unwrap();
+    assert_eq!(out, vec![1, 0, 57, 39]);
+    assert_eq!(prove_model(config, false, MatMulCheck::Naive).unwrap(), out);
+  }
+
+  #[test]
+  fn conv2d_matches_circuit() {
+    let inp = tensor(
+      0,
+      &[1, 3, 3, 1],
+      &(1..10).map(|x| x * 16).collect::<Vec<_>>(),
+    );
+    let weight = tensor(1, &[2, 2, 2, 1], &[16, 0, 0, 16, 0, -16, 16, 0]);
+    let bias = tensor(2, &[2], &[1, -1]);
+    // Valid padding, relu6, stride 1
+    let conv = layer(
+      "Conv2D",
+      &[0, 1, 3, 1, 1],
+      &[&inp, &weight, &bias],
+      3,
+      &[1, 2, 2, 2],
+    );
+    let config = model(vec![inp, weight, bias], vec![conv]);
+
+    let out = eval(&config).unwrap();
+    assert_eq!(out, vec![96, 31, 96, 31, 96, 31, 96, 31]);
+    assert_eq!(prove_model(config, false, MatMulCheck::Naive).unwrap(), out);
+  }
+
+  #[test]
+  fn unsupported_activation_names_the_layer() {
+    let err = eval(&fc_model(2)).unwrap_err();
+    assert_eq!(
+      format!("{:#}", err),
+      "layer 0 (FullyConnected): unsupported activation 2"
+    );
+  }
+}
@@ -18,6 +18,7 @@ pub struct LayerMsgpack {
   pub out_idxes: Vec<i64>,
   pub out_shapes: Vec<Vec<i64>>,
   pub mask: Vec<i64>,
+  pub matmul_check: Option<String>, // "auto", "naive" or "freivalds", for matrix multiplications
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -29,7 +30,7 @@ pub struct ModelMsgpack {
   pub out_idxes: Vec<i64>,
   pub tensors: Vec<TensorMsgpack>,
   pub layers: Vec<LayerMsgpack>,
-  // these parameters are currently ignored
+  // use_selectors is currently ignored
   pub use_selectors: Option<bool>,
   pub commit_before: Option<Vec<Vec<i64>>>,
   pub commit_after: Option<Vec<Vec<i64>>>,
@@ -0,0 +1,388 @@
+use std::{
+  collections::BTreeMap,
+  fmt::Write,
+  sync::Mutex,
+  time::{Duration, Instant},
+};
+
+use lazy_static::lazy_static;
+use plonky2::{
+  field::extension::Extendable,
+  hash::hash_types::RichField,
+  iop::{
+    generator::GeneratedValues,
+    target::Target,
+    witness::{PartialWitness, PartitionWitness, WitnessWrite},
+  },
+  plonk::{
+    circuit_builder::CircuitBuilder,
+    circuit_data::{CommonCircuitData, ProverOnlyCircuitData},
+    config::GenericConfig,
+    prover::set_lookup_wires,
+  },
+};
+use serde_derive::Serialize;
+
+use crate::model::{ModelCircuit, GADGET_CONFIG};
+
+lazy_static! {
+  // Filled by the DAG while the circuit is constructed
+  pub static ref LAYER_PROFILE: Mutex<Vec<LayerProfile>> = Mutex::new(vec![]);
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LayerProfile {
+  pub name: String,
+  #[serde(skip)]
+  pub first_gate: usize,
+  pub gates: usize,
+  pub lookups: usize,
+  pub build_time_ms: f64,
+  pub witness_time_ms: f64,
+  pub time_share: f64,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct GeneratorProfile {
+  pub name: String,
+  pub runs: usize,
+  pub witness_time_ms: f64,
+  pub time_share: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ProfileReport {
+  pub gates: usize,
+  pub degree: usize,
+  pub lookups: usize,
+  pub witness_time_ms: f64,
+  // Witness generation that happens outside of the DAG layers, e.g. commitments and lookups
+  pub other_time_ms: f64,
+  pub layers: Vec<LayerProfile>,
+  pub gadget_lookups: BTreeMap<String, usize>,
+  pub generators: Vec<GeneratorProfile>,
+}
+
+impl ProfileReport {
+  pub fn to_csv(&self) -> String {
+    let mut csv = "kind,name,gates,lookups,witness_time_ms,time_share\n".to_string();
+    for layer in self.layers.iter() {
+      writeln!(
+        csv,
+        "layer,\"{}\",{},{},{:.3},{:.4}",
+        layer.name, layer.gates, layer.lookups, layer.witness_time_ms, layer.time_share
+      )
+      .unwrap();
+    }
+    for (gadget, lookups) in self.gadget_lookups.iter() {
+      writeln!(csv, "gadget,\"{}\",,{},,", gadget, lookups).unwrap();
+    }
+    for generator in self.generators.iter() {
+      writeln!(
+        csv,
+        "generator,\"{}\",,,{:.3},{:.4}",
+        generator.name, generator.witness_time_ms, generator.time_share
+      )
+      .unwrap();
+    }
+    csv
+  }
+}
+
+fn num_lookups<F: RichField + Extendable<D>, const D: usize>(
+  builder: &CircuitBuilder<F, D>,
+) -> usize {
+  (0..builder.num_luts())
+    .map(|i| builder.get_lut_lookups(i).len())
+    .sum()
+}
+
+// Marks the start of a layer in the builder, see `LayerStart::finish`
+pub struct LayerStart {
+  gates: usize,
+  lookups: usize,
+  time: Instant,
+}
+
+impl LayerStart {
+  pub fn new<F: RichField + Extendable<D>, const D: usize>(builder: &CircuitBuilder<F, D>) -> Self {
+    Self {
+      gates: builder.num_gates(),
+      lookups: num_lookups(builder),
+      time: Instant::now(),
+    }
+  }
+
+  pub fn finish<F: RichField + Extendable<D>, const D: usize>(
+    self,
+    builder: &CircuitBuilder<F, D>,
+    name: String,
+  ) {
+    LAYER_PROFILE.lock().unwrap().push(LayerProfile {
+      name,
+      first_gate: self.gates,
+      gates: builder.num_gates() - self.gates,
+      lookups: num_lookups(builder) - self.lookups,
+      build_time_ms: self.time.elapsed().as_secs_f64() * 1000.,
+      ..Default::default()
+    });
+  }
+}
+
+// The layer that added the gate at the given row. Gates added after the DAG (commitments, lookup
+// and constant gates) don't belong to any layer.
+fn layer_of_row(layers: &[LayerProfile], row: usize) -> Option<usize> {
+  let idx = layers.partition_point(|layer| layer.first_gate <= row);
+  if idx > 0 && row < layers[idx - 1].first_gate + layers[idx - 1].gates {
+    Some(idx - 1)
+  } else {
+    None
+  }
+}
+
+fn wire_row(mut targets: impl Iterator<Item = Target>) -> Option<usize> {
+  targets.find_map(|t| match t {
+    Target::Wire(wire) => Some(wire.row),
+    _ => None,
+  })
+}
+
+// Generator ids may include their parameters
+fn generator_name(id: &str) -> String {
+  id.split(|c: char| !c.is_alphanumeric() && c != '_')
+    .next()
+    .unwrap_or(id)
+    .to_string()
+}
+
+// Same as `generate_partial_witness`, but times every generator. The time is charged to the layer
+// that owns the wires the generator writes (or watches, if it only writes virtual targets).
+pub fn profile_partial_witness<
+  'a,
+  F: RichField + Extendable<D>,
+  C: GenericConfig<D, F = F>,
+  const D: usize,
+>(
+  inputs: PartialWitness<F>,
+  prover_data: &'a ProverOnlyCircuitData<F, C, D>,
+  common_data: &'a CommonCircuitData<F, D>,
+  layers: &mut [LayerProfile],
+  generators_profile: &mut BTreeMap<String, (usize, Duration)>,
+) -> (PartitionWitness<'a, F>, Duration) {
+  let config = &common_data.config;
+  let generators = &prover_data.generators;
+  let generator_indices_by_watches = &prover_data.generator_indices_by_watches;
+
+  let mut witness = PartitionWitness::new(
+    config.num_wires,
+    common_data.degree(),
+    &prover_data.representative_map,
+  );
+
+  for (t, v) in inputs.target_values.into_iter() {
+    witness.set_target(t, v);
+  }
+
+  let mut layer_times = vec![Duration::ZERO; layers.len()];
+  let mut other_time = Duration::ZERO;
+
+  let mut pending_generator_indices: Vec<_> = (0..generators.len()).collect();
+  let mut generator_is_expired = vec![false; generators.len()];
+  let mut remaining_generators = generators.len();
+
+  let mut buffer = GeneratedValues::empty();
+
+  while !pending_generator_indices.is_empty() {
+    let mut next_pending_generator_indices = Vec::new();
+
+    for &generator_idx in &pending_generator_indices {
+      if generator_is_expired[generator_idx] {
+        continue;
+      }
+
+      let generator = &generators[generator_idx].0;
+      let start = Instant::now();
+      let finished = generator.run(&witness, &mut buffer);
+      let elapsed = start.elapsed();
+      if finished {
+        generator_is_expired[generator_idx] = true;
+        remaining_generators -= 1;
+      }
+
+      let row = wire_row(buffer.target_values.iter().map(|(t, _)| *t))
+        .or_else(|| wire_row(generator.watch_list().into_iter()));
+      match row.and_then(|row| layer_of_row(layers, row)) {
+        Some(layer_idx) => layer_times[layer_idx] += elapsed,
+        None => other_time += elapsed,
+      }
+      let entry = generators_profile
+        .entry(generator_name(&generator.id()))
+        .or_insert((0, Duration::ZERO));
+      entry.0 += 1;
+      entry.1 += elapsed;
+
+      let new_target_reps = buffer
+        .target_values
+        .drain(..)
+        .flat_map(|(t, v)| witness.set_target_returning_rep(t, v));
+
+      for watch in new_target_reps {
+        let opt_watchers = generator_indices_by_watches.get(&watch);
+        if let Some(watchers) = opt_watchers {
+          for &watching_generator_idx in watchers {
+            if !generator_is_expired[watching_generator_idx] {
+              next_pending_generator_indices.push(watching_generator_idx);
+            }
+          }
+        }
+      }
+    }
+
+    pending_generator_indices = next_pending_generator_indices;
+  }
+
+  assert_eq!(
+    remaining_generators, 0,
+    "{} generators weren't run",
+    remaining_generators,
+  );
+
+  for (layer, time) in layers.iter_mut().zip(layer_times) {
+    layer.witness_time_ms += time.as_secs_f64() * 1000.;
+  }
+
+  (witness, other_time)
+}
+
+pub fn profile_circuit<
+  F: RichField + Extendable<D>,
+  C: GenericConfig<D, F = F> + 'static,
+  const D: usize,
+>(
+  circuit: ModelCircuit,
+  mut builder: CircuitBuilder<F, D>,
+  mut pw: PartialWitness<F>,
+) -> ProfileReport {
+  LAYER_PROFILE.lock().unwrap().clear();
+  let (_, rand_targets) = circuit.construct::<F, C, D>(&mut builder);
+  let mut layers = LAYER_PROFILE.lock().unwrap().clone();
+
+  let gadget_config = GADGET_CONFIG.lock().unwrap().clone();
+  let gadget_lookups = gadget_config
+    .tables
+    .iter()
+    .map(|(gadget_type, luts)| {
+      let lookups = luts
+        .iter()
+        .map(|lut| builder.get_lut_lookups(*lut).len())
+        .sum::<usize>();
+      (format!("{:?}", gadget_type), lookups)
+    })
+    .collect();
+  let gates = builder.num_gates();
+  let lookups = num_lookups(&builder);
+
+  builder.print_gate_counts(0);
+  let data = builder.build::<C>();
+
+  // The random challenges only matter for soundness, not for the cost of the witness
+  for t in &rand_targets {
+    pw.set_target(*t, F::ZERO);
+  }
+
+  let start = Instant::now();
+  let mut generators_profile = BTreeMap::new();
+  let (mut partition_witness, mut other_time) = profile_partial_witness(
+    pw,
+    &data.prover_only,
+    &data.common,
+    &mut layers,
+    &mut generators_profile,
+  );
+  let lookup_start = Instant::now();
+  set_lookup_wires(&data.prover_only, &data.common, &mut partition_witness);
+  other_time += lookup_start.elapsed();
+  let total = start.elapsed().as_secs_f64() * 1000.;
+
+  let share = |time_ms: f64| if total > 0. { time_ms / total } else { 0. };
+  for layer in layers.iter_mut() {
+    layer.time_share = share(layer.witness_time_ms);
+  }
+  let generators = generators_profile
+    .into_iter()
+    .map(|(name, (runs, time))| {
+      let witness_time_ms = time.as_secs_f64() * 1000.;
+      GeneratorProfile {
+        name,
+        runs,
+        witness_time_ms,
+        time_share: share(witness_time_ms),
+      }
+    })
+    .collect();
+
+  ProfileReport {
+    gates,
+    degree: data.common.degree(),
+    lookups,
+    witness_time_ms: total,
+    other_time_ms: other_time.as_secs_f64() * 1000.,
+    layers,
+    gadget_lookups,
+    generators,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::BTreeMap;
+
+  use plonky2::iop::{generator::generate_partial_witness, witness::WitnessWrite};
+
+  use super::{profile_partial_witness, LAYER_PROFILE};
+  use crate::{
+    layers::layer::MatMulCheck,
+    model::ModelCircuit,
+    utils::{
+      proving::i64_to_field,
+      testing::{layer, model, tensor, C, D, F, MODEL_LOCK, TEST_COLS},
+    },
+  };
+
+  #[test]
+  fn profiled_witness_matches_generated() {
+    let _guard = MODEL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let inp = tensor(0, &[2, 3], &[16, 32, -16, 8, 0, 48]);
+    let weight = tensor(1, &[2, 3], &[16, 0, 16, 32, -16, 8]);
+    let bias = tensor(2, &[2], &[1, -1]);
+    let fc = layer("FullyConnected", &[1], &[&inp, &weight, &bias], 3, &[2, 2]);
+    let (circuit, mut builder, mut pw) = ModelCircuit::generate_from_msgpack::<F, C, D>(
+      model(vec![inp, weight, bias], vec![fc]),
+      true,
+      &TEST_COLS,
+      false,
+      MatMulCheck::Freivalds,
+    );
+    LAYER_PROFILE.lock().unwrap().clear();
+    let (_, rand_targets) = circuit.construct::<F, C, D>(&mut builder);
+    let mut layers = LAYER_PROFILE.lock().unwrap().clone();
+    for t in &rand_targets {
+      pw.set_target(*t, i64_to_field::<F, D>(7));
+    }
+    let data = builder.build::<C>();
+
+    let expected = generate_partial_witness(pw.clone(), &data.prover_only, &data.common);
+    let (witness, _) = profile_partial_witness(
+      pw,
+      &data.prover_only,
+      &data.common,
+      &mut layers,
+      &mut BTreeMap::new(),
+    );
+    assert_eq!(
+      witness.full_witness().wire_values,
+      expected.full_witness().wire_values
+    );
+    assert_eq!(layers.len(), 1);
+  }
+}
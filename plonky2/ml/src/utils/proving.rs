@@ -1,5 +1,5 @@
 use log::Level;
-use ndarray::Array;
+use ndarray::{Array, IxDyn};
 use plonky2::fri::oracle::PolynomialBatch;
 use plonky2::gadgets::arithmetic::EqualityGenerator;
 use plonky2::gadgets::arithmetic_extension::QuotientGeneratorExtension;
@@ -44,7 +44,9 @@ use plonky2::iop::generator::{
 };
 use plonky2::iop::witness::WitnessWrite;
 use plonky2::plonk::circuit_data::CircuitData;
-use plonky2::plonk::prover::prove;
+use plonky2::plonk::circuit_data::VerifierCircuitData;
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use plonky2::plonk::prover::prove_with_partition_witness;
 use plonky2::plonk::prover::set_lookup_wires;
 use plonky2::read_gate_impl;
 use plonky2::read_generator_impl;
@@ -55,14 +57,22 @@ use plonky2::{get_generator_tag_impl, impl_gate_serializer};
 use plonky2_field::polynomial::PolynomialValues;
 use plonky2_maybe_rayon::MaybeParIter;
 use plonky2_maybe_rayon::ParallelIterator;
-use serde_json::json;
+use serde_derive::{Deserialize, Serialize};
 use std::io::prelude::*;
-use std::{fs::File, io::BufWriter, time::Instant};
+use std::ops::Range;
+use std::rc::Rc;
+use std::{
+  fs::File,
+  io::{BufReader, BufWriter},
+  time::Instant,
+};
+use zkperf_report::{BenchmarkResult, PhaseTracker};
 
 use plonky2::{
   field::extension::Extendable,
   hash::hash_types::RichField,
   iop::generator::generate_partial_witness,
+  iop::target::Target,
   iop::witness::{PartialWitness, Witness},
   plonk::{circuit_builder::CircuitBuilder, config::GenericConfig},
 };
@@ -75,6 +85,8 @@ use crate::gates::dot_prod::DotProductGate;
 use crate::gates::dot_prod::DotProductGenerator;
 use crate::gates::var_div::DivRoundGate;
 use crate::gates::var_div::DivRoundGenerator;
+use crate::gates::var_div_big::VarDivRoundBigGate;
+use crate::gates::var_div_big::VarDivRoundBigGenerator;
 use crate::layers::fully_connected::MatMulGenerator;
 use crate::{gadgets::gadget::convert_to_u64, model::ModelCircuit};
 
@@ -101,7 +113,8 @@ impl<F: RichField + Extendable<D>, const D: usize> GateSerializer<F, D> for MLGa
     PublicInputGate,
     RandomAccessGate<F, D>,
     ReducingExtensionGate<D>,
-    ReducingGate<D>
+    ReducingGate<D>,
+    VarDivRoundBigGate
   }
 }
 pub struct MLGeneratorSerializer {}
@@ -139,10 +152,43 @@ where
       ReducingGenerator<D>,
       ReducingExtensionGenerator<D>,
       SplitGenerator,
+      VarDivRoundBigGenerator,
       WireSplitGenerator
   }
 }
 
+// Builds the model and, if `public_outputs` is set, registers its output as the last public inputs
+pub fn construct_with_public_outputs<
+  F: RichField + Extendable<D>,
+  C: GenericConfig<D, F = F> + 'static,
+  const D: usize,
+>(
+  circuit: &ModelCircuit,
+  builder: &mut CircuitBuilder<F, D>,
+  public_outputs: bool,
+) -> (
+  Vec<Array<Rc<Target>, IxDyn>>,
+  Vec<Target>,
+  PublicInputLayout,
+) {
+  // The input tensors are registered while loading the model, the commitments while building it
+  let num_inputs = builder.num_public_inputs();
+  let (result_targets, rand_targets) = circuit.construct::<F, C, D>(builder);
+  let num_commitments = builder.num_public_inputs() - num_inputs;
+
+  if public_outputs && result_targets.len() > 0 {
+    for t in result_targets[0].iter() {
+      builder.register_public_input(**t);
+    }
+  }
+  let layout = PublicInputLayout {
+    num_inputs,
+    num_commitments,
+    num_outputs: builder.num_public_inputs() - num_inputs - num_commitments,
+  };
+  (result_targets, rand_targets, layout)
+}
+
 pub fn time_circuit<
   F: RichField + Extendable<D>,
   C: GenericConfig<D, F = F> + 'static,
@@ -154,27 +200,37 @@ pub fn time_circuit<
   circuit_type: String,
   build_prove: String,
   outp_json: String,
+  public_outputs: bool,
 ) {
-  let (result_targets, rand_targets) = circuit.construct::<F, C, D>(&mut builder);
+  let (result_targets, rand_targets, layout) =
+    construct_with_public_outputs::<F, C, D>(&circuit, &mut builder, public_outputs);
 
   if build_prove == "build" {
+    let mut results =
+      BenchmarkResult::new("plonky2", &circuit_type.to_uppercase(), "Plonk+FRI", "NaN");
     builder.print_gate_counts(0);
     println!("building circuit");
+    let tracker = PhaseTracker::start("build");
     let start = Instant::now();
     let data = builder.build::<C>();
     let build_duration = start.elapsed();
+    results.record_phase(tracker.finish());
     println!("circuit build duration: {:?}", build_duration);
     let gate_serializer = MLGateSerializer {};
     let generator_serializer = MLGeneratorSerializer {};
 
-    let mut file = File::create(circuit_type + "_data").unwrap();
+    let mut file = File::create(format!("{}_data", circuit_type)).unwrap();
     let _ = file.write_all(
       &data
         .to_bytes(&gate_serializer, &generator_serializer)
         .unwrap(),
     );
+    let mut file = File::create(format!("{}_verifier_data", circuit_type)).unwrap();
+    let _ = file.write_all(&data.verifier_data().to_bytes(&gate_serializer).unwrap());
+    layout.write(&circuit_type).unwrap();
+    results.write(outp_json).unwrap();
   } else {
-    let mut file = File::open(circuit_type + "_data").expect("File not found");
+    let mut file = File::open(format!("{}_data", circuit_type)).expect("File not found");
 
     let mut buffer = Vec::new();
     file
@@ -185,56 +241,84 @@ pub fn time_circuit<
     let generator_serializer = MLGeneratorSerializer {};
     let data: CircuitData<F, C, D> =
       CircuitData::from_bytes(&buffer, &gate_serializer, &generator_serializer).unwrap();
-    println!("generating random values");
-    // set the pw_commit rand targets to 0
-    let mut pw_commit = pw.clone();
-    for t in &rand_targets {
-      pw_commit.set_target(*t, F::ZERO);
-    }
+    let mut results = BenchmarkResult::load_or_new(
+      &outp_json,
+      "plonky2",
+      &circuit_type.to_uppercase(),
+      "Plonk+FRI",
+      "NaN",
+    );
 
+    println!("generating random values");
+    let tracker = PhaseTracker::start("witness");
+    let mut timing = TimingTree::new("witness", Level::Info);
     let CircuitData {
       prover_only: ref prover_data,
       verifier_only: _,
       common: ref common_data,
     } = data;
 
-    let mut partition_witness = generate_partial_witness(pw_commit, &prover_data, &common_data);
-    set_lookup_wires(&prover_data, &common_data, &mut partition_witness);
+    // Layers that take their challenges from a commitment to the witness. The matmul checks
+    // squeeze theirs in-circuit, so this is skipped when nothing asks for one.
+    if !rand_targets.is_empty() {
+      // set the pw_commit rand targets to 0
+      let mut pw_commit = pw.clone();
+      for t in &rand_targets {
+        pw_commit.set_target(*t, F::ZERO);
+      }
+
+      let mut partition_witness = generate_partial_witness(pw_commit, &prover_data, &common_data);
+      set_lookup_wires(&prover_data, &common_data, &mut partition_witness);
 
-    let witness = partition_witness.full_witness();
+      let witness = partition_witness.full_witness();
 
-    let wires_values: Vec<PolynomialValues<F>> = witness
-      .wire_values
-      .par_iter()
-      .map(|column| PolynomialValues::new(column.clone()))
-      .collect();
+      let wires_values: Vec<PolynomialValues<F>> = witness
+        .wire_values
+        .par_iter()
+        .map(|column| PolynomialValues::new(column.clone()))
+        .collect();
 
-    let wires_commitment = PolynomialBatch::<F, C, D>::from_values(
-      wires_values,
-      common_data.config.fri_config.rate_bits,
-      common_data.config.zero_knowledge,
-      common_data.config.fri_config.cap_height,
-      &mut TimingTree::default(),
-      prover_data.fft_root_table.as_ref(),
-    );
+      let wires_commitment = PolynomialBatch::<F, C, D>::from_values(
+        wires_values,
+        common_data.config.fri_config.rate_bits,
+        common_data.config.zero_knowledge,
+        common_data.config.fri_config.cap_height,
+        &mut timing,
+        prover_data.fft_root_table.as_ref(),
+      );
 
-    let mut challenger = Challenger::<F, C::Hasher>::new();
+      let mut challenger = Challenger::<F, C::Hasher>::new();
 
-    // Observe the instance.
-    challenger.observe_cap::<C::Hasher>(&wires_commitment.merkle_tree.cap);
-    let rand_values = challenger.get_n_challenges(rand_targets.len());
+      // Observe the instance.
+      challenger.observe_cap::<C::Hasher>(&wires_commitment.merkle_tree.cap);
+      let rand_values = challenger.get_n_challenges(rand_targets.len());
 
-    for i in 0..rand_targets.len() {
-      pw.set_target(rand_targets[i], rand_values[i]);
+      for i in 0..rand_targets.len() {
+        pw.set_target(rand_targets[i], rand_values[i]);
+      }
     }
 
     let pw2 = pw.clone();
+    let partition_witness = generate_partial_witness(pw, &prover_data, &common_data);
+    timing.pop();
+    results.record_phase(tracker.finish());
+    results.record_timing("witness", &timing).unwrap();
+    let witness_duration = timing.duration();
 
     println!("proving circuit");
+    let tracker = PhaseTracker::start("prove");
     let mut timing = TimingTree::new("prove", Level::Info);
-    let proof = prove::<F, C, D>(&prover_data, &common_data, pw, &mut timing).unwrap();
+    let proof = prove_with_partition_witness::<F, C, D>(
+      &prover_data,
+      &common_data,
+      partition_witness,
+      &mut timing,
+    )
+    .unwrap();
     timing.pop();
+    results.record_phase(tracker.finish());
     timing.print();
+    results.record_timing("prove", &timing).unwrap();
 
     let proof_duration = timing.duration();
     println!("Proving time: {:?}", proof_duration);
@@ -243,27 +327,34 @@ pub fn time_circuit<
     let proof_len = proof_bytes.len();
     println!("Proof size: {} bytes", proof_len);
 
-    println!("verifying circuit");
-    let mut timing = TimingTree::new("verify", Level::Info);
-    data.verify(proof.clone()).expect("verify error");
-    timing.pop();
-    timing.print();
-
-    let verify_duration = timing.duration();
-    println!("Verifying time: {:?}", verify_duration);
+    // Verification runs in a separate process, see `verify_circuit`
+    let proof_fname = format!("{}_proof", circuit_type);
+    let mut file = File::create(&proof_fname).unwrap();
+    file.write_all(&proof_bytes).unwrap();
+    println!("wrote proof to {}", proof_fname);
 
-    println!("generating witness");
-    let witness = generate_partial_witness(pw2, &prover_data, &common_data);
+    if public_outputs {
+      // The output is read back from the proof, so it is exactly what was proven
+      let values = proof.public_inputs[layout.outputs()]
+        .iter()
+        .map(|x| field_to_i64::<F, D>(x))
+        .collect::<Vec<_>>();
+      for (idx, v) in values.iter().enumerate() {
+        println!("final out [{}] x: {}", idx, v);
+      }
+      let out_fname = "out.msgpack";
+      let f = File::create(out_fname).unwrap();
+      let mut buf = BufWriter::new(f);
+      rmp_serde::encode::write_named(&mut buf, &values).unwrap();
+    } else if result_targets.len() > 0 {
+      println!("generating witness");
+      let witness = generate_partial_witness(pw2, &prover_data, &common_data);
 
-    if result_targets.len() > 0 {
       let out = Array::from_iter(result_targets[0].iter().cloned());
       let mut values: Vec<i64> = vec![];
       for (idx, t) in out.iter().enumerate() {
         let value = witness.get_target(**t);
-        let bias: i64 = 1 << 60 as i64;
-
-        let v_pos = value + F::from_canonical_u64(bias as u64);
-        let v = convert_to_u64(&v_pos) as i64 - bias as i64;
+        let v = field_to_i64::<F, D>(&value);
         println!("final out [{}] x: {}", idx, v);
         values.push(v);
       }
@@ -274,18 +365,278 @@ pub fn time_circuit<
     }
 
     println!("writing results");
-    let results = json!({
-      "Framework": "plonky2",
-      "Backend": "Plonk+FRI",
-      "Curve": "NaN",
-      "ProverTime": proof_duration.as_secs_f32(),
-      "VerifierTime": verify_duration.as_nanos() as f32 / 1000000.,
-      "ProofSize": proof_len
-    });
-
-    let json_string = serde_json::to_string(&results).unwrap();
-
-    let mut file = File::create(outp_json).unwrap();
-    let _ = file.write_all(json_string.as_bytes());
+    results.set_shape(common_data.degree(), common_data.config.num_wires);
+    results.set_prover_time(witness_duration + proof_duration);
+    results.set_proof_size(proof_len);
+    results.record_peak_rss();
+    results.write(outp_json).unwrap();
+  }
+}
+
+pub fn read_verifier_data<
+  F: RichField + Extendable<D>,
+  C: GenericConfig<D, F = F> + 'static,
+  const D: usize,
+>(
+  circuit_type: &str,
+) -> (VerifierCircuitData<F, C, D>, ProofWithPublicInputs<F, C, D>) {
+  let read_bytes = |fname: String| {
+    let mut file = File::open(fname).expect("File not found");
+    let mut buffer = Vec::new();
+    file
+      .read_to_end(&mut buffer)
+      .expect("Failed to read the file");
+    buffer
+  };
+
+  let gate_serializer = MLGateSerializer {};
+  let data = VerifierCircuitData::<F, C, D>::from_bytes(
+    read_bytes(format!("{}_verifier_data", circuit_type)),
+    &gate_serializer,
+  )
+  .unwrap();
+  let proof = ProofWithPublicInputs::<F, C, D>::from_bytes(
+    read_bytes(format!("{}_proof", circuit_type)),
+    &data.common,
+  )
+  .unwrap();
+  (data, proof)
+}
+
+// Verifies the proof written by `time_circuit` using only the serialized verifier data, so the
+// verifier time does not include any prover state
+pub fn verify_circuit<
+  F: RichField + Extendable<D>,
+  C: GenericConfig<D, F = F> + 'static,
+  const D: usize,
+>(
+  circuit_type: String,
+  outp_json: String,
+) {
+  let mut results = BenchmarkResult::load_or_new(
+    &outp_json,
+    "plonky2",
+    &circuit_type.to_uppercase(),
+    "Plonk+FRI",
+    "NaN",
+  );
+  let (data, proof) = read_verifier_data::<F, C, D>(&circuit_type);
+
+  println!("verifying circuit");
+  let tracker = PhaseTracker::start("verify");
+  let mut timing = TimingTree::new("verify", Level::Info);
+  data.verify(proof).expect("verify error");
+  timing.pop();
+  results.record_phase(tracker.finish());
+  timing.print();
+  results.record_timing("verify", &timing).unwrap();
+
+  let verify_duration = timing.duration();
+  println!("Verifying time: {:?}", verify_duration);
+
+  println!("writing results");
+  results.set_verifier_time(verify_duration);
+  results.write(outp_json).unwrap();
+}
+
+pub fn field_to_i64<F: RichField + Extendable<D>, const D: usize>(x: &F) -> i64 {
+  let bias: i64 = 1 << 60 as i64;
+
+  let v_pos = *x + F::from_canonical_u64(bias as u64);
+  convert_to_u64::<F, D>(&v_pos) as i64 - bias as i64
+}
+
+pub fn i64_to_field<F: RichField + Extendable<D>, const D: usize>(x: i64) -> F {
+  let bias: i64 = 1 << 60 as i64;
+
+  F::from_canonical_u64((x + bias) as u64) - F::from_canonical_u64(bias as u64)
+}
+
+// The public inputs of an ML circuit are the input tensors, then the tensor commitments (four
+// elements each), then the outputs if they are public. Written next to the verifier data as
+// `{circuit_type}_public_inputs`, so the verifier knows where each segment starts
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicInputLayout {
+  pub num_inputs: usize,
+  pub num_commitments: usize,
+  pub num_outputs: usize,
+}
+
+impl PublicInputLayout {
+  pub fn num_public_inputs(&self) -> usize {
+    self.num_inputs + self.num_commitments + self.num_outputs
+  }
+
+  pub fn inputs(&self) -> Range<usize> {
+    0..self.num_inputs
+  }
+
+  pub fn commitments(&self) -> Range<usize> {
+    self.num_inputs..self.num_inputs + self.num_commitments
+  }
+
+  pub fn outputs(&self) -> Range<usize> {
+    self.num_inputs + self.num_commitments..self.num_public_inputs()
+  }
+
+  pub fn write(&self, circuit_type: &str) -> anyhow::Result<()> {
+    let file = File::create(format!("{}_public_inputs", circuit_type))?;
+    rmp_serde::encode::write_named(&mut BufWriter::new(file), self)?;
+    Ok(())
+  }
+
+  pub fn read(circuit_type: &str) -> anyhow::Result<Self> {
+    let file = File::open(format!("{}_public_inputs", circuit_type))?;
+    Ok(rmp_serde::from_read(BufReader::new(file))?)
+  }
+}
+
+// Checks the proof's public inputs against the layout, then the claimed inputs (if any) and
+// outputs against their segments, then verifies the proof itself
+pub fn verify_with_public_values<
+  F: RichField + Extendable<D>,
+  C: GenericConfig<D, F = F> + 'static,
+  const D: usize,
+>(
+  data: &VerifierCircuitData<F, C, D>,
+  layout: &PublicInputLayout,
+  proof: ProofWithPublicInputs<F, C, D>,
+  claimed_inputs: Option<&Vec<i64>>,
+  claimed_outputs: &Vec<i64>,
+) -> anyhow::Result<()> {
+  let public_inputs = &proof.public_inputs;
+  if public_inputs.len() != layout.num_public_inputs() {
+    anyhow::bail!(
+      "proof has {} public inputs, but the circuit has {}",
+      public_inputs.len(),
+      layout.num_public_inputs()
+    );
+  }
+  if let Some(claimed_inputs) = claimed_inputs {
+    if claimed_inputs.len() != layout.num_inputs {
+      anyhow::bail!(
+        "{} inputs were claimed, but the circuit has {}",
+        claimed_inputs.len(),
+        layout.num_inputs
+      );
+    }
+  }
+  if claimed_outputs.len() != layout.num_outputs {
+    anyhow::bail!(
+      "{} outputs were claimed, but the circuit has {} public outputs",
+      claimed_outputs.len(),
+      layout.num_outputs
+    );
+  }
+
+  let proven_inputs = &public_inputs[layout.inputs()];
+  let claimed_inputs = claimed_inputs.map_or(&[][..], |x| &x[..]);
+  for (idx, (proven, claimed)) in proven_inputs.iter().zip(claimed_inputs.iter()).enumerate() {
+    if *proven != i64_to_field::<F, D>(*claimed) {
+      anyhow::bail!(
+        "input [{}] mismatch: proven {}, claimed {}",
+        idx,
+        field_to_i64::<F, D>(proven),
+        claimed
+      );
+    }
+  }
+
+  let proven_outputs = &public_inputs[layout.outputs()];
+  for (idx, (proven, claimed)) in proven_outputs
+    .iter()
+    .zip(claimed_outputs.iter())
+    .enumerate()
+  {
+    if *proven != i64_to_field::<F, D>(*claimed) {
+      anyhow::bail!(
+        "output [{}] mismatch: proven {}, claimed {}",
+        idx,
+        field_to_i64::<F, D>(proven),
+        claimed
+      );
+    }
+  }
+
+  data.verify(proof)
+}
+
+#[cfg(test)]
+mod tests {
+  use plonky2::plonk::{circuit_data::VerifierCircuitData, proof::ProofWithPublicInputs};
+
+  use crate::layers::layer::MatMulCheck;
+  use crate::model::ModelCircuit;
+  use crate::utils::testing::{layer, model, tensor, C, D, F, MODEL_LOCK, TEST_COLS};
+
+  use super::{construct_with_public_outputs, verify_with_public_values, PublicInputLayout};
+
+  // [5, -3, 0] - [2, 2, -4] with the input public and the output committed and public
+  fn prove_sub() -> (
+    VerifierCircuitData<F, C, D>,
+    PublicInputLayout,
+    ProofWithPublicInputs<F, C, D>,
+  ) {
+    let _guard = MODEL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let a = tensor(0, &[1, 3], &[5, -3, 0]);
+    let b = tensor(1, &[1, 3], &[2, 2, -4]);
+    let sub = layer("Sub", &[], &[&a, &b], 2, &[1, 3]);
+    let mut config = model(vec![a, b], vec![sub]);
+    config.commit_after = Some(vec![vec![2]]);
+
+    let (circuit, mut builder, pw) = ModelCircuit::generate_from_msgpack::<F, C, D>(
+      config,
+      true,
+      &TEST_COLS,
+      true,
+      MatMulCheck::Naive,
+    );
+    let (_, _, layout) = construct_with_public_outputs::<F, C, D>(&circuit, &mut builder, true);
+    let data = builder.build::<C>();
+    let proof = data.prove(pw).unwrap();
+    (data.verifier_data(), layout, proof)
+  }
+
+  #[test]
+  fn layout_matches_proof() {
+    let (_, layout, proof) = prove_sub();
+    assert_eq!(
+      layout,
+      PublicInputLayout {
+        num_inputs: 3,
+        num_commitments: 4,
+        num_outputs: 3,
+      }
+    );
+    assert_eq!(proof.public_inputs.len(), layout.num_public_inputs());
+  }
+
+  #[test]
+  fn checks_each_segment() {
+    let (data, layout, proof) = prove_sub();
+    let inputs = vec![5, -3, 0];
+    let outputs = vec![3, -5, 4];
+    let verify = |inputs: Option<&Vec<i64>>, outputs: &Vec<i64>| {
+      verify_with_public_values(&data, &layout, proof.clone(), inputs, outputs)
+    };
+
+    assert!(verify(Some(&inputs), &outputs).is_ok());
+    assert!(verify(None, &outputs).is_ok());
+    assert!(verify(Some(&vec![5, -3, 1]), &outputs).is_err());
+    assert!(verify(Some(&inputs), &vec![3, -5, 5]).is_err());
+    // A suffix of the outputs, or a prefix of the inputs, is not enough
+    assert!(verify(Some(&inputs), &vec![-5, 4]).is_err());
+    assert!(verify(Some(&vec![5, -3]), &outputs).is_err());
+  }
+
+  #[test]
+  fn rejects_other_layouts() {
+    let (data, layout, proof) = prove_sub();
+    // A layout that does not cover every public input
+    let short = PublicInputLayout {
+      num_outputs: 2,
+      ..layout
+    };
+    assert!(verify_with_public_values(&data, &short, proof, None, &vec![-5, 4]).is_err());
   }
 }
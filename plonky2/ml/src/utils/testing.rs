@@ -0,0 +1,216 @@
+use std::{rc::Rc, sync::Mutex};
+
+use lazy_static::lazy_static;
+use ndarray::{Array, IxDyn};
+use plonky2::{
+  iop::{
+    generator::generate_partial_witness,
+    target::Target,
+    witness::{PartialWitness, Witness, WitnessWrite},
+  },
+  plonk::{
+    circuit_builder::CircuitBuilder,
+    circuit_data::CircuitConfig,
+    config::{GenericConfig, PoseidonGoldilocksConfig},
+    prover::prove_with_partition_witness,
+  },
+  util::timing::TimingTree,
+};
+
+use crate::{
+  gadgets::{
+    gadget::{Gadget, GadgetConfig},
+    input_lookup::InputLookupCircuit,
+  },
+  layers::layer::MatMulCheck,
+  model::ModelCircuit,
+  utils::{
+    loader::{LayerMsgpack, ModelMsgpack, TensorMsgpack},
+    proving::{field_to_i64, i64_to_field},
+  },
+};
+
+pub(crate) const D: usize = 2;
+pub(crate) type C = PoseidonGoldilocksConfig;
+pub(crate) type F = <C as GenericConfig<D>>::F;
+
+// Small enough to keep the tables cheap: min_val = -512, max_val = 502
+pub(crate) const TEST_SF: i64 = 16;
+pub(crate) const TEST_K: i64 = 10;
+pub(crate) const TEST_COLS: usize = 80;
+
+lazy_static! {
+  // The model circuits go through the global GADGET_CONFIG, so they must not be built in parallel
+  pub(crate) static ref MODEL_LOCK: Mutex<()> = Mutex::new(());
+}
+
+pub(crate) fn circuit_config() -> CircuitConfig {
+  CircuitConfig {
+    num_constants: 3,
+    num_routed_wires: TEST_COLS,
+    ..CircuitConfig::standard_recursion_config()
+  }
+}
+
+// The gadget config `ModelCircuit::generate_from_msgpack` derives for TEST_SF and TEST_K, with
+// the gadgets in `configure` loaded on top of the input lookup
+pub(crate) fn gadget_builder(
+  configure: &[fn(&mut CircuitBuilder<F, D>, GadgetConfig) -> GadgetConfig],
+) -> (CircuitBuilder<F, D>, Rc<GadgetConfig>) {
+  let mut builder = CircuitBuilder::<F, D>::new(circuit_config());
+  let mut gadget_config = GadgetConfig {
+    scale_factor: TEST_SF as u64,
+    shift_min_val: -(TEST_SF * TEST_SF * (1 << 17)),
+    div_outp_min_val: -(1 << (TEST_K - 1)),
+    min_val: -(1 << (TEST_K - 1)),
+    max_val: (1 << (TEST_K - 1)) - 10,
+    k: TEST_K as usize,
+    num_rows: (1 << TEST_K) - 10 + 1,
+    num_cols: TEST_COLS,
+    num_bits_per_elem: TEST_K,
+    ..GadgetConfig::default()
+  };
+  gadget_config = InputLookupCircuit::configure(&mut builder, gadget_config);
+  for f in configure {
+    gadget_config = f(&mut builder, gadget_config);
+  }
+  (builder, Rc::new(gadget_config))
+}
+
+pub(crate) fn add_inputs(
+  builder: &mut CircuitBuilder<F, D>,
+  pw: &mut PartialWitness<F>,
+  values: &[i64],
+) -> Vec<Target> {
+  let targets = builder.add_virtual_targets(values.len());
+  for (t, v) in targets.iter().zip(values.iter()) {
+    pw.set_target(*t, i64_to_field::<F, D>(*v));
+  }
+  targets
+}
+
+// Builds and proves the circuit, returning the values of `outputs` once the proof verifies.
+// `overrides` replaces values after witness generation, as a cheating prover would, so the
+// constraints on them are what rejects the proof
+pub(crate) fn prove_and_verify(
+  builder: CircuitBuilder<F, D>,
+  pw: PartialWitness<F>,
+  outputs: &[Target],
+  overrides: &[(Target, i64)],
+) -> anyhow::Result<Vec<i64>> {
+  let data = builder.build::<C>();
+  let mut witness = generate_partial_witness(pw, &data.prover_only, &data.common);
+  for (t, v) in overrides {
+    let rep = witness.representative_map[t.index(witness.num_wires, witness.degree)];
+    witness.values[rep] = Some(i64_to_field::<F, D>(*v));
+  }
+  let values = outputs
+    .iter()
+    .map(|t| field_to_i64::<F, D>(&witness.get_target(*t)))
+    .collect::<Vec<_>>();
+
+  let mut timing = TimingTree::default();
+  let proof =
+    prove_with_partition_witness::<F, C, D>(&data.prover_only, &data.common, witness, &mut timing)?;
+  data.verify(proof)?;
+  Ok(values)
+}
+
+pub(crate) fn tensor(idx: i64, shape: &[i64], data: &[i64]) -> TensorMsgpack {
+  TensorMsgpack {
+    idx,
+    shape: shape.to_vec(),
+    data: data.to_vec(),
+  }
+}
+
+// A layer reading `inputs` and writing tensor `out_idx`
+pub(crate) fn layer(
+  layer_type: &str,
+  params: &[i64],
+  inputs: &[&TensorMsgpack],
+  out_idx: i64,
+  out_shape: &[i64],
+) -> LayerMsgpack {
+  LayerMsgpack {
+    layer_type: layer_type.to_string(),
+    params: params.to_vec(),
+    inp_idxes: inputs.iter().map(|t| t.idx).collect(),
+    inp_shapes: inputs.iter().map(|t| t.shape.clone()).collect(),
+    out_idxes: vec![out_idx],
+    out_shapes: vec![out_shape.to_vec()],
+    mask: vec![],
+    matmul_check: None,
+  }
+}
+
+pub(crate) fn model(tensors: Vec<TensorMsgpack>, layers: Vec<LayerMsgpack>) -> ModelMsgpack {
+  let out_idxes = layers.last().unwrap().out_idxes.clone();
+  ModelMsgpack {
+    global_sf: TEST_SF,
+    k: TEST_K,
+    num_cols: TEST_COLS as i64,
+    inp_idxes: vec![tensors[0].idx],
+    out_idxes,
+    tensors,
+    layers,
+    use_selectors: None,
+    commit_before: None,
+    commit_after: None,
+    bits_per_elem: None,
+    num_random: None,
+  }
+}
+
+// Proves the model and returns the flattened first output tensor
+pub(crate) fn prove_model(
+  config: ModelMsgpack,
+  no_lookups: bool,
+  matmul_check: MatMulCheck,
+) -> anyhow::Result<Vec<i64>> {
+  prove_model_with(config, no_lookups, matmul_check, |_| vec![])
+}
+
+// As `prove_model`, with the overrides computed from the output tensors
+pub(crate) fn prove_model_with(
+  config: ModelMsgpack,
+  no_lookups: bool,
+  matmul_check: MatMulCheck,
+  overrides: impl FnOnce(&Vec<Array<Rc<Target>, IxDyn>>) -> Vec<(Target, i64)>,
+) -> anyhow::Result<Vec<i64>> {
+  let _guard = MODEL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+  let (circuit, mut builder, mut pw) = ModelCircuit::generate_from_msgpack::<F, C, D>(
+    config,
+    true,
+    &TEST_COLS,
+    no_lookups,
+    matmul_check,
+  );
+  let (result, rand_targets) = circuit.construct::<F, C, D>(&mut builder);
+  // Any value works for the challenges, the honest witness satisfies the circuit regardless
+  for (i, t) in rand_targets.iter().enumerate() {
+    pw.set_target(*t, i64_to_field::<F, D>(i as i64 + 7));
+  }
+  let outputs = result[0].iter().map(|t| **t).collect::<Vec<_>>();
+  let overrides = overrides(&result);
+  prove_and_verify(builder, pw, &outputs, &overrides)
+}
+
+// Runs the gadget built by `construct` on `inps` and returns its proven outputs
+pub(crate) fn eval_gadget<G: Gadget<F, D>>(
+  construct: impl FnOnce(Rc<GadgetConfig>) -> G,
+  configure: &[fn(&mut CircuitBuilder<F, D>, GadgetConfig) -> GadgetConfig],
+  inps: &[i64],
+) -> anyhow::Result<Vec<i64>> {
+  let (mut builder, gadget_config) = gadget_builder(configure);
+  let mut pw = PartialWitness::<F>::new();
+  let inp_targets = add_inputs(&mut builder, &mut pw, inps);
+  let gadget = construct(gadget_config.clone());
+  let outp = gadget.make_circuit(
+    &mut builder,
+    &vec![inp_targets.iter().collect()],
+    &vec![],
+    gadget_config,
+  );
+  prove_and_verify(builder, pw, &outp, &[])
+}
@@ -0,0 +1,227 @@
+use core::arch::x86_64::*;
+
+use crate::field::goldilocks_field::GoldilocksField;
+use crate::field::types::{Field, PrimeField64};
+use crate::hash::poseidon::{HALF_N_FULL_ROUNDS, N_FULL_ROUNDS_TOTAL, N_PARTIAL_ROUNDS};
+use crate::hash::poseidon2::Poseidon2;
+
+// The state is kept in four vectors. Vector `k` holds element `k` of each of the three chunks of
+// four elements, i.e. `[state[k], state[4 + k], state[8 + k], 0]`, so that the 4x4 matrix of the
+// external layer is applied to all chunks at once with vertical operations. The last lane is
+// always zero.
+
+const WIDTH: usize = 12;
+
+const ORDER: u64 = 0xffffffff00000001;
+const EPSILON: u64 = 0xffffffff;
+const SIGN_BIT: u64 = 1 << 63;
+const SHIFTED_ORDER: u64 = ORDER ^ SIGN_BIT;
+
+/// Rearranges the rows of the state into the vector layout above.
+const fn transpose(row: &[u64; WIDTH]) -> [[u64; 4]; 4] {
+    let mut res = [[0; 4]; 4];
+    let mut k = 0;
+    while k < 4 {
+        let mut j = 0;
+        while j < 3 {
+            res[k][j] = row[4 * j + k];
+            j += 1;
+        }
+        k += 1;
+    }
+    res
+}
+
+const fn make_external_round_constants() -> [[[u64; 4]; 4]; N_FULL_ROUNDS_TOTAL] {
+    let mut res = [[[0; 4]; 4]; N_FULL_ROUNDS_TOTAL];
+    let mut r = 0;
+    while r < N_FULL_ROUNDS_TOTAL {
+        res[r] = transpose(&<GoldilocksField as Poseidon2>::EXTERNAL_ROUND_CONSTANTS[r]);
+        r += 1;
+    }
+    res
+}
+const EXTERNAL_ROUND_CONSTANTS: [[[u64; 4]; 4]; N_FULL_ROUNDS_TOTAL] =
+    make_external_round_constants();
+const INTERNAL_MATRIX_DIAG: [[u64; 4]; 4] =
+    transpose(&<GoldilocksField as Poseidon2>::INTERNAL_MATRIX_DIAG);
+
+// * Goldilocks arithmetic on 4 lanes *
+//
+// Values are not necessarily canonical, any u64 is accepted. Comparisons of unsigned values are
+// done on "shifted" values (with the sign bit flipped), since AVX2 only has signed comparisons.
+
+#[inline(always)]
+unsafe fn shift(x: __m256i) -> __m256i {
+    _mm256_xor_si256(x, _mm256_set1_epi64x(SIGN_BIT as i64))
+}
+
+/// Reduces a shifted value to a shifted canonical value.
+#[inline(always)]
+unsafe fn canonicalize_s(x_s: __m256i) -> __m256i {
+    // If x >= ORDER, subtract ORDER, i.e. add EPSILON.
+    let mask = _mm256_cmpgt_epi64(_mm256_set1_epi64x(SHIFTED_ORDER as i64), x_s);
+    let wrapback_amt = _mm256_andnot_si256(mask, _mm256_set1_epi64x(EPSILON as i64));
+    _mm256_add_epi64(x_s, wrapback_amt)
+}
+
+#[inline(always)]
+unsafe fn add(x: __m256i, y: __m256i) -> __m256i {
+    // y is canonical, so the sum overflows at most once, and adding EPSILON afterwards can't
+    // overflow again.
+    let y_s = canonicalize_s(shift(y));
+    let res_wrapped_s = _mm256_add_epi64(x, y_s);
+    let mask = _mm256_cmpgt_epi64(y_s, res_wrapped_s);
+    let wrapback_amt = _mm256_srli_epi64::<32>(mask);
+    shift(_mm256_add_epi64(res_wrapped_s, wrapback_amt))
+}
+
+#[inline(always)]
+unsafe fn double(x: __m256i) -> __m256i {
+    add(x, x)
+}
+
+/// Full 64x64 -> 128 bit product, as (hi, lo).
+#[inline(always)]
+unsafe fn mul64_64(x: __m256i, y: __m256i) -> (__m256i, __m256i) {
+    let eps = _mm256_set1_epi64x(EPSILON as i64);
+    let x_hi = _mm256_srli_epi64::<32>(x);
+    let y_hi = _mm256_srli_epi64::<32>(y);
+    let mul_ll = _mm256_mul_epu32(x, y);
+    let mul_lh = _mm256_mul_epu32(x, y_hi);
+    let mul_hl = _mm256_mul_epu32(x_hi, y);
+    let mul_hh = _mm256_mul_epu32(x_hi, y_hi);
+
+    let t0 = _mm256_add_epi64(mul_hl, _mm256_srli_epi64::<32>(mul_ll));
+    let t1 = _mm256_add_epi64(mul_lh, _mm256_and_si256(t0, eps));
+    let t2 = _mm256_add_epi64(mul_hh, _mm256_srli_epi64::<32>(t0));
+    let res_hi = _mm256_add_epi64(t2, _mm256_srli_epi64::<32>(t1));
+    let res_lo = _mm256_or_si256(_mm256_slli_epi64::<32>(t1), _mm256_and_si256(mul_ll, eps));
+    (res_hi, res_lo)
+}
+
+/// Reduces `hi * 2^64 + lo` using `2^64 = EPSILON` and `2^96 = -1`.
+#[inline(always)]
+unsafe fn reduce128((hi, lo): (__m256i, __m256i)) -> __m256i {
+    let lo_s = shift(lo);
+
+    // lo - (hi >> 32); on underflow, subtract EPSILON.
+    let hi_hi = _mm256_srli_epi64::<32>(hi);
+    let lo1_wrapped_s = _mm256_sub_epi64(lo_s, hi_hi);
+    let mask = _mm256_cmpgt_epi64(lo1_wrapped_s, lo_s);
+    let lo1_s = _mm256_sub_epi64(lo1_wrapped_s, _mm256_srli_epi64::<32>(mask));
+
+    // + (hi & EPSILON) * EPSILON; on overflow, add EPSILON.
+    let t = _mm256_mul_epu32(hi, _mm256_set1_epi64x(EPSILON as i64));
+    let lo2_wrapped_s = _mm256_add_epi64(lo1_s, t);
+    let mask = _mm256_cmpgt_epi64(lo1_s, lo2_wrapped_s);
+    let lo2_s = _mm256_add_epi64(lo2_wrapped_s, _mm256_srli_epi64::<32>(mask));
+
+    shift(lo2_s)
+}
+
+#[inline(always)]
+unsafe fn mul(x: __m256i, y: __m256i) -> __m256i {
+    reduce128(mul64_64(x, y))
+}
+
+#[inline(always)]
+unsafe fn sbox_monomial(x: __m256i) -> __m256i {
+    // x |--> x^7
+    let x2 = mul(x, x);
+    let x4 = mul(x2, x2);
+    let x3 = mul(x, x2);
+    mul(x3, x4)
+}
+
+/// Sum of all lanes, broadcast to every lane.
+#[inline(always)]
+unsafe fn horizontal_sum(x: __m256i) -> __m256i {
+    let x = add(x, _mm256_permute4x64_epi64::<0b01_00_11_10>(x));
+    add(x, _mm256_permute4x64_epi64::<0b10_11_00_01>(x))
+}
+
+#[inline(always)]
+unsafe fn clear_last_lane(x: __m256i) -> __m256i {
+    _mm256_blend_epi32::<0b1100_0000>(x, _mm256_setzero_si256())
+}
+
+#[inline(always)]
+unsafe fn load(x: &[u64; 4]) -> __m256i {
+    _mm256_loadu_si256(x.as_ptr().cast::<__m256i>())
+}
+
+// * Permutation layers *
+
+#[inline(always)]
+unsafe fn external_linear_layer(state: &mut [__m256i; 4]) {
+    // M4 on every chunk, see `poseidon2::m4`.
+    let [x0, x1, x2, x3] = *state;
+    let t0 = add(x0, x1);
+    let t1 = add(x2, x3);
+    let t2 = add(double(x1), t1);
+    let t3 = add(double(x3), t0);
+    let t4 = add(double(double(t1)), t3);
+    let t5 = add(double(double(t0)), t2);
+    let t6 = add(t3, t5);
+    let t7 = add(t2, t4);
+
+    // Add the sum of the chunks; the last lane is zero, so it doesn't contribute.
+    for (s, x) in state.iter_mut().zip([t6, t5, t7, t4]) {
+        *s = clear_last_lane(add(x, horizontal_sum(x)));
+    }
+}
+
+#[inline(always)]
+unsafe fn internal_linear_layer(state: &mut [__m256i; 4]) {
+    let sum = horizontal_sum(add(add(state[0], state[1]), add(state[2], state[3])));
+    for k in 0..4 {
+        let prod = mul(state[k], load(&INTERNAL_MATRIX_DIAG[k]));
+        state[k] = clear_last_lane(add(prod, sum));
+    }
+}
+
+#[inline(always)]
+unsafe fn full_round(state: &mut [__m256i; 4], round: usize) {
+    for k in 0..4 {
+        let c = load(&EXTERNAL_ROUND_CONSTANTS[round][k]);
+        state[k] = sbox_monomial(add(state[k], c));
+    }
+    external_linear_layer(state);
+}
+
+#[inline(always)]
+unsafe fn partial_round(state: &mut [__m256i; 4], round: usize) {
+    let c = <GoldilocksField as Poseidon2>::INTERNAL_ROUND_CONSTANTS[round];
+    let x = add(state[0], _mm256_set_epi64x(0, 0, 0, c as i64));
+    // Only the first lane goes through the S-box.
+    state[0] = _mm256_blend_epi32::<0b0000_0011>(x, sbox_monomial(x));
+    internal_linear_layer(state);
+}
+
+#[inline]
+pub unsafe fn poseidon2(input: &[GoldilocksField; WIDTH]) -> [GoldilocksField; WIDTH] {
+    let input = input.map(|x| x.to_noncanonical_u64());
+    let mut state = transpose(&input).map(|x| load(&x));
+
+    external_linear_layer(&mut state);
+    for r in 0..HALF_N_FULL_ROUNDS {
+        full_round(&mut state, r);
+    }
+    for r in 0..N_PARTIAL_ROUNDS {
+        partial_round(&mut state, r);
+    }
+    for r in HALF_N_FULL_ROUNDS..N_FULL_ROUNDS_TOTAL {
+        full_round(&mut state, r);
+    }
+
+    let mut lanes = [[0u64; 4]; 4];
+    for k in 0..4 {
+        _mm256_storeu_si256(lanes[k].as_mut_ptr().cast::<__m256i>(), state[k]);
+    }
+    let mut output = [GoldilocksField::ZERO; WIDTH];
+    for i in 0..WIDTH {
+        output[i] = GoldilocksField::from_noncanonical_u64(lanes[i % 4][i / 4]);
+    }
+    output
+}
@@ -7,3 +7,5 @@ pub mod merkle_tree;
 pub mod path_compression;
 pub mod poseidon;
 pub mod poseidon_goldilocks;
+pub mod poseidon2;
+pub mod poseidon2_goldilocks;
@@ -0,0 +1,471 @@
+//! Implementation of the Poseidon2 hash function, as described in
+//! <https://eprint.iacr.org/2023/323.pdf>
+//!
+//! The sponge, the number of rounds and the S-box are the same as for Poseidon, so that the two
+//! hashes can be swapped in a `GenericConfig`. Poseidon2 replaces the dense MDS matrix with a cheap
+//! external matrix in the full rounds and a diagonal-plus-ones matrix in the partial rounds.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::ops::Add;
+
+use crate::field::extension::{Extendable, FieldExtension};
+use crate::field::types::{Field, PrimeField64};
+use crate::gates::poseidon2::Poseidon2Gate;
+use crate::hash::hash_types::{HashOut, RichField};
+use crate::hash::hashing::{compress, hash_n_to_hash_no_pad, PlonkyPermutation};
+use crate::hash::poseidon::{
+    HALF_N_FULL_ROUNDS, N_FULL_ROUNDS_TOTAL, N_PARTIAL_ROUNDS, SPONGE_RATE, SPONGE_WIDTH,
+};
+use crate::iop::ext_target::ExtensionTarget;
+use crate::iop::target::{BoolTarget, Target};
+use crate::plonk::circuit_builder::CircuitBuilder;
+use crate::plonk::config::{AlgebraicHasher, Hasher};
+
+/// Multiplies a chunk of four state elements by the matrix
+/// ```text
+/// [ 5 7 1 3 ]
+/// [ 4 6 1 1 ]
+/// [ 1 3 5 7 ]
+/// [ 1 1 4 6 ]
+/// ```
+/// with the addition chain of the paper (appendix B).
+#[inline(always)]
+fn m4<T: Copy + Add<Output = T>>(x: [T; 4]) -> [T; 4] {
+    let t0 = x[0] + x[1];
+    let t1 = x[2] + x[3];
+    let t2 = x[1] + x[1] + t1;
+    let t3 = x[3] + x[3] + t0;
+    let t1_2 = t1 + t1;
+    let t4 = t1_2 + t1_2 + t3;
+    let t0_2 = t0 + t0;
+    let t5 = t0_2 + t0_2 + t2;
+    let t6 = t3 + t5;
+    let t7 = t2 + t4;
+    [t6, t5, t7, t4]
+}
+
+/// The external matrix `circ(2 M4, M4, M4)`: `M4` is applied to every chunk of four elements, then
+/// the sum of the chunks is added to each of them.
+#[inline(always)]
+fn external_linear_layer_generic<T: Copy + Add<Output = T>>(state: &mut [T; SPONGE_WIDTH]) {
+    for chunk in 0..SPONGE_WIDTH / 4 {
+        let start = 4 * chunk;
+        let out = m4([
+            state[start],
+            state[start + 1],
+            state[start + 2],
+            state[start + 3],
+        ]);
+        state[start..start + 4].copy_from_slice(&out);
+    }
+
+    let sums: [T; 4] = core::array::from_fn(|i| {
+        (4..SPONGE_WIDTH)
+            .step_by(4)
+            .fold(state[i], |acc, j| acc + state[j + i])
+    });
+    for i in 0..SPONGE_WIDTH {
+        state[i] = state[i] + sums[i % 4];
+    }
+}
+
+pub trait Poseidon2: PrimeField64 {
+    // Total number of round constants required: one per element in the full rounds and one per
+    // partial round, which only adds a constant to the first element.
+    const N_ROUND_CONSTANTS: usize = SPONGE_WIDTH * N_FULL_ROUNDS_TOTAL + N_PARTIAL_ROUNDS;
+
+    /// Round constants of the full rounds, in the order they are used.
+    const EXTERNAL_ROUND_CONSTANTS: [[u64; SPONGE_WIDTH]; N_FULL_ROUNDS_TOTAL];
+    /// Round constants of the partial rounds.
+    const INTERNAL_ROUND_CONSTANTS: [u64; N_PARTIAL_ROUNDS];
+    // The internal matrix is D + 1, where D is the diagonal matrix whose diagonal is given by
+    // `INTERNAL_MATRIX_DIAG` and 1 is the all-ones matrix.
+    const INTERNAL_MATRIX_DIAG: [u64; SPONGE_WIDTH];
+
+    #[inline(always)]
+    fn external_linear_layer(state: &mut [Self; SPONGE_WIDTH]) {
+        // The coefficients of a row of the external matrix add up to 64, so the sums fit in a u128.
+        let mut state_u128 = [0u128; SPONGE_WIDTH];
+        for i in 0..SPONGE_WIDTH {
+            state_u128[i] = state[i].to_noncanonical_u64() as u128;
+        }
+        external_linear_layer_generic(&mut state_u128);
+        for i in 0..SPONGE_WIDTH {
+            state[i] = Self::from_noncanonical_u128(state_u128[i]);
+        }
+    }
+
+    /// Same as `external_linear_layer` for field extensions of `Self`.
+    fn external_linear_layer_field<F: FieldExtension<D, BaseField = Self>, const D: usize>(
+        state: &mut [F; SPONGE_WIDTH],
+    ) {
+        external_linear_layer_generic(state);
+    }
+
+    /// Recursive version of `external_linear_layer`.
+    fn external_linear_layer_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        state: &mut [ExtensionTarget<D>; SPONGE_WIDTH],
+    ) where
+        Self: RichField + Extendable<D>,
+    {
+        let two = Self::TWO;
+        let four = Self::from_canonical_u64(4);
+        for chunk in 0..SPONGE_WIDTH / 4 {
+            let x = &mut state[4 * chunk..4 * chunk + 4];
+            let t0 = builder.add_extension(x[0], x[1]);
+            let t1 = builder.add_extension(x[2], x[3]);
+            let t2 = builder.mul_const_add_extension(two, x[1], t1);
+            let t3 = builder.mul_const_add_extension(two, x[3], t0);
+            let t4 = builder.mul_const_add_extension(four, t1, t3);
+            let t5 = builder.mul_const_add_extension(four, t0, t2);
+            let t6 = builder.add_extension(t3, t5);
+            let t7 = builder.add_extension(t2, t4);
+            x.copy_from_slice(&[t6, t5, t7, t4]);
+        }
+
+        let sums: Vec<_> = (0..4)
+            .map(|i| builder.add_many_extension((i..SPONGE_WIDTH).step_by(4).map(|j| state[j])))
+            .collect();
+        for i in 0..SPONGE_WIDTH {
+            state[i] = builder.add_extension(state[i], sums[i % 4]);
+        }
+    }
+
+    #[inline(always)]
+    fn internal_linear_layer(state: &mut [Self; SPONGE_WIDTH]) {
+        let mut sum = 0u128;
+        for i in 0..SPONGE_WIDTH {
+            sum += state[i].to_noncanonical_u64() as u128;
+        }
+        let sum = Self::from_noncanonical_u128(sum);
+        for i in 0..SPONGE_WIDTH {
+            let prod =
+                state[i].to_noncanonical_u64() as u128 * Self::INTERNAL_MATRIX_DIAG[i] as u128;
+            state[i] = Self::from_noncanonical_u128(prod) + sum;
+        }
+    }
+
+    /// Same as `internal_linear_layer` for field extensions of `Self`.
+    fn internal_linear_layer_field<F: FieldExtension<D, BaseField = Self>, const D: usize>(
+        state: &mut [F; SPONGE_WIDTH],
+    ) {
+        let sum = state.iter().fold(F::ZERO, |acc, &x| acc + x);
+        for i in 0..SPONGE_WIDTH {
+            state[i] = state[i] * F::from_canonical_u64(Self::INTERNAL_MATRIX_DIAG[i]) + sum;
+        }
+    }
+
+    /// Recursive version of `internal_linear_layer`.
+    fn internal_linear_layer_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        state: &mut [ExtensionTarget<D>; SPONGE_WIDTH],
+    ) where
+        Self: RichField + Extendable<D>,
+    {
+        let sum = builder.add_many_extension(state.iter());
+        for i in 0..SPONGE_WIDTH {
+            let diag = Self::from_canonical_u64(<Self as Poseidon2>::INTERNAL_MATRIX_DIAG[i]);
+            state[i] = builder.mul_const_add_extension(diag, state[i], sum);
+        }
+    }
+
+    /// Adds the round constants of the `round`-th full round.
+    #[inline(always)]
+    fn external_constant_layer(state: &mut [Self; SPONGE_WIDTH], round: usize) {
+        for i in 0..SPONGE_WIDTH {
+            let round_constant = Self::EXTERNAL_ROUND_CONSTANTS[round][i];
+            unsafe {
+                state[i] = state[i].add_canonical_u64(round_constant);
+            }
+        }
+    }
+
+    /// Same as `external_constant_layer` for field extensions of `Self`.
+    fn external_constant_layer_field<F: FieldExtension<D, BaseField = Self>, const D: usize>(
+        state: &mut [F; SPONGE_WIDTH],
+        round: usize,
+    ) {
+        for i in 0..SPONGE_WIDTH {
+            state[i] += F::from_canonical_u64(Self::EXTERNAL_ROUND_CONSTANTS[round][i]);
+        }
+    }
+
+    /// Recursive version of `external_constant_layer`.
+    fn external_constant_layer_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        state: &mut [ExtensionTarget<D>; SPONGE_WIDTH],
+        round: usize,
+    ) where
+        Self: RichField + Extendable<D>,
+    {
+        for i in 0..SPONGE_WIDTH {
+            let c = <Self as Poseidon2>::EXTERNAL_ROUND_CONSTANTS[round][i];
+            let c = Self::Extension::from_canonical_u64(c);
+            let c = builder.constant_extension(c);
+            state[i] = builder.add_extension(state[i], c);
+        }
+    }
+
+    #[inline(always)]
+    fn sbox_monomial<F: FieldExtension<D, BaseField = Self>, const D: usize>(x: F) -> F {
+        // x |--> x^7
+        let x2 = x.square();
+        let x4 = x2.square();
+        let x3 = x * x2;
+        x3 * x4
+    }
+
+    /// Recursive version of `sbox_monomial`.
+    fn sbox_monomial_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        x: ExtensionTarget<D>,
+    ) -> ExtensionTarget<D>
+    where
+        Self: RichField + Extendable<D>,
+    {
+        // x |--> x^7
+        builder.exp_u64_extension(x, 7)
+    }
+
+    #[inline(always)]
+    fn sbox_layer(state: &mut [Self; SPONGE_WIDTH]) {
+        for i in 0..SPONGE_WIDTH {
+            state[i] = Self::sbox_monomial(state[i]);
+        }
+    }
+
+    /// Same as `sbox_layer` for field extensions of `Self`.
+    fn sbox_layer_field<F: FieldExtension<D, BaseField = Self>, const D: usize>(
+        state: &mut [F; SPONGE_WIDTH],
+    ) {
+        for i in 0..SPONGE_WIDTH {
+            state[i] = Self::sbox_monomial(state[i]);
+        }
+    }
+
+    /// Recursive version of `sbox_layer`.
+    fn sbox_layer_circuit<const D: usize>(
+        builder: &mut CircuitBuilder<Self, D>,
+        state: &mut [ExtensionTarget<D>; SPONGE_WIDTH],
+    ) where
+        Self: RichField + Extendable<D>,
+    {
+        for i in 0..SPONGE_WIDTH {
+            state[i] = <Self as Poseidon2>::sbox_monomial_circuit(builder, state[i]);
+        }
+    }
+
+    /// Full rounds `start_round..start_round + HALF_N_FULL_ROUNDS`.
+    #[inline]
+    fn full_rounds(state: &mut [Self; SPONGE_WIDTH], start_round: usize) {
+        for r in start_round..start_round + HALF_N_FULL_ROUNDS {
+            Self::external_constant_layer(state, r);
+            Self::sbox_layer(state);
+            Self::external_linear_layer(state);
+        }
+    }
+
+    #[inline]
+    fn partial_rounds(state: &mut [Self; SPONGE_WIDTH]) {
+        for r in 0..N_PARTIAL_ROUNDS {
+            unsafe {
+                state[0] = state[0].add_canonical_u64(Self::INTERNAL_ROUND_CONSTANTS[r]);
+            }
+            state[0] = Self::sbox_monomial(state[0]);
+            Self::internal_linear_layer(state);
+        }
+    }
+
+    #[inline]
+    fn poseidon2(input: [Self; SPONGE_WIDTH]) -> [Self; SPONGE_WIDTH] {
+        let mut state = input;
+
+        // The initial linear layer makes the first full round depend on all the inputs.
+        Self::external_linear_layer(&mut state);
+        Self::full_rounds(&mut state, 0);
+        Self::partial_rounds(&mut state);
+        Self::full_rounds(&mut state, HALF_N_FULL_ROUNDS);
+
+        state
+    }
+}
+
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub struct Poseidon2Permutation<T> {
+    state: [T; SPONGE_WIDTH],
+}
+
+impl<T: Eq> Eq for Poseidon2Permutation<T> {}
+
+impl<T> AsRef<[T]> for Poseidon2Permutation<T> {
+    fn as_ref(&self) -> &[T] {
+        &self.state
+    }
+}
+
+trait Permuter: Sized {
+    fn permute(input: [Self; SPONGE_WIDTH]) -> [Self; SPONGE_WIDTH];
+}
+
+impl<F: Poseidon2> Permuter for F {
+    fn permute(input: [Self; SPONGE_WIDTH]) -> [Self; SPONGE_WIDTH] {
+        <F as Poseidon2>::poseidon2(input)
+    }
+}
+
+impl Permuter for Target {
+    fn permute(_input: [Self; SPONGE_WIDTH]) -> [Self; SPONGE_WIDTH] {
+        panic!("Call `permute_swapped()` instead of `permute()`");
+    }
+}
+
+impl<T: Copy + Debug + Default + Eq + Permuter + Send + Sync> PlonkyPermutation<T>
+    for Poseidon2Permutation<T>
+{
+    const RATE: usize = SPONGE_RATE;
+    const WIDTH: usize = SPONGE_WIDTH;
+
+    fn new<I: IntoIterator<Item = T>>(elts: I) -> Self {
+        let mut perm = Self {
+            state: [T::default(); SPONGE_WIDTH],
+        };
+        perm.set_from_iter(elts, 0);
+        perm
+    }
+
+    fn set_elt(&mut self, elt: T, idx: usize) {
+        self.state[idx] = elt;
+    }
+
+    fn set_from_slice(&mut self, elts: &[T], start_idx: usize) {
+        let begin = start_idx;
+        let end = start_idx + elts.len();
+        self.state[begin..end].copy_from_slice(elts);
+    }
+
+    fn set_from_iter<I: IntoIterator<Item = T>>(&mut self, elts: I, start_idx: usize) {
+        for (s, e) in self.state[start_idx..].iter_mut().zip(elts) {
+            *s = e;
+        }
+    }
+
+    fn permute(&mut self) {
+        self.state = T::permute(self.state);
+    }
+
+    fn squeeze(&self) -> &[T] {
+        &self.state[..Self::RATE]
+    }
+}
+
+/// Poseidon2 hash function.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Poseidon2Hash;
+impl<F: RichField> Hasher<F> for Poseidon2Hash {
+    const HASH_SIZE: usize = 4 * 8;
+    type Hash = HashOut<F>;
+    type Permutation = Poseidon2Permutation<F>;
+
+    fn hash_no_pad(input: &[F]) -> Self::Hash {
+        hash_n_to_hash_no_pad::<F, Self::Permutation>(input)
+    }
+
+    fn two_to_one(left: Self::Hash, right: Self::Hash) -> Self::Hash {
+        compress::<F, Self::Permutation>(left, right)
+    }
+}
+
+impl<F: RichField> AlgebraicHasher<F> for Poseidon2Hash {
+    type AlgebraicPermutation = Poseidon2Permutation<Target>;
+
+    fn permute_swapped<const D: usize>(
+        inputs: Self::AlgebraicPermutation,
+        swap: BoolTarget,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Self::AlgebraicPermutation
+    where
+        F: RichField + Extendable<D>,
+    {
+        let gate_type = Poseidon2Gate::<F, D>::new();
+        let gate = builder.add_gate(gate_type, vec![]);
+
+        let swap_wire = Poseidon2Gate::<F, D>::WIRE_SWAP;
+        let swap_wire = Target::wire(gate, swap_wire);
+        builder.connect(swap.target, swap_wire);
+
+        // Route input wires.
+        let inputs = inputs.as_ref();
+        for i in 0..SPONGE_WIDTH {
+            let in_wire = Poseidon2Gate::<F, D>::wire_input(i);
+            let in_wire = Target::wire(gate, in_wire);
+            builder.connect(inputs[i], in_wire);
+        }
+
+        // Collect output wires.
+        Self::AlgebraicPermutation::new(
+            (0..SPONGE_WIDTH).map(|i| Target::wire(gate, Poseidon2Gate::<F, D>::wire_output(i))),
+        )
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_helpers {
+    use crate::field::types::Field;
+    use crate::hash::poseidon::{HALF_N_FULL_ROUNDS, N_PARTIAL_ROUNDS, SPONGE_WIDTH};
+    use crate::hash::poseidon2::Poseidon2;
+
+    pub(crate) fn check_test_vectors<F: Field>(
+        test_vectors: Vec<([u64; SPONGE_WIDTH], [u64; SPONGE_WIDTH])>,
+    ) where
+        F: Poseidon2,
+    {
+        for (input_, expected_output_) in test_vectors.into_iter() {
+            let mut input = [F::ZERO; SPONGE_WIDTH];
+            for i in 0..SPONGE_WIDTH {
+                input[i] = F::from_canonical_u64(input_[i]);
+            }
+            let output = F::poseidon2(input);
+            for i in 0..SPONGE_WIDTH {
+                let ex_output = F::from_canonical_u64(expected_output_[i]);
+                assert_eq!(output[i], ex_output);
+            }
+        }
+    }
+
+    /// Checks `poseidon2`, which may be vectorized, against the layers used by the gate.
+    pub(crate) fn check_consistency<F: Field>()
+    where
+        F: Poseidon2,
+    {
+        let mut input = [F::ZERO; SPONGE_WIDTH];
+        for i in 0..SPONGE_WIDTH {
+            input[i] = F::from_canonical_u64(i as u64);
+        }
+        let output = F::poseidon2(input);
+
+        let mut state = input;
+        F::external_linear_layer_field(&mut state);
+        for r in 0..HALF_N_FULL_ROUNDS {
+            F::external_constant_layer_field(&mut state, r);
+            F::sbox_layer_field(&mut state);
+            F::external_linear_layer_field(&mut state);
+        }
+        for r in 0..N_PARTIAL_ROUNDS {
+            state[0] += F::from_canonical_u64(F::INTERNAL_ROUND_CONSTANTS[r]);
+            state[0] = F::sbox_monomial(state[0]);
+            F::internal_linear_layer_field(&mut state);
+        }
+        for r in HALF_N_FULL_ROUNDS..2 * HALF_N_FULL_ROUNDS {
+            F::external_constant_layer_field(&mut state, r);
+            F::sbox_layer_field(&mut state);
+            F::external_linear_layer_field(&mut state);
+        }
+
+        for i in 0..SPONGE_WIDTH {
+            assert_eq!(output[i], state[i]);
+        }
+    }
+}
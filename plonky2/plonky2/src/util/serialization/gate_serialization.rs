@@ -98,6 +98,7 @@ pub mod default {
     use crate::gates::noop::NoopGate;
     use crate::gates::poseidon::PoseidonGate;
     use crate::gates::poseidon_mds::PoseidonMdsGate;
+    use crate::gates::poseidon2::Poseidon2Gate;
     use crate::gates::public_input::PublicInputGate;
     use crate::gates::random_access::RandomAccessGate;
     use crate::gates::reducing::ReducingGate;
@@ -121,6 +122,7 @@ pub mod default {
             NoopGate,
             PoseidonMdsGate<F, D>,
             PoseidonGate<F, D>,
+            Poseidon2Gate<F, D>,
             PublicInputGate,
             RandomAccessGate<F, D>,
             ReducingExtensionGate<D>,
@@ -109,6 +109,7 @@ pub mod default {
     use crate::gates::multiplication_extension::MulExtensionGenerator;
     use crate::gates::poseidon::PoseidonGenerator;
     use crate::gates::poseidon_mds::PoseidonMdsGenerator;
+    use crate::gates::poseidon2::Poseidon2Generator;
     use crate::gates::random_access::RandomAccessGenerator;
     use crate::gates::reducing::ReducingGenerator;
     use crate::gates::reducing_extension::ReducingGenerator as ReducingExtensionGenerator;
@@ -149,6 +150,7 @@ pub mod default {
             NonzeroTestGenerator,
             PoseidonGenerator<F, D>,
             PoseidonMdsGenerator<D>,
+            Poseidon2Generator<F, D>,
             QuotientGeneratorExtension<D>,
             RandomAccessGenerator<F, D>,
             RandomValueGenerator,
@@ -2,6 +2,8 @@
 use std::time::{Duration, Instant};
 
 use log::{log, Level};
+#[cfg(feature = "timing")]
+use serde::{ser::SerializeStruct, Serialize, Serializer};
 
 /// The hierarchy of scopes, and the time consumed by each one. Useful for profiling.
 #[cfg(feature = "timing")]
@@ -173,6 +175,19 @@ impl TimingTree {
     }
 }
 
+/// Serializes the tree as nested `{ name, duration, children }` objects, with `duration` in
+/// seconds. Scopes that are still open report the time elapsed so far.
+#[cfg(feature = "timing")]
+impl Serialize for TimingTree {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("TimingTree", 3)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("duration", &self.duration().as_secs_f64())?;
+        state.serialize_field("children", &self.children)?;
+        state.end()
+    }
+}
+
 /// Creates a named scope; useful for debugging.
 #[macro_export]
 macro_rules! timed {
@@ -190,3 +205,30 @@ macro_rules! timed {
         res
     }};
 }
+
+#[cfg(all(test, feature = "timing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_nested_scopes() {
+        let mut timing = TimingTree::new("prove", Level::Info);
+        timed!(timing, "compute quotient polys", {
+            timed!(timing, "commit to quotient polys", ())
+        });
+        timed!(timing, "compute openings proof", ());
+        timing.pop();
+
+        let value = serde_json::to_value(&timing).unwrap();
+        assert_eq!(value["name"], "prove");
+        assert!(value["duration"].as_f64().unwrap() >= 0.0);
+        let children = value["children"].as_array().unwrap();
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0]["name"], "compute quotient polys");
+        assert_eq!(
+            children[0]["children"][0]["name"],
+            "commit to quotient polys"
+        );
+        assert!(children[1]["children"].as_array().unwrap().is_empty());
+    }
+}
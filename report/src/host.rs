@@ -0,0 +1,86 @@
+//! Information about the machine and checkout a benchmark ran on.
+
+use std::fs;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct HostInfo {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub hostname: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub cpu: Option<String>,
+  pub cores: usize,
+  /// Total physical memory in MB.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub total_memory: Option<f64>,
+  pub os: String,
+  pub arch: String,
+}
+
+impl HostInfo {
+  pub fn current() -> Self {
+    Self {
+      hostname: read_trimmed("/proc/sys/kernel/hostname"),
+      cpu: cpu_model(),
+      cores: std::thread::available_parallelism().map_or(1, |n| n.get()),
+      total_memory: proc_kib_field("/proc/meminfo", "MemTotal:").map(|kib| kib as f64 / 1024.),
+      os: std::env::consts::OS.to_string(),
+      arch: std::env::consts::ARCH.to_string(),
+    }
+  }
+}
+
+/// Revision of the ZKPerf checkout. `ZKPERF_GIT_REVISION` overrides the
+/// value, for runs from exported source trees without a `.git` directory.
+pub fn git_revision() -> Option<String> {
+  if let Ok(rev) = std::env::var("ZKPERF_GIT_REVISION") {
+    return Some(rev);
+  }
+  let output = Command::new("git")
+    .args(["rev-parse", "--short=12", "HEAD"])
+    .output()
+    .ok()?;
+  if !output.status.success() {
+    return None;
+  }
+  let rev = String::from_utf8(output.stdout).ok()?;
+  Some(rev.trim().to_string()).filter(|r| !r.is_empty())
+}
+
+/// Peak resident set size of the current process (`VmHWM`), i.e. the same
+/// number `/usr/bin/time -v` reports as "Maximum resident set size".
+pub fn peak_rss_bytes() -> Option<u64> {
+  proc_kib_field("/proc/self/status", "VmHWM:").map(|kib| kib * 1024)
+}
+
+/// Current resident set size of the process (`VmRSS`).
+pub fn rss_bytes() -> Option<u64> {
+  proc_kib_field("/proc/self/status", "VmRSS:").map(|kib| kib * 1024)
+}
+
+fn cpu_model() -> Option<String> {
+  let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+  cpuinfo
+    .lines()
+    .find(|line| line.starts_with("model name"))
+    .and_then(|line| line.split(':').nth(1))
+    .map(|model| model.trim().to_string())
+}
+
+fn proc_kib_field(path: &str, key: &str) -> Option<u64> {
+  let contents = fs::read_to_string(path).ok()?;
+  contents
+    .lines()
+    .find(|line| line.starts_with(key))?
+    .split_whitespace()
+    .nth(1)?
+    .parse()
+    .ok()
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+  fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
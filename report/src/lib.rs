@@ -0,0 +1,261 @@
+//! Shared result format for the ZKPerf Rust benchmark runners.
+//!
+//! Every halo2 and plonky2 binary writes its measurements through
+//! [`BenchmarkResult`] so that the emitted JSON always has the same keys and
+//! units. The layout is described by `schema/benchmark_result.v1.json`;
+//! bump [`SCHEMA_VERSION`] and add a new schema file whenever a key is
+//! renamed or its unit changes.
+
+pub mod host;
+pub mod memory;
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub use host::{git_revision, peak_rss_bytes, HostInfo};
+pub use memory::{PhaseMemory, PhaseTracker};
+
+/// Version of the JSON layout written by [`BenchmarkResult::write`].
+pub const SCHEMA_VERSION: u32 = 1;
+
+pub const BYTES_PER_MB: f64 = 1024. * 1024.;
+
+/// Units of the measured quantities. These are fixed by the schema version
+/// and are written out so that consumers never have to guess.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Units {
+  pub prover_time: String,
+  pub verifier_time: String,
+  pub proof_size: String,
+  pub memory_consumption: String,
+  #[serde(default = "bytes")]
+  pub memory_phases: String,
+}
+
+fn bytes() -> String {
+  "B".to_string()
+}
+
+impl Default for Units {
+  fn default() -> Self {
+    Self {
+      prover_time: "s".to_string(),
+      verifier_time: "ms".to_string(),
+      proof_size: "B".to_string(),
+      memory_consumption: "MB".to_string(),
+      memory_phases: bytes(),
+    }
+  }
+}
+
+/// One benchmark run of one circuit.
+///
+/// Measurements are optional because the halo2 runners split setup, proving
+/// and verification across processes; each step loads the file written by
+/// the previous one and fills in what it measured.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BenchmarkResult {
+  pub schema_version: u32,
+  pub framework: String,
+  pub circuit: String,
+  pub backend: String,
+  pub curve: String,
+  /// Proving time in seconds.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub prover_time: Option<f64>,
+  /// Verification time in milliseconds.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub verifier_time: Option<f64>,
+  /// Serialized proof size in bytes.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub proof_size: Option<u64>,
+  /// Peak resident set size of the proving process in MB.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub memory_consumption: Option<f64>,
+  /// Peak allocator usage per phase, in bytes.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub memory_phases: Vec<PhaseMemory>,
+  /// Nested timing scopes per step, as reported by the framework's own
+  /// profiler (plonky2's `TimingTree`). Durations are in seconds.
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub timing: BTreeMap<String, Value>,
+  /// log2 of the number of rows, for frameworks that fix it up front.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub k: Option<u32>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub rows: Option<u64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub columns: Option<u64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub git_revision: Option<String>,
+  /// CPU model name, kept at the top level for the existing dashboards.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub hardware: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub host: Option<HostInfo>,
+  #[serde(default)]
+  pub units: Units,
+}
+
+impl BenchmarkResult {
+  /// Starts a result for the given circuit and records the git revision and
+  /// host information of the current machine.
+  pub fn new(framework: &str, circuit: &str, backend: &str, curve: &str) -> Self {
+    let host = HostInfo::current();
+    Self {
+      schema_version: SCHEMA_VERSION,
+      framework: framework.to_string(),
+      circuit: circuit.to_string(),
+      backend: backend.to_string(),
+      curve: curve.to_string(),
+      prover_time: None,
+      verifier_time: None,
+      proof_size: None,
+      memory_consumption: None,
+      memory_phases: vec![],
+      timing: BTreeMap::new(),
+      k: None,
+      rows: None,
+      columns: None,
+      git_revision: git_revision(),
+      hardware: host.cpu.clone(),
+      host: Some(host),
+      units: Units::default(),
+    }
+  }
+
+  /// Loads a previously written result, or starts a new one if `path` does
+  /// not hold a result of the current schema version.
+  pub fn load_or_new(
+    path: impl AsRef<Path>,
+    framework: &str,
+    circuit: &str,
+    backend: &str,
+    curve: &str,
+  ) -> Self {
+    match Self::read(path) {
+      Ok(result) if result.schema_version == SCHEMA_VERSION => result,
+      _ => Self::new(framework, circuit, backend, curve),
+    }
+  }
+
+  pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+    let file = File::open(path)?;
+    serde_json::from_reader(BufReader::new(file)).map_err(io::Error::from)
+  }
+
+  pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut writer, self)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+  }
+
+  pub fn set_prover_time(&mut self, duration: Duration) {
+    self.prover_time = Some(duration.as_secs_f64());
+  }
+
+  pub fn set_verifier_time(&mut self, duration: Duration) {
+    self.verifier_time = Some(duration.as_secs_f64() * 1000.);
+  }
+
+  pub fn set_proof_size(&mut self, bytes: usize) {
+    self.proof_size = Some(bytes as u64);
+  }
+
+  /// Records the peak resident set size of this process so far.
+  pub fn record_peak_rss(&mut self) {
+    if let Some(bytes) = peak_rss_bytes() {
+      self.memory_consumption = Some(bytes as f64 / BYTES_PER_MB);
+    }
+  }
+
+  /// Records the memory usage of a finished phase, replacing an earlier
+  /// measurement of the same phase.
+  pub fn record_phase(&mut self, phase: PhaseMemory) {
+    self.memory_phases.retain(|p| p.phase != phase.phase);
+    self.memory_phases.push(phase);
+  }
+
+  /// Records the scope tree of a step, e.g. the `TimingTree` passed to
+  /// plonky2's `prove`, replacing an earlier tree for the same step.
+  pub fn record_timing(&mut self, step: &str, tree: &impl Serialize) -> serde_json::Result<()> {
+    self
+      .timing
+      .insert(step.to_string(), serde_json::to_value(tree)?);
+    Ok(())
+  }
+
+  /// Records the shape of a circuit with `2^k` rows.
+  pub fn set_k(&mut self, k: u32, columns: usize) {
+    self.k = Some(k);
+    self.rows = Some(1 << k);
+    self.columns = Some(columns as u64);
+  }
+
+  /// Records the shape of a circuit whose row count is not a fixed power of
+  /// two chosen by the caller, e.g. plonky2 where it follows from the gates.
+  pub fn set_shape(&mut self, rows: usize, columns: usize) {
+    self.k = Some(rows.next_power_of_two().trailing_zeros());
+    self.rows = Some(rows as u64);
+    self.columns = Some(columns as u64);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn units_are_explicit() {
+    let mut result = BenchmarkResult::new("plonky2", "ECDSA", "Plonk+FRI", "NaN");
+    result.set_prover_time(Duration::from_millis(1500));
+    result.set_verifier_time(Duration::from_micros(2500));
+    result.set_proof_size(1234);
+
+    let value = serde_json::to_value(&result).unwrap();
+    assert_eq!(value["SchemaVersion"], SCHEMA_VERSION);
+    assert_eq!(value["ProverTime"], 1.5);
+    assert_eq!(value["VerifierTime"], 2.5);
+    assert_eq!(value["ProofSize"], 1234);
+    assert_eq!(value["Units"]["ProverTime"], "s");
+    assert_eq!(value["Units"]["VerifierTime"], "ms");
+    assert!(value.get("MemoryConsumption").is_none());
+    assert!(value.get("Timing").is_none());
+  }
+
+  #[test]
+  fn timing_is_keyed_by_step() {
+    let mut result = BenchmarkResult::new("plonky2", "ECDSA", "Plonk+FRI", "NaN");
+    let tree = serde_json::json!({ "name": "prove", "duration": 1.5, "children": [] });
+    result.record_timing("prove", &tree).unwrap();
+
+    let value = serde_json::to_value(&result).unwrap();
+    assert_eq!(value["Timing"]["prove"]["duration"], 1.5);
+  }
+
+  #[test]
+  fn round_trip_through_file() {
+    let path = std::env::temp_dir().join(format!("zkperf_report_{}.json", std::process::id()));
+    let mut result = BenchmarkResult::new("Halo2", "MNIST", "Plonk", "BN254");
+    result.set_k(15, 10);
+    result.write(&path).unwrap();
+
+    let mut loaded = BenchmarkResult::load_or_new(&path, "Halo2", "MNIST", "Plonk", "BN254");
+    assert_eq!(loaded, result);
+    assert_eq!(loaded.rows, Some(1 << 15));
+
+    loaded.set_proof_size(10);
+    loaded.write(&path).unwrap();
+    assert_eq!(BenchmarkResult::read(&path).unwrap().proof_size, Some(10));
+    std::fs::remove_file(&path).unwrap();
+  }
+}
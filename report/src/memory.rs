@@ -0,0 +1,125 @@
+//! Per-phase memory tracking.
+//!
+//! A [`PhaseTracker`] samples the allocator statistics on a background
+//! thread while a phase (keygen, witness generation, proving, ...) runs and
+//! keeps the maximum. With the `jemalloc` feature the numbers come from
+//! jemalloc's `stats.allocated` and `stats.resident`, which requires the
+//! binary to install `jemallocator::Jemalloc` as its global allocator.
+//! Without it only the resident set size of the process is available.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Peak memory usage observed during one phase, in bytes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PhaseMemory {
+  pub phase: String,
+  /// Bytes handed out by the allocator; `None` without jemalloc stats.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub peak_allocated: Option<u64>,
+  pub peak_resident: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Sample {
+  allocated: Option<u64>,
+  resident: u64,
+}
+
+impl Sample {
+  fn max(self, other: Sample) -> Sample {
+    Sample {
+      allocated: self.allocated.max(other.allocated),
+      resident: self.resident.max(other.resident),
+    }
+  }
+}
+
+#[cfg(feature = "jemalloc")]
+fn sample() -> Sample {
+  use jemalloc_ctl::{epoch, stats};
+
+  // The statistics are cached by jemalloc and only refreshed on an epoch
+  // advance.
+  if epoch::advance().is_err() {
+    return Sample::default();
+  }
+  Sample {
+    allocated: stats::allocated::read().ok().map(|b| b as u64),
+    resident: stats::resident::read().map_or(0, |b| b as u64),
+  }
+}
+
+#[cfg(not(feature = "jemalloc"))]
+fn sample() -> Sample {
+  Sample {
+    allocated: None,
+    resident: crate::host::rss_bytes().unwrap_or(0),
+  }
+}
+
+/// Tracks the peak memory usage of a single phase until [`finish`] is called.
+///
+/// [`finish`]: PhaseTracker::finish
+pub struct PhaseTracker {
+  phase: String,
+  stop: Arc<AtomicBool>,
+  sampler: JoinHandle<Sample>,
+}
+
+impl PhaseTracker {
+  pub fn start(phase: &str) -> Self {
+    let stop = Arc::new(AtomicBool::new(false));
+    let sampler_stop = stop.clone();
+    let sampler = thread::spawn(move || {
+      let mut peak = sample();
+      while !sampler_stop.load(Ordering::Relaxed) {
+        thread::sleep(SAMPLE_INTERVAL);
+        peak = peak.max(sample());
+      }
+      peak
+    });
+    Self {
+      phase: phase.to_string(),
+      stop,
+      sampler,
+    }
+  }
+
+  pub fn finish(self) -> PhaseMemory {
+    self.stop.store(true, Ordering::Relaxed);
+    let peak = self.sampler.join().unwrap_or_default().max(sample());
+    PhaseMemory {
+      phase: self.phase,
+      peak_allocated: peak.allocated,
+      peak_resident: peak.resident,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // jemalloc stats need the jemalloc global allocator, which only the
+  // runner binaries install.
+  #[cfg(not(feature = "jemalloc"))]
+  #[test]
+  fn sees_allocations_during_phase() {
+    let tracker = PhaseTracker::start("alloc");
+    let buf = vec![1u8; 64 << 20];
+    thread::sleep(SAMPLE_INTERVAL * 3);
+    let phase = tracker.finish();
+    drop(buf);
+
+    assert_eq!(phase.phase, "alloc");
+    assert!(phase.peak_resident >= 64 << 20);
+  }
+}
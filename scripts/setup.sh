@@ -19,6 +19,9 @@ cd ../zkml
 cd $MAIN_DIR/halo2
 cd ecdsa
 ./configure.sh
+cd keccak_ecdsa
+./configure.sh
+cd ..
 cd ../merkle_tree
 ./configure.sh
 mkdir -p params